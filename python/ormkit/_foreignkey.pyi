@@ -51,8 +51,13 @@ class QueryResult:
         """Get the first row, or None if empty."""
         ...
 
-    def one(self) -> dict[str, Any]:
-        """Get a single row, raising error if not exactly one row."""
+    def one(
+        self,
+        no_result_exc: type[Exception] | None = None,
+        multi_result_exc: type[Exception] | None = None,
+    ) -> dict[str, Any]:
+        """Get a single row, raising NoResultFound/MultipleResultsFound (or the
+        given exception classes) if not exactly one row."""
         ...
 
     def one_or_none(self) -> dict[str, Any] | None:
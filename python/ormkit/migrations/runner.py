@@ -86,7 +86,9 @@ class MigrationRunner:
         await self.ensure_version_table()
         table = self._version_table
 
-        result = await self.pool.execute(f'SELECT version_num FROM "{table}" LIMIT 1')
+        result = await self.pool.execute(
+            f'SELECT version_num FROM "{table}" LIMIT 1', row_factory="result"
+        )
         row = result.first()
         if row:
             return row["version_num"]
@@ -101,7 +103,9 @@ class MigrationRunner:
         await self.ensure_version_table()
         table = self._version_table
 
-        result = await self.pool.execute(f'SELECT version_num FROM "{table}"')
+        result = await self.pool.execute(
+            f'SELECT version_num FROM "{table}"', row_factory="result"
+        )
         return [row["version_num"] for row in result.all()]
 
     async def stamp(self, revision: str) -> None:
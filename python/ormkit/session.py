@@ -8,7 +8,7 @@ from dataclasses import dataclass, field
 from datetime import UTC
 from typing import TYPE_CHECKING, Any, TypeVar
 
-from ormkit._ormkit import ConnectionPool, QueryResult
+from ormkit._ormkit import ConnectionPool, MultipleResultsFound, NoResultFound, QueryResult
 
 if TYPE_CHECKING:
     from ormkit.base import Base
@@ -642,7 +642,8 @@ class AsyncSession:
         conflict_target: str | list[str],
         update_fields: list[str] | None = None,
         do_nothing: bool = False,
-    ) -> T:
+        return_action: bool = False,
+    ) -> T | tuple[T, dict[str, Any]]:
         """Insert or update a single model instance (upsert).
 
         If a record with the same conflict_target value(s) exists, it will be
@@ -653,9 +654,13 @@ class AsyncSession:
             conflict_target: Column(s) that define uniqueness (e.g., "email" or ["team_id", "user_id"])
             update_fields: Columns to update on conflict. If None, updates all non-PK columns.
             do_nothing: If True, ignore conflicts instead of updating
+            return_action: If True, also return what the statement did as
+                ``{"action": "insert"|"update"|"none", "rowid": ...}``
+                (SQLite only)
 
         Returns:
-            The upserted instance with generated ID
+            The upserted instance with generated ID, or an
+            ``(instance, outcome)`` tuple when ``return_action`` is set
 
         Example:
             >>> user = await session.upsert(
@@ -663,6 +668,13 @@ class AsyncSession:
             ...     conflict_target="email",
             ...     update_fields=["name"]
             ... )
+            >>> user, outcome = await session.upsert(
+            ...     User(email="alice@example.com", name="Alice"),
+            ...     conflict_target="email",
+            ...     return_action=True,
+            ... )
+            >>> outcome["action"]
+            'update'
         """
         cls = type(instance)
         table = cls.__tablename__
@@ -723,16 +735,26 @@ class AsyncSession:
             sql += f" ON CONFLICT ({conflict_str}) DO UPDATE SET {', '.join(set_parts)}"
 
         row: dict[str, Any] | None = None
+        outcome: dict[str, Any] | None = None
 
-        if pk_col and self._dialect == "postgresql":
+        if return_action:
+            # last_insert_rowid() tells an insert apart from DO UPDATE
+            outcome = await self._pool.execute_upsert(sql, params)
+            if pk_col and outcome["action"] != "none":
+                keys = self._conflict_keys_from_instances([instance], conflict_cols)
+                rows = await self._fetch_rows_by_conflict_keys(table, conflict_cols, keys)
+                row = rows[0] if rows else None
+        elif pk_col and self._dialect == "postgresql":
             sql += " RETURNING *"
-            result = await self._pool.execute(sql, params)
+            result = await self._pool.execute(sql, params, row_factory="result")
             row = result.first()
         elif pk_col and not do_nothing and self._dialect == "sqlite":
             supports_returning = await self._sqlite_supports_returning()
             if supports_returning:
                 try:
-                    result = await self._pool.execute(f"{sql} RETURNING *", params)
+                    result = await self._pool.execute(
+                        f"{sql} RETURNING *", params, row_factory="result"
+                    )
                     row = result.first()
                 except Exception as exc:
                     if self._is_sqlite_returning_unsupported_error(exc):
@@ -761,6 +783,8 @@ class AsyncSession:
             if pk_value is not None and not isinstance(pk_value, ColumnInfo):
                 self._identity_map[(cls, pk_value)] = instance
 
+        if outcome is not None:
+            return instance, outcome
         return instance
 
     async def upsert_all(
@@ -879,14 +903,16 @@ class AsyncSession:
 
         if pk_col and self._dialect == "postgresql":
             sql += " RETURNING *"
-            result = await self._pool.execute(sql, params)
+            result = await self._pool.execute(sql, params, row_factory="result")
             rows = list(result.all())
             used_returning = True
         elif pk_col and not do_nothing and self._dialect == "sqlite":
             supports_returning = await self._sqlite_supports_returning()
             if supports_returning:
                 try:
-                    result = await self._pool.execute(f"{sql} RETURNING *", params)
+                    result = await self._pool.execute(
+                        f"{sql} RETURNING *", params, row_factory="result"
+                    )
                     rows = list(result.all())
                     used_returning = True
                 except Exception as exc:
@@ -987,12 +1013,12 @@ class AsyncSession:
             await self._flush_inserts()
 
         sql, params = statement.to_sql(self._dialect)
-        result = await self._pool.execute(sql, params)
+        result = await self._pool.execute(sql, params, row_factory="result")
         return ExecuteResult(result, getattr(statement, "model", None))
 
     async def execute_raw(self, sql: str, params: list[Any] | None = None) -> QueryResult:
         """Execute raw SQL and return results."""
-        return await self._pool.execute(sql, params or [])
+        return await self._pool.execute(sql, params or [], row_factory="result")
 
     @staticmethod
     def _is_sqlite_returning_unsupported_error(exc: Exception) -> bool:
@@ -1010,7 +1036,9 @@ class AsyncSession:
             return self._sqlite_returning_supported
 
         try:
-            result = await self._pool.execute("SELECT sqlite_version() AS version", [])
+            result = await self._pool.execute(
+                "SELECT sqlite_version() AS version", [], row_factory="result"
+            )
             row = result.first()
             version = row["version"] if row and "version" in row else ""
             parts = [int(p) for p in str(version).split(".")[:3]]
@@ -1075,7 +1103,7 @@ class AsyncSession:
             else:
                 placeholders = ", ".join("?" for _ in values)
             sql = f"SELECT * FROM {table} WHERE {col} IN ({placeholders})"
-            result = await self._pool.execute(sql, values)
+            result = await self._pool.execute(sql, values, row_factory="result")
             return list(result.all())
 
         row_placeholders: list[str] = []
@@ -1098,7 +1126,7 @@ class AsyncSession:
         cols_expr = ", ".join(conflict_cols)
         where_expr = f"({cols_expr}) IN ({', '.join(row_placeholders)})"
         sql = f"SELECT * FROM {table} WHERE {where_expr}"
-        result = await self._pool.execute(sql, params)
+        result = await self._pool.execute(sql, params, row_factory="result")
         return list(result.all())
 
     # ========== Internal Methods ==========
@@ -1169,7 +1197,7 @@ class AsyncSession:
         if pk_col:
             # Use RETURNING to get generated IDs (works in PostgreSQL and SQLite 3.35+)
             sql += f" RETURNING {pk_col}"
-            result = await self._pool.execute(sql, params)
+            result = await self._pool.execute(sql, params, row_factory="result")
             rows = result.all()
             for i, instance in enumerate(instances):
                 if i < len(rows):
@@ -1482,35 +1510,35 @@ class Query[T]:
     async def count(self) -> int:
         """Return count of matching rows."""
         sql, params = self._build_aggregate_sql("COUNT(*)", "count")
-        result = await self._session._pool.execute(sql, params)
+        result = await self._session._pool.execute(sql, params, row_factory="result")
         row = result.first()
         return row["count"] if row else 0
 
     async def sum(self, column: str) -> float | None:
         """Return sum of a column."""
         sql, params = self._build_aggregate_sql(f"SUM({column})", "sum")
-        result = await self._session._pool.execute(sql, params)
+        result = await self._session._pool.execute(sql, params, row_factory="result")
         row = result.first()
         return row["sum"] if row else None
 
     async def avg(self, column: str) -> float | None:
         """Return average of a column."""
         sql, params = self._build_aggregate_sql(f"AVG({column})", "avg")
-        result = await self._session._pool.execute(sql, params)
+        result = await self._session._pool.execute(sql, params, row_factory="result")
         row = result.first()
         return row["avg"] if row else None
 
     async def min(self, column: str) -> Any:
         """Return minimum value of a column."""
         sql, params = self._build_aggregate_sql(f"MIN({column})", "min")
-        result = await self._session._pool.execute(sql, params)
+        result = await self._session._pool.execute(sql, params, row_factory="result")
         row = result.first()
         return row["min"] if row else None
 
     async def max(self, column: str) -> Any:
         """Return maximum value of a column."""
         sql, params = self._build_aggregate_sql(f"MAX({column})", "max")
-        result = await self._session._pool.execute(sql, params)
+        result = await self._session._pool.execute(sql, params, row_factory="result")
         row = result.first()
         return row["max"] if row else None
 
@@ -1520,7 +1548,7 @@ class Query[T]:
         sql = f"SELECT 1 FROM {table}"
         where_sql, params = self._build_where_clause()
         sql += where_sql + " LIMIT 1"
-        result = await self._session._pool.execute(sql, params)
+        result = await self._session._pool.execute(sql, params, row_factory="result")
         return result.first() is not None
 
     async def delete(self) -> int:
@@ -1549,7 +1577,7 @@ class Query[T]:
             [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]
         """
         sql, params = self._build_select_sql(columns)
-        result = await self._session._pool.execute(sql, params)
+        result = await self._session._pool.execute(sql, params, row_factory="result")
         return list(result.all())
 
     async def values_list(self, *columns: str, flat: bool = False) -> list[Any]:
@@ -1563,7 +1591,7 @@ class Query[T]:
             ["Alice", "Bob"]
         """
         sql, params = self._build_select_sql(columns)
-        result = await self._session._pool.execute(sql, params)
+        result = await self._session._pool.execute(sql, params, row_factory="result")
 
         if flat and len(columns) == 1:
             return list(result.column(columns[0]))
@@ -1573,7 +1601,7 @@ class Query[T]:
         """Build and execute the SELECT statement."""
         join_infos = self._build_join_info()
         sql, params = self._build_select_sql(join_infos=join_infos)
-        result = await self._session._pool.execute(sql, params)
+        result = await self._session._pool.execute(sql, params, row_factory="result")
         if join_infos:
             return ExecuteResult(result, self._model, join_infos)
 
@@ -1857,7 +1885,7 @@ class Query[T]:
                 placeholders = ", ".join("?" for _ in parent_ids)
 
             sql = f"SELECT * FROM {table} WHERE {fk_col} IN ({placeholders})"
-            result = await self._session._pool.execute(sql, parent_ids)
+            result = await self._session._pool.execute(sql, parent_ids, row_factory="result")
 
             related_by_parent: dict[Any, list[Any]] = {pid: [] for pid in parent_ids}
             for row in result.all():
@@ -1895,7 +1923,7 @@ class Query[T]:
                 placeholders = ", ".join("?" for _ in fk_values)
 
             sql = f"SELECT * FROM {table} WHERE {remote_pk} IN ({placeholders})"
-            result = await self._session._pool.execute(sql, fk_values)
+            result = await self._session._pool.execute(sql, fk_values, row_factory="result")
 
             related_by_pk: dict[Any, Any] = {}
             for row in result.all():
@@ -1957,7 +1985,9 @@ class Query[T]:
             f"FROM {junction_table} "
             f"WHERE {junction_local} IN ({placeholders})"
         )
-        junction_result = await self._session._pool.execute(junction_sql, parent_ids)
+        junction_result = await self._session._pool.execute(
+            junction_sql, parent_ids, row_factory="result"
+        )
         junction_rows = junction_result.all()
 
         # Build mapping: parent_id -> list of target_ids
@@ -1991,7 +2021,9 @@ class Query[T]:
             f"SELECT * FROM {target_table} "
             f"WHERE {target_pk} IN ({target_placeholders})"
         )
-        target_result = await self._session._pool.execute(target_sql, target_ids_list)
+        target_result = await self._session._pool.execute(
+            target_sql, target_ids_list, row_factory="result"
+        )
 
         # Build mapping: target_id -> target instance
         targets_by_id: dict[Any, Any] = {}
@@ -2038,9 +2070,17 @@ class ExecuteResult[T: "Base"]:
         """Get the first result as a dictionary."""
         return self._result.first()
 
-    def one(self) -> dict[str, Any]:
-        """Get exactly one result, or raise an error."""
-        return self._result.one()
+    def one(
+        self,
+        no_result_exc: type[Exception] | None = None,
+        multi_result_exc: type[Exception] | None = None,
+    ) -> dict[str, Any]:
+        """Get exactly one result, or raise an error.
+
+        Raises NoResultFound or MultipleResultsFound, or the given
+        exception classes instead.
+        """
+        return self._result.one(no_result_exc, multi_result_exc)
 
     def one_or_none(self) -> dict[str, Any] | None:
         """Get one result or None."""
@@ -2128,8 +2168,10 @@ class ScalarResult[T: "Base"]:
         """Get exactly one result as a model instance."""
         if self._model is None:
             raise ValueError("Cannot convert to model: no model specified")
-        if self._result.rowcount != 1:
-            raise ValueError(f"Expected exactly 1 row, got {self._result.rowcount}")
+        if self._result.rowcount == 0:
+            raise NoResultFound("Expected exactly 1 row, got 0")
+        if self._result.rowcount > 1:
+            raise MultipleResultsFound(f"Expected exactly 1 row, got {self._result.rowcount}")
 
         if self._join_infos:
             rows = self._result.all()
@@ -2137,7 +2179,7 @@ class ScalarResult[T: "Base"]:
 
         result = self._result.to_model(self._model)
         if result is None:
-            raise ValueError("Expected exactly 1 row, got 0")
+            raise NoResultFound("Expected exactly 1 row, got 0")
         return result
 
     def one_or_none(self) -> T | None:
@@ -2145,7 +2187,7 @@ class ScalarResult[T: "Base"]:
         if self._model is None:
             return None
         if self._result.rowcount > 1:
-            raise ValueError(f"Expected at most 1 row, got {self._result.rowcount}")
+            raise MultipleResultsFound(f"Expected at most 1 row, got {self._result.rowcount}")
 
         if self._join_infos:
             rows = self._result.all()
@@ -0,0 +1,387 @@
+//! Minimal CSV reading for `import_csv` and writing for
+//! `QueryResult.to_csv`.
+//!
+//! Follows RFC 4180: comma-separated fields, double-quoted fields may hold
+//! commas, newlines and doubled quotes. As in PostgreSQL's CSV format, an
+//! unquoted empty field is NULL while a quoted empty field (`""`) is an
+//! empty string.
+
+use std::io::BufRead;
+
+use crate::pool::SqlParam;
+use crate::sql::Dialect;
+
+/// One record: the line it starts on and its fields (None for NULL).
+pub type CsvRecord = (usize, Vec<Option<String>>);
+
+/// Reads CSV records from a buffered reader.
+pub struct CsvReader<R> {
+    reader: R,
+    /// Lines consumed so far
+    line: usize,
+}
+
+impl<R: BufRead> CsvReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader, line: 0 }
+    }
+
+    /// Read the next record, skipping blank lines.
+    ///
+    /// Errors name the line, for I/O failures and unterminated quotes.
+    pub fn next_record(&mut self) -> Result<Option<CsvRecord>, String> {
+        let mut buf = String::new();
+        loop {
+            buf.clear();
+            if self.read_line(&mut buf)? == 0 {
+                return Ok(None);
+            }
+            if !trim_newline(&buf).is_empty() {
+                break;
+            }
+        }
+        let start = self.line;
+
+        let mut fields = Vec::new();
+        let mut field = String::new();
+        let mut quoted = false;
+        let mut in_quotes = false;
+        let mut chars = trim_newline(&buf).to_string();
+
+        loop {
+            let mut iter = chars.chars().peekable();
+            while let Some(c) = iter.next() {
+                if in_quotes {
+                    match c {
+                        '"' if iter.peek() == Some(&'"') => {
+                            iter.next();
+                            field.push('"');
+                        }
+                        '"' => in_quotes = false,
+                        c => field.push(c),
+                    }
+                } else {
+                    match c {
+                        ',' => {
+                            fields.push(finish_field(&mut field, quoted));
+                            quoted = false;
+                        }
+                        '"' if field.is_empty() && !quoted => {
+                            quoted = true;
+                            in_quotes = true;
+                        }
+                        c => field.push(c),
+                    }
+                }
+            }
+
+            if !in_quotes {
+                break;
+            }
+            // A quoted field continues on the next line
+            field.push('\n');
+            buf.clear();
+            if self.read_line(&mut buf)? == 0 {
+                return Err(format!("line {}: unterminated quoted field", start));
+            }
+            chars = trim_newline(&buf).to_string();
+        }
+
+        fields.push(finish_field(&mut field, quoted));
+        Ok(Some((start, fields)))
+    }
+
+    fn read_line(&mut self, buf: &mut String) -> Result<usize, String> {
+        let read = self
+            .reader
+            .read_line(buf)
+            .map_err(|e| format!("line {}: {}", self.line + 1, e))?;
+        if read > 0 {
+            self.line += 1;
+        }
+        Ok(read)
+    }
+}
+
+/// How a CSV field is converted for its target column.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColumnKind {
+    Int,
+    Float,
+    /// Exact decimal, checked and passed on as text
+    Numeric,
+    Bool,
+    /// Anything else; the database parses the text itself
+    Text,
+}
+
+impl ColumnKind {
+    /// Pick the conversion for a column's introspected `data_type`.
+    ///
+    /// PostgreSQL reports standard type names; SQLite columns follow its
+    /// type affinity rules on the declared type.
+    pub fn from_data_type(data_type: &str, dialect: Dialect) -> Self {
+        let t = data_type.to_ascii_lowercase();
+        match dialect {
+            Dialect::Postgres => match t.as_str() {
+                "smallint" | "integer" | "bigint" => ColumnKind::Int,
+                "real" | "double precision" => ColumnKind::Float,
+                "numeric" => ColumnKind::Numeric,
+                "boolean" => ColumnKind::Bool,
+                _ => ColumnKind::Text,
+            },
+            Dialect::Sqlite => {
+                if t.contains("bool") {
+                    ColumnKind::Bool
+                } else if t.contains("int") {
+                    ColumnKind::Int
+                } else if ["char", "clob", "text", "blob"]
+                    .iter()
+                    .any(|k| t.contains(k))
+                    || t.is_empty()
+                {
+                    ColumnKind::Text
+                } else if t.contains("real") || t.contains("floa") || t.contains("doub") {
+                    ColumnKind::Float
+                } else {
+                    ColumnKind::Numeric
+                }
+            }
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            ColumnKind::Int => "integer",
+            ColumnKind::Float => "float",
+            ColumnKind::Numeric => "numeric",
+            ColumnKind::Bool => "boolean",
+            ColumnKind::Text => "text",
+        }
+    }
+
+    /// Convert one field, or explain why it can't be converted.
+    pub fn convert(self, field: Option<String>) -> Result<SqlParam, String> {
+        let Some(value) = field else {
+            return Ok(SqlParam::Null);
+        };
+        let invalid = || format!("cannot convert {:?} to {}", value, self.name());
+        match self {
+            ColumnKind::Text => Ok(SqlParam::String(value)),
+            ColumnKind::Int => value
+                .trim()
+                .parse()
+                .map(SqlParam::Int)
+                .map_err(|_| invalid()),
+            ColumnKind::Float => value
+                .trim()
+                .parse()
+                .map(SqlParam::Float)
+                .map_err(|_| invalid()),
+            ColumnKind::Numeric => {
+                let trimmed = value.trim();
+                let digits = trimmed.strip_prefix(['-', '+']).unwrap_or(trimmed);
+                let mut parts = digits.splitn(2, '.');
+                let whole = parts.next().unwrap_or("");
+                let frac = parts.next().unwrap_or("");
+                let valid = !(whole.is_empty() && frac.is_empty())
+                    && whole.bytes().all(|b| b.is_ascii_digit())
+                    && frac.bytes().all(|b| b.is_ascii_digit());
+                if valid {
+                    Ok(SqlParam::String(trimmed.to_string()))
+                } else {
+                    Err(invalid())
+                }
+            }
+            ColumnKind::Bool => match value.trim().to_ascii_lowercase().as_str() {
+                "t" | "true" | "y" | "yes" | "on" | "1" => Ok(SqlParam::Bool(true)),
+                "f" | "false" | "n" | "no" | "off" | "0" => Ok(SqlParam::Bool(false)),
+                _ => Err(invalid()),
+            },
+        }
+    }
+}
+
+/// Append one field to a CSV line.
+///
+/// The field is quoted when it holds the delimiter, a quote or a line
+/// break. NULL is written as an empty field and an empty string as `""`, so
+/// `CsvReader` reads both back as they were.
+pub fn write_field(out: &mut String, field: Option<&str>, delimiter: char) {
+    let Some(field) = field else {
+        return;
+    };
+    if field.is_empty() || field.contains([delimiter, '"', '\n', '\r']) {
+        out.push('"');
+        out.push_str(&field.replace('"', "\"\""));
+        out.push('"');
+    } else {
+        out.push_str(field);
+    }
+}
+
+fn trim_newline(line: &str) -> &str {
+    line.strip_suffix('\n')
+        .map(|l| l.strip_suffix('\r').unwrap_or(l))
+        .unwrap_or(line)
+}
+
+fn finish_field(field: &mut String, quoted: bool) -> Option<String> {
+    let value = std::mem::take(field);
+    if value.is_empty() && !quoted {
+        None
+    } else {
+        Some(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn records(input: &str) -> Vec<CsvRecord> {
+        let mut reader = CsvReader::new(input.as_bytes());
+        let mut out = Vec::new();
+        while let Some(record) = reader.next_record().unwrap() {
+            out.push(record);
+        }
+        out
+    }
+
+    fn s(value: &str) -> Option<String> {
+        Some(value.to_string())
+    }
+
+    #[test]
+    fn test_plain_and_null_fields() {
+        assert_eq!(
+            records("a,b,c\r\n1,,\"\"\n\n2,x,y"),
+            vec![
+                (1, vec![s("a"), s("b"), s("c")]),
+                (2, vec![s("1"), None, s("")]),
+                (4, vec![s("2"), s("x"), s("y")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_quoted_fields() {
+        assert_eq!(
+            records("\"a,b\",\"say \"\"hi\"\"\"\n\"two\nlines\",z\nnext,1\n"),
+            vec![
+                (1, vec![s("a,b"), s("say \"hi\"")]),
+                (2, vec![s("two\nlines"), s("z")]),
+                (4, vec![s("next"), s("1")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_column_kinds() {
+        use Dialect::{Postgres, Sqlite};
+        assert_eq!(
+            ColumnKind::from_data_type("bigint", Postgres),
+            ColumnKind::Int
+        );
+        assert_eq!(
+            ColumnKind::from_data_type("interval", Postgres),
+            ColumnKind::Text
+        );
+        assert_eq!(
+            ColumnKind::from_data_type("double precision", Postgres),
+            ColumnKind::Float
+        );
+        assert_eq!(
+            ColumnKind::from_data_type("INTEGER", Sqlite),
+            ColumnKind::Int
+        );
+        assert_eq!(
+            ColumnKind::from_data_type("VARCHAR(20)", Sqlite),
+            ColumnKind::Text
+        );
+        assert_eq!(
+            ColumnKind::from_data_type("DECIMAL(10,2)", Sqlite),
+            ColumnKind::Numeric
+        );
+        assert_eq!(
+            ColumnKind::from_data_type("BOOLEAN", Sqlite),
+            ColumnKind::Bool
+        );
+    }
+
+    #[test]
+    fn test_convert_fields() {
+        assert!(matches!(
+            ColumnKind::Int.convert(s(" 42")),
+            Ok(SqlParam::Int(42))
+        ));
+        assert!(matches!(ColumnKind::Int.convert(None), Ok(SqlParam::Null)));
+        assert!(matches!(
+            ColumnKind::Bool.convert(s("Yes")),
+            Ok(SqlParam::Bool(true))
+        ));
+        assert!(matches!(
+            ColumnKind::Numeric.convert(s("-12.50")),
+            Ok(SqlParam::String(ref v)) if v == "-12.50"
+        ));
+        assert_eq!(
+            ColumnKind::Int.convert(s("4.5")).unwrap_err(),
+            "cannot convert \"4.5\" to integer"
+        );
+        assert!(ColumnKind::Numeric.convert(s("1e5")).is_err());
+        assert!(ColumnKind::Numeric.convert(s(".")).is_err());
+    }
+
+    #[test]
+    fn test_write_fields() {
+        let mut line = String::new();
+        for (i, field) in [
+            Some("plain"),
+            None,
+            Some(""),
+            Some("a,b"),
+            Some("say \"hi\""),
+            Some("two\nlines"),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            if i > 0 {
+                line.push(',');
+            }
+            write_field(&mut line, field, ',');
+        }
+        assert_eq!(
+            line,
+            "plain,,\"\",\"a,b\",\"say \"\"hi\"\"\",\"two\nlines\""
+        );
+        assert_eq!(
+            records(&line),
+            vec![(
+                1,
+                vec![
+                    s("plain"),
+                    None,
+                    s(""),
+                    s("a,b"),
+                    s("say \"hi\""),
+                    s("two\nlines")
+                ]
+            )]
+        );
+
+        let mut tabbed = String::new();
+        write_field(&mut tabbed, Some("a,b"), '\t');
+        assert_eq!(tabbed, "a,b");
+    }
+
+    #[test]
+    fn test_unterminated_quote() {
+        let mut reader = CsvReader::new("ok\n\"open,\nstill open".as_bytes());
+        assert!(reader.next_record().unwrap().is_some());
+        assert_eq!(
+            reader.next_record().unwrap_err(),
+            "line 2: unterminated quoted field"
+        );
+    }
+}
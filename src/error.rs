@@ -2,10 +2,118 @@
 //!
 //! No external database driver dependencies.
 
-use pyo3::exceptions::{PyRuntimeError, PyValueError};
-use pyo3::PyErr;
+use pyo3::create_exception;
+use pyo3::exceptions::{PyRuntimeError, PyTimeoutError, PyValueError};
+use pyo3::prelude::*;
 use thiserror::Error;
 
+use crate::pg::{PgError, ServerError};
+use crate::sqlite::SqliteError;
+
+create_exception!(
+    _ormkit,
+    PoolClosedError,
+    PyRuntimeError,
+    "Raised when acquiring from a connection pool that has been closed."
+);
+
+create_exception!(
+    _ormkit,
+    PoolTimeoutError,
+    PyTimeoutError,
+    "Raised when no pooled connection becomes free within `acquire_timeout`."
+);
+
+create_exception!(
+    _ormkit,
+    NoResultFound,
+    PyValueError,
+    "Raised by `QueryResult.one()` when the result has no rows."
+);
+
+create_exception!(
+    _ormkit,
+    MultipleResultsFound,
+    PyValueError,
+    "Raised by `QueryResult.one()` and `one_or_none()` when the result has \
+     more than one row."
+);
+
+create_exception!(
+    _ormkit,
+    DatabaseError,
+    PyRuntimeError,
+    "Raised when the database server reports an error.\n\nCarries the server's \
+     error fields as `sqlstate`, `severity`, `detail`, `hint`, `schema`, `table`, \
+     `column` and `constraint` (None when not reported; also available as \
+     `table_name`, `column_name` and `constraint_name`), plus the \
+     `connection_id` of the connection that received it. `fields` maps every \
+     field code the server sent (e.g. `P` position, `W` where) to its value."
+);
+
+create_exception!(
+    _ormkit,
+    IntegrityError,
+    DatabaseError,
+    "Raised for integrity constraint violations (SQLSTATE class 23), such as \
+     a unique violation (`23505`) or a foreign key violation (`23503`)."
+);
+
+create_exception!(
+    _ormkit,
+    UniqueViolationError,
+    IntegrityError,
+    "Raised when a row would duplicate a unique key (SQLSTATE 23505)."
+);
+
+create_exception!(
+    _ormkit,
+    ForeignKeyViolationError,
+    IntegrityError,
+    "Raised when a row references a missing key or is still referenced \
+     (SQLSTATE 23503)."
+);
+
+create_exception!(
+    _ormkit,
+    NotNullViolationError,
+    IntegrityError,
+    "Raised when a NOT NULL column is given NULL (SQLSTATE 23502)."
+);
+
+create_exception!(
+    _ormkit,
+    CheckViolationError,
+    IntegrityError,
+    "Raised when a row fails a CHECK constraint (SQLSTATE 23514)."
+);
+
+create_exception!(
+    _ormkit,
+    OperationalError,
+    DatabaseError,
+    "Raised for errors in the database's operation rather than the query: \
+     connection failures (08), transaction rollbacks such as deadlocks and \
+     serialization failures (40), insufficient resources (53), operator \
+     intervention such as cancellation (57) and system errors (58)."
+);
+
+create_exception!(
+    _ormkit,
+    ProgrammingError,
+    DatabaseError,
+    "Raised for errors in the SQL itself: syntax errors, undefined tables or \
+     columns and permission failures (SQLSTATE class 42)."
+);
+
+create_exception!(
+    _ormkit,
+    DataError,
+    DatabaseError,
+    "Raised for invalid data, such as division by zero, numeric overflow or \
+     malformed input (SQLSTATE class 22)."
+);
+
 #[derive(Error, Debug)]
 pub enum ForeignKeyError {
     #[error("Database connection error: {0}")]
@@ -14,6 +122,12 @@ pub enum ForeignKeyError {
     #[error("Query execution error: {0}")]
     QueryError(String),
 
+    #[error("Query execution error: {0}")]
+    Database(Box<ServerError>),
+
+    #[error("Query execution error: {0}")]
+    Sqlite(SqliteError),
+
     #[error("Type conversion error: {0}")]
     TypeError(String),
 
@@ -28,6 +142,33 @@ pub enum ForeignKeyError {
 
     #[error("I/O error: {0}")]
     IoError(#[from] std::io::Error),
+
+    #[error("Pool is closed")]
+    PoolClosed,
+
+    #[error("Timed out waiting for a pooled connection")]
+    PoolTimeout,
+}
+
+impl From<PgError> for ForeignKeyError {
+    fn from(err: PgError) -> Self {
+        match err {
+            PgError::PoolClosed => ForeignKeyError::PoolClosed,
+            PgError::Timeout => ForeignKeyError::PoolTimeout,
+            PgError::Server(e) => ForeignKeyError::Database(e),
+            e => ForeignKeyError::QueryError(e.to_string()),
+        }
+    }
+}
+
+impl From<SqliteError> for ForeignKeyError {
+    fn from(err: SqliteError) -> Self {
+        match err {
+            SqliteError::PoolClosed => ForeignKeyError::PoolClosed,
+            SqliteError::AcquireTimeout => ForeignKeyError::PoolTimeout,
+            e => ForeignKeyError::Sqlite(e),
+        }
+    }
 }
 
 impl From<ForeignKeyError> for PyErr {
@@ -36,9 +177,119 @@ impl From<ForeignKeyError> for PyErr {
             ForeignKeyError::TypeError(_) | ForeignKeyError::ConfigError(_) => {
                 PyValueError::new_err(err.to_string())
             }
+            ForeignKeyError::PoolClosed => PoolClosedError::new_err(err.to_string()),
+            ForeignKeyError::PoolTimeout => PoolTimeoutError::new_err(err.to_string()),
+            ForeignKeyError::Database(ref e) => server_error_to_py(e, err.to_string()),
+            ForeignKeyError::Sqlite(ref e) => sqlite_error_to_py(e, err.to_string()),
             _ => PyRuntimeError::new_err(err.to_string()),
         }
     }
 }
 
+/// Convert a PostgreSQL error into a Python exception with the given message.
+///
+/// Server errors become `DatabaseError` with their fields as attributes and
+/// an acquire timeout `PoolTimeoutError`; anything else is a plain
+/// `RuntimeError`.
+pub fn pg_error_to_py(err: &PgError, message: String) -> PyErr {
+    match err {
+        PgError::Server(e) => server_error_to_py(e, message),
+        PgError::Timeout => PoolTimeoutError::new_err(message),
+        _ => PyRuntimeError::new_err(message),
+    }
+}
+
+/// Convert a SQLite error into a Python exception with the given message.
+///
+/// Constraint failures raise the same `IntegrityError` subclasses as their
+/// PostgreSQL counterparts, with every error field None since SQLite
+/// reports only the message; anything else is a plain `RuntimeError`.
+pub fn sqlite_error_to_py(err: &SqliteError, message: String) -> PyErr {
+    use rusqlite::ffi;
+
+    let extended_code = match err {
+        SqliteError::Sqlite(rusqlite::Error::SqliteFailure(e, _))
+            if e.code == rusqlite::ErrorCode::ConstraintViolation =>
+        {
+            e.extended_code
+        }
+        _ => return PyRuntimeError::new_err(message),
+    };
+    let py_err = match extended_code {
+        ffi::SQLITE_CONSTRAINT_UNIQUE | ffi::SQLITE_CONSTRAINT_PRIMARYKEY => {
+            UniqueViolationError::new_err(message)
+        }
+        ffi::SQLITE_CONSTRAINT_FOREIGNKEY => ForeignKeyViolationError::new_err(message),
+        ffi::SQLITE_CONSTRAINT_NOTNULL => NotNullViolationError::new_err(message),
+        ffi::SQLITE_CONSTRAINT_CHECK => CheckViolationError::new_err(message),
+        _ => IntegrityError::new_err(message),
+    };
+    Python::with_gil(|py| {
+        let value = py_err.value(py);
+        for name in [
+            "sqlstate",
+            "severity",
+            "detail",
+            "hint",
+            "schema",
+            "table",
+            "column",
+            "constraint",
+            "table_name",
+            "column_name",
+            "constraint_name",
+            "connection_id",
+        ] {
+            let _ = value.setattr(name, py.None());
+        }
+        let _ = value.setattr("fields", pyo3::types::PyDict::new(py));
+    });
+    py_err
+}
+
+/// Build a `DatabaseError` carrying the server's error fields.
+///
+/// The common integrity violations get their own subclasses; otherwise the
+/// SQLSTATE class (its first two characters) picks one, and codes outside
+/// the mapped classes raise `DatabaseError` itself.
+fn server_error_to_py(err: &ServerError, message: String) -> PyErr {
+    Python::with_gil(|py| {
+        let py_err = match err.code.as_str() {
+            "23505" => UniqueViolationError::new_err(message),
+            "23503" => ForeignKeyViolationError::new_err(message),
+            "23502" => NotNullViolationError::new_err(message),
+            "23514" => CheckViolationError::new_err(message),
+            code => match code.get(..2).unwrap_or("") {
+                "23" => IntegrityError::new_err(message),
+                "08" | "40" | "53" | "57" | "58" => OperationalError::new_err(message),
+                "42" => ProgrammingError::new_err(message),
+                "22" => DataError::new_err(message),
+                _ => DatabaseError::new_err(message),
+            },
+        };
+        let value = py_err.value(py);
+        let attrs = [
+            ("sqlstate", Some(&err.code)),
+            ("severity", Some(&err.severity)),
+            ("detail", err.detail.as_ref()),
+            ("hint", err.hint.as_ref()),
+            ("schema", err.schema.as_ref()),
+            ("table", err.table.as_ref()),
+            ("column", err.column.as_ref()),
+            ("constraint", err.constraint.as_ref()),
+            ("table_name", err.table.as_ref()),
+            ("column_name", err.column.as_ref()),
+            ("constraint_name", err.constraint.as_ref()),
+        ];
+        for (name, field) in attrs {
+            // Only fails if the exception object rejects attributes, which
+            // plain exception instances never do
+            let _ = value.setattr(name, field);
+        }
+        let _ = value.setattr("connection_id", err.connection_id);
+        let _ = value.setattr("fields", &err.fields);
+        py_err
+    })
+}
+
 pub type Result<T> = std::result::Result<T, ForeignKeyError>;
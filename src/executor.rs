@@ -1,11 +1,22 @@
+use base64::prelude::{Engine as _, BASE64_STANDARD};
+use chrono::Datelike;
 use pyo3::intern;
 use pyo3::prelude::*;
 use pyo3::sync::GILOnceCell;
-use pyo3::types::{PyDict, PyList, PyString, PyTuple};
+use pyo3::types::{
+    PyBytes, PyDate, PyDateTime, PyDelta, PyDict, PyList, PyString, PyTime, PyTuple, PyType,
+    PyTzInfo,
+};
 use smallvec::SmallVec;
+use std::collections::HashSet;
 use std::sync::Arc;
 use std::sync::OnceLock;
 
+use crate::csv::write_field;
+use crate::error::{MultipleResultsFound, NoResultFound};
+use crate::pg::datetime::{days_to_date, micros_to_datetime, Interval};
+use crate::pool::format_uuid;
+
 // Re-export serde_json::Value for JSON support
 pub use serde_json::Value as JsonValue;
 
@@ -42,9 +53,107 @@ pub enum RowValue {
     Int(i64),
     Float(f64),
     String(String),
+    /// String shared by equal values in the result (see `StringInterner`)
+    SharedString(Arc<str>),
+    /// Exact decimal text ("12.50", "NaN") - converted to `decimal.Decimal`
+    Decimal(String),
+    /// UUID bytes - converted to a `uuid.UUID`
+    Uuid([u8; 16]),
     Bytes(Vec<u8>),
     /// JSON value - converted to Python dict/list via pythonize
     Json(JsonValue),
+    /// Array - converted to a Python list
+    Array(Vec<RowValue>),
+    /// Composite value - converted to a Python tuple
+    Record(Vec<RowValue>),
+    /// Date as days since 2000-01-01 - converted to a `datetime.date`
+    Date(i32),
+    /// Timestamp as microseconds since 2000-01-01 - converted to a
+    /// `datetime.datetime`, in UTC when `utc` is set and naive otherwise
+    DateTime {
+        micros: i64,
+        utc: bool,
+    },
+    /// Time of day as microseconds since midnight - converted to a naive
+    /// `datetime.time`
+    Time(i64),
+    /// Interval with no months part - converted to a `datetime.timedelta`
+    TimeDelta {
+        days: i32,
+        micros: i64,
+    },
+    /// Time with UTC offset (seconds east) - converted to a tz-aware
+    /// `datetime.time`
+    TimeTz {
+        micros: i64,
+        tz_offset_secs: i32,
+    },
+    /// Range - converted to a Python `Range`; a None bound is infinite
+    Range {
+        lower: Option<Box<RowValue>>,
+        upper: Option<Box<RowValue>>,
+        lower_inc: bool,
+        upper_inc: bool,
+        empty: bool,
+    },
+}
+
+/// A PostgreSQL range value (`int4range`, `tstzrange`, ...)
+///
+/// An infinite bound is None; an empty range has no bounds.
+#[pyclass(frozen)]
+pub struct Range {
+    /// Lower bound, or None if unbounded
+    #[pyo3(get)]
+    lower: PyObject,
+    /// Upper bound, or None if unbounded
+    #[pyo3(get)]
+    upper: PyObject,
+    /// Whether the lower bound is included
+    #[pyo3(get)]
+    lower_inc: bool,
+    /// Whether the upper bound is included
+    #[pyo3(get)]
+    upper_inc: bool,
+    /// Whether the range is empty
+    #[pyo3(get)]
+    is_empty: bool,
+}
+
+#[pymethods]
+impl Range {
+    /// Bound inclusivity in PostgreSQL notation, e.g. "[)"
+    #[getter]
+    fn bounds(&self) -> &'static str {
+        match (self.lower_inc, self.upper_inc) {
+            (true, true) => "[]",
+            (true, false) => "[)",
+            (false, true) => "(]",
+            (false, false) => "()",
+        }
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        if self.is_empty {
+            return Ok("Range(empty)".to_string());
+        }
+        let bound = |value: &PyObject| -> PyResult<String> {
+            let value = value.bind(py);
+            if value.is_none() {
+                Ok(String::new())
+            } else {
+                Ok(value.repr()?.to_string())
+            }
+        };
+        let bounds = self.bounds();
+        Ok(format!(
+            "Range({}{}, {}{})",
+            &bounds[..1],
+            bound(&self.lower)?,
+            bound(&self.upper)?,
+            &bounds[1..]
+        ))
+    }
 }
 
 /// A row stored as column values (lazy conversion to Python)
@@ -58,6 +167,167 @@ pub struct LazyRow {
 /// Shared row data - wrapped in Arc to avoid cloning on iteration
 pub type SharedRows = Arc<Vec<LazyRow>>;
 
+/// Deduplicates equal string values while a result is decoded.
+///
+/// Each distinct string is stored once and shared by every row holding it,
+/// which saves memory on low-cardinality columns (statuses, enum labels) at
+/// the cost of hashing every string.
+#[derive(Default)]
+pub struct StringInterner {
+    strings: HashSet<Arc<str>>,
+}
+
+impl StringInterner {
+    /// Swap a string value for its shared copy; other values pass through.
+    #[inline]
+    pub fn intern(&mut self, value: RowValue) -> RowValue {
+        let RowValue::String(s) = value else {
+            return value;
+        };
+        if let Some(shared) = self.strings.get(s.as_str()) {
+            return RowValue::SharedString(Arc::clone(shared));
+        }
+        let shared: Arc<str> = Arc::from(s);
+        self.strings.insert(Arc::clone(&shared));
+        RowValue::SharedString(shared)
+    }
+}
+
+/// How rows should be materialized when a query is executed.
+pub enum RowFactory {
+    /// One dict per row (same as `QueryResult.all()`)
+    Dict,
+    /// One tuple per row (same as `QueryResult.tuples()`)
+    Tuple,
+    /// A Python callable invoked with each row dict
+    Callable(PyObject),
+}
+
+impl RowFactory {
+    /// Parse a `row_factory` argument: `"dict"`, `"tuple"`, or a callable.
+    pub fn from_py(obj: &Bound<'_, PyAny>) -> PyResult<Self> {
+        if let Ok(name) = obj.extract::<String>() {
+            return match name.as_str() {
+                "dict" => Ok(RowFactory::Dict),
+                "tuple" => Ok(RowFactory::Tuple),
+                _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "row_factory must be \"dict\", \"tuple\" or a callable, got {:?}",
+                    name
+                ))),
+            };
+        }
+        if obj.is_callable() {
+            Ok(RowFactory::Callable(obj.clone().unbind()))
+        } else {
+            Err(pyo3::exceptions::PyTypeError::new_err(
+                "row_factory must be \"dict\", \"tuple\" or a callable",
+            ))
+        }
+    }
+}
+
+/// How column names are cased before they become row dict keys.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColumnCase {
+    /// Lowercase names
+    Lower,
+    /// Uppercase names
+    Upper,
+    /// Names exactly as the database reports them
+    #[default]
+    Preserve,
+}
+
+impl ColumnCase {
+    /// Parse a `column_case` argument: `"lower"`, `"upper"` or `"preserve"`.
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "lower" => Ok(ColumnCase::Lower),
+            "upper" => Ok(ColumnCase::Upper),
+            "preserve" => Ok(ColumnCase::Preserve),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "column_case must be \"lower\", \"upper\" or \"preserve\", got {:?}",
+                name
+            ))),
+        }
+    }
+
+    /// Apply the casing to a column name.
+    pub fn apply(self, name: String) -> String {
+        match self {
+            ColumnCase::Lower => name.to_lowercase(),
+            ColumnCase::Upper => name.to_uppercase(),
+            ColumnCase::Preserve => name,
+        }
+    }
+}
+
+/// How BYTEA/BLOB values are returned to Python.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BytesFormat {
+    /// `bytes` objects
+    #[default]
+    Raw,
+    /// Lowercase hex strings
+    Hex,
+    /// Standard base64 strings (with padding)
+    Base64,
+}
+
+impl BytesFormat {
+    /// Parse a `bytes_format` argument: `"raw"`, `"hex"` or `"base64"`.
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "raw" => Ok(BytesFormat::Raw),
+            "hex" => Ok(BytesFormat::Hex),
+            "base64" => Ok(BytesFormat::Base64),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "bytes_format must be \"raw\", \"hex\" or \"base64\", got {:?}",
+                name
+            ))),
+        }
+    }
+}
+
+/// Lowercase hex encoding of a byte string.
+fn hex_string(bytes: &[u8]) -> String {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for &byte in bytes {
+        out.push(HEX[(byte >> 4) as usize] as char);
+        out.push(HEX[(byte & 0x0f) as usize] as char);
+    }
+    out
+}
+
+/// Suffix repeated column names (`id`, `id_1`, ...), skipping suffixes
+/// that are already taken by another column.
+fn disambiguate_columns(columns: Vec<String>) -> Vec<String> {
+    let mut seen: HashSet<&str> = HashSet::with_capacity(columns.len());
+    if columns.iter().all(|name| seen.insert(name)) {
+        return columns;
+    }
+
+    let mut taken: HashSet<String> = columns.iter().cloned().collect();
+    let mut first_use: HashSet<String> = HashSet::with_capacity(columns.len());
+    columns
+        .into_iter()
+        .map(|name| {
+            if first_use.insert(name.clone()) {
+                return name;
+            }
+            let mut n = 1;
+            loop {
+                let candidate = format!("{}_{}", name, n);
+                if taken.insert(candidate.clone()) {
+                    return candidate;
+                }
+                n += 1;
+            }
+        })
+        .collect()
+}
+
 /// Result from executing a SQL query
 /// Uses lazy conversion - rows are stored as Rust data and converted to Python on demand
 #[pyclass]
@@ -68,27 +338,86 @@ pub struct QueryResult {
     columns: Arc<Vec<String>>,
     /// Cached Python tuple of column names (lazy, avoids repeated Vec cloning)
     columns_tuple_cache: CachedColumnsTuple,
+    /// How bytes values are converted
+    bytes_format: BytesFormat,
+    /// Rows the statement reported affecting, if it reports a count
+    rows_affected: Option<u64>,
 }
 
 impl QueryResult {
-    /// Create from lazy rows (optimized path)
+    /// Create from lazy rows (optimized path), casing column names
+    ///
+    /// Duplicate names (e.g. `SELECT a.id, b.id`) are suffixed so that no
+    /// column is lost when rows become dicts: `id`, `id_1`, `id_2`, ...
     #[inline]
-    pub fn from_lazy(rows: Vec<LazyRow>, columns: Vec<String>) -> Self {
+    pub fn from_lazy(rows: Vec<LazyRow>, columns: Vec<String>, case: ColumnCase) -> Self {
+        let columns = match case {
+            ColumnCase::Preserve => columns,
+            case => columns.into_iter().map(|name| case.apply(name)).collect(),
+        };
+        let columns = disambiguate_columns(columns);
         Self {
             rows: Arc::new(rows),
             columns: Arc::new(columns),
             columns_tuple_cache: CachedColumnsTuple {
                 tuple: OnceLock::new(),
             },
+            bytes_format: BytesFormat::Raw,
+            rows_affected: None,
         }
     }
 
+    /// Set the row count the statement reported.
+    pub fn with_rows_affected(mut self, rows_affected: Option<u64>) -> Self {
+        self.rows_affected = rows_affected;
+        self
+    }
+
+    /// Return bytes values in the given format.
+    pub fn with_bytes_format(mut self, bytes_format: BytesFormat) -> Self {
+        self.bytes_format = bytes_format;
+        self
+    }
+
     /// Get a reference to the rows
     #[inline]
     pub fn rows(&self) -> &[LazyRow] {
         &self.rows
     }
 
+    /// Materialize all rows as a list using the given row factory.
+    pub fn materialize<'py>(
+        &self,
+        py: Python<'py>,
+        factory: &RowFactory,
+    ) -> PyResult<Bound<'py, PyList>> {
+        match factory {
+            RowFactory::Dict => self.all(py),
+            RowFactory::Tuple => self.tuples(py),
+            RowFactory::Callable(func) => {
+                let func = func.bind(py);
+                let cols = self.columns.as_ref();
+                let interned_cols: Vec<Bound<'py, PyString>> =
+                    cols.iter().map(|c| PyString::intern(py, c)).collect();
+
+                let items: PyResult<Vec<Bound<'py, PyAny>>> = self
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        func.call1((row_to_dict(
+                            py,
+                            row,
+                            cols,
+                            Some(&interned_cols),
+                            self.bytes_format,
+                        )?,))
+                    })
+                    .collect();
+                PyList::new(py, items?)
+            }
+        }
+    }
+
     /// Get or create a cached Python tuple of column names.
     /// This avoids repeated Vec cloning when accessing columns multiple times.
     #[inline]
@@ -104,14 +433,21 @@ impl QueryResult {
 /// Convert RowValue to Python object - hyper-optimized version
 #[inline(always)]
 #[allow(deprecated)] // ToPyObject is being replaced, but still works
-fn row_value_to_py(py: Python<'_>, val: &RowValue) -> PyObject {
+fn row_value_to_py(py: Python<'_>, val: &RowValue, bytes: BytesFormat) -> PyObject {
     match val {
         RowValue::Null => py.None(),
         RowValue::Bool(b) => b.to_object(py),
         RowValue::Int(i) => i.to_object(py),
         RowValue::Float(f) => f.to_object(py),
         RowValue::String(s) => s.to_object(py),
-        RowValue::Bytes(b) => b.to_object(py),
+        RowValue::SharedString(s) => s.as_ref().to_object(py),
+        RowValue::Decimal(s) => decimal_to_py(py, s).unwrap_or_else(|_| s.to_object(py)),
+        RowValue::Uuid(u) => uuid_to_py(py, u).unwrap_or_else(|_| format_uuid(u).to_object(py)),
+        RowValue::Bytes(b) => match bytes {
+            BytesFormat::Raw => b.to_object(py),
+            BytesFormat::Hex => hex_string(b).to_object(py),
+            BytesFormat::Base64 => BASE64_STANDARD.encode(b).to_object(py),
+        },
         RowValue::Json(json) => {
             // Use pythonize to convert serde_json::Value to Python dict/list
             // This is very fast as pythonize is optimized for this conversion
@@ -119,6 +455,370 @@ fn row_value_to_py(py: Python<'_>, val: &RowValue) -> PyObject {
                 .map(|bound| bound.unbind())
                 .unwrap_or_else(|_| py.None())
         }
+        RowValue::Array(items) => {
+            PyList::new(py, items.iter().map(|v| row_value_to_py(py, v, bytes)))
+                .map(|list| list.into_any().unbind())
+                .unwrap_or_else(|_| py.None())
+        }
+        RowValue::Record(fields) => {
+            PyTuple::new(py, fields.iter().map(|v| row_value_to_py(py, v, bytes)))
+                .map(|tuple| tuple.into_any().unbind())
+                .unwrap_or_else(|_| py.None())
+        }
+        RowValue::Date(days) => date_to_py(py, *days).unwrap_or_else(|_| days.to_object(py)),
+        RowValue::DateTime { micros, utc } => {
+            datetime_to_py(py, *micros, *utc).unwrap_or_else(|_| micros.to_object(py))
+        }
+        RowValue::Time(micros) => time_to_py(py, *micros, None).unwrap_or_else(|_| py.None()),
+        RowValue::TimeDelta { days, micros } => timedelta_to_py(py, *days, *micros)
+            .unwrap_or_else(|_| interval_text(*days, *micros).to_object(py)),
+        RowValue::TimeTz {
+            micros,
+            tz_offset_secs,
+        } => time_to_py(py, *micros, Some(*tz_offset_secs)).unwrap_or_else(|_| py.None()),
+        RowValue::Range {
+            lower,
+            upper,
+            lower_inc,
+            upper_inc,
+            empty,
+        } => {
+            let bound = |value: &Option<Box<RowValue>>| match value {
+                Some(value) => row_value_to_py(py, value, bytes),
+                None => py.None(),
+            };
+            let range = Range {
+                lower: bound(lower),
+                upper: bound(upper),
+                lower_inc: *lower_inc,
+                upper_inc: *upper_inc,
+                is_empty: *empty,
+            };
+            Py::new(py, range)
+                .map(|range| range.into_any())
+                .unwrap_or_else(|_| py.None())
+        }
+    }
+}
+
+/// Build a `datetime.date` from days since 2000-01-01.
+///
+/// `infinity` and `-infinity` map to `date.max` and `date.min`. Other dates
+/// outside Python's year 1-9999 range fail, and the caller falls back to the
+/// raw day count.
+fn date_to_py(py: Python<'_>, days: i32) -> PyResult<PyObject> {
+    let date = match days {
+        i32::MAX => PyDate::new(py, 9999, 12, 31)?,
+        i32::MIN => PyDate::new(py, 1, 1, 1)?,
+        _ => {
+            let date = days_to_date(days)
+                .ok_or_else(|| pyo3::exceptions::PyOverflowError::new_err("date out of range"))?;
+            PyDate::new(py, date.year(), date.month() as u8, date.day() as u8)?
+        }
+    };
+    Ok(date.into_any().unbind())
+}
+
+/// Build a `datetime.datetime` from microseconds since the PostgreSQL epoch
+/// (2000-01-01 00:00:00), attaching `datetime.timezone.utc` when `utc` is set.
+///
+/// `infinity` and `-infinity` map to `datetime.max` and `datetime.min`.
+/// Other values outside Python's year 1-9999 range fail, and the caller
+/// falls back to the raw microsecond count.
+fn datetime_to_py(py: Python<'_>, micros: i64, utc: bool) -> PyResult<PyObject> {
+    const MICROS_PER_DAY: i64 = 86_400_000_000;
+    let tzinfo = if utc {
+        Some(
+            py.import(intern!(py, "datetime"))?
+                .getattr(intern!(py, "timezone"))?
+                .getattr(intern!(py, "utc"))?,
+        )
+    } else {
+        None
+    };
+    let tzinfo = tzinfo
+        .as_ref()
+        .map(|tz| tz.downcast::<PyTzInfo>())
+        .transpose()?;
+
+    let value = match micros {
+        i64::MAX => PyDateTime::new(py, 9999, 12, 31, 23, 59, 59, 999_999, tzinfo)?,
+        i64::MIN => PyDateTime::new(py, 1, 1, 1, 0, 0, 0, 0, tzinfo)?,
+        _ => {
+            let epoch = PyDateTime::new(py, 2000, 1, 1, 0, 0, 0, 0, tzinfo)?;
+            let offset = PyDelta::new(
+                py,
+                micros.div_euclid(MICROS_PER_DAY) as i32,
+                0,
+                micros.rem_euclid(MICROS_PER_DAY) as i32,
+                true,
+            )?;
+            epoch.add(offset)?.downcast_into::<PyDateTime>()?
+        }
+    };
+    Ok(value.into_any().unbind())
+}
+
+/// Build a `datetime.timedelta` from an interval's days and microseconds.
+///
+/// Fails past timedelta's +/-999999999 day range; the caller falls back to
+/// the ISO 8601 text.
+fn timedelta_to_py(py: Python<'_>, days: i32, micros: i64) -> PyResult<PyObject> {
+    const MICROS_PER_DAY: i64 = 86_400_000_000;
+    let days = i32::try_from(days as i64 + micros.div_euclid(MICROS_PER_DAY))
+        .map_err(|_| pyo3::exceptions::PyOverflowError::new_err("interval out of range"))?;
+    let micros = micros.rem_euclid(MICROS_PER_DAY);
+    let delta = PyDelta::new(
+        py,
+        days,
+        (micros / 1_000_000) as i32,
+        (micros % 1_000_000) as i32,
+        false,
+    )?;
+    Ok(delta.into_any().unbind())
+}
+
+/// ISO 8601 text of an interval with no months part, e.g. `P1DT2H`.
+fn interval_text(days: i32, micros: i64) -> String {
+    Interval {
+        months: 0,
+        days,
+        microseconds: micros,
+    }
+    .to_iso8601()
+}
+
+/// Build a `datetime.time`, with a fixed-offset `datetime.timezone` when
+/// `tz_offset_secs` is given and naive otherwise.
+///
+/// PostgreSQL allows `24:00:00`, which `datetime.time` can't hold; it is
+/// returned as an ISO string instead.
+fn time_to_py(py: Python<'_>, micros: i64, tz_offset_secs: Option<i32>) -> PyResult<PyObject> {
+    const MICROS_PER_DAY: i64 = 86_400_000_000;
+    if !(0..MICROS_PER_DAY).contains(&micros) {
+        let offset = tz_offset_secs.map(format_offset).unwrap_or_default();
+        return Ok(format!("24:00:00{}", offset)
+            .into_pyobject(py)?
+            .into_any()
+            .unbind());
+    }
+
+    let tzinfo = match tz_offset_secs {
+        Some(secs) => Some(
+            py.import(intern!(py, "datetime"))?
+                .getattr(intern!(py, "timezone"))?
+                .call1((PyDelta::new(py, 0, secs, 0, true)?,))?,
+        ),
+        None => None,
+    };
+    let tzinfo = tzinfo
+        .as_ref()
+        .map(|tz| tz.downcast::<PyTzInfo>())
+        .transpose()?;
+    let seconds = micros / 1_000_000;
+    let time = PyTime::new(
+        py,
+        (seconds / 3600) as u8,
+        (seconds % 3600 / 60) as u8,
+        (seconds % 60) as u8,
+        (micros % 1_000_000) as u32,
+        tzinfo,
+    )?;
+    Ok(time.into_any().unbind())
+}
+
+/// Cached `decimal.Decimal`
+static DECIMAL_TYPE: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+/// Build a `decimal.Decimal` from decimal text, keeping its scale.
+fn decimal_to_py(py: Python<'_>, text: &str) -> PyResult<PyObject> {
+    Ok(DECIMAL_TYPE
+        .import(py, "decimal", "Decimal")?
+        .call1((text,))?
+        .unbind())
+}
+
+/// Cached `uuid.UUID`
+static UUID_TYPE: GILOnceCell<Py<PyType>> = GILOnceCell::new();
+
+/// Build a `uuid.UUID` from its 16 bytes.
+fn uuid_to_py(py: Python<'_>, bytes: &[u8; 16]) -> PyResult<PyObject> {
+    let kwargs = PyDict::new(py);
+    kwargs.set_item("bytes", PyBytes::new(py, bytes))?;
+    Ok(UUID_TYPE
+        .import(py, "uuid", "UUID")?
+        .call((), Some(&kwargs))?
+        .unbind())
+}
+
+/// Bytes of CSV text `to_csv` buffers between `write` calls
+const CSV_CHUNK_BYTES: usize = 64 * 1024;
+
+/// `HH:MM:SS[.ffffff]` text of a time of day; `24:00:00` when it is past
+/// the last microsecond of the day.
+fn time_text(micros: i64) -> String {
+    chrono::NaiveTime::from_num_seconds_from_midnight_opt(
+        (micros / 1_000_000) as u32,
+        (micros % 1_000_000 * 1000) as u32,
+    )
+    .map(|t| t.format("%H:%M:%S%.f").to_string())
+    .unwrap_or_else(|| "24:00:00".to_string())
+}
+
+/// `+HH:MM` text of a UTC offset in seconds east.
+fn format_offset(secs: i32) -> String {
+    let sign = if secs < 0 { '-' } else { '+' };
+    let secs = secs.unsigned_abs();
+    format!("{}{:02}:{:02}", sign, secs / 3600, secs % 3600 / 60)
+}
+
+/// Text of a value in a CSV field, or None for NULL.
+fn csv_text(value: &RowValue, bytes: BytesFormat) -> Option<String> {
+    Some(match value {
+        RowValue::Null => return None,
+        RowValue::Bool(b) => b.to_string(),
+        RowValue::Int(i) => i.to_string(),
+        // Debug keeps the ".0" of whole floats, as Python's str() does
+        RowValue::Float(f) => format!("{:?}", f),
+        RowValue::String(s) | RowValue::Decimal(s) => s.clone(),
+        RowValue::SharedString(s) => s.to_string(),
+        RowValue::Uuid(u) => format_uuid(u),
+        RowValue::Bytes(b) => match bytes {
+            BytesFormat::Base64 => BASE64_STANDARD.encode(b),
+            BytesFormat::Raw | BytesFormat::Hex => hex_string(b),
+        },
+        RowValue::Json(json) => json.to_string(),
+        RowValue::Array(_) | RowValue::Record(_) => row_value_json(value, bytes).to_string(),
+        RowValue::Date(i32::MAX) => "infinity".to_string(),
+        RowValue::Date(i32::MIN) => "-infinity".to_string(),
+        RowValue::Date(days) => match days_to_date(*days) {
+            Some(date) => date.format("%Y-%m-%d").to_string(),
+            None => days.to_string(),
+        },
+        RowValue::DateTime {
+            micros: i64::MAX, ..
+        } => "infinity".to_string(),
+        RowValue::DateTime {
+            micros: i64::MIN, ..
+        } => "-infinity".to_string(),
+        RowValue::DateTime { micros, utc } => match micros_to_datetime(*micros) {
+            Some(dt) if *utc => dt.format("%Y-%m-%dT%H:%M:%S%.f+00:00").to_string(),
+            Some(dt) => dt.format("%Y-%m-%dT%H:%M:%S%.f").to_string(),
+            None => micros.to_string(),
+        },
+        RowValue::Time(micros) => time_text(*micros),
+        RowValue::TimeDelta { days, micros } => interval_text(*days, *micros),
+        RowValue::TimeTz {
+            micros,
+            tz_offset_secs,
+        } => time_text(*micros) + &format_offset(*tz_offset_secs),
+        RowValue::Range {
+            lower,
+            upper,
+            lower_inc,
+            upper_inc,
+            empty,
+        } => {
+            if *empty {
+                return Some("empty".to_string());
+            }
+            let bound = |value: &Option<Box<RowValue>>| {
+                value
+                    .as_deref()
+                    .and_then(|v| csv_text(v, bytes))
+                    .unwrap_or_default()
+            };
+            format!(
+                "{}{},{}{}",
+                if *lower_inc { '[' } else { '(' },
+                bound(lower),
+                bound(upper),
+                if *upper_inc { ']' } else { ')' }
+            )
+        }
+    })
+}
+
+/// A value as JSON, for arrays and composites in CSV fields.
+fn row_value_json(value: &RowValue, bytes: BytesFormat) -> JsonValue {
+    match value {
+        RowValue::Null => JsonValue::Null,
+        RowValue::Bool(b) => JsonValue::Bool(*b),
+        RowValue::Int(i) => JsonValue::from(*i),
+        RowValue::Float(f) => serde_json::Number::from_f64(*f)
+            .map(JsonValue::Number)
+            .unwrap_or_else(|| JsonValue::String(format!("{:?}", f))),
+        RowValue::Json(json) => json.clone(),
+        RowValue::Array(items) | RowValue::Record(items) => {
+            JsonValue::Array(items.iter().map(|v| row_value_json(v, bytes)).collect())
+        }
+        other => csv_text(other, bytes).map_or(JsonValue::Null, JsonValue::String),
+    }
+}
+
+/// Infer the polars dtype name for a column, or None to let polars decide.
+fn polars_dtype<'a>(values: impl Iterator<Item = &'a RowValue>) -> Option<&'static str> {
+    let mut dtype = "Null";
+    for value in values {
+        let kind = match value {
+            RowValue::Null => continue,
+            RowValue::Bool(_) => "Boolean",
+            RowValue::Int(_) => "Int64",
+            RowValue::Float(_) => "Float64",
+            RowValue::String(_) | RowValue::SharedString(_) => "Utf8",
+            RowValue::Bytes(_) => "Binary",
+            RowValue::Json(_)
+            | RowValue::Decimal(_)
+            | RowValue::Uuid(_)
+            | RowValue::Array(_)
+            | RowValue::Record(_)
+            | RowValue::Date(_)
+            | RowValue::DateTime { .. }
+            | RowValue::Time(_)
+            | RowValue::TimeDelta { .. }
+            | RowValue::TimeTz { .. }
+            | RowValue::Range { .. } => return None,
+        };
+        dtype = match (dtype, kind) {
+            ("Null", kind) => kind,
+            (a, b) if a == b => a,
+            ("Int64", "Float64") | ("Float64", "Int64") => "Float64",
+            _ => "Object",
+        };
+    }
+    Some(dtype)
+}
+
+/// Approximate payload size of a value in bytes (excluding container overhead).
+fn row_value_size(value: &RowValue) -> usize {
+    match value {
+        RowValue::Null => 0,
+        RowValue::Bool(_) => 1,
+        RowValue::Int(_) | RowValue::Float(_) | RowValue::DateTime { .. } | RowValue::Time(_) => 8,
+        RowValue::Date(_) => 4,
+        RowValue::Uuid(_) => 16,
+        RowValue::TimeTz { .. } | RowValue::TimeDelta { .. } => 12,
+        RowValue::String(s) => s.len(),
+        RowValue::SharedString(s) => s.len(),
+        RowValue::Decimal(s) => s.len(),
+        RowValue::Bytes(b) => b.len(),
+        RowValue::Json(json) => json_value_size(json),
+        RowValue::Array(items) | RowValue::Record(items) => items.iter().map(row_value_size).sum(),
+        RowValue::Range { lower, upper, .. } => {
+            lower.as_deref().map_or(0, row_value_size) + upper.as_deref().map_or(0, row_value_size)
+        }
+    }
+}
+
+/// Approximate payload size of a JSON value in bytes.
+fn json_value_size(value: &JsonValue) -> usize {
+    match value {
+        JsonValue::Null => 0,
+        JsonValue::Bool(_) => 1,
+        JsonValue::Number(_) => 8,
+        JsonValue::String(s) => s.len(),
+        JsonValue::Array(items) => items.iter().map(json_value_size).sum(),
+        JsonValue::Object(map) => map.iter().map(|(k, v)| k.len() + json_value_size(v)).sum(),
     }
 }
 
@@ -129,6 +829,7 @@ fn row_to_dict<'py>(
     row: &LazyRow,
     cols: &[String],
     interned_cols: Option<&[Bound<'py, PyString>]>,
+    bytes: BytesFormat,
 ) -> PyResult<Bound<'py, PyDict>> {
     let dict = PyDict::new(py);
     let vals = &row.values;
@@ -137,13 +838,13 @@ fn row_to_dict<'py>(
     match interned_cols {
         Some(interned) => {
             for i in 0..len {
-                let py_val = row_value_to_py(py, unsafe { vals.get_unchecked(i) });
+                let py_val = row_value_to_py(py, unsafe { vals.get_unchecked(i) }, bytes);
                 dict.set_item(unsafe { interned.get_unchecked(i) }, py_val)?;
             }
         }
         None => {
             for i in 0..len {
-                let py_val = row_value_to_py(py, unsafe { vals.get_unchecked(i) });
+                let py_val = row_value_to_py(py, unsafe { vals.get_unchecked(i) }, bytes);
                 dict.set_item(unsafe { cols.get_unchecked(i) }, py_val)?;
             }
         }
@@ -151,6 +852,52 @@ fn row_to_dict<'py>(
     Ok(dict)
 }
 
+/// The error `QueryResult.one()` raises: an instance of the caller's
+/// exception class `exc` when given, otherwise `default`.
+fn row_count_error(
+    exc: Option<Bound<'_, PyAny>>,
+    default: fn(String) -> PyErr,
+    message: String,
+) -> PyResult<PyErr> {
+    match exc {
+        Some(exc) => Ok(PyErr::from_value(exc.call1((message,))?)),
+        None => Ok(default(message)),
+    }
+}
+
+/// Which columns `cls(**row)` accepts as keyword arguments.
+///
+/// Reads `inspect.signature(cls)`; a `**kwargs` parameter accepts every
+/// column.
+fn accepted_columns(
+    py: Python<'_>,
+    cls: &Bound<'_, PyAny>,
+    cols: &[String],
+) -> PyResult<Vec<bool>> {
+    let inspect = py.import("inspect")?;
+    let parameter = inspect.getattr("Parameter")?;
+    let var_keyword = parameter.getattr("VAR_KEYWORD")?;
+    let positional_only = parameter.getattr("POSITIONAL_ONLY")?;
+
+    let signature = inspect.call_method1("signature", (cls,))?;
+    let mut names = HashSet::new();
+    for param in signature
+        .getattr("parameters")?
+        .call_method0("values")?
+        .try_iter()?
+    {
+        let param = param?;
+        let kind = param.getattr("kind")?;
+        if kind.eq(&var_keyword)? {
+            return Ok(vec![true; cols.len()]);
+        }
+        if !kind.eq(&positional_only)? {
+            names.insert(param.getattr("name")?.extract::<String>()?);
+        }
+    }
+    Ok(cols.iter().map(|col| names.contains(col)).collect())
+}
+
 #[pymethods]
 impl QueryResult {
     /// Get all rows as a list of dictionaries - optimized
@@ -169,7 +916,7 @@ impl QueryResult {
         // Build all dicts
         let dicts: PyResult<Vec<Bound<'py, PyDict>>> = rows
             .iter()
-            .map(|row| row_to_dict(py, row, cols, Some(&interned_cols)))
+            .map(|row| row_to_dict(py, row, cols, Some(&interned_cols), self.bytes_format))
             .collect();
 
         PyList::new(py, dicts?)
@@ -179,30 +926,55 @@ impl QueryResult {
     #[inline]
     fn first<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
         if let Some(row) = self.rows.first() {
-            Ok(Some(row_to_dict(py, row, &self.columns, None)?))
+            Ok(Some(row_to_dict(
+                py,
+                row,
+                &self.columns,
+                None,
+                self.bytes_format,
+            )?))
         } else {
             Ok(None)
         }
     }
 
     /// Get a single row, raising error if not exactly one row
-    fn one<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
-        if self.rows.len() != 1 {
-            return Err(pyo3::exceptions::PyValueError::new_err(format!(
-                "Expected exactly 1 row, got {}",
-                self.rows.len()
-            )));
+    ///
+    /// Raises `NoResultFound` for an empty result and
+    /// `MultipleResultsFound` for more than one row (both ValueErrors).
+    /// `no_result_exc` and `multi_result_exc` name exception classes to
+    /// raise instead; each is called with the message.
+    #[pyo3(signature = (no_result_exc=None, multi_result_exc=None))]
+    fn one<'py>(
+        &self,
+        py: Python<'py>,
+        no_result_exc: Option<Bound<'py, PyAny>>,
+        multi_result_exc: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyDict>> {
+        let message = format!("Expected exactly 1 row, got {}", self.rows.len());
+        match self.rows.len() {
+            0 => Err(row_count_error(
+                no_result_exc,
+                NoResultFound::new_err,
+                message,
+            )?),
+            1 => Ok(self.first(py)?.expect("one row")),
+            _ => Err(row_count_error(
+                multi_result_exc,
+                MultipleResultsFound::new_err,
+                message,
+            )?),
         }
-        self.first(py)?
-            .ok_or_else(|| pyo3::exceptions::PyValueError::new_err("Expected exactly 1 row, got 0"))
     }
 
     /// Get a single row or None
+    ///
+    /// Raises `MultipleResultsFound` for more than one row.
     fn one_or_none<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyDict>>> {
         match self.rows.len() {
             0 => Ok(None),
             1 => self.first(py),
-            n => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            n => Err(MultipleResultsFound::new_err(format!(
                 "Expected at most 1 row, got {}",
                 n
             ))),
@@ -226,6 +998,35 @@ impl QueryResult {
         self.rows.len()
     }
 
+    /// Rows the statement affected, from PostgreSQL's command tag
+    ///
+    /// Set for INSERT, UPDATE, DELETE and MERGE (with or without
+    /// RETURNING), and for SELECT, where it matches `rowcount`. None for
+    /// statements that report no count, such as DDL, and on SQLite.
+    #[getter]
+    pub fn rowcount_affected(&self) -> Option<u64> {
+        self.rows_affected
+    }
+
+    /// Approximate size of the row data in bytes
+    ///
+    /// Sums the payload of every value: 8 bytes per int or float, 1 per
+    /// bool, the length of each string or bytes value, and the contents of
+    /// JSON and array values. An interned string is counted once however
+    /// many rows share it. Per-row and per-object overhead is not counted,
+    /// so this is a lower bound for logging and guards.
+    fn estimated_bytes(&self) -> usize {
+        let mut shared = HashSet::new();
+        self.rows
+            .iter()
+            .flat_map(|row| row.values.iter())
+            .map(|value| match value {
+                RowValue::SharedString(s) if !shared.insert(Arc::as_ptr(s)) => 0,
+                value => row_value_size(value),
+            })
+            .sum()
+    }
+
     /// Check if result is empty
     #[inline]
     fn is_empty(&self) -> bool {
@@ -243,6 +1044,7 @@ impl QueryResult {
             rows: Arc::clone(&self.rows),
             columns: Arc::clone(&self.columns),
             index: 0,
+            bytes_format: self.bytes_format,
         }
     }
 
@@ -257,8 +1059,11 @@ impl QueryResult {
         let tuples: PyResult<Vec<Bound<'py, PyTuple>>> = rows
             .iter()
             .map(|row| {
-                let values: Vec<PyObject> =
-                    row.values.iter().map(|v| row_value_to_py(py, v)).collect();
+                let values: Vec<PyObject> = row
+                    .values
+                    .iter()
+                    .map(|v| row_value_to_py(py, v, self.bytes_format))
+                    .collect();
                 PyTuple::new(py, values)
             })
             .collect();
@@ -278,7 +1083,7 @@ impl QueryResult {
             .map(|row| {
                 row.values
                     .get(col_idx)
-                    .map(|v| row_value_to_py(py, v))
+                    .map(|v| row_value_to_py(py, v, self.bytes_format))
                     .unwrap_or_else(|| py.None())
             })
             .collect();
@@ -310,7 +1115,7 @@ impl QueryResult {
                     .map(|&idx| {
                         row.values
                             .get(idx)
-                            .map(|v| row_value_to_py(py, v))
+                            .map(|v| row_value_to_py(py, v, self.bytes_format))
                             .unwrap_or_else(|| py.None())
                     })
                     .collect();
@@ -321,13 +1126,139 @@ impl QueryResult {
         PyList::new(py, tuples?)
     }
 
+    /// Group rows by a column: maps each distinct value to the list of row
+    /// dicts sharing it, in result order. NULLs are grouped under `None`.
+    fn group_by<'py>(&self, py: Python<'py>, column: &str) -> PyResult<Bound<'py, PyDict>> {
+        let col_idx = self
+            .columns
+            .iter()
+            .position(|c| c == column)
+            .ok_or_else(|| {
+                pyo3::exceptions::PyKeyError::new_err(format!("Column '{}' not found", column))
+            })?;
+
+        let cols = self.columns.as_ref();
+        let interned_cols: Vec<Bound<'py, PyString>> =
+            cols.iter().map(|c| PyString::intern(py, c)).collect();
+
+        let groups = PyDict::new(py);
+        for row in self.rows.iter() {
+            let key = row
+                .values
+                .get(col_idx)
+                .map(|v| row_value_to_py(py, v, self.bytes_format))
+                .unwrap_or_else(|| py.None());
+            let dict = row_to_dict(py, row, cols, Some(&interned_cols), self.bytes_format)?;
+            match groups.get_item(&key)? {
+                Some(group) => group.downcast::<PyList>()?.append(dict)?,
+                None => groups.set_item(key, PyList::new(py, [dict])?)?,
+            }
+        }
+
+        Ok(groups)
+    }
+
+    /// Build a `polars.DataFrame` from the result (requires polars).
+    ///
+    /// Each column becomes a Series whose dtype is inferred from its values:
+    /// NULLs are allowed anywhere, ints mixed with floats become Float64,
+    /// JSON and array columns are left to polars, and columns mixing other
+    /// types fall back to Object.
+    fn to_polars<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let polars = py.import("polars")?;
+
+        let series: PyResult<Vec<Bound<'py, PyAny>>> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| {
+                let values: Vec<PyObject> = self
+                    .rows
+                    .iter()
+                    .map(|row| {
+                        row.values
+                            .get(idx)
+                            .map(|v| row_value_to_py(py, v, self.bytes_format))
+                            .unwrap_or_else(|| py.None())
+                    })
+                    .collect();
+                let dtype = match polars_dtype(self.rows.iter().filter_map(|r| r.values.get(idx))) {
+                    Some(dtype) => polars.getattr(dtype)?.unbind(),
+                    None => py.None(),
+                };
+                polars.getattr("Series")?.call1((name, values, dtype))
+            })
+            .collect();
+
+        polars.getattr("DataFrame")?.call1((series?,))
+    }
+
+    /// Write the result as CSV to a file-like object with a `write` method
+    ///
+    /// Fields are formatted straight from the row data, without building
+    /// row dicts: NULL is an empty field (an empty string is `""`), bytes
+    /// are hex, or base64 under `bytes_format="base64"`, dates and
+    /// timestamps are ISO-8601, and arrays and JSON are JSON text. Lines end
+    /// with `\r\n`; open files with `newline=""`.
+    #[pyo3(signature = (writer, header=true, delimiter=","))]
+    fn to_csv(
+        &self,
+        py: Python<'_>,
+        writer: Bound<'_, PyAny>,
+        header: bool,
+        delimiter: &str,
+    ) -> PyResult<()> {
+        let mut chars = delimiter.chars();
+        let delimiter = match (chars.next(), chars.next()) {
+            (Some(c), None) if !matches!(c, '"' | '\n' | '\r') => c,
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(
+                    "delimiter must be a single character other than a quote or line break",
+                ))
+            }
+        };
+        let write = writer.getattr(intern!(py, "write"))?;
+
+        let mut buf = String::new();
+        if header {
+            for (i, name) in self.columns.iter().enumerate() {
+                if i > 0 {
+                    buf.push(delimiter);
+                }
+                write_field(&mut buf, Some(name), delimiter);
+            }
+            buf.push_str("\r\n");
+        }
+        for row in self.rows.iter() {
+            for (i, value) in row.values.iter().enumerate() {
+                if i > 0 {
+                    buf.push(delimiter);
+                }
+                write_field(
+                    &mut buf,
+                    csv_text(value, self.bytes_format).as_deref(),
+                    delimiter,
+                );
+            }
+            buf.push_str("\r\n");
+            if buf.len() >= CSV_CHUNK_BYTES {
+                write.call1((buf.as_str(),))?;
+                buf.clear();
+            }
+        }
+        if !buf.is_empty() {
+            write.call1((buf.as_str(),))?;
+        }
+        Ok(())
+    }
+
     /// Get a scalar value from first row, first column
     #[inline]
     fn scalar<'py>(&self, py: Python<'py>) -> PyObject {
         self.rows
             .first()
             .and_then(|row| row.values.first())
-            .map(|val| row_value_to_py(py, val))
+            .map(|val| row_value_to_py(py, val, self.bytes_format))
             .unwrap_or_else(|| py.None())
     }
 
@@ -339,7 +1270,7 @@ impl QueryResult {
             .map(|row| {
                 row.values
                     .first()
-                    .map(|val| row_value_to_py(py, val))
+                    .map(|val| row_value_to_py(py, val, self.bytes_format))
                     .unwrap_or_else(|| py.None())
             })
             .collect();
@@ -347,6 +1278,78 @@ impl QueryResult {
         PyList::new(py, values)
     }
 
+    /// Get the first column as a typed `array.array` when possible
+    ///
+    /// A column of only integers comes back as `array('q')` and one of only
+    /// floats as `array('d')`: one buffer instead of a Python object per
+    /// row. Any other column (NULLs, mixed or non-numeric values, or no
+    /// rows) falls back to the list `scalars()` returns.
+    fn scalars_typed<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let typecode = match self.rows.first().and_then(|row| row.values.first()) {
+            Some(RowValue::Int(_)) => "q",
+            Some(RowValue::Float(_)) => "d",
+            _ => return Ok(self.scalars(py)?.into_any()),
+        };
+
+        let mut buf = Vec::with_capacity(self.rows.len() * 8);
+        for row in self.rows.iter() {
+            match (typecode, row.values.first()) {
+                ("q", Some(RowValue::Int(i))) => buf.extend_from_slice(&i.to_ne_bytes()),
+                ("d", Some(RowValue::Float(f))) => buf.extend_from_slice(&f.to_ne_bytes()),
+                _ => return Ok(self.scalars(py)?.into_any()),
+            }
+        }
+
+        let array = py
+            .import(intern!(py, "array"))?
+            .getattr(intern!(py, "array"))?
+            .call1((typecode,))?;
+        array.call_method1(intern!(py, "frombytes"), (PyBytes::new(py, &buf),))?;
+        Ok(array)
+    }
+
+    /// Create an instance of a plain class per row, as `cls(**row)`
+    ///
+    /// Lighter than `to_models`: no ORM model or dataclass is needed, only
+    /// an `__init__` taking the column names as keyword arguments. When
+    /// `strict` is true a column `__init__` doesn't accept, or a required
+    /// parameter with no column, raises `TypeError`. When false, extra
+    /// columns are dropped (unless `__init__` takes `**kwargs`); missing
+    /// ones still fail unless the parameter has a default.
+    #[pyo3(signature = (cls, strict=true))]
+    fn to_objects<'py>(
+        &self,
+        py: Python<'py>,
+        cls: &Bound<'py, PyAny>,
+        strict: bool,
+    ) -> PyResult<Bound<'py, PyList>> {
+        let cols = self.columns.as_ref();
+        let keep = if strict {
+            None
+        } else {
+            Some(accepted_columns(py, cls, cols)?)
+        };
+        let interned_cols: Vec<Bound<'py, PyString>> =
+            cols.iter().map(|col| PyString::intern(py, col)).collect();
+
+        let mut objects: Vec<Bound<'py, PyAny>> = Vec::with_capacity(self.rows.len());
+        for row in self.rows.iter() {
+            let kwargs = PyDict::new(py);
+            for (i, value) in row.values.iter().enumerate().take(cols.len()) {
+                if keep.as_ref().is_some_and(|keep| !keep[i]) {
+                    continue;
+                }
+                kwargs.set_item(
+                    &interned_cols[i],
+                    row_value_to_py(py, value, self.bytes_format),
+                )?;
+            }
+            objects.push(cls.call((), Some(&kwargs))?);
+        }
+
+        PyList::new(py, objects)
+    }
+
     /// Create model instances using Python's _from_row_fast for proper JSON handling.
     /// This delegates to Python for type conversions (JSON deserialization, etc.)
     fn to_models<'py>(
@@ -378,7 +1381,7 @@ impl QueryResult {
             let len = cols.len().min(vals.len());
 
             for i in 0..len {
-                let py_val = row_value_to_py(py, &vals[i]);
+                let py_val = row_value_to_py(py, &vals[i], self.bytes_format);
                 dict.set_item(&interned_cols[i], py_val)?;
             }
 
@@ -413,7 +1416,7 @@ impl QueryResult {
 
         for i in 0..len {
             let col = &cols[i];
-            let py_val = row_value_to_py(py, &vals[i]);
+            let py_val = row_value_to_py(py, &vals[i], self.bytes_format);
             dict.set_item(PyString::intern(py, col), py_val)?;
         }
 
@@ -429,6 +1432,7 @@ pub struct QueryResultIter {
     rows: SharedRows,
     columns: Arc<Vec<String>>,
     index: usize,
+    bytes_format: BytesFormat,
 }
 
 #[pymethods]
@@ -442,7 +1446,7 @@ impl QueryResultIter {
             let row = &self.rows[self.index];
             self.index += 1;
 
-            let dict = row_to_dict(py, row, &self.columns, None)?;
+            let dict = row_to_dict(py, row, &self.columns, None, self.bytes_format)?;
             Ok(Some(dict.into()))
         } else {
             Ok(None)
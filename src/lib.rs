@@ -1,31 +1,103 @@
 use pyo3::prelude::*;
 
+mod csv;
 mod error;
 mod executor;
+mod params;
 mod pg;
 mod pool;
 mod schema;
+mod sql;
 mod sqlite;
+mod typed;
 
 // No more sqlx types module - we use our own drivers
 
-use executor::QueryResult;
-use pool::{ConnectionPool, PoolConfig, Transaction};
-use schema::{ColumnInfo, ConstraintInfo, IndexInfo, TableInfo};
+use executor::{BytesFormat, ColumnCase, QueryResult, Range, RowFactory};
+use pool::{
+    ConnectionPool, CopyOutStream, Listener, NestedTransaction, PoolConfig, PreparedQuery,
+    RowStream, Session, Transaction,
+};
+use schema::{
+    BlockedQuery, ColumnInfo, ConstraintInfo, EnumInfo, ForeignKeyViolation, IndexColumn,
+    IndexInfo, IndexStats, TableInfo, TableSize,
+};
 
 /// Create a new database connection pool
 #[pyfunction]
-#[pyo3(signature = (url, min_connections=1, max_connections=10))]
+#[pyo3(signature = (
+    url,
+    min_connections=1,
+    max_connections=10,
+    test_before_acquire=false,
+    validation_query=None,
+    heartbeat_interval=None,
+    statement_timeout=None,
+    statement_cache_memory_limit=None,
+    column_case="preserve",
+    default_row_factory=None,
+    bytes_format="raw",
+    intern_strings=false,
+    uuid_as_object=false,
+    reset_on_return=false,
+    acquire_timeout=None,
+    empty_string_as_null=false,
+    pipeline_depth=1000,
+))]
+#[allow(clippy::too_many_arguments)] // one per keyword argument
 fn create_pool<'py>(
     py: Python<'py>,
     url: String,
     min_connections: u32,
     max_connections: u32,
+    test_before_acquire: bool,
+    validation_query: Option<String>,
+    heartbeat_interval: Option<f64>,
+    statement_timeout: Option<f64>,
+    statement_cache_memory_limit: Option<usize>,
+    column_case: &str,
+    default_row_factory: Option<Bound<'py, PyAny>>,
+    bytes_format: &str,
+    intern_strings: bool,
+    uuid_as_object: bool,
+    reset_on_return: bool,
+    acquire_timeout: Option<f64>,
+    empty_string_as_null: bool,
+    pipeline_depth: usize,
 ) -> PyResult<Bound<'py, PyAny>> {
+    let heartbeat_interval = heartbeat_interval
+        .map(|secs| positive_duration("heartbeat_interval", secs))
+        .transpose()?;
+    let statement_timeout = statement_timeout
+        .map(|secs| positive_duration("statement_timeout", secs))
+        .transpose()?;
+    let acquire_timeout = acquire_timeout
+        .map(|secs| positive_duration("acquire_timeout", secs))
+        .transpose()?;
+    let column_case = ColumnCase::from_name(column_case)?;
+    let bytes_format = BytesFormat::from_name(bytes_format)?;
+    let default_row_factory = default_row_factory
+        .as_ref()
+        .map(RowFactory::from_py)
+        .transpose()?;
     let config = PoolConfig {
         url,
         min_connections,
         max_connections,
+        test_before_acquire,
+        validation_query,
+        heartbeat_interval,
+        statement_timeout,
+        statement_cache_memory_limit,
+        reset_on_return,
+        acquire_timeout,
+        empty_string_as_null,
+        pipeline_depth,
+        column_case,
+        bytes_format,
+        intern_strings,
+        uuid_as_object,
+        default_row_factory,
     };
 
     pyo3_async_runtimes::tokio::future_into_py(py, async move {
@@ -36,17 +108,93 @@ fn create_pool<'py>(
     })
 }
 
+/// Parse a positive number of seconds for the keyword argument `name`.
+fn positive_duration(name: &str, secs: f64) -> PyResult<std::time::Duration> {
+    match std::time::Duration::try_from_secs_f64(secs) {
+        Ok(duration) if !duration.is_zero() => Ok(duration),
+        _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "{} must be a positive number of seconds",
+            name
+        ))),
+    }
+}
+
 /// OrmKit - A blazingly fast Python ORM powered by Rust
 #[pymodule]
 fn _ormkit(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(create_pool, m)?)?;
+    m.add_function(wrap_pyfunction!(sql::py_quote_ident, m)?)?;
+    m.add_function(wrap_pyfunction!(sql::py_quote_literal, m)?)?;
     m.add_class::<ConnectionPool>()?;
     m.add_class::<QueryResult>()?;
+    m.add_class::<Range>()?;
     m.add_class::<Transaction>()?;
+    m.add_class::<NestedTransaction>()?;
+    m.add_class::<Session>()?;
+    m.add_class::<Listener>()?;
+    m.add_class::<RowStream>()?;
+    m.add_class::<CopyOutStream>()?;
+    m.add_class::<PreparedQuery>()?;
     // Schema introspection types
     m.add_class::<ColumnInfo>()?;
     m.add_class::<IndexInfo>()?;
     m.add_class::<ConstraintInfo>()?;
     m.add_class::<TableInfo>()?;
+    m.add_class::<ForeignKeyViolation>()?;
+    m.add_class::<BlockedQuery>()?;
+    m.add_class::<IndexStats>()?;
+    m.add_class::<IndexColumn>()?;
+    m.add_class::<EnumInfo>()?;
+    m.add_class::<TableSize>()?;
+    // Typed parameter wrappers
+    m.add_class::<typed::Int2>()?;
+    m.add_class::<typed::Int4>()?;
+    m.add_class::<typed::Int8>()?;
+    m.add_class::<typed::Float4>()?;
+    m.add_class::<typed::Float8>()?;
+    m.add_class::<typed::Numeric>()?;
+    m.add_class::<typed::Text>()?;
+    m.add_class::<typed::Jsonb>()?;
+    // Exceptions
+    m.add(
+        "PoolClosedError",
+        m.py().get_type::<error::PoolClosedError>(),
+    )?;
+    m.add(
+        "PoolTimeoutError",
+        m.py().get_type::<error::PoolTimeoutError>(),
+    )?;
+    m.add("DatabaseError", m.py().get_type::<error::DatabaseError>())?;
+    m.add("IntegrityError", m.py().get_type::<error::IntegrityError>())?;
+    m.add(
+        "UniqueViolationError",
+        m.py().get_type::<error::UniqueViolationError>(),
+    )?;
+    m.add(
+        "ForeignKeyViolationError",
+        m.py().get_type::<error::ForeignKeyViolationError>(),
+    )?;
+    m.add(
+        "NotNullViolationError",
+        m.py().get_type::<error::NotNullViolationError>(),
+    )?;
+    m.add(
+        "CheckViolationError",
+        m.py().get_type::<error::CheckViolationError>(),
+    )?;
+    m.add(
+        "OperationalError",
+        m.py().get_type::<error::OperationalError>(),
+    )?;
+    m.add(
+        "ProgrammingError",
+        m.py().get_type::<error::ProgrammingError>(),
+    )?;
+    m.add("DataError", m.py().get_type::<error::DataError>())?;
+    m.add("NoResultFound", m.py().get_type::<error::NoResultFound>())?;
+    m.add(
+        "MultipleResultsFound",
+        m.py().get_type::<error::MultipleResultsFound>(),
+    )?;
     Ok(())
 }
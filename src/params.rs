@@ -0,0 +1,156 @@
+//! Named parameter support.
+//!
+//! Rewrites `:name` placeholders into the positional form each driver
+//! understands (`$1` for PostgreSQL, `?1` for SQLite), skipping string
+//! literals, quoted identifiers, comments and `::` casts.
+
+/// Positional placeholder style to rewrite named parameters into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceholderStyle {
+    /// PostgreSQL: `$1`, `$2`, ...
+    Dollar,
+    /// SQLite: `?1`, `?2`, ...
+    Question,
+}
+
+/// Rewrite `:name` placeholders to positional ones.
+///
+/// Returns the rewritten SQL and the parameter names in positional order.
+/// A name used more than once maps to the same position.
+pub fn rewrite_named_params(sql: &str, style: PlaceholderStyle) -> (String, Vec<String>) {
+    let bytes = sql.as_bytes();
+    let mut out = String::with_capacity(sql.len());
+    let mut names: Vec<String> = Vec::new();
+    let mut i = 0;
+    // Start of the span not yet copied to `out`
+    let mut copied = 0;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            // String literal or quoted identifier: skip to the closing quote
+            // (a doubled quote is an escaped quote and is skipped the same way)
+            quote @ (b'\'' | b'"') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += 1;
+                }
+                i += 1;
+            }
+            // Line comment
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                while i < bytes.len() && bytes[i] != b'\n' {
+                    i += 1;
+                }
+            }
+            // Block comment
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i += 2;
+                while i < bytes.len() && !(bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/')) {
+                    i += 1;
+                }
+                i += 2;
+            }
+            // PostgreSQL dollar-quoted string: $tag$ ... $tag$
+            b'$' if style == PlaceholderStyle::Dollar => {
+                let tag_end = bytes[i + 1..]
+                    .iter()
+                    .position(|&b| !(b.is_ascii_alphanumeric() || b == b'_'))
+                    .map(|p| i + 1 + p);
+                match tag_end {
+                    Some(end) if bytes[end] == b'$' && !bytes[i + 1].is_ascii_digit() => {
+                        let tag = &sql[i..=end];
+                        i = sql[end + 1..]
+                            .find(tag)
+                            .map(|p| end + 1 + p + tag.len())
+                            .unwrap_or(bytes.len());
+                    }
+                    _ => i += 1,
+                }
+            }
+            // `::` cast
+            b':' if bytes.get(i + 1) == Some(&b':') => i += 2,
+            b':' if bytes
+                .get(i + 1)
+                .is_some_and(|&b| b.is_ascii_alphabetic() || b == b'_') =>
+            {
+                let start = i + 1;
+                let mut end = start;
+                while end < bytes.len()
+                    && (bytes[end].is_ascii_alphanumeric() || bytes[end] == b'_')
+                {
+                    end += 1;
+                }
+                let name = &sql[start..end];
+                let position = match names.iter().position(|n| n == name) {
+                    Some(p) => p + 1,
+                    None => {
+                        names.push(name.to_string());
+                        names.len()
+                    }
+                };
+
+                out.push_str(&sql[copied..i]);
+                out.push(match style {
+                    PlaceholderStyle::Dollar => '$',
+                    PlaceholderStyle::Question => '?',
+                });
+                out.push_str(&position.to_string());
+                i = end;
+                copied = end;
+            }
+            _ => i += 1,
+        }
+    }
+
+    out.push_str(&sql[copied.min(sql.len())..]);
+    (out, names)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_dollar() {
+        let (sql, names) = rewrite_named_params("SELECT :a + :b", PlaceholderStyle::Dollar);
+        assert_eq!(sql, "SELECT $1 + $2");
+        assert_eq!(names, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_rewrite_question() {
+        let (sql, names) = rewrite_named_params(
+            "SELECT * FROM t WHERE a = :a AND b = :b",
+            PlaceholderStyle::Question,
+        );
+        assert_eq!(sql, "SELECT * FROM t WHERE a = ?1 AND b = ?2");
+        assert_eq!(names, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_repeated_name_reuses_position() {
+        let (sql, names) = rewrite_named_params("SELECT :x, :y, :x", PlaceholderStyle::Dollar);
+        assert_eq!(sql, "SELECT $1, $2, $1");
+        assert_eq!(names, vec!["x", "y"]);
+    }
+
+    #[test]
+    fn test_skips_casts_literals_and_comments() {
+        let (sql, names) = rewrite_named_params(
+            "SELECT :a::int, ':b', \":c\", $$ :d $$ -- :e\n/* :f */ FROM t",
+            PlaceholderStyle::Dollar,
+        );
+        assert_eq!(
+            sql,
+            "SELECT $1::int, ':b', \":c\", $$ :d $$ -- :e\n/* :f */ FROM t"
+        );
+        assert_eq!(names, vec!["a"]);
+    }
+
+    #[test]
+    fn test_no_named_params() {
+        let (sql, names) = rewrite_named_params("SELECT $1, 'it''s'", PlaceholderStyle::Dollar);
+        assert_eq!(sql, "SELECT $1, 'it''s'");
+        assert!(names.is_empty());
+    }
+}
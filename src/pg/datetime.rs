@@ -0,0 +1,643 @@
+//! Text-format date and interval decoding, and the ISO text sent in
+//! COPY data.
+//!
+//! The binary format is style-independent, but text output (the simple
+//! query protocol) follows the server's `DateStyle` and `IntervalStyle`
+//! settings. Both are reported in ParameterStatus messages at startup and
+//! whenever they change.
+//! Reference: https://www.postgresql.org/docs/current/datatype-datetime.html#DATATYPE-DATETIME-OUTPUT
+
+use chrono::{NaiveDate, NaiveDateTime};
+
+use super::error::{PgError, PgResult};
+
+// ============================================================================
+// DateStyle
+// ============================================================================
+
+/// Date output format (first part of `DateStyle`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateFormat {
+    /// `2024-03-15`
+    Iso,
+    /// `03/15/2024` or `15/03/2024`
+    Sql,
+    /// `15.03.2024`
+    German,
+    /// `03-15-2024` or `15-03-2024`
+    Postgres,
+}
+
+/// Day/month order (second part of `DateStyle`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateOrder {
+    Mdy,
+    Dmy,
+    Ymd,
+}
+
+/// The server's `DateStyle` setting, e.g. `ISO, MDY`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DateStyle {
+    pub format: DateFormat,
+    pub order: DateOrder,
+}
+
+impl Default for DateStyle {
+    fn default() -> Self {
+        Self {
+            format: DateFormat::Iso,
+            order: DateOrder::Mdy,
+        }
+    }
+}
+
+impl DateStyle {
+    /// Parse a `DateStyle` setting value.
+    pub fn from_setting(value: &str) -> Option<Self> {
+        let mut style = Self::default();
+        for part in value.split(',').map(str::trim) {
+            match part.to_ascii_uppercase().as_str() {
+                "ISO" => style.format = DateFormat::Iso,
+                "SQL" => style.format = DateFormat::Sql,
+                "GERMAN" => style.format = DateFormat::German,
+                "POSTGRES" => style.format = DateFormat::Postgres,
+                "MDY" => style.order = DateOrder::Mdy,
+                "DMY" => style.order = DateOrder::Dmy,
+                "YMD" => style.order = DateOrder::Ymd,
+                _ => return None,
+            }
+        }
+        Some(style)
+    }
+
+    /// Decode a text `date` into days since 2000-01-01 (the binary encoding).
+    ///
+    /// `infinity` and `-infinity` map to `i32::MAX` and `i32::MIN`, as in
+    /// the binary format.
+    pub fn decode_date(&self, text: &str) -> PgResult<i32> {
+        match text {
+            "infinity" => return Ok(i32::MAX),
+            "-infinity" => return Ok(i32::MIN),
+            _ => {}
+        }
+
+        let invalid = || PgError::Type(format!("Invalid DATE: {}", text));
+        let (date, bc) = match text.strip_suffix(" BC") {
+            Some(date) => (date, true),
+            None => (text, false),
+        };
+
+        let (separator, fields) = match self.format {
+            DateFormat::Iso => ('-', [0, 1, 2]),
+            DateFormat::German => ('.', [2, 1, 0]),
+            // SQL and Postgres styles only distinguish DMY from everything else
+            DateFormat::Sql | DateFormat::Postgres => {
+                let separator = if self.format == DateFormat::Sql {
+                    '/'
+                } else {
+                    '-'
+                };
+                match self.order {
+                    DateOrder::Dmy => (separator, [2, 1, 0]),
+                    DateOrder::Mdy | DateOrder::Ymd => (separator, [2, 0, 1]),
+                }
+            }
+        };
+
+        let parts: Vec<&str> = date.split(separator).collect();
+        if parts.len() != 3 {
+            return Err(invalid());
+        }
+        let [y, m, d] = fields;
+        let mut year: i32 = parts[y].parse().map_err(|_| invalid())?;
+        let month: u32 = parts[m].parse().map_err(|_| invalid())?;
+        let day: u32 = parts[d].parse().map_err(|_| invalid())?;
+        if bc {
+            // 1 BC is year 0
+            year = 1 - year;
+        }
+
+        let date = NaiveDate::from_ymd_opt(year, month, day).ok_or_else(invalid)?;
+        Ok(date_to_days(date))
+    }
+}
+
+/// The epoch of the binary date and timestamp encodings.
+fn pg_epoch() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2000, 1, 1).unwrap()
+}
+
+/// Days since 2000-01-01, as in the binary `date` encoding.
+pub fn date_to_days(date: NaiveDate) -> i32 {
+    (date - pg_epoch()).num_days() as i32
+}
+
+/// The date `days` after 2000-01-01, or None outside chrono's range (which
+/// includes the `infinity` sentinels).
+pub fn days_to_date(days: i32) -> Option<NaiveDate> {
+    pg_epoch().checked_add_signed(chrono::Duration::try_days(days as i64)?)
+}
+
+/// Microseconds since 2000-01-01 00:00:00, as in the binary `timestamp`
+/// encoding.
+pub fn datetime_to_micros(value: NaiveDateTime) -> i64 {
+    (value - pg_epoch().and_hms_opt(0, 0, 0).unwrap())
+        .num_microseconds()
+        .expect("chrono datetimes are within i64 microseconds of 2000")
+}
+
+/// The timestamp `micros` after 2000-01-01 00:00:00, or None outside
+/// chrono's range.
+pub fn micros_to_datetime(micros: i64) -> Option<NaiveDateTime> {
+    pg_epoch()
+        .and_hms_opt(0, 0, 0)?
+        .checked_add_signed(chrono::Duration::microseconds(micros))
+}
+
+/// ISO text for a binary `date`, e.g. `2024-03-15` or `0044-03-15 BC`.
+pub fn format_date(days: i32) -> Option<String> {
+    match days {
+        i32::MAX => Some("infinity".to_string()),
+        i32::MIN => Some("-infinity".to_string()),
+        _ => {
+            let (date, era) = split_era(days_to_date(days)?);
+            Some(format!("{}{}", date, era))
+        }
+    }
+}
+
+/// ISO text for a binary `timestamp`, e.g. `2024-03-15 04:05:06.000000`.
+/// `utc` appends the `+00` offset of a `timestamptz`.
+pub fn format_timestamp(micros: i64, utc: bool) -> Option<String> {
+    match micros {
+        i64::MAX => Some("infinity".to_string()),
+        i64::MIN => Some("-infinity".to_string()),
+        _ => {
+            let value = micros_to_datetime(micros)?;
+            let (date, era) = split_era(value.date());
+            let offset = if utc { "+00" } else { "" };
+            Some(format!(
+                "{} {}{}{}",
+                date,
+                value.time().format("%H:%M:%S%.6f"),
+                offset,
+                era
+            ))
+        }
+    }
+}
+
+/// Text for a binary `time`, e.g. `04:05:06.000000`; `24:00:00` is allowed.
+pub fn format_time(micros: i64) -> String {
+    let secs = micros / 1_000_000;
+    format!(
+        "{:02}:{:02}:{:02}.{:06}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        micros % 1_000_000
+    )
+}
+
+/// Text for a UTC offset in seconds east of UTC, e.g. `+05:30:00`.
+pub fn format_utc_offset(secs: i32) -> String {
+    let sign = if secs < 0 { '-' } else { '+' };
+    let secs = secs.unsigned_abs();
+    format!(
+        "{}{:02}:{:02}:{:02}",
+        sign,
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60
+    )
+}
+
+/// `YYYY-MM-DD` and the era suffix; PostgreSQL writes years before 1 AD
+/// as positive years followed by ` BC`.
+fn split_era(date: NaiveDate) -> (String, &'static str) {
+    use chrono::Datelike;
+
+    let (year, era) = if date.year() <= 0 {
+        (1 - date.year(), " BC")
+    } else {
+        (date.year(), "")
+    };
+    (
+        format!("{:04}-{:02}-{:02}", year, date.month(), date.day()),
+        era,
+    )
+}
+
+// ============================================================================
+// IntervalStyle
+// ============================================================================
+
+/// The server's `IntervalStyle` setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IntervalStyle {
+    /// `1 year 2 mons 3 days 04:05:06`
+    #[default]
+    Postgres,
+    /// `@ 1 year 2 mons 3 days 4 hours 5 mins 6 secs`
+    PostgresVerbose,
+    /// `1-2 3 4:05:06`
+    SqlStandard,
+    /// `P1Y2M3DT4H5M6S`
+    Iso8601,
+}
+
+/// An interval in its binary representation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Interval {
+    pub months: i32,
+    pub days: i32,
+    pub microseconds: i64,
+}
+
+impl IntervalStyle {
+    /// Parse an `IntervalStyle` setting value.
+    pub fn from_setting(value: &str) -> Option<Self> {
+        match value {
+            "postgres" => Some(IntervalStyle::Postgres),
+            "postgres_verbose" => Some(IntervalStyle::PostgresVerbose),
+            "sql_standard" => Some(IntervalStyle::SqlStandard),
+            "iso_8601" => Some(IntervalStyle::Iso8601),
+            _ => None,
+        }
+    }
+
+    /// Decode a text `interval`.
+    pub fn decode_interval(&self, text: &str) -> PgResult<Interval> {
+        let parsed = match self {
+            IntervalStyle::Postgres | IntervalStyle::PostgresVerbose => parse_postgres(text),
+            IntervalStyle::SqlStandard => parse_sql_standard(text),
+            IntervalStyle::Iso8601 => parse_iso8601(text),
+        };
+        parsed.ok_or_else(|| PgError::Type(format!("Invalid INTERVAL: {}", text)))
+    }
+}
+
+impl Interval {
+    /// Format as an ISO 8601 duration, e.g. `P1Y2M3DT4H5M6.5S`.
+    pub fn to_iso8601(self) -> String {
+        if self == Interval::default() {
+            return "PT0S".to_string();
+        }
+
+        let mut out = String::from("P");
+        let (years, months) = (self.months / 12, self.months % 12);
+        for (value, unit) in [(years, 'Y'), (months, 'M'), (self.days, 'D')] {
+            if value != 0 {
+                out.push_str(&format!("{}{}", value, unit));
+            }
+        }
+
+        if self.microseconds != 0 {
+            out.push('T');
+            let hours = self.microseconds / 3_600_000_000;
+            let minutes = self.microseconds / 60_000_000 % 60;
+            let micros = self.microseconds % 60_000_000;
+            for (value, unit) in [(hours, 'H'), (minutes, 'M')] {
+                if value != 0 {
+                    out.push_str(&format!("{}{}", value, unit));
+                }
+            }
+            if micros != 0 {
+                let sign = if micros < 0 { "-" } else { "" };
+                let (secs, frac) = (micros.abs() / 1_000_000, micros.abs() % 1_000_000);
+                if frac == 0 {
+                    out.push_str(&format!("{}{}S", sign, secs));
+                } else {
+                    let frac = format!("{:06}", frac);
+                    out.push_str(&format!("{}{}.{}S", sign, secs, frac.trim_end_matches('0')));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Parse a signed `[-]h:mm:ss[.ffffff]` time into microseconds.
+fn parse_time(text: &str) -> Option<i64> {
+    let (negative, text) = split_sign(text);
+    let mut parts = text.splitn(3, ':');
+    let hours: i64 = parts.next()?.parse().ok()?;
+    let minutes: i64 = parts.next()?.parse().ok()?;
+    let seconds = parts.next().map(parse_seconds).unwrap_or(Some(0))?;
+    let micros = (hours * 3600 + minutes * 60) * 1_000_000 + seconds;
+    Some(if negative { -micros } else { micros })
+}
+
+/// Parse unsigned `ss[.ffffff]` seconds into microseconds.
+fn parse_seconds(text: &str) -> Option<i64> {
+    let (whole, frac) = text.split_once('.').unwrap_or((text, ""));
+    let whole: i64 = whole.parse().ok()?;
+    if frac.len() > 6 || !frac.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let frac: i64 = format!("{:0<6}", frac).parse().ok()?;
+    Some(whole * 1_000_000 + frac)
+}
+
+/// Parse signed seconds (`-6.5`) into microseconds.
+fn parse_signed_seconds(text: &str) -> Option<i64> {
+    let (negative, text) = split_sign(text);
+    let micros = parse_seconds(text)?;
+    Some(if negative { -micros } else { micros })
+}
+
+/// Strip a leading `+` or `-`, returning whether it was negative.
+fn split_sign(text: &str) -> (bool, &str) {
+    match text.as_bytes().first() {
+        Some(b'-') => (true, &text[1..]),
+        Some(b'+') => (false, &text[1..]),
+        _ => (false, text),
+    }
+}
+
+/// `postgres`: `-1 years +2 mons 3 days -04:05:06`, and `postgres_verbose`:
+/// `@ 1 year 2 mons 3 days 4 hours 5 mins 6 secs ago`.
+fn parse_postgres(text: &str) -> Option<Interval> {
+    let mut interval = Interval::default();
+    let mut tokens = text.split_whitespace().peekable();
+    let verbose = tokens.next_if_eq(&"@").is_some();
+    let mut ago = false;
+
+    while let Some(token) = tokens.next() {
+        if token.contains(':') {
+            interval.microseconds += parse_time(token)?;
+            continue;
+        }
+        if verbose && token == "ago" {
+            ago = true;
+            continue;
+        }
+
+        let unit = tokens.next()?;
+        let unit = unit.strip_suffix('s').unwrap_or(unit);
+        match unit {
+            "year" => interval.months += token.parse::<i32>().ok()? * 12,
+            "mon" => interval.months += token.parse::<i32>().ok()?,
+            "day" => interval.days += token.parse::<i32>().ok()?,
+            "hour" => interval.microseconds += token.parse::<i64>().ok()? * 3_600_000_000,
+            "min" => interval.microseconds += token.parse::<i64>().ok()? * 60_000_000,
+            "sec" => interval.microseconds += parse_signed_seconds(token)?,
+            _ => return None,
+        }
+    }
+
+    if ago {
+        interval = Interval {
+            months: -interval.months,
+            days: -interval.days,
+            microseconds: -interval.microseconds,
+        };
+    }
+    Some(interval)
+}
+
+/// `sql_standard`: `1-2 3 4:05:06`.
+///
+/// A leading `-` negates every field unless fields carry their own signs,
+/// which the server only writes (with explicit `+`) when signs are mixed.
+fn parse_sql_standard(text: &str) -> Option<Interval> {
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    let per_field = tokens.iter().any(|t| t.starts_with('+'));
+    let (negate_all, first) = match tokens.first() {
+        Some(first) if !per_field => split_sign(first),
+        Some(first) => (false, *first),
+        None => return None,
+    };
+
+    let mut interval = Interval::default();
+    for token in std::iter::once(first).chain(tokens[1..].iter().copied()) {
+        if token.contains(':') {
+            interval.microseconds += parse_time(token)?;
+        } else if let Some(pos) = token[1..].find('-') {
+            // Year-month: the sign (if any) applies to both parts
+            let (years, months) = (&token[..pos + 1], &token[pos + 2..]);
+            let (negative, years) = split_sign(years);
+            let total = years.parse::<i32>().ok()? * 12 + months.parse::<i32>().ok()?;
+            interval.months += if negative { -total } else { total };
+        } else {
+            interval.days += token.parse::<i32>().ok()?;
+        }
+    }
+
+    if negate_all {
+        interval = Interval {
+            months: -interval.months,
+            days: -interval.days,
+            microseconds: -interval.microseconds,
+        };
+    }
+    Some(interval)
+}
+
+/// `iso_8601`: `P1Y2M3DT4H5M6S`, each field optionally signed.
+fn parse_iso8601(text: &str) -> Option<Interval> {
+    let mut interval = Interval::default();
+    let mut rest = text.strip_prefix('P')?;
+    let mut in_time = false;
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('T') {
+            in_time = true;
+            rest = after;
+            continue;
+        }
+        let end = rest.find(|c: char| c.is_ascii_alphabetic())?;
+        let (value, unit) = (&rest[..end], rest.as_bytes()[end]);
+        rest = &rest[end + 1..];
+
+        match (in_time, unit) {
+            (false, b'Y') => interval.months += value.parse::<i32>().ok()? * 12,
+            (false, b'M') => interval.months += value.parse::<i32>().ok()?,
+            (false, b'W') => interval.days += value.parse::<i32>().ok()? * 7,
+            (false, b'D') => interval.days += value.parse::<i32>().ok()?,
+            (true, b'H') => interval.microseconds += value.parse::<i64>().ok()? * 3_600_000_000,
+            (true, b'M') => interval.microseconds += value.parse::<i64>().ok()? * 60_000_000,
+            (true, b'S') => interval.microseconds += parse_signed_seconds(value)?,
+            _ => return None,
+        }
+    }
+    Some(interval)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn style(value: &str) -> DateStyle {
+        DateStyle::from_setting(value).unwrap()
+    }
+
+    #[test]
+    fn test_date_style_setting() {
+        assert_eq!(style("ISO, MDY"), DateStyle::default());
+        assert_eq!(
+            style("German, DMY"),
+            DateStyle {
+                format: DateFormat::German,
+                order: DateOrder::Dmy,
+            }
+        );
+        assert!(DateStyle::from_setting("Klingon").is_none());
+    }
+
+    #[test]
+    fn test_decode_date_styles() {
+        // 2024-03-15 is 8840 days after 2000-01-01
+        assert_eq!(style("ISO, MDY").decode_date("2024-03-15").unwrap(), 8840);
+        assert_eq!(
+            style("German, DMY").decode_date("15.03.2024").unwrap(),
+            8840
+        );
+        assert_eq!(style("SQL, MDY").decode_date("03/15/2024").unwrap(), 8840);
+        assert_eq!(style("SQL, DMY").decode_date("15/03/2024").unwrap(), 8840);
+        assert_eq!(
+            style("Postgres, MDY").decode_date("03-15-2024").unwrap(),
+            8840
+        );
+        assert_eq!(
+            style("Postgres, DMY").decode_date("15-03-2024").unwrap(),
+            8840
+        );
+
+        // An ISO date is not valid German output
+        assert!(style("German, DMY").decode_date("2024-03-15").is_err());
+    }
+
+    #[test]
+    fn test_decode_date_special_values() {
+        let iso = DateStyle::default();
+        assert_eq!(iso.decode_date("2000-01-01").unwrap(), 0);
+        assert_eq!(iso.decode_date("infinity").unwrap(), i32::MAX);
+        assert_eq!(iso.decode_date("-infinity").unwrap(), i32::MIN);
+        // 0001-01-01 BC is proleptic year 0
+        let bc = iso.decode_date("0001-01-01 BC").unwrap();
+        let ad = iso.decode_date("0001-01-01").unwrap();
+        assert_eq!(ad - bc, 366);
+    }
+
+    #[test]
+    fn test_date_day_counts() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        assert_eq!(date_to_days(date(2000, 1, 1)), 0);
+        assert_eq!(date_to_days(date(1999, 12, 31)), -1);
+        assert_eq!(date_to_days(date(2024, 3, 15)), 8840);
+        assert_eq!(days_to_date(-1), Some(date(1999, 12, 31)));
+        assert_eq!(days_to_date(-730119), Some(date(1, 1, 1)));
+        assert_eq!(days_to_date(i32::MAX), None);
+        assert_eq!(days_to_date(i32::MIN), None);
+    }
+
+    #[test]
+    fn test_timestamp_micro_counts() {
+        let at = |y, mo, d, h, mi, s, us| {
+            NaiveDate::from_ymd_opt(y, mo, d)
+                .unwrap()
+                .and_hms_micro_opt(h, mi, s, us)
+                .unwrap()
+        };
+        assert_eq!(datetime_to_micros(at(2000, 1, 1, 0, 0, 0, 0)), 0);
+        assert_eq!(
+            datetime_to_micros(at(1999, 12, 31, 23, 59, 59, 500_000)),
+            -500_000
+        );
+        assert_eq!(
+            datetime_to_micros(at(2000, 1, 2, 0, 0, 1, 5)),
+            86_401_000_005
+        );
+        assert_eq!(
+            micros_to_datetime(-500_000),
+            Some(at(1999, 12, 31, 23, 59, 59, 500_000))
+        );
+        assert_eq!(micros_to_datetime(i64::MAX), None);
+    }
+
+    #[test]
+    fn test_decode_interval_styles() {
+        let expected = Interval {
+            months: 14,
+            days: 3,
+            microseconds: 14_706_500_000,
+        };
+        let cases = [
+            ("postgres", "1 year 2 mons 3 days 04:05:06.5"),
+            (
+                "postgres_verbose",
+                "@ 1 year 2 mons 3 days 4 hours 5 mins 6.5 secs",
+            ),
+            ("sql_standard", "1-2 3 4:05:06.5"),
+            ("iso_8601", "P1Y2M3DT4H5M6.5S"),
+        ];
+        for (setting, text) in cases {
+            let style = IntervalStyle::from_setting(setting).unwrap();
+            assert_eq!(
+                style.decode_interval(text).unwrap(),
+                expected,
+                "{}",
+                setting
+            );
+        }
+    }
+
+    #[test]
+    fn test_decode_negative_intervals() {
+        let expected = Interval {
+            months: -14,
+            days: 3,
+            microseconds: -14_706_000_000,
+        };
+        let cases = [
+            ("postgres", "-1 years -2 mons +3 days -04:05:06"),
+            (
+                "postgres_verbose",
+                "@ 1 year 2 mons -3 days 4 hours 5 mins 6 secs ago",
+            ),
+            ("sql_standard", "-1-2 +3 -4:05:06"),
+            ("iso_8601", "P-1Y-2M3DT-4H-5M-6S"),
+        ];
+        for (setting, text) in cases {
+            let style = IntervalStyle::from_setting(setting).unwrap();
+            assert_eq!(
+                style.decode_interval(text).unwrap(),
+                expected,
+                "{}",
+                setting
+            );
+        }
+
+        // All-negative sql_standard values carry a single leading sign
+        let all_negative = IntervalStyle::SqlStandard
+            .decode_interval("-1-2 3 4:05:06")
+            .unwrap();
+        assert_eq!(
+            all_negative,
+            Interval {
+                months: -14,
+                days: -3,
+                microseconds: -14_706_000_000,
+            }
+        );
+    }
+
+    #[test]
+    fn test_interval_to_iso8601() {
+        let interval = Interval {
+            months: 14,
+            days: 3,
+            microseconds: 14_706_500_000,
+        };
+        assert_eq!(interval.to_iso8601(), "P1Y2M3DT4H5M6.5S");
+        assert_eq!(Interval::default().to_iso8601(), "PT0S");
+        assert_eq!(
+            IntervalStyle::Iso8601
+                .decode_interval(&interval.to_iso8601())
+                .unwrap(),
+            interval
+        );
+    }
+}
@@ -1,5 +1,6 @@
 //! Error types for the PostgreSQL protocol implementation.
 
+use std::collections::HashMap;
 use std::fmt;
 use std::io;
 
@@ -19,13 +20,7 @@ pub enum PgError {
     Auth(String),
 
     /// Server returned an error.
-    Server {
-        severity: String,
-        code: String,
-        message: String,
-        detail: Option<String>,
-        hint: Option<String>,
-    },
+    Server(Box<ServerError>),
 
     /// Type conversion error.
     Type(String),
@@ -36,8 +31,55 @@ pub enum PgError {
     /// Statement not found in cache.
     StatementNotFound(String),
 
-    /// Timeout waiting for response.
+    /// Timed out waiting for a pooled connection.
     Timeout,
+
+    /// The connection pool has been closed.
+    PoolClosed,
+
+    /// A query returned more rows than the configured limit.
+    RowLimitExceeded(usize),
+
+    /// TLS negotiation or certificate loading failed.
+    Tls(String),
+
+    /// A parameter set doesn't match the statement's parameter count.
+    ParameterCount { expected: usize, given: usize },
+}
+
+/// Fields of an ErrorResponse from the server.
+///
+/// The optional object fields are only sent for errors tied to a specific
+/// schema object, such as constraint violations.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ServerError {
+    pub severity: String,
+    /// SQLSTATE code
+    pub code: String,
+    pub message: String,
+    pub detail: Option<String>,
+    pub hint: Option<String>,
+    pub schema: Option<String>,
+    pub table: Option<String>,
+    pub column: Option<String>,
+    pub constraint: Option<String>,
+    /// Id of the connection that received the error (see `PgConnection::id`)
+    pub connection_id: Option<u64>,
+    /// Every field the server sent, keyed by its one-character code
+    pub fields: HashMap<char, String>,
+}
+
+impl fmt::Display for ServerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} ({})", self.severity, self.message, self.code)?;
+        if let Some(d) = &self.detail {
+            write!(f, "\nDetail: {}", d)?;
+        }
+        if let Some(h) = &self.hint {
+            write!(f, "\nHint: {}", h)?;
+        }
+        Ok(())
+    }
 }
 
 impl fmt::Display for PgError {
@@ -46,28 +88,28 @@ impl fmt::Display for PgError {
             PgError::Io(e) => write!(f, "I/O error: {}", e),
             PgError::Protocol(msg) => write!(f, "Protocol error: {}", msg),
             PgError::Auth(msg) => write!(f, "Authentication failed: {}", msg),
-            PgError::Server {
-                severity,
-                code,
-                message,
-                detail,
-                hint,
-            } => {
-                write!(f, "{}: {} ({})", severity, message, code)?;
-                if let Some(d) = detail {
-                    write!(f, "\nDetail: {}", d)?;
-                }
-                if let Some(h) = hint {
-                    write!(f, "\nHint: {}", h)?;
-                }
-                Ok(())
-            }
+            PgError::Server(e) => e.fmt(f),
             PgError::Type(msg) => write!(f, "Type error: {}", msg),
             PgError::ConnectionClosed => write!(f, "Connection is closed"),
             PgError::StatementNotFound(name) => {
                 write!(f, "Prepared statement not found: {}", name)
             }
-            PgError::Timeout => write!(f, "Operation timed out"),
+            PgError::Timeout => write!(f, "Timed out waiting for a pooled connection"),
+            PgError::PoolClosed => write!(f, "Pool is closed"),
+            PgError::RowLimitExceeded(limit) => {
+                write!(f, "Query returned more than {} rows", limit)
+            }
+            PgError::Tls(msg) => write!(f, "TLS error: {}", msg),
+            PgError::ParameterCount { expected, given: 0 } => write!(
+                f,
+                "Statement expects {} parameter(s) but none were supplied",
+                expected
+            ),
+            PgError::ParameterCount { expected, given } => write!(
+                f,
+                "Statement expects {} parameter(s) but {} were supplied",
+                expected, given
+            ),
         }
     }
 }
@@ -0,0 +1,160 @@
+//! LISTEN/NOTIFY subscriptions.
+//!
+//! A listener owns a dedicated connection (never a pooled one, since
+//! subscriptions are per-session). If that connection drops, the listener
+//! reconnects, re-issues `LISTEN` for every channel and reports a
+//! `Reconnected` event: notifications sent during the gap are lost.
+
+use std::time::Duration;
+
+use super::connection::{Notification, PgConfig, PgConnection};
+use super::error::{PgError, PgResult};
+use crate::sql::{quote_ident, Dialect};
+
+/// Reconnection attempts before `recv` gives up.
+const RECONNECT_ATTEMPTS: u32 = 5;
+
+/// Delay before the first reconnection attempt; doubles after each failure.
+const RECONNECT_INITIAL_DELAY: Duration = Duration::from_millis(100);
+
+/// What a listener received.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenerEvent {
+    /// A notification on one of the subscribed channels
+    Notification(Notification),
+    /// The connection dropped and was re-established; notifications sent
+    /// while it was down were missed
+    Reconnected,
+}
+
+/// A LISTEN subscription on a dedicated connection.
+pub struct PgListener {
+    /// Configuration used to (re)connect
+    config: PgConfig,
+    /// The listening connection (None while disconnected)
+    conn: Option<PgConnection>,
+    /// Subscribed channels, re-issued after reconnecting
+    channels: Vec<String>,
+    /// Set by `close`, so `recv` fails rather than reconnecting
+    closed: bool,
+}
+
+impl PgListener {
+    /// Open a dedicated listening connection.
+    pub async fn connect(config: PgConfig) -> PgResult<Self> {
+        let conn = PgConnection::connect_with_config(config.clone()).await?;
+        Ok(Self {
+            config,
+            conn: Some(conn),
+            channels: Vec::new(),
+            closed: false,
+        })
+    }
+
+    /// Subscribe to a channel.
+    pub async fn listen(&mut self, channel: &str) -> PgResult<()> {
+        self.connection()?
+            .simple_query(&format!(
+                "LISTEN {}",
+                quote_ident(channel, Dialect::Postgres)
+            ))
+            .await?;
+        if !self.channels.iter().any(|c| c == channel) {
+            self.channels.push(channel.to_string());
+        }
+        Ok(())
+    }
+
+    /// Unsubscribe from a channel.
+    pub async fn unlisten(&mut self, channel: &str) -> PgResult<()> {
+        self.connection()?
+            .simple_query(&format!(
+                "UNLISTEN {}",
+                quote_ident(channel, Dialect::Postgres)
+            ))
+            .await?;
+        self.channels.retain(|c| c != channel);
+        Ok(())
+    }
+
+    /// Subscribed channels.
+    pub fn channels(&self) -> &[String] {
+        &self.channels
+    }
+
+    /// Backend PID of the listening connection, if connected.
+    pub fn backend_pid(&self) -> Option<i32> {
+        self.conn.as_ref().map(|c| c.backend_pid())
+    }
+
+    /// Wait for the next notification.
+    ///
+    /// If the connection has dropped, reconnects and re-subscribes first and
+    /// returns `ListenerEvent::Reconnected`. Fails only when reconnecting
+    /// does, or after `close`.
+    ///
+    /// Cancel-safe while waiting for a notification, so a caller can drop
+    /// it to run `listen` or `unlisten` and then call it again.
+    pub async fn recv(&mut self) -> PgResult<ListenerEvent> {
+        if self.closed {
+            return Err(PgError::ConnectionClosed);
+        }
+        if let Some(conn) = self.conn.as_mut() {
+            match conn.wait_for_notification().await {
+                Ok(notification) => return Ok(ListenerEvent::Notification(notification)),
+                // The listening connection only fails when it has been lost
+                Err(_) => self.conn = None,
+            }
+        }
+
+        self.reconnect().await?;
+        Ok(ListenerEvent::Reconnected)
+    }
+
+    /// Close the listening connection.
+    pub async fn close(&mut self) -> PgResult<()> {
+        self.closed = true;
+        match self.conn.take() {
+            Some(mut conn) => conn.close().await,
+            None => Ok(()),
+        }
+    }
+
+    fn connection(&mut self) -> PgResult<&mut PgConnection> {
+        self.conn.as_mut().ok_or(PgError::ConnectionClosed)
+    }
+
+    /// Reconnect with exponential backoff and re-issue every `LISTEN`.
+    async fn reconnect(&mut self) -> PgResult<()> {
+        let mut delay = RECONNECT_INITIAL_DELAY;
+        let mut last_error = PgError::ConnectionClosed;
+
+        for _ in 0..RECONNECT_ATTEMPTS {
+            match self.resubscribe().await {
+                Ok(conn) => {
+                    self.conn = Some(conn);
+                    return Ok(());
+                }
+                Err(e) => last_error = e,
+            }
+            tokio::time::sleep(delay).await;
+            delay *= 2;
+        }
+
+        Err(last_error)
+    }
+
+    /// Open a new connection subscribed to all current channels.
+    async fn resubscribe(&self) -> PgResult<PgConnection> {
+        let mut conn = PgConnection::connect_with_config(self.config.clone()).await?;
+        if !self.channels.is_empty() {
+            let sql: Vec<String> = self
+                .channels
+                .iter()
+                .map(|c| format!("LISTEN {}", quote_ident(c, Dialect::Postgres)))
+                .collect();
+            conn.simple_query(&sql.join("; ")).await?;
+        }
+        Ok(conn)
+    }
+}
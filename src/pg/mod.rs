@@ -9,15 +9,21 @@
 //! - `protocol`: Low-level wire protocol encoding/decoding
 //! - `connection`: Connection state machine and management
 //! - `types`: PostgreSQL type encoding/decoding
+//! - `datetime`: DateStyle/IntervalStyle-aware text decoding
 //! - `statement`: Prepared statement cache
 //! - `pool`: Connection pool with per-connection statement cache
+//! - `listener`: LISTEN/NOTIFY subscriptions with automatic reconnection
+//! - `tls`: TLS negotiation and client certificates
 
 pub mod connection;
+pub mod datetime;
 pub mod error;
+pub mod listener;
 pub mod pool;
 pub mod protocol;
 pub mod scram;
 pub mod statement;
+pub mod tls;
 pub mod types;
 
 #[cfg(test)]
@@ -25,11 +31,14 @@ mod tests;
 
 // Public API re-exports for library consumers
 #[allow(unused_imports)]
-pub use connection::PgConnection;
+pub use connection::{CancelToken, ConnectionMetrics, PgConnection};
 #[allow(unused_imports)]
-pub use error::{PgError, PgResult};
-pub use pool::{PgPool, PgPoolConfig, PooledConnection};
+pub use error::{PgError, PgResult, ServerError};
+pub use listener::{ListenerEvent, PgListener};
+pub use pool::{PgPool, PgPoolConfig, PgPreparedQuery, PooledConnection};
 #[allow(unused_imports)]
 pub use statement::{PreparedStatement, SharedColumns};
 #[allow(unused_imports)]
+pub use tls::{SslMode, TlsConfig};
+#[allow(unused_imports)]
 pub use types::{Oid, PgValue};
@@ -3,14 +3,25 @@
 //! This module provides a connection pool built on top of our custom
 //! PostgreSQL connection implementation.
 
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
+use std::time::Duration;
 
+use bytes::Bytes;
 use parking_lot::Mutex;
 use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
-use super::connection::{PgConfig, PgConnection, QueryResult};
+use super::connection::{
+    param_types_match, parse_query_params, CancelToken, ConnectionMetrics, PgConfig, PgConnection,
+    QueryResult, DEFAULT_PIPELINE_DEPTH,
+};
 use super::error::{PgError, PgResult};
-use super::types::PgValue;
+use super::listener::PgListener;
+use super::protocol::{TransactionStatus, DEFAULT_MAX_MESSAGE_SIZE};
+use super::statement::{is_schema_change, PreparedStatement};
+use super::tls::TlsConfig;
+use super::types::{Oid, PgValue};
 
 // ============================================================================
 // Pool Configuration
@@ -27,6 +38,28 @@ pub struct PgPoolConfig {
     pub max_connections: u32,
     /// Statement cache capacity per connection
     pub statement_cache_capacity: usize,
+    /// Soft cap on statement cache memory summed over connections, in bytes
+    pub statement_cache_memory_limit: Option<usize>,
+    /// Validate idle connections before handing them out
+    pub test_before_acquire: bool,
+    /// SQL used to validate a connection (default: protocol-level ping)
+    pub validation_query: Option<String>,
+    /// Maximum rows buffered by `simple_query` (default: unlimited)
+    pub simple_query_max_rows: Option<usize>,
+    /// Largest backend message accepted, in bytes (default: 1 GiB)
+    pub max_message_size: usize,
+    /// Interval between idle-connection heartbeats (default: disabled)
+    pub heartbeat_interval: Option<Duration>,
+    /// `statement_timeout` set on every new connection (default: server's)
+    pub statement_timeout: Option<Duration>,
+    /// Run `DISCARD ALL` on connections returned to the pool
+    pub reset_on_return: bool,
+    /// Longest `acquire` waits for a free connection (default: forever)
+    pub acquire_timeout: Option<Duration>,
+    /// Executions `execute_many` sends ahead of their replies (default: 1000)
+    pub pipeline_depth: usize,
+    /// TLS settings, replacing any `ssl*` parameters in the URL
+    pub tls: Option<TlsConfig>,
 }
 
 impl PgPoolConfig {
@@ -37,6 +70,17 @@ impl PgPoolConfig {
             min_connections: 1,
             max_connections: 10,
             statement_cache_capacity: 100,
+            statement_cache_memory_limit: None,
+            test_before_acquire: false,
+            validation_query: None,
+            simple_query_max_rows: None,
+            max_message_size: DEFAULT_MAX_MESSAGE_SIZE,
+            heartbeat_interval: None,
+            statement_timeout: None,
+            reset_on_return: false,
+            acquire_timeout: None,
+            pipeline_depth: DEFAULT_PIPELINE_DEPTH,
+            tls: None,
         }
     }
 
@@ -57,6 +101,192 @@ impl PgPoolConfig {
         self.statement_cache_capacity = capacity;
         self
     }
+
+    /// Cap the approximate memory of all connections' statement caches.
+    ///
+    /// When the pool's caches exceed the limit, the per-connection capacity
+    /// is halved until they fit, down to a single statement. Each
+    /// connection shrinks to the current capacity when it is next acquired,
+    /// so the cap is soft. Capacity is not raised again.
+    pub fn statement_cache_memory_limit(mut self, limit_bytes: Option<usize>) -> Self {
+        self.statement_cache_memory_limit = limit_bytes;
+        self
+    }
+
+    /// Validate idle connections before handing them out.
+    pub fn test_before_acquire(mut self, enabled: bool) -> Self {
+        self.test_before_acquire = enabled;
+        self
+    }
+
+    /// Set the SQL used to validate connections.
+    ///
+    /// Some proxies (e.g. pgbouncer in transaction mode) need a real query
+    /// rather than a protocol-level ping. Only used with `test_before_acquire`.
+    pub fn validation_query(mut self, query: Option<String>) -> Self {
+        self.validation_query = query;
+        self
+    }
+
+    /// Limit the size of a single message from the server.
+    ///
+    /// A corrupt or hostile length header fails with a protocol error
+    /// instead of buffering an arbitrarily large body.
+    pub fn max_message_size(mut self, max_bytes: usize) -> Self {
+        self.max_message_size = max_bytes;
+        self
+    }
+
+    /// Run `SELECT 1` on idle connections at this interval.
+    ///
+    /// Keeps connections alive behind proxies that drop idle sessions and
+    /// evicts dead ones before they are handed out. See `PgPool::heartbeat`.
+    pub fn heartbeat_interval(mut self, interval: Option<Duration>) -> Self {
+        self.heartbeat_interval = interval;
+        self
+    }
+
+    /// Limit the rows `simple_query` will buffer before failing.
+    ///
+    /// Guards multi-statement scripts against runaway result sets.
+    pub fn simple_query_max_rows(mut self, max_rows: Option<usize>) -> Self {
+        self.simple_query_max_rows = max_rows;
+        self
+    }
+
+    /// Bound every statement on the pool's connections.
+    ///
+    /// Runs `SET statement_timeout` when each connection is opened; a query
+    /// that runs longer is cancelled by the server with SQLSTATE 57014. A
+    /// session can still override it with its own `SET`.
+    pub fn statement_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.statement_timeout = timeout;
+        self
+    }
+
+    /// Reset every connection returned to the pool with `DISCARD ALL`.
+    ///
+    /// Keeps temp tables, `SET` values and other session state from leaking
+    /// to the next borrower. The reset runs when the connection is next
+    /// acquired and also empties its statement cache, so each borrow
+    /// prepares its statements afresh. Pool-level settings such as
+    /// `statement_timeout` are applied again afterwards.
+    pub fn reset_on_return(mut self, enabled: bool) -> Self {
+        self.reset_on_return = enabled;
+        self
+    }
+
+    /// Fail `acquire` with `PgError::Timeout` after waiting this long for
+    /// a free connection.
+    ///
+    /// Without it, `acquire` waits as long as every connection is checked
+    /// out. When unset, the URL's `pool_timeout` (or else `connect_timeout`)
+    /// parameter is used, in seconds.
+    pub fn acquire_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.acquire_timeout = timeout;
+        self
+    }
+
+    /// Limit how many executions a batch leaves unanswered.
+    ///
+    /// `execute_many` pipelines its executions; once this many are in
+    /// flight it reads replies before sending more, which bounds the
+    /// replies buffered on either side. Larger values save round trips on
+    /// high-latency links.
+    pub fn pipeline_depth(mut self, depth: usize) -> Self {
+        self.pipeline_depth = depth.max(1);
+        self
+    }
+
+    /// Connect with these TLS settings.
+    ///
+    /// Equivalent to the URL's `sslmode`, `sslrootcert`, `sslcert`, `sslkey`
+    /// and `sslpassword` parameters, which it replaces.
+    pub fn tls(mut self, tls: TlsConfig) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+}
+
+// ============================================================================
+// Prepared Query
+// ============================================================================
+
+/// A query prepared once per connection and executed without a statement
+/// cache lookup.
+///
+/// Server-side statements belong to one connection, so the handle remembers
+/// the statement it prepared on each pooled connection. The statements live
+/// in the connection's statement cache, which closes them on the server when
+/// it evicts them, so dropping the handle leaks nothing. On a connection it
+/// hasn't seen, one whose cache has dropped statements since, or for
+/// parameters of other types than last time, it looks the query up in the
+/// cache and prepares again only on a miss.
+pub struct PgPreparedQuery {
+    /// The SQL query text
+    sql: String,
+    /// Connection id → (epoch, statement prepared on it)
+    statements: Mutex<HashMap<u64, (StatementEpoch, Arc<PreparedStatement>)>>,
+}
+
+/// A connection's (schema generation, resets, statements displaced from its
+/// cache); statements prepared on it stay valid while this is unchanged.
+type StatementEpoch = (u64, u64, u64);
+
+fn statement_epoch(conn: &PgConnection) -> StatementEpoch {
+    (
+        conn.schema_generation,
+        conn.resets,
+        conn.statement_cache().displaced(),
+    )
+}
+
+impl PgPreparedQuery {
+    /// Create a handle; statements are prepared on first execution.
+    pub fn new(sql: &str) -> Self {
+        Self {
+            sql: sql.to_string(),
+            statements: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The SQL query text.
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// Number of connections the query is currently prepared on.
+    pub fn prepared_count(&self) -> usize {
+        self.statements.lock().len()
+    }
+
+    /// The statement prepared on a connection, if still valid there and
+    /// able to bind `params` as they are.
+    fn statement_for(
+        &self,
+        conn: &PgConnection,
+        params: &[PgValue],
+    ) -> Option<Arc<PreparedStatement>> {
+        self.statements
+            .lock()
+            .get(&conn.id())
+            .filter(|(epoch, stmt)| {
+                *epoch == statement_epoch(conn) && param_types_match(stmt, params)
+            })
+            .map(|(_, stmt)| Arc::clone(stmt))
+    }
+
+    /// Remember the statement prepared on a connection.
+    ///
+    /// Entries for connections that have since closed are never looked up
+    /// again, so the map is cleared once it outgrows the pool.
+    fn remember(&self, conn: &PgConnection, stmt: Arc<PreparedStatement>, max_entries: usize) {
+        let mut statements = self.statements.lock();
+        if statements.len() >= max_entries {
+            statements.clear();
+        }
+        statements.insert(conn.id(), (statement_epoch(conn), stmt));
+    }
 }
 
 // ============================================================================
@@ -73,24 +303,101 @@ pub struct PooledConnection {
     pool: Arc<PgPoolInner>,
     /// Semaphore permit (controls pool size)
     _permit: OwnedSemaphorePermit,
+    /// Set while `simple_query`, `query`, `execute_prepared`, `execute_many`
+    /// or a portal read waits on the server, and from the start of a COPY until it ends; still set on drop
+    /// means the caller gave up mid-query
+    in_flight: bool,
 }
 
 impl PooledConnection {
     /// Execute a simple query.
     pub async fn simple_query(&mut self, query: &str) -> PgResult<Vec<QueryResult>> {
-        self.conn
-            .as_mut()
-            .ok_or(PgError::ConnectionClosed)?
-            .simple_query(query)
-            .await
+        let conn = self.conn.as_mut().ok_or(PgError::ConnectionClosed)?;
+        self.in_flight = true;
+        let result = conn.simple_query(query).await;
+        self.in_flight = false;
+        self.invalidate_if_schema_change(query);
+        result
     }
 
     /// Execute a parameterized query.
+    ///
+    /// Dropping the future before it completes (e.g. when the awaiting task
+    /// is cancelled) cancels the query on the server; see `Drop`.
     pub async fn query(&mut self, query: &str, params: &[PgValue]) -> PgResult<QueryResult> {
+        let conn = self.conn.as_mut().ok_or(PgError::ConnectionClosed)?;
+        self.in_flight = true;
+        let result = conn.query(query, params).await;
+        self.in_flight = false;
+        self.invalidate_if_schema_change(query);
+        result
+    }
+
+    /// Get a token for cancelling this connection's queries from elsewhere.
+    pub fn cancel_token(&self) -> Option<CancelToken> {
+        self.conn.as_ref().map(|c| c.cancel_token())
+    }
+
+    /// Execute a prepared query handle, preparing it on this connection
+    /// first if needed.
+    pub async fn execute_prepared(
+        &mut self,
+        query: &PgPreparedQuery,
+        params: &[PgValue],
+    ) -> PgResult<QueryResult> {
+        let max_entries = self.pool.config.max_connections.max(1) as usize;
+        let conn = self.conn.as_mut().ok_or(PgError::ConnectionClosed)?;
+        self.in_flight = true;
+        let result = async {
+            let stmt = match query.statement_for(conn, params) {
+                Some(stmt) => stmt,
+                None => {
+                    let stmt = conn.prepare_cached(&query.sql, params).await?;
+                    query.remember(conn, Arc::clone(&stmt), max_entries);
+                    stmt
+                }
+            };
+            conn.execute(&stmt, params).await
+        }
+        .await;
+        self.in_flight = false;
+        self.invalidate_if_schema_change(&query.sql);
+        result
+    }
+
+    /// Start reading a query's rows through a portal, `max_rows` at a time.
+    ///
+    /// See `PgConnection::query_portal`. Dropping the future mid-batch
+    /// cancels the query and discards the connection, as with `query`; a
+    /// portal left open between batches is closed on its next acquire.
+    pub async fn query_portal(
+        &mut self,
+        query: &str,
+        params: &[PgValue],
+        max_rows: u32,
+    ) -> PgResult<QueryResult> {
+        let conn = self.conn.as_mut().ok_or(PgError::ConnectionClosed)?;
+        self.in_flight = true;
+        let result = conn.query_portal(query, params, max_rows).await;
+        self.in_flight = false;
+        result
+    }
+
+    /// Read the next batch from the portal opened by `query_portal`.
+    pub async fn fetch_portal(&mut self, max_rows: u32) -> PgResult<QueryResult> {
+        let conn = self.conn.as_mut().ok_or(PgError::ConnectionClosed)?;
+        self.in_flight = true;
+        let result = conn.fetch_portal(max_rows).await;
+        self.in_flight = false;
+        result
+    }
+
+    /// Close the portal opened by `query_portal`, if it's still open.
+    pub async fn close_portal(&mut self) -> PgResult<()> {
         self.conn
             .as_mut()
             .ok_or(PgError::ConnectionClosed)?
-            .query(query, params)
+            .close_portal()
             .await
     }
 
@@ -102,13 +409,81 @@ impl PooledConnection {
         query: &str,
         params: &[PgValue],
     ) -> PgResult<QueryResult> {
-        self.conn
+        let result = self
+            .conn
             .as_mut()
             .ok_or(PgError::ConnectionClosed)?
             .query_no_sync(query, params)
+            .await;
+        self.invalidate_if_schema_change(query);
+        result
+    }
+
+    /// Execute a statement once per parameter set in one atomic pipeline.
+    ///
+    /// Dropping the future mid-pipeline cancels the batch on the server and
+    /// discards the connection, so its transaction is never left open.
+    pub async fn execute_many(
+        &mut self,
+        query: &str,
+        params_list: &[Vec<PgValue>],
+    ) -> PgResult<Vec<String>> {
+        let conn = self.conn.as_mut().ok_or(PgError::ConnectionClosed)?;
+        self.in_flight = true;
+        let result = conn.execute_many(query, params_list).await;
+        self.in_flight = false;
+        self.invalidate_if_schema_change(query);
+        result
+    }
+
+    /// Look up or prepare one statement for a batch of parameter sets.
+    pub async fn prepare_batch(
+        &mut self,
+        query: &str,
+        params_list: &[Vec<PgValue>],
+    ) -> PgResult<Arc<PreparedStatement>> {
+        self.conn
+            .as_mut()
+            .ok_or(PgError::ConnectionClosed)?
+            .prepare_batch(query, params_list)
             .await
     }
 
+    /// Execute a prepared statement once per parameter set in one pipeline,
+    /// without a transaction of its own.
+    pub async fn execute_batch(
+        &mut self,
+        stmt: &PreparedStatement,
+        params_list: &[Vec<PgValue>],
+    ) -> PgResult<Vec<String>> {
+        let result = self
+            .conn
+            .as_mut()
+            .ok_or(PgError::ConnectionClosed)?
+            .execute_batch(stmt, params_list)
+            .await;
+        self.invalidate_if_schema_change(&stmt.query);
+        result
+    }
+
+    /// Execute a prepared statement without syncing (for pipelining).
+    ///
+    /// Use sync() after all pipelined operations.
+    pub async fn execute_no_sync(
+        &mut self,
+        stmt: &PreparedStatement,
+        params: &[PgValue],
+    ) -> PgResult<QueryResult> {
+        let result = self
+            .conn
+            .as_mut()
+            .ok_or(PgError::ConnectionClosed)?
+            .execute_no_sync(stmt, params)
+            .await;
+        self.invalidate_if_schema_change(&stmt.query);
+        result
+    }
+
     /// Send sync and wait for server to catch up.
     pub async fn sync(&mut self) -> PgResult<()> {
         self.conn
@@ -118,6 +493,77 @@ impl PooledConnection {
             .await
     }
 
+    /// Start a `COPY ... FROM STDIN` statement.
+    ///
+    /// Dropping the connection before `copy_in_finish` or `copy_in_fail`
+    /// completes cancels the COPY and discards the connection.
+    pub async fn copy_in_start(&mut self, query: &str) -> PgResult<()> {
+        let conn = self.conn.as_mut().ok_or(PgError::ConnectionClosed)?;
+        self.in_flight = true;
+        let result = conn.copy_in_start(query).await;
+        self.in_flight = result.is_ok();
+        result
+    }
+
+    /// Send a chunk of COPY data.
+    pub async fn copy_in_send(&mut self, data: Vec<u8>) -> PgResult<()> {
+        self.conn
+            .as_mut()
+            .ok_or(PgError::ConnectionClosed)?
+            .copy_in_send(data)
+            .await
+    }
+
+    /// Start a `COPY ... TO STDOUT` statement.
+    ///
+    /// Dropping the connection before `copy_out_next` returns None cancels
+    /// the COPY and discards the connection.
+    pub async fn copy_out_start(&mut self, query: &str) -> PgResult<()> {
+        let conn = self.conn.as_mut().ok_or(PgError::ConnectionClosed)?;
+        self.in_flight = true;
+        let result = conn.copy_out_start(query).await;
+        self.in_flight = result.is_ok();
+        result
+    }
+
+    /// Read the next chunk of COPY data, or None once the COPY is done.
+    pub async fn copy_out_next(&mut self) -> PgResult<Option<Bytes>> {
+        let result = self
+            .conn
+            .as_mut()
+            .ok_or(PgError::ConnectionClosed)?
+            .copy_out_next()
+            .await;
+        if !matches!(result, Ok(Some(_))) {
+            self.in_flight = false;
+        }
+        result
+    }
+
+    /// Finish COPY FROM STDIN, returning the number of rows copied.
+    pub async fn copy_in_finish(&mut self) -> PgResult<u64> {
+        let result = self
+            .conn
+            .as_mut()
+            .ok_or(PgError::ConnectionClosed)?
+            .copy_in_finish()
+            .await;
+        self.in_flight = false;
+        result
+    }
+
+    /// Abort COPY FROM STDIN.
+    pub async fn copy_in_fail(&mut self, reason: &str) -> PgResult<()> {
+        let result = self
+            .conn
+            .as_mut()
+            .ok_or(PgError::ConnectionClosed)?
+            .copy_in_fail(reason)
+            .await;
+        self.in_flight = false;
+        result
+    }
+
     /// Begin a transaction.
     pub async fn begin(&mut self) -> PgResult<()> {
         self.conn
@@ -143,11 +589,32 @@ impl PooledConnection {
         params: &[PgValue],
         consume_begin: bool,
     ) -> PgResult<QueryResult> {
-        self.conn
+        let result = self
+            .conn
             .as_mut()
             .ok_or(PgError::ConnectionClosed)?
             .query_in_transaction(query, params, consume_begin)
-            .await
+            .await;
+        self.invalidate_if_schema_change(query);
+        result
+    }
+
+    /// Execute query in transaction through the unnamed statement, leaving
+    /// the statement cache untouched.
+    pub async fn query_in_transaction_uncached(
+        &mut self,
+        query: &str,
+        params: &[PgValue],
+        consume_begin: bool,
+    ) -> PgResult<QueryResult> {
+        let result = self
+            .conn
+            .as_mut()
+            .ok_or(PgError::ConnectionClosed)?
+            .query_in_transaction_uncached(query, params, consume_begin)
+            .await;
+        self.invalidate_if_schema_change(query);
+        result
     }
 
     /// Commit the transaction.
@@ -168,19 +635,104 @@ impl PooledConnection {
             .await
     }
 
+    /// Transaction status reported by the server's last ReadyForQuery.
+    pub fn transaction_status(&self) -> TransactionStatus {
+        self.conn
+            .as_ref()
+            .map(|c| c.transaction_status())
+            .unwrap_or(TransactionStatus::Idle)
+    }
+
+    /// After DDL, invalidate cached statements on every pooled connection.
+    ///
+    /// Statements prepared before a schema change can fail with "cached plan
+    /// must not change result type" or decode with stale column types. This
+    /// connection forgets its statements now; every other connection
+    /// deallocates its own the next time it is acquired.
+    fn invalidate_if_schema_change(&mut self, query: &str) {
+        if is_schema_change(query) {
+            self.pool.schema_generation.fetch_add(1, Ordering::AcqRel);
+            if let Some(conn) = self.conn.as_mut() {
+                conn.clear_statement_cache();
+            }
+        }
+    }
+
+    /// Id of the underlying connection (None once returned to the pool).
+    pub fn id(&self) -> Option<u64> {
+        self.conn.as_ref().map(|c| c.id())
+    }
+
+    /// Return the session to a pristine state with `DISCARD ALL`.
+    ///
+    /// See `PgConnection::reset`; the pool's `statement_timeout` is applied
+    /// again afterwards.
+    pub async fn reset(&mut self) -> PgResult<()> {
+        let conn = self.conn.as_mut().ok_or(PgError::ConnectionClosed)?;
+        conn.reset().await?;
+        self.pool.apply_session_settings(conn).await
+    }
+
     /// Check if the connection is healthy.
     pub fn is_healthy(&self) -> bool {
         self.conn.as_ref().map(|c| !c.is_closed()).unwrap_or(false)
     }
+
+    /// List the connection's cached prepared statements as
+    /// `(sql, statement_name, param_oids)`, most recently used first.
+    pub fn prepared_statements(&self) -> Vec<(String, String, Vec<Oid>)> {
+        self.conn
+            .as_ref()
+            .map(|c| {
+                c.statement_cache()
+                    .statements()
+                    .iter()
+                    .map(|stmt| {
+                        (
+                            stmt.query.clone(),
+                            stmt.name.clone(),
+                            stmt.param_types.clone(),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Get this connection's query counters.
+    pub fn metrics(&self) -> ConnectionMetrics {
+        self.conn
+            .as_ref()
+            .map(|c| c.metrics().clone())
+            .unwrap_or_default()
+    }
 }
 
 impl Drop for PooledConnection {
     fn drop(&mut self) {
         if let Some(conn) = self.conn.take() {
-            // Only return healthy connections to the pool
-            if !conn.is_closed() {
+            if self.in_flight {
+                // Dropped mid-query: the connection is out of step with the
+                // server, so discard it and cancel what it was running
+                self.pool.retire(&conn);
+                if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+                    let token = conn.cancel_token();
+                    runtime.spawn(async move {
+                        let _ = token.cancel_request().await;
+                        drop(conn);
+                    });
+                }
+                return;
+            }
+            // Only return healthy connections to an open pool
+            if !conn.is_closed() && !self.pool.semaphore.is_closed() {
+                let mut conn = conn;
+                // Drop can't wait on the server; the reset runs on next acquire
+                conn.needs_reset |= self.pool.config.reset_on_return;
                 let mut idle = self.pool.idle_connections.lock();
                 idle.push(conn);
+            } else {
+                self.pool.retire(&conn);
             }
         }
     }
@@ -198,6 +750,32 @@ struct PgPoolInner {
     idle_connections: Mutex<Vec<PgConnection>>,
     /// Semaphore to limit total connections
     semaphore: Arc<Semaphore>,
+    /// Counters from connections that have left the pool
+    retired_metrics: Mutex<ConnectionMetrics>,
+    /// Bumped on every DDL statement; connections whose statement cache is
+    /// older reset it on acquire
+    schema_generation: AtomicU64,
+    /// Current per-connection statement cache capacity, lowered to keep
+    /// within `statement_cache_memory_limit`
+    statement_cache_capacity: AtomicUsize,
+}
+
+impl PgPoolInner {
+    /// Keep a discarded connection's counters in the pool totals.
+    fn retire(&self, conn: &PgConnection) {
+        self.retired_metrics.lock().merge(conn.metrics());
+    }
+
+    /// Apply the pool's session settings to a new or reset connection.
+    async fn apply_session_settings(&self, conn: &mut PgConnection) -> PgResult<()> {
+        if let Some(timeout) = self.config.statement_timeout {
+            // Round up so a sub-millisecond timeout doesn't become 0 (disabled)
+            let millis = timeout.as_micros().div_ceil(1000);
+            conn.simple_query(&format!("SET statement_timeout = {}", millis))
+                .await?;
+        }
+        Ok(())
+    }
 }
 
 // ============================================================================
@@ -215,11 +793,17 @@ pub struct PgPool {
 
 impl PgPool {
     /// Create a new connection pool.
-    pub async fn connect(config: PgPoolConfig) -> PgResult<Self> {
+    pub async fn connect(mut config: PgPoolConfig) -> PgResult<Self> {
+        if config.acquire_timeout.is_none() {
+            config.acquire_timeout = url_acquire_timeout(&config.url)?;
+        }
         let inner = Arc::new(PgPoolInner {
             semaphore: Arc::new(Semaphore::new(config.max_connections as usize)),
+            statement_cache_capacity: AtomicUsize::new(config.statement_cache_capacity),
             config,
             idle_connections: Mutex::new(Vec::new()),
+            retired_metrics: Mutex::new(ConnectionMetrics::default()),
+            schema_generation: AtomicU64::new(0),
         });
 
         let pool = Self { inner };
@@ -230,17 +814,25 @@ impl PgPool {
             pool.inner.idle_connections.lock().push(conn);
         }
 
+        if let Some(interval) = pool.inner.config.heartbeat_interval {
+            tokio::spawn(heartbeat_task(Arc::downgrade(&pool.inner), interval));
+        }
+
         Ok(pool)
     }
 
     /// Get a connection from the pool.
     pub async fn acquire(&self) -> PgResult<PooledConnection> {
-        // Acquire a permit (blocks if pool is exhausted)
+        // Acquire a permit (blocks if pool is exhausted, up to acquire_timeout)
         // Use Arc::clone() for clarity that this is a cheap reference count increment
-        let permit = Arc::clone(&self.inner.semaphore)
-            .acquire_owned()
-            .await
-            .map_err(|_| PgError::Protocol("Pool closed".to_string()))?;
+        let permit = Arc::clone(&self.inner.semaphore).acquire_owned();
+        let permit = match self.inner.config.acquire_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, permit)
+                .await
+                .map_err(|_| PgError::Timeout)?,
+            None => permit.await,
+        }
+        .map_err(|_| PgError::PoolClosed)?;
 
         // Try to get an idle connection
         let conn = {
@@ -249,14 +841,34 @@ impl PgPool {
         };
 
         let conn = match conn {
-            Some(c) if !c.is_closed() => c,
-            _ => self.create_connection().await?,
+            Some(mut c) if !c.is_closed() => {
+                if c.close_portal().await.is_err()
+                    || (self.inner.config.test_before_acquire
+                        && self.validate_connection(&mut c).await.is_err())
+                    || self.reset_if_needed(&mut c).await.is_err()
+                    || self.refresh_statements(&mut c).await.is_err()
+                    || self.limit_statement_memory(&mut c).await.is_err()
+                {
+                    // Suspected dead - discard it and open a fresh one
+                    self.inner.retire(&c);
+                    let _ = c.close().await;
+                    self.create_connection().await?
+                } else {
+                    c
+                }
+            }
+            Some(c) => {
+                self.inner.retire(&c);
+                self.create_connection().await?
+            }
+            None => self.create_connection().await?,
         };
 
         Ok(PooledConnection {
             conn: Some(conn),
             pool: Arc::clone(&self.inner),
             _permit: permit,
+            in_flight: false,
         })
     }
 
@@ -272,6 +884,16 @@ impl PgPool {
         conn.query(query, params).await
     }
 
+    /// Execute a prepared query handle on a pooled connection.
+    pub async fn execute_prepared(
+        &self,
+        query: &PgPreparedQuery,
+        params: &[PgValue],
+    ) -> PgResult<QueryResult> {
+        let mut conn = self.acquire().await?;
+        conn.execute_prepared(query, params).await
+    }
+
     /// Execute a query without returning results (INSERT, UPDATE, DELETE).
     pub async fn execute(&self, query: &str, params: &[PgValue]) -> PgResult<u64> {
         let result = self.query(query, params).await?;
@@ -279,8 +901,22 @@ impl PgPool {
         Ok(parse_rows_affected(&result.command_tag))
     }
 
+    /// Execute a statement once per parameter set on a pooled connection.
+    ///
+    /// The batch runs in its own transaction: either every execution takes
+    /// effect or none do. Returns the total rows affected.
+    pub async fn execute_many(&self, query: &str, params_list: &[Vec<PgValue>]) -> PgResult<u64> {
+        let mut conn = self.acquire().await?;
+        let tags = conn.execute_many(query, params_list).await?;
+        Ok(tags.iter().map(|tag| parse_rows_affected(tag)).sum())
+    }
+
     /// Close the pool and all connections.
+    ///
+    /// Subsequent `acquire` calls fail with `PgError::PoolClosed`.
     pub async fn close(&self) {
+        self.inner.semaphore.close();
+
         // Drain and close all idle connections
         let connections = {
             let mut idle = self.inner.idle_connections.lock();
@@ -288,44 +924,242 @@ impl PgPool {
         };
 
         for mut conn in connections {
+            self.inner.retire(&conn);
             let _ = conn.close().await;
         }
     }
 
+    /// Run `SELECT 1` on each idle connection, evicting any that fail.
+    ///
+    /// A connection is only pinged while a pool permit is free, so the pool
+    /// never exceeds `max_connections`; when every permit is taken the rest
+    /// are skipped until the next heartbeat. Returns the number evicted.
+    pub async fn heartbeat(&self) -> usize {
+        heartbeat_idle(&self.inner).await
+    }
+
     /// Get the current number of idle connections.
     pub fn idle_count(&self) -> usize {
         self.inner.idle_connections.lock().len()
     }
 
+    /// Get query counters summed over the pool's connections.
+    ///
+    /// Includes idle connections and connections that have been discarded;
+    /// a checked-out connection is counted once it is returned.
+    pub fn metrics(&self) -> ConnectionMetrics {
+        let mut metrics = self.inner.retired_metrics.lock().clone();
+        for conn in self.inner.idle_connections.lock().iter() {
+            metrics.merge(conn.metrics());
+        }
+        metrics
+    }
+
     /// Get the pool configuration.
     pub fn config(&self) -> &PgPoolConfig {
         &self.inner.config
     }
 
+    /// Validate a connection using the configured validation query or a ping.
+    async fn validate_connection(&self, conn: &mut PgConnection) -> PgResult<()> {
+        match &self.inner.config.validation_query {
+            Some(query) => conn.simple_query(query).await.map(|_| ()),
+            None => conn.ping().await,
+        }
+    }
+
+    /// Reset a connection returned while `reset_on_return` was set.
+    async fn reset_if_needed(&self, conn: &mut PgConnection) -> PgResult<()> {
+        if conn.needs_reset {
+            conn.reset().await?;
+            self.inner.apply_session_settings(conn).await?;
+        }
+        Ok(())
+    }
+
+    /// Deallocate a connection's statements if DDL has run since it cached them.
+    async fn refresh_statements(&self, conn: &mut PgConnection) -> PgResult<()> {
+        let generation = self.inner.schema_generation.load(Ordering::Acquire);
+        if conn.schema_generation != generation {
+            conn.deallocate_all().await?;
+            conn.schema_generation = generation;
+        }
+        Ok(())
+    }
+
+    /// Shrink a connection's statement cache to keep the pool's caches
+    /// within `statement_cache_memory_limit`.
+    ///
+    /// Checked-out connections aren't visible here; they are counted when
+    /// they are next acquired.
+    async fn limit_statement_memory(&self, conn: &mut PgConnection) -> PgResult<()> {
+        let Some(limit) = self.inner.config.statement_cache_memory_limit else {
+            return Ok(());
+        };
+        let idle: usize = self
+            .inner
+            .idle_connections
+            .lock()
+            .iter()
+            .map(|c| c.statement_cache().memory_usage())
+            .sum();
+
+        let mut capacity = self.inner.statement_cache_capacity.load(Ordering::Acquire);
+        conn.shrink_statement_cache(capacity).await?;
+        while capacity > 1 && idle + conn.statement_cache().memory_usage() > limit {
+            capacity /= 2;
+            conn.shrink_statement_cache(capacity).await?;
+        }
+        self.inner
+            .statement_cache_capacity
+            .fetch_min(capacity, Ordering::AcqRel);
+        Ok(())
+    }
+
+    /// Approximate memory held by the statement caches of idle connections.
+    pub fn statement_cache_memory(&self) -> usize {
+        self.inner
+            .idle_connections
+            .lock()
+            .iter()
+            .map(|c| c.statement_cache().memory_usage())
+            .sum()
+    }
+
+    /// Current per-connection statement cache capacity.
+    ///
+    /// Starts at `statement_cache_capacity` and only drops when
+    /// `statement_cache_memory_limit` is exceeded.
+    pub fn statement_cache_capacity(&self) -> usize {
+        self.inner.statement_cache_capacity.load(Ordering::Acquire)
+    }
+
+    /// Open a LISTEN subscription on a new dedicated connection.
+    ///
+    /// The listener's connection is not counted against the pool size.
+    pub async fn listener(&self) -> PgResult<PgListener> {
+        PgListener::connect(self.connection_config()?).await
+    }
+
+    /// Connection configuration derived from the pool's configuration.
+    fn connection_config(&self) -> PgResult<PgConfig> {
+        let mut pg_config = PgConfig::from_url(&self.inner.config.url)?;
+        pg_config.statement_cache_capacity =
+            self.inner.statement_cache_capacity.load(Ordering::Acquire);
+        pg_config.simple_query_max_rows = self.inner.config.simple_query_max_rows;
+        pg_config.max_message_size = self.inner.config.max_message_size;
+        pg_config.pipeline_depth = self.inner.config.pipeline_depth;
+        if let Some(tls) = &self.inner.config.tls {
+            pg_config.tls = tls.clone();
+        }
+        Ok(pg_config)
+    }
+
     /// Create a new connection with the pool's configuration.
     async fn create_connection(&self) -> PgResult<PgConnection> {
-        let mut pg_config = PgConfig::from_url(&self.inner.config.url)?;
-        pg_config.statement_cache_capacity = self.inner.config.statement_cache_capacity;
-        PgConnection::connect_with_config(pg_config).await
+        let mut conn = PgConnection::connect_with_config(self.connection_config()?).await?;
+        self.inner.apply_session_settings(&mut conn).await?;
+        conn.schema_generation = self.inner.schema_generation.load(Ordering::Acquire);
+        Ok(conn)
     }
 }
 
+// ============================================================================
+// Heartbeat
+// ============================================================================
+
+/// Heartbeat the pool until it is closed or dropped.
+async fn heartbeat_task(pool: Weak<PgPoolInner>, interval: Duration) {
+    loop {
+        tokio::time::sleep(interval).await;
+        let Some(inner) = pool.upgrade() else {
+            return;
+        };
+        if inner.semaphore.is_closed() {
+            return;
+        }
+        heartbeat_idle(&inner).await;
+    }
+}
+
+/// Ping every connection that was idle when the heartbeat started.
+async fn heartbeat_idle(inner: &Arc<PgPoolInner>) -> usize {
+    let idle = inner.idle_connections.lock().len();
+    let mut evicted = 0;
+
+    for _ in 0..idle {
+        // Hold a permit while the connection is out of the idle list
+        let Ok(_permit) = Arc::clone(&inner.semaphore).try_acquire_owned() else {
+            break;
+        };
+        // Oldest first; live connections go back on the end
+        let conn = {
+            let mut idle = inner.idle_connections.lock();
+            (!idle.is_empty()).then(|| idle.remove(0))
+        };
+        let Some(mut conn) = conn else {
+            break;
+        };
+
+        if !conn.is_closed() && conn.simple_query("SELECT 1").await.is_ok() {
+            inner.idle_connections.lock().push(conn);
+        } else {
+            inner.retire(&conn);
+            let _ = conn.close().await;
+            evicted += 1;
+        }
+    }
+
+    evicted
+}
+
 // ============================================================================
 // Helper functions
 // ============================================================================
 
+/// Read the acquire timeout from a URL's `pool_timeout` parameter, or else
+/// its `connect_timeout`, in seconds.
+fn url_acquire_timeout(url: &str) -> PgResult<Option<Duration>> {
+    let Some((_, query)) = url.split_once('?') else {
+        return Ok(None);
+    };
+    let params: HashMap<&str, String> = parse_query_params(query).collect();
+    let Some((key, value)) = ["pool_timeout", "connect_timeout"]
+        .into_iter()
+        .find_map(|key| params.get(key).map(|value| (key, value)))
+    else {
+        return Ok(None);
+    };
+    match value.parse::<f64>().ok().map(Duration::try_from_secs_f64) {
+        // libpq treats a connect_timeout of 0 as no timeout
+        Some(Ok(timeout)) if timeout.is_zero() => Ok(None),
+        Some(Ok(timeout)) => Ok(Some(timeout)),
+        _ => Err(PgError::Protocol(format!("Invalid {}: {}", key, value))),
+    }
+}
+
 /// Parse rows affected from a PostgreSQL command tag.
-fn parse_rows_affected(tag: &str) -> u64 {
+pub(crate) fn parse_rows_affected(tag: &str) -> u64 {
+    command_tag_rows(tag).unwrap_or(0)
+}
+
+/// Row count in a command tag, or None for commands that don't report one
+/// (DDL, SET, ...).
+pub(crate) fn command_tag_rows(tag: &str) -> Option<u64> {
     // Common formats:
     // - "INSERT 0 5" -> 5 rows
     // - "UPDATE 3" -> 3 rows
     // - "DELETE 2" -> 2 rows
     // - "SELECT 10" -> 10 rows (though we typically don't use this)
+    // - "MERGE 4", "COPY 100", "FETCH 1", "MOVE 7"
 
     let parts: Vec<&str> = tag.split_whitespace().collect();
     match parts.as_slice() {
-        ["INSERT", _, n] | ["UPDATE", n] | ["DELETE", n] | ["SELECT", n] => n.parse().unwrap_or(0),
-        _ => 0,
+        ["INSERT", _, n]
+        | ["UPDATE" | "DELETE" | "SELECT" | "MERGE" | "COPY" | "FETCH" | "MOVE", n] => {
+            n.parse().ok()
+        }
+        _ => None,
     }
 }
 
@@ -340,6 +1174,10 @@ mod tests {
         assert_eq!(parse_rows_affected("DELETE 2"), 2);
         assert_eq!(parse_rows_affected("SELECT 10"), 10);
         assert_eq!(parse_rows_affected("UNKNOWN"), 0);
+        assert_eq!(parse_rows_affected("MERGE 4"), 4);
+        assert_eq!(command_tag_rows("UPDATE 0"), Some(0));
+        assert_eq!(command_tag_rows("CREATE TABLE"), None);
+        assert_eq!(command_tag_rows(""), None);
     }
 
     #[test]
@@ -352,5 +1190,66 @@ mod tests {
         assert_eq!(config.min_connections, 2);
         assert_eq!(config.max_connections, 20);
         assert_eq!(config.statement_cache_capacity, 200);
+        assert!(!config.test_before_acquire);
+        assert!(config.validation_query.is_none());
+        assert!(config.simple_query_max_rows.is_none());
+        assert_eq!(config.max_message_size, DEFAULT_MAX_MESSAGE_SIZE);
+        assert_eq!(config.pipeline_depth, DEFAULT_PIPELINE_DEPTH);
+        assert!(config.heartbeat_interval.is_none());
+        assert!(config.statement_cache_memory_limit.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_acquire_after_close() {
+        let config = PgPoolConfig::new("postgresql://localhost/test").min_connections(0);
+        let pool = PgPool::connect(config).await.unwrap();
+
+        pool.close().await;
+        assert!(matches!(pool.acquire().await, Err(PgError::PoolClosed)));
+    }
+
+    #[test]
+    fn test_url_acquire_timeout() {
+        let timeout = |url| url_acquire_timeout(url).unwrap();
+        assert_eq!(timeout("postgresql://localhost/test"), None);
+        assert_eq!(
+            timeout("postgresql://localhost/test?pool_timeout=2.5&connect_timeout=10"),
+            Some(Duration::from_millis(2500))
+        );
+        assert_eq!(
+            timeout("postgresql://localhost/test?connect_timeout=10"),
+            Some(Duration::from_secs(10))
+        );
+        assert_eq!(
+            timeout("postgresql://localhost/test?connect_timeout=0"),
+            None
+        );
+        assert!(url_acquire_timeout("postgresql://localhost/test?pool_timeout=soon").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_acquire_timeout() {
+        let config = PgPoolConfig::new("postgresql://localhost/test")
+            .min_connections(0)
+            .max_connections(1)
+            .acquire_timeout(Some(Duration::from_millis(50)));
+        let pool = PgPool::connect(config).await.unwrap();
+
+        // Hold the only permit, as a checked-out connection would
+        let _permit = Arc::clone(&pool.inner.semaphore)
+            .acquire_owned()
+            .await
+            .unwrap();
+        assert!(matches!(pool.acquire().await, Err(PgError::Timeout)));
+    }
+
+    #[test]
+    fn test_pool_config_validation_query() {
+        let config = PgPoolConfig::new("postgresql://localhost/test")
+            .test_before_acquire(true)
+            .validation_query(Some("SELECT 1".to_string()));
+
+        assert!(config.test_before_acquire);
+        assert_eq!(config.validation_query.as_deref(), Some("SELECT 1"));
     }
 }
@@ -7,7 +7,7 @@ use bytes::{Buf, BufMut, Bytes, BytesMut};
 use std::collections::HashMap;
 
 use super::error::{PgError, PgResult};
-use super::types::Oid;
+use super::types::{Oid, PgValue};
 
 // ============================================================================
 // Protocol Constants
@@ -16,6 +16,13 @@ use super::types::Oid;
 /// PostgreSQL protocol version 3.0
 pub const PROTOCOL_VERSION: i32 = 196608; // (3 << 16) | 0
 
+/// CancelRequest code: 1234 in the high 16 bits, 5678 in the low 16 bits.
+pub const CANCEL_REQUEST_CODE: i32 = 80877102;
+
+/// Default limit on the size of a single backend message (1 GiB, the
+/// largest value the server will send in one field).
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 1 << 30;
+
 /// Format codes
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 #[repr(i16)]
@@ -35,6 +42,17 @@ pub enum TransactionStatus {
     Failed,
 }
 
+impl TransactionStatus {
+    /// Status name as exposed to Python.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TransactionStatus::Idle => "idle",
+            TransactionStatus::InTransaction => "in_transaction",
+            TransactionStatus::Failed => "failed",
+        }
+    }
+}
+
 impl From<u8> for TransactionStatus {
     fn from(b: u8) -> Self {
         match b {
@@ -102,6 +120,25 @@ impl FrontendMessage for StartupMessage {
     }
 }
 
+/// CancelRequest - sent on a fresh connection to cancel the query running
+/// on another backend
+#[derive(Debug, Clone, Copy)]
+pub struct CancelRequestMessage {
+    pub process_id: i32,
+    pub secret_key: i32,
+}
+
+impl FrontendMessage for CancelRequestMessage {
+    fn encode(&self) -> BytesMut {
+        let mut buf = BytesMut::with_capacity(16);
+        buf.put_i32(16);
+        buf.put_i32(CANCEL_REQUEST_CODE);
+        buf.put_i32(self.process_id);
+        buf.put_i32(self.secret_key);
+        buf
+    }
+}
+
 /// Password message (for MD5 or plaintext auth)
 #[derive(Debug, Clone)]
 pub struct PasswordMessage {
@@ -192,7 +229,7 @@ pub struct BindMessage {
     pub portal: String,
     pub statement: String,
     pub param_formats: Vec<Format>,
-    pub params: Vec<super::types::PgValue>,
+    pub params: Vec<PgValue>,
     pub result_formats: Vec<Format>,
 }
 
@@ -294,6 +331,30 @@ impl FrontendMessage for DescribeMessage {
     }
 }
 
+/// Close message ('C') - Close a prepared statement or portal
+#[derive(Debug, Clone)]
+pub struct CloseMessage {
+    /// 'S' for statement, 'P' for portal
+    pub kind: u8,
+    pub name: String,
+}
+
+impl FrontendMessage for CloseMessage {
+    fn encode(&self) -> BytesMut {
+        let mut buf = BytesMut::new();
+        buf.put_u8(b'C');
+
+        let len = 4 + 1 + self.name.len() as i32 + 1;
+        buf.put_i32(len);
+
+        buf.put_u8(self.kind);
+        buf.put_slice(self.name.as_bytes());
+        buf.put_u8(0);
+
+        buf
+    }
+}
+
 /// Sync message ('S') - Marks end of an extended query
 #[derive(Debug, Clone, Copy)]
 pub struct SyncMessage;
@@ -388,6 +449,83 @@ impl FrontendMessage for SaslResponseMessage {
     }
 }
 
+/// Signature opening a binary COPY stream
+pub const COPY_BINARY_SIGNATURE: &[u8; 11] = b"PGCOPY\n\xff\r\n\0";
+
+/// Start a binary COPY stream: the signature, a zero flags field and an
+/// empty header extension.
+pub fn copy_binary_header() -> Vec<u8> {
+    let mut buf = Vec::with_capacity(19);
+    buf.extend_from_slice(COPY_BINARY_SIGNATURE);
+    buf.extend_from_slice(&0i32.to_be_bytes());
+    buf.extend_from_slice(&0i32.to_be_bytes());
+    buf
+}
+
+/// Append one binary COPY tuple: the field count, then each field's length
+/// (-1 for NULL) and binary value.
+pub fn encode_copy_binary_row(buf: &mut Vec<u8>, values: &[PgValue]) {
+    buf.extend_from_slice(&(values.len() as i16).to_be_bytes());
+    for value in values {
+        if value.is_null() {
+            buf.extend_from_slice(&(-1i32).to_be_bytes());
+        } else {
+            let encoded = value.encode_binary();
+            buf.extend_from_slice(&(encoded.len() as i32).to_be_bytes());
+            buf.extend_from_slice(&encoded);
+        }
+    }
+}
+
+/// Trailer ending a binary COPY stream (a field count of -1)
+pub const COPY_BINARY_TRAILER: [u8; 2] = (-1i16).to_be_bytes();
+
+/// CopyData message ('d') - a chunk of COPY FROM STDIN data
+#[derive(Debug, Clone)]
+pub struct CopyDataMessage {
+    pub data: Vec<u8>,
+}
+
+impl FrontendMessage for CopyDataMessage {
+    fn encode(&self) -> BytesMut {
+        let mut buf = BytesMut::with_capacity(5 + self.data.len());
+        buf.put_u8(b'd');
+        buf.put_i32(4 + self.data.len() as i32);
+        buf.put_slice(&self.data);
+        buf
+    }
+}
+
+/// CopyDone message ('c') - end of COPY FROM STDIN data
+#[derive(Debug, Clone)]
+pub struct CopyDoneMessage;
+
+impl FrontendMessage for CopyDoneMessage {
+    fn encode(&self) -> BytesMut {
+        let mut buf = BytesMut::with_capacity(5);
+        buf.put_u8(b'c');
+        buf.put_i32(4);
+        buf
+    }
+}
+
+/// CopyFail message ('f') - abort COPY FROM STDIN with an error message
+#[derive(Debug, Clone)]
+pub struct CopyFailMessage {
+    pub message: String,
+}
+
+impl FrontendMessage for CopyFailMessage {
+    fn encode(&self) -> BytesMut {
+        let mut buf = BytesMut::new();
+        buf.put_u8(b'f');
+        buf.put_i32(4 + self.message.len() as i32 + 1);
+        buf.put_slice(self.message.as_bytes());
+        buf.put_u8(0);
+        buf
+    }
+}
+
 // ============================================================================
 // Backend (Server -> Client) Messages
 // ============================================================================
@@ -435,6 +573,20 @@ pub enum BackendMessage {
     },
     EmptyQueryResponse,
 
+    // COPY
+    CopyInResponse {
+        format: Format,
+        column_formats: Vec<Format>,
+    },
+    CopyOutResponse {
+        format: Format,
+        column_formats: Vec<Format>,
+    },
+    CopyData {
+        data: Bytes,
+    },
+    CopyDone,
+
     // Extended query protocol
     ParseComplete,
     BindComplete,
@@ -474,6 +626,34 @@ pub enum BackendMessage {
     },
 }
 
+/// Total length (type byte included) of the message starting at `header`.
+///
+/// Returns `Ok(None)` until the 5-byte header is available. Lengths below 4
+/// or above `max_size` are rejected from the header alone, before the body
+/// is buffered.
+pub fn message_length(header: &[u8], max_size: usize) -> PgResult<Option<usize>> {
+    if header.len() < 5 {
+        return Ok(None);
+    }
+
+    let length = i32::from_be_bytes([header[1], header[2], header[3], header[4]]);
+    if length < 4 {
+        return Err(PgError::Protocol(format!(
+            "Invalid message length: {}",
+            length
+        )));
+    }
+    if length as usize > max_size {
+        return Err(PgError::Protocol(format!(
+            "message too large: {} bytes (limit {})",
+            length, max_size
+        )));
+    }
+
+    // type byte + length field value (includes length field itself)
+    Ok(Some(1 + length as usize))
+}
+
 impl BackendMessage {
     /// Decode a backend message from bytes.
     ///
@@ -484,7 +664,14 @@ impl BackendMessage {
         }
 
         let msg_type = buf.get_u8();
-        let len = buf.get_i32() as usize;
+        let len = buf.get_i32();
+        if len < 4 {
+            return Err(PgError::Protocol(format!(
+                "Invalid message length: {}",
+                len
+            )));
+        }
+        let len = len as usize;
 
         if buf.remaining() < len - 4 {
             return Err(PgError::Protocol("Incomplete message body".to_string()));
@@ -510,6 +697,11 @@ impl BackendMessage {
             b's' => Ok(BackendMessage::PortalSuspended),
             b't' => Self::decode_parameter_description(body),
             b'A' => Self::decode_notification_response(body),
+            // 'H' is Flush coming from the frontend, but CopyOutResponse
+            // from the backend
+            b'G' | b'H' => Self::decode_copy_response(msg_type, body),
+            b'd' => Ok(BackendMessage::CopyData { data: body }),
+            b'c' => Ok(BackendMessage::CopyDone),
             _ => Err(PgError::Protocol(format!(
                 "Unknown message type: {}",
                 msg_type as char
@@ -643,6 +835,36 @@ impl BackendMessage {
         Ok(BackendMessage::ParameterDescription { type_oids })
     }
 
+    fn decode_copy_response(msg_type: u8, mut body: Bytes) -> PgResult<Self> {
+        let format = if body.get_i8() == 1 {
+            Format::Binary
+        } else {
+            Format::Text
+        };
+        let count = body.get_i16() as usize;
+        let column_formats = (0..count)
+            .map(|_| {
+                if body.get_i16() == 1 {
+                    Format::Binary
+                } else {
+                    Format::Text
+                }
+            })
+            .collect();
+
+        if msg_type == b'H' {
+            Ok(BackendMessage::CopyOutResponse {
+                format,
+                column_formats,
+            })
+        } else {
+            Ok(BackendMessage::CopyInResponse {
+                format,
+                column_formats,
+            })
+        }
+    }
+
     fn decode_notification_response(mut body: Bytes) -> PgResult<Self> {
         let process_id = body.get_i32();
         let channel = read_cstring(&mut body)?;
@@ -722,6 +944,21 @@ mod tests {
         assert_eq!(len as usize, encoded.len());
     }
 
+    #[test]
+    fn test_cancel_request_structure() {
+        let msg = CancelRequestMessage {
+            process_id: 4242,
+            secret_key: -7,
+        };
+
+        let encoded = msg.encode();
+
+        assert_eq!(&encoded[..4], &16i32.to_be_bytes());
+        assert_eq!(&encoded[4..8], &80877102i32.to_be_bytes());
+        assert_eq!(&encoded[8..12], &4242i32.to_be_bytes());
+        assert_eq!(&encoded[12..], &(-7i32).to_be_bytes());
+    }
+
     #[test]
     fn test_query_message_structure() {
         let msg = QueryMessage {
@@ -5,7 +5,7 @@
 //! - `StatementCache`: O(1) LRU cache for prepared statements per connection
 
 use std::num::NonZeroUsize;
-use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 use std::sync::Arc;
 
 use lru::LruCache;
@@ -65,6 +65,20 @@ impl PreparedStatement {
     pub fn returns_rows(&self) -> bool {
         !self.columns.is_empty()
     }
+
+    /// Approximate heap and inline size of this statement in bytes.
+    pub fn memory_size(&self) -> usize {
+        let columns: usize = self
+            .columns
+            .iter()
+            .map(|c| std::mem::size_of::<FieldDescription>() + c.name.len())
+            .sum();
+        std::mem::size_of::<Self>()
+            + self.name.len()
+            + self.query.len()
+            + self.param_types.len() * std::mem::size_of::<Oid>()
+            + columns
+    }
 }
 
 // ============================================================================
@@ -84,6 +98,10 @@ pub struct StatementCache {
     cache: LruCache<String, Arc<PreparedStatement>>,
     /// Counter for generating unique statement names
     next_id: AtomicU32,
+    /// Lookups by query text via `get` or `get_and_touch`
+    lookups: AtomicU64,
+    /// Statements that have left the cache, by any route
+    displaced: u64,
 }
 
 impl StatementCache {
@@ -93,6 +111,8 @@ impl StatementCache {
         Self {
             cache: LruCache::new(cap),
             next_id: AtomicU32::new(0),
+            lookups: AtomicU64::new(0),
+            displaced: 0,
         }
     }
 
@@ -102,6 +122,7 @@ impl StatementCache {
     /// The Arc clone is cheap (reference count increment only).
     /// Note: Does NOT update LRU order (use `get_and_touch` for that).
     pub fn get(&self, query: &str) -> Option<Arc<PreparedStatement>> {
+        self.lookups.fetch_add(1, Ordering::Relaxed);
         self.cache.peek(query).map(Arc::clone)
     }
 
@@ -110,9 +131,24 @@ impl StatementCache {
     /// This is O(1) and updates LRU order.
     /// Returns Arc clone for cheap sharing.
     pub fn get_and_touch(&mut self, query: &str) -> Option<Arc<PreparedStatement>> {
+        self.lookups.fetch_add(1, Ordering::Relaxed);
         self.cache.get(query).map(Arc::clone)
     }
 
+    /// Number of lookups by query text made so far, hit or miss.
+    pub fn lookups(&self) -> u64 {
+        self.lookups.load(Ordering::Relaxed)
+    }
+
+    /// Number of statements that have left the cache so far, by eviction,
+    /// replacement, removal or clearing.
+    ///
+    /// A statement taken from the cache is still in it while this is
+    /// unchanged.
+    pub fn displaced(&self) -> u64 {
+        self.displaced
+    }
+
     /// Check if a query is cached (without cloning).
     #[inline]
     pub fn contains(&self, query: &str) -> bool {
@@ -122,7 +158,8 @@ impl StatementCache {
     /// Insert a prepared statement into the cache.
     ///
     /// If the cache is at capacity, the least recently used statement
-    /// will be evicted. Returns the evicted statement name if any.
+    /// will be evicted. Returns the name of the statement that left the
+    /// cache, evicted or replaced under the same query, if any.
     ///
     /// This is O(1). The statement is wrapped in Arc for efficient sharing.
     pub fn insert(&mut self, query: String, statement: PreparedStatement) -> Option<String> {
@@ -148,14 +185,26 @@ impl StatementCache {
         };
 
         // Insert (or update) - this will evict LRU if needed
-        self.cache.put(query, statement);
-
-        evicted
+        let replaced = self
+            .cache
+            .put(query, Arc::clone(&statement))
+            .filter(|old| old.name != statement.name)
+            .map(|old| old.name.clone());
+
+        let displaced = evicted.or(replaced);
+        if displaced.is_some() {
+            self.displaced += 1;
+        }
+        displaced
     }
 
     /// Remove a statement from the cache.
     pub fn remove(&mut self, query: &str) -> Option<Arc<PreparedStatement>> {
-        self.cache.pop(query)
+        let removed = self.cache.pop(query);
+        if removed.is_some() {
+            self.displaced += 1;
+        }
+        removed
     }
 
     /// Generate a unique statement name for this connection.
@@ -164,6 +213,39 @@ impl StatementCache {
         format!("__fk_{}", id)
     }
 
+    /// Approximate memory held by the cached statements and their keys.
+    pub fn memory_usage(&self) -> usize {
+        self.cache
+            .iter()
+            .map(|(query, stmt)| query.len() + stmt.memory_size())
+            .sum()
+    }
+
+    /// Maximum number of statements the cache holds.
+    pub fn capacity(&self) -> usize {
+        self.cache.cap().get()
+    }
+
+    /// Lower the capacity, evicting least recently used statements.
+    ///
+    /// Returns the names of the evicted statements so they can be closed on
+    /// the server. A capacity at or above the current one is ignored.
+    pub fn shrink_to(&mut self, capacity: usize) -> Vec<String> {
+        let capacity = capacity.max(1);
+        if capacity >= self.capacity() {
+            return Vec::new();
+        }
+        let mut evicted = Vec::new();
+        while self.cache.len() > capacity {
+            if let Some((_, stmt)) = self.cache.pop_lru() {
+                evicted.push(stmt.name.clone());
+            }
+        }
+        self.displaced += evicted.len() as u64;
+        self.cache.resize(NonZeroUsize::new(capacity).unwrap());
+        evicted
+    }
+
     /// Get the number of cached statements.
     pub fn len(&self) -> usize {
         self.cache.len()
@@ -179,6 +261,7 @@ impl StatementCache {
     /// Note: This does NOT close the statements on the server.
     /// Use `close_all` to properly close server-side statements.
     pub fn clear(&mut self) {
+        self.displaced += self.cache.len() as u64;
         self.cache.clear();
     }
 
@@ -190,6 +273,88 @@ impl StatementCache {
         }
         names
     }
+
+    /// Get all cached statements, most recently used first.
+    pub fn statements(&self) -> Vec<Arc<PreparedStatement>> {
+        self.cache
+            .iter()
+            .map(|(_, stmt)| Arc::clone(stmt))
+            .collect()
+    }
+}
+
+/// Heuristic: does this SQL change the schema (CREATE, ALTER or DROP)?
+///
+/// Checks the first keyword of each `;`-separated statement, skipping
+/// leading whitespace and `--` comments. A false positive only costs an
+/// unnecessary cache reset.
+pub fn is_schema_change(sql: &str) -> bool {
+    sql.split(';').any(|statement| {
+        let keyword = first_keyword(statement);
+        ["CREATE", "ALTER", "DROP"]
+            .iter()
+            .any(|ddl| keyword.eq_ignore_ascii_case(ddl))
+    })
+}
+
+/// Is this a SQL-level `PREPARE`, `EXECUTE` or `DEALLOCATE` statement?
+///
+/// These manage server-side named statements themselves, so they are run
+/// with the simple query protocol rather than through the statement cache.
+pub fn is_sql_prepared_statement(sql: &str) -> bool {
+    let keyword = first_keyword(sql);
+    ["PREPARE", "EXECUTE", "DEALLOCATE"]
+        .iter()
+        .any(|command| keyword.eq_ignore_ascii_case(command))
+}
+
+/// Does this SQL start with CREATE, ALTER or DROP?
+///
+/// Unlike `is_schema_change` only the first keyword counts. Such statements
+/// are run with the simple query protocol when they have no parameters:
+/// preparing DDL saves nothing and costs extra messages.
+pub fn is_ddl(sql: &str) -> bool {
+    let keyword = first_keyword(sql);
+    ["CREATE", "ALTER", "DROP"]
+        .iter()
+        .any(|ddl| keyword.eq_ignore_ascii_case(ddl))
+}
+
+/// Is this `DEALLOCATE ALL`, which also drops the cached statements?
+pub fn is_deallocate_all(sql: &str) -> bool {
+    let mut words = skip_comments(sql)
+        .split(|c: char| c.is_whitespace() || c == ';')
+        .filter(|w| !w.is_empty());
+    if !words
+        .next()
+        .is_some_and(|w| w.eq_ignore_ascii_case("DEALLOCATE"))
+    {
+        return false;
+    }
+    let mut target = words.next();
+    if target.is_some_and(|w| w.eq_ignore_ascii_case("PREPARE")) {
+        target = words.next();
+    }
+    target.is_some_and(|w| w.eq_ignore_ascii_case("ALL"))
+}
+
+/// First keyword of a statement, skipping whitespace and `--` comments.
+fn first_keyword(statement: &str) -> &str {
+    skip_comments(statement)
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .next()
+        .unwrap_or("")
+}
+
+fn skip_comments(statement: &str) -> &str {
+    let mut rest = statement.trim_start();
+    while let Some(comment) = rest.strip_prefix("--") {
+        rest = comment
+            .split_once('\n')
+            .map(|(_, after)| after.trim_start())
+            .unwrap_or("");
+    }
+    rest
 }
 
 impl Default for StatementCache {
@@ -203,6 +368,41 @@ impl Default for StatementCache {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_is_schema_change() {
+        assert!(is_schema_change("ALTER TABLE t ADD COLUMN b int"));
+        assert!(is_schema_change("  drop table t"));
+        assert!(is_schema_change("-- migrate\nCREATE INDEX i ON t (a)"));
+        assert!(is_schema_change("INSERT INTO t VALUES (1); DROP TABLE u"));
+        assert!(!is_schema_change("SELECT * FROM t"));
+        assert!(!is_schema_change("UPDATE t SET created = now()"));
+        assert!(!is_schema_change("SELECT 'DROP TABLE t'"));
+    }
+
+    #[test]
+    fn test_is_sql_prepared_statement() {
+        assert!(is_sql_prepared_statement("PREPARE q (int) AS SELECT $1"));
+        assert!(is_sql_prepared_statement("  execute q(1)"));
+        assert!(is_sql_prepared_statement("-- done\nDEALLOCATE q"));
+        assert!(!is_sql_prepared_statement("SELECT 'EXECUTE q'"));
+        assert!(!is_sql_prepared_statement("PREPARED_ITEMS"));
+
+        assert!(is_deallocate_all("DEALLOCATE ALL"));
+        assert!(is_deallocate_all("deallocate prepare all;"));
+        assert!(!is_deallocate_all("DEALLOCATE q"));
+        assert!(!is_deallocate_all("DEALLOCATE PREPARE all_items"));
+    }
+
+    #[test]
+    fn test_is_ddl() {
+        assert!(is_ddl("CREATE TABLE t (a int)"));
+        assert!(is_ddl("-- setup\n  alter table t add column b int"));
+        assert!(is_ddl("DROP INDEX i"));
+        assert!(!is_ddl("INSERT INTO t VALUES (1); DROP TABLE u"));
+        assert!(!is_ddl("SELECT 'CREATE'"));
+        assert!(!is_ddl("CREATED_AT"));
+    }
+
     #[test]
     fn test_prepared_statement_creation() {
         let mut stmt = PreparedStatement::new("s1".to_string(), "SELECT $1".to_string());
@@ -216,6 +416,74 @@ mod tests {
         assert_eq!(stmt.param_types[0], Oid::INT4);
     }
 
+    #[test]
+    fn test_cache_shrink_evicts_lru() {
+        let mut cache = StatementCache::new(4);
+        for i in 0..4 {
+            let query = format!("SELECT {}", i);
+            let stmt = PreparedStatement::new(format!("s{}", i), query.clone());
+            cache.insert(query, stmt);
+        }
+        cache.get_and_touch("SELECT 0");
+        let before = cache.memory_usage();
+
+        assert_eq!(cache.shrink_to(2), vec!["s1", "s2"]);
+        assert_eq!(cache.capacity(), 2);
+        assert!(cache.contains("SELECT 0") && cache.contains("SELECT 3"));
+        assert!(cache.memory_usage() < before);
+
+        // Growing is not done by shrink_to
+        assert!(cache.shrink_to(8).is_empty());
+        assert_eq!(cache.capacity(), 2);
+    }
+
+    #[test]
+    fn test_cache_reports_displaced_statements() {
+        let mut cache = StatementCache::new(2);
+        let stmt =
+            |name: &str, query: &str| PreparedStatement::new(name.to_string(), query.to_string());
+        assert_eq!(
+            cache.insert("SELECT 1".into(), stmt("s1", "SELECT 1")),
+            None
+        );
+        assert_eq!(
+            cache.insert("SELECT 2".into(), stmt("s2", "SELECT 2")),
+            None
+        );
+        assert_eq!(cache.displaced(), 0);
+
+        // Re-preparing a cached query replaces its statement
+        assert_eq!(
+            cache.insert("SELECT 1".into(), stmt("s3", "SELECT 1")),
+            Some("s1".to_string())
+        );
+        assert_eq!(cache.displaced(), 1);
+
+        // A new query evicts the least recently used statement
+        assert_eq!(
+            cache.insert("SELECT 3".into(), stmt("s4", "SELECT 3")),
+            Some("s2".to_string())
+        );
+        assert_eq!(cache.displaced(), 2);
+
+        cache.remove("SELECT 1");
+        cache.clear();
+        assert_eq!(cache.displaced(), 4);
+    }
+
+    #[test]
+    fn test_cache_counts_lookups() {
+        let mut cache = StatementCache::new(10);
+        assert_eq!(cache.get("SELECT 1").map(|s| s.name.clone()), None);
+        cache.insert(
+            "SELECT 1".to_string(),
+            PreparedStatement::new("s1".to_string(), "SELECT 1".to_string()),
+        );
+        assert!(cache.get_and_touch("SELECT 1").is_some());
+        assert!(cache.contains("SELECT 1"));
+        assert_eq!(cache.lookups(), 2);
+    }
+
     #[test]
     fn test_cache_basic_operations() {
         let mut cache = StatementCache::new(10);
@@ -279,6 +547,25 @@ mod tests {
         assert!(cache.get("q1").is_none());
     }
 
+    #[test]
+    fn test_cache_statements() {
+        let mut cache = StatementCache::new(10);
+        let mut stmt = PreparedStatement::new("s1".to_string(), "SELECT $1".to_string());
+        stmt.set_param_types(vec![Oid::INT4]);
+        cache.insert("SELECT $1".to_string(), stmt);
+        cache.insert(
+            "SELECT 2".to_string(),
+            PreparedStatement::new("s2".to_string(), "SELECT 2".to_string()),
+        );
+
+        let statements = cache.statements();
+        assert_eq!(statements.len(), 2);
+        // Most recently used first
+        assert_eq!(statements[0].name, "s2");
+        assert_eq!(statements[1].query, "SELECT $1");
+        assert_eq!(statements[1].param_types, vec![Oid::INT4]);
+    }
+
     #[test]
     fn test_unique_statement_names() {
         let mut cache = StatementCache::new(10);
@@ -3,6 +3,10 @@
 //! This module provides binary format encoding/decoding for PostgreSQL types.
 //! Reference: https://www.postgresql.org/docs/current/protocol-overview.html#PROTOCOL-FORMAT-CODES
 
+use super::datetime::{
+    format_date, format_time, format_timestamp, format_utc_offset, DateStyle, Interval,
+    IntervalStyle,
+};
 use super::error::{PgError, PgResult};
 
 // ============================================================================
@@ -31,12 +35,22 @@ impl Oid {
     pub const INT2: Oid = Oid(21);
     pub const INT4: Oid = Oid(23);
 
+    // Catalog vector types (one-dimensional, space-separated in text form)
+    pub const INT2VECTOR: Oid = Oid(22);
+    pub const OIDVECTOR: Oid = Oid(30);
+
     // Text types
     pub const TEXT: Oid = Oid(25);
+    /// Type of an uncast string literal (`SELECT 'x'`); always text on the wire
+    pub const UNKNOWN: Oid = Oid(705);
 
     // OID type
     pub const OID_TYPE: Oid = Oid(26);
 
+    // OID alias types; text output is the referenced object's name
+    pub const REGCLASS: Oid = Oid(2205);
+    pub const REGTYPE: Oid = Oid(2206);
+
     // Floating point types
     pub const FLOAT4: Oid = Oid(700);
     pub const FLOAT8: Oid = Oid(701);
@@ -69,8 +83,47 @@ impl Oid {
     pub const JSONB: Oid = Oid(3802);
 
     // Array types (some common ones)
+    pub const BOOL_ARRAY: Oid = Oid(1000);
+    pub const BYTEA_ARRAY: Oid = Oid(1001);
+    pub const CHAR_ARRAY: Oid = Oid(1002);
+    pub const NAME_ARRAY: Oid = Oid(1003);
+    pub const INT2_ARRAY: Oid = Oid(1005);
     pub const INT4_ARRAY: Oid = Oid(1007);
     pub const TEXT_ARRAY: Oid = Oid(1009);
+    pub const BPCHAR_ARRAY: Oid = Oid(1014);
+    pub const VARCHAR_ARRAY: Oid = Oid(1015);
+    pub const INT8_ARRAY: Oid = Oid(1016);
+    pub const FLOAT4_ARRAY: Oid = Oid(1021);
+    pub const FLOAT8_ARRAY: Oid = Oid(1022);
+    pub const OID_ARRAY: Oid = Oid(1028);
+    pub const TIMESTAMP_ARRAY: Oid = Oid(1115);
+    pub const DATE_ARRAY: Oid = Oid(1182);
+    pub const TIMESTAMPTZ_ARRAY: Oid = Oid(1185);
+    pub const INTERVAL_ARRAY: Oid = Oid(1187);
+    pub const NUMERIC_ARRAY: Oid = Oid(1231);
+    pub const UUID_ARRAY: Oid = Oid(2951);
+    pub const JSON_ARRAY: Oid = Oid(199);
+    pub const JSONB_ARRAY: Oid = Oid(3807);
+    pub const RECORD_ARRAY: Oid = Oid(2287);
+
+    // Anonymous composite (ROW(...) expressions)
+    pub const RECORD: Oid = Oid(2249);
+
+    // Range types
+    pub const INT4RANGE: Oid = Oid(3904);
+    pub const NUMRANGE: Oid = Oid(3906);
+    pub const TSRANGE: Oid = Oid(3908);
+    pub const TSTZRANGE: Oid = Oid(3910);
+    pub const DATERANGE: Oid = Oid(3912);
+    pub const INT8RANGE: Oid = Oid(3926);
+
+    // Multirange types (PostgreSQL 14+)
+    pub const INT4MULTIRANGE: Oid = Oid(4451);
+    pub const NUMMULTIRANGE: Oid = Oid(4532);
+    pub const TSMULTIRANGE: Oid = Oid(4533);
+    pub const TSTZMULTIRANGE: Oid = Oid(4534);
+    pub const DATEMULTIRANGE: Oid = Oid(4535);
+    pub const INT8MULTIRANGE: Oid = Oid(4536);
 
     // Numeric
     pub const NUMERIC: Oid = Oid(1700);
@@ -91,7 +144,7 @@ impl Oid {
     pub fn is_text_like(self) -> bool {
         matches!(
             self,
-            Oid::TEXT | Oid::VARCHAR | Oid::BPCHAR | Oid::CHAR | Oid::NAME
+            Oid::TEXT | Oid::VARCHAR | Oid::BPCHAR | Oid::CHAR | Oid::NAME | Oid::UNKNOWN
         )
     }
 
@@ -104,6 +157,35 @@ impl Oid {
     pub fn is_float(self) -> bool {
         matches!(self, Oid::FLOAT4 | Oid::FLOAT8)
     }
+
+    /// Check if this is one of the array types decoded as `PgValue::Array`
+    pub fn is_array(self) -> bool {
+        matches!(
+            self,
+            Oid::BOOL_ARRAY
+                | Oid::BYTEA_ARRAY
+                | Oid::CHAR_ARRAY
+                | Oid::NAME_ARRAY
+                | Oid::INT2_ARRAY
+                | Oid::INT4_ARRAY
+                | Oid::TEXT_ARRAY
+                | Oid::BPCHAR_ARRAY
+                | Oid::VARCHAR_ARRAY
+                | Oid::INT8_ARRAY
+                | Oid::FLOAT4_ARRAY
+                | Oid::FLOAT8_ARRAY
+                | Oid::OID_ARRAY
+                | Oid::TIMESTAMP_ARRAY
+                | Oid::DATE_ARRAY
+                | Oid::TIMESTAMPTZ_ARRAY
+                | Oid::INTERVAL_ARRAY
+                | Oid::NUMERIC_ARRAY
+                | Oid::UUID_ARRAY
+                | Oid::JSON_ARRAY
+                | Oid::JSONB_ARRAY
+                | Oid::RECORD_ARRAY
+        )
+    }
 }
 
 // ============================================================================
@@ -123,13 +205,43 @@ pub enum PgValue {
     Text(String),
     Bytea(Vec<u8>),
     Uuid([u8; 16]),
-    // Timestamps stored as microseconds since 2000-01-01
+    // Timestamps stored as microseconds since 2000-01-01; i64::MAX and
+    // i64::MIN are `infinity` and `-infinity`
     Timestamp(i64),
+    // TIMESTAMPTZ, as microseconds since 2000-01-01 00:00:00 UTC
+    TimestampTz(i64),
     Date(i32),
     Time(i64),
+    // Time of day with its UTC offset in seconds, positive east of UTC as
+    // written in `12:00:00+02`
+    TimeTz {
+        micros: i64,
+        tz_offset_secs: i32,
+    },
+    Interval(Interval),
+    // Decimal text such as "-12.50"; sent as NUMERIC
+    Numeric(String),
     Json(String),
+    // Array of elements; a multi-dimensional array nests one Array per
+    // dimension
+    Array(Vec<PgValue>),
+    // Composite value (a ROW or table row type), one entry per field
+    Record(Vec<PgValue>),
+    // Range; a None bound is infinite. Bounds of an empty range are None
+    Range {
+        lower: Option<Box<PgValue>>,
+        upper: Option<Box<PgValue>>,
+        lower_inc: bool,
+        upper_inc: bool,
+        empty: bool,
+    },
+    // Multirange: its ranges in order, each a `PgValue::Range`
+    MultiRange(Vec<PgValue>),
     // For types we don't handle specially - store raw bytes
-    Raw { oid: Oid, data: Vec<u8> },
+    Raw {
+        oid: Oid,
+        data: Vec<u8>,
+    },
 }
 
 impl PgValue {
@@ -152,14 +264,183 @@ impl PgValue {
             PgValue::Text(v) => v.as_bytes().to_vec(),
             PgValue::Bytea(v) => v.clone(),
             PgValue::Uuid(v) => v.to_vec(),
-            PgValue::Timestamp(v) => v.to_be_bytes().to_vec(),
+            PgValue::Timestamp(v) | PgValue::TimestampTz(v) => v.to_be_bytes().to_vec(),
             PgValue::Date(v) => v.to_be_bytes().to_vec(),
             PgValue::Time(v) => v.to_be_bytes().to_vec(),
+            PgValue::TimeTz {
+                micros,
+                tz_offset_secs,
+            } => {
+                // The wire format counts the offset west of UTC
+                let mut buf = Vec::with_capacity(12);
+                buf.extend_from_slice(&micros.to_be_bytes());
+                buf.extend_from_slice(&(-tz_offset_secs).to_be_bytes());
+                buf
+            }
+            PgValue::Interval(v) => {
+                let mut buf = Vec::with_capacity(16);
+                buf.extend_from_slice(&v.microseconds.to_be_bytes());
+                buf.extend_from_slice(&v.days.to_be_bytes());
+                buf.extend_from_slice(&v.months.to_be_bytes());
+                buf
+            }
+            PgValue::Numeric(v) => encode_binary_numeric(v),
             PgValue::Json(v) => v.as_bytes().to_vec(),
+            PgValue::Array(items) => encode_binary_array(items),
+            PgValue::Record(fields) => encode_binary_record(fields),
+            PgValue::Range {
+                lower,
+                upper,
+                lower_inc,
+                upper_inc,
+                empty,
+            } => encode_binary_range(
+                lower.as_deref(),
+                upper.as_deref(),
+                *lower_inc,
+                *upper_inc,
+                *empty,
+            ),
+            PgValue::MultiRange(ranges) => encode_binary_multirange(ranges),
             PgValue::Raw { data, .. } => data.clone(),
         }
     }
 
+    /// Append this value in COPY text format (no delimiter or row terminator).
+    ///
+    /// NULL becomes `\N`; backslash, tab, newline and carriage return are
+    /// escaped so values can't break the row/column framing.
+    pub fn encode_copy_text(&self, buf: &mut Vec<u8>) -> PgResult<()> {
+        match self {
+            PgValue::Null => buf.extend_from_slice(b"\\N"),
+            value => escape_copy_text(value.encode_text()?.as_bytes(), buf),
+        }
+        Ok(())
+    }
+
+    /// The text input form of a non-NULL value, before any COPY escaping.
+    fn encode_text(&self) -> PgResult<String> {
+        let out_of_range = || {
+            PgError::Type(format!(
+                "{:?} value is out of range for COPY text format",
+                self.type_oid()
+            ))
+        };
+        Ok(match self {
+            PgValue::Bool(v) => if *v { "t" } else { "f" }.to_string(),
+            PgValue::Int2(v) => v.to_string(),
+            PgValue::Int4(v) => v.to_string(),
+            PgValue::Int8(v) => v.to_string(),
+            PgValue::Float4(v) => v.to_string(),
+            PgValue::Float8(v) => v.to_string(),
+            PgValue::Text(v) | PgValue::Json(v) | PgValue::Numeric(v) => v.clone(),
+            PgValue::Interval(v) => v.to_iso8601(),
+            // Hex bytea input
+            PgValue::Bytea(v) => {
+                let mut out = String::from("\\x");
+                for byte in v {
+                    out.push_str(&format!("{:02x}", byte));
+                }
+                out
+            }
+            PgValue::Uuid(v) => {
+                let mut out = String::with_capacity(36);
+                for (i, byte) in v.iter().enumerate() {
+                    if matches!(i, 4 | 6 | 8 | 10) {
+                        out.push('-');
+                    }
+                    out.push_str(&format!("{:02x}", byte));
+                }
+                out
+            }
+            PgValue::Timestamp(v) => format_timestamp(*v, false).ok_or_else(out_of_range)?,
+            PgValue::TimestampTz(v) => format_timestamp(*v, true).ok_or_else(out_of_range)?,
+            PgValue::Date(v) => format_date(*v).ok_or_else(out_of_range)?,
+            PgValue::Time(v) => format_time(*v),
+            PgValue::TimeTz {
+                micros,
+                tz_offset_secs,
+            } => format_time(*micros) + &format_utc_offset(*tz_offset_secs),
+            PgValue::Array(items) => {
+                let mut out = String::new();
+                encode_text_array(items, &mut out)?;
+                out
+            }
+            other => {
+                return Err(PgError::Type(format!(
+                    "Cannot encode {:?} in COPY text format",
+                    other.type_oid()
+                )))
+            }
+        })
+    }
+
+    /// Convert this value to type `oid` for binary COPY or a batch Bind.
+    ///
+    /// Bind lets the server cast a parameter to the column's type, but
+    /// binary COPY data must already be in the column's own format, as must
+    /// a parameter bound to a statement prepared for another type. Integers
+    /// convert to any numeric type (checking range), floats to the other
+    /// float type or NUMERIC, and text to the other text types and JSON.
+    /// NULL and values already of that type pass through.
+    pub fn coerce_to(self, oid: Oid) -> PgResult<PgValue> {
+        let mismatch = |value: &PgValue| {
+            PgError::Type(format!(
+                "Cannot convert {:?} to {:?}",
+                value.type_oid(),
+                oid
+            ))
+        };
+        let int = match self {
+            PgValue::Int2(v) => Some(v as i64),
+            PgValue::Int4(v) => Some(v as i64),
+            PgValue::Int8(v) => Some(v),
+            _ => None,
+        };
+        if let Some(v) = int {
+            let out_of_range = |_| PgError::Type(format!("{} is out of range for {:?}", v, oid));
+            return match oid {
+                Oid::INT2 => i16::try_from(v).map(PgValue::Int2).map_err(out_of_range),
+                Oid::INT4 => i32::try_from(v).map(PgValue::Int4).map_err(out_of_range),
+                Oid::INT8 => Ok(PgValue::Int8(v)),
+                Oid::FLOAT4 => Ok(PgValue::Float4(v as f32)),
+                Oid::FLOAT8 => Ok(PgValue::Float8(v as f64)),
+                Oid::NUMERIC => Ok(PgValue::Numeric(v.to_string())),
+                _ => Err(mismatch(&self)),
+            };
+        }
+
+        match (self, oid) {
+            (PgValue::Json(v), Oid::JSON) => Ok(PgValue::Raw {
+                oid,
+                data: v.into_bytes(),
+            }),
+            // JSONB's binary form is a version byte and the text
+            (PgValue::Text(v) | PgValue::Json(v), Oid::JSONB) => {
+                let mut data = Vec::with_capacity(v.len() + 1);
+                data.push(1);
+                data.extend_from_slice(v.as_bytes());
+                Ok(PgValue::Raw { oid, data })
+            }
+            (value, _) if value.is_null() || value.type_oid() == oid => Ok(value),
+            // Text types share one binary form: the UTF-8 bytes
+            (PgValue::Text(v), _) if oid.is_text_like() => Ok(PgValue::Text(v)),
+            (PgValue::Text(v), Oid::JSON) => Ok(PgValue::Raw {
+                oid,
+                data: v.into_bytes(),
+            }),
+            (PgValue::Float4(v), Oid::FLOAT8) => Ok(PgValue::Float8(v as f64)),
+            (PgValue::Float8(v), Oid::FLOAT4) => Ok(PgValue::Float4(v as f32)),
+            (PgValue::Float4(v), Oid::NUMERIC) if v.is_finite() => {
+                Ok(PgValue::Numeric(v.to_string()))
+            }
+            (PgValue::Float8(v), Oid::NUMERIC) if v.is_finite() => {
+                Ok(PgValue::Numeric(v.to_string()))
+            }
+            (value, _) => Err(mismatch(&value)),
+        }
+    }
+
     /// Get the OID for this value's type
     pub fn type_oid(&self) -> Oid {
         match self {
@@ -174,9 +455,28 @@ impl PgValue {
             PgValue::Bytea(_) => Oid::BYTEA,
             PgValue::Uuid(_) => Oid::UUID,
             PgValue::Timestamp(_) => Oid::TIMESTAMP,
+            PgValue::TimestampTz(_) => Oid::TIMESTAMPTZ,
             PgValue::Date(_) => Oid::DATE,
             PgValue::Time(_) => Oid::TIME,
+            PgValue::TimeTz { .. } => Oid::TIMETZ,
+            PgValue::Interval(_) => Oid::INTERVAL,
+            PgValue::Numeric(_) => Oid::NUMERIC,
             PgValue::Json(_) => Oid::JSONB,
+            PgValue::Array(items) => array_oid(array_element_oid(items)),
+            PgValue::Record(_) => Oid::RECORD,
+            PgValue::Range { lower, upper, .. } => range_oid(
+                lower
+                    .as_deref()
+                    .or(upper.as_deref())
+                    .map(PgValue::type_oid)
+                    .unwrap_or(Oid::INT4),
+            ),
+            PgValue::MultiRange(ranges) => multirange_oid(
+                ranges
+                    .first()
+                    .map(PgValue::type_oid)
+                    .unwrap_or(Oid::INT4RANGE),
+            ),
             PgValue::Raw { oid, .. } => *oid,
         }
     }
@@ -245,7 +545,7 @@ impl PgValue {
                 )))
             }
 
-            Oid::TEXT | Oid::VARCHAR | Oid::BPCHAR | Oid::CHAR | Oid::NAME => {
+            Oid::TEXT | Oid::VARCHAR | Oid::BPCHAR | Oid::CHAR | Oid::NAME | Oid::UNKNOWN => {
                 // Validate UTF-8 in place, then convert to String
                 // Using from_utf8 validates, then we can use from_utf8_unchecked to avoid double validation
                 match std::str::from_utf8(data) {
@@ -279,9 +579,12 @@ impl PgValue {
                         data.len()
                     )));
                 }
-                Ok(PgValue::Timestamp(i64::from_be_bytes(
-                    data.try_into().unwrap(),
-                )))
+                let micros = i64::from_be_bytes(data.try_into().unwrap());
+                if oid == Oid::TIMESTAMPTZ {
+                    Ok(PgValue::TimestampTz(micros))
+                } else {
+                    Ok(PgValue::Timestamp(micros))
+                }
             }
 
             Oid::DATE => {
@@ -294,7 +597,21 @@ impl PgValue {
                 Ok(PgValue::Date(i32::from_be_bytes(data.try_into().unwrap())))
             }
 
-            Oid::TIME | Oid::TIMETZ => {
+            Oid::TIMETZ => {
+                if data.len() != 12 {
+                    return Err(PgError::Type(format!(
+                        "Invalid TIMETZ length: {}",
+                        data.len()
+                    )));
+                }
+                Ok(PgValue::TimeTz {
+                    micros: i64::from_be_bytes(data[..8].try_into().unwrap()),
+                    // Sent as seconds west of UTC
+                    tz_offset_secs: -i32::from_be_bytes(data[8..].try_into().unwrap()),
+                })
+            }
+
+            Oid::TIME => {
                 if data.len() < 8 {
                     return Err(PgError::Type(format!(
                         "Invalid TIME length: {}",
@@ -306,6 +623,20 @@ impl PgValue {
                 )))
             }
 
+            Oid::INTERVAL => {
+                if data.len() != 16 {
+                    return Err(PgError::Type(format!(
+                        "Invalid INTERVAL length: {}",
+                        data.len()
+                    )));
+                }
+                Ok(PgValue::Interval(Interval {
+                    microseconds: i64::from_be_bytes(data[..8].try_into().unwrap()),
+                    days: i32::from_be_bytes(data[8..12].try_into().unwrap()),
+                    months: i32::from_be_bytes(data[12..].try_into().unwrap()),
+                }))
+            }
+
             Oid::JSON | Oid::JSONB => {
                 // JSONB has a version byte prefix
                 let json_data = if oid == Oid::JSONB && !data.is_empty() {
@@ -324,6 +655,61 @@ impl PgValue {
                 }
             }
 
+            Oid::INT2VECTOR | Oid::OIDVECTOR => decode_binary_array(data).map(PgValue::Array),
+
+            // The bare OID; oid is unsigned 32-bit, so widen to INT8
+            Oid::REGCLASS | Oid::REGTYPE => {
+                if data.len() != 4 {
+                    return Err(PgError::Type(format!("Invalid OID length: {}", data.len())));
+                }
+                Ok(PgValue::Int8(
+                    u32::from_be_bytes(data.try_into().unwrap()) as i64
+                ))
+            }
+
+            _ if oid.is_array() => decode_binary_array(data).map(PgValue::Array),
+
+            Oid::RECORD => decode_binary_record(data).map(PgValue::Record),
+
+            Oid::INT4RANGE
+            | Oid::INT8RANGE
+            | Oid::NUMRANGE
+            | Oid::TSRANGE
+            | Oid::TSTZRANGE
+            | Oid::DATERANGE => decode_binary_range(range_element_oid(oid), data),
+
+            Oid::INT4MULTIRANGE
+            | Oid::INT8MULTIRANGE
+            | Oid::NUMMULTIRANGE
+            | Oid::TSMULTIRANGE
+            | Oid::TSTZMULTIRANGE
+            | Oid::DATEMULTIRANGE => {
+                decode_binary_multirange(range_element_oid(multirange_range_oid(oid)), data)
+                    .map(PgValue::MultiRange)
+            }
+
+            Oid::NUMERIC => decode_binary_numeric(data).map(PgValue::Numeric),
+
+            // An integer count of the smallest currency unit; assumes the
+            // usual two fractional digits
+            Oid::MONEY => {
+                if data.len() != 8 {
+                    return Err(PgError::Type(format!(
+                        "Invalid MONEY length: {}",
+                        data.len()
+                    )));
+                }
+                let cents = i64::from_be_bytes(data.try_into().unwrap());
+                let sign = if cents < 0 { "-" } else { "" };
+                let abs = cents.unsigned_abs();
+                Ok(PgValue::Numeric(format!(
+                    "{}{}.{:02}",
+                    sign,
+                    abs / 100,
+                    abs % 100
+                )))
+            }
+
             // For unknown types, store raw bytes
             _ => Ok(PgValue::Raw {
                 oid,
@@ -367,6 +753,46 @@ impl PgValue {
                 .map(PgValue::Float8)
                 .map_err(|e| PgError::Type(format!("Invalid FLOAT8: {}", e))),
 
+            Oid::BYTEA => decode_bytea_text(data, ByteaOutput::detect(data)).map(PgValue::Bytea),
+
+            // NUMERIC output is always plain decimal, whatever the locale
+            Oid::NUMERIC => Ok(PgValue::Numeric(text)),
+
+            // MONEY output follows lc_monetary
+            Oid::MONEY => normalize_money_text(&text).map(PgValue::Numeric),
+
+            // Assumes the default DateStyle/IntervalStyle; connections that
+            // know the server's settings decode these themselves
+            Oid::DATE => DateStyle::default().decode_date(&text).map(PgValue::Date),
+
+            Oid::INTERVAL => IntervalStyle::default()
+                .decode_interval(&text)
+                .map(PgValue::Interval),
+
+            Oid::INT2VECTOR => text
+                .split_ascii_whitespace()
+                .map(|item| {
+                    item.parse::<i16>()
+                        .map(PgValue::Int2)
+                        .map_err(|e| PgError::Type(format!("Invalid INT2VECTOR: {}", e)))
+                })
+                .collect::<PgResult<_>>()
+                .map(PgValue::Array),
+
+            // oid is unsigned 32-bit, so widen to INT8
+            Oid::OIDVECTOR => text
+                .split_ascii_whitespace()
+                .map(|item| {
+                    item.parse::<u32>()
+                        .map(|v| PgValue::Int8(v as i64))
+                        .map_err(|e| PgError::Type(format!("Invalid OIDVECTOR: {}", e)))
+                })
+                .collect::<PgResult<_>>()
+                .map(PgValue::Array),
+
+            // The object's name, e.g. `users` or `integer`
+            Oid::REGCLASS | Oid::REGTYPE => Ok(PgValue::Text(text)),
+
             // Text types
             _ if oid.is_text_like() => Ok(PgValue::Text(text)),
 
@@ -376,6 +802,643 @@ impl PgValue {
     }
 }
 
+// ============================================================================
+// Binary Numeric Format
+// ============================================================================
+
+/// Encode decimal text (optional sign, digits, optional fraction, or
+/// "NaN"/"Infinity") as a binary NUMERIC: base-10000 digit groups with a
+/// weight and display scale.
+fn encode_binary_numeric(text: &str) -> Vec<u8> {
+    const NUMERIC_POS: u16 = 0x0000;
+    const NUMERIC_NEG: u16 = 0x4000;
+    const NUMERIC_NAN: u16 = 0xC000;
+    const NUMERIC_PINF: u16 = 0xD000;
+    const NUMERIC_NINF: u16 = 0xF000;
+
+    let special = match text {
+        "NaN" | "-NaN" => Some(NUMERIC_NAN),
+        "Infinity" | "+Infinity" => Some(NUMERIC_PINF),
+        "-Infinity" => Some(NUMERIC_NINF),
+        _ => None,
+    };
+    if let Some(sign) = special {
+        let mut buf = Vec::with_capacity(8);
+        buf.extend_from_slice(&0i16.to_be_bytes());
+        buf.extend_from_slice(&0i16.to_be_bytes());
+        buf.extend_from_slice(&sign.to_be_bytes());
+        buf.extend_from_slice(&0u16.to_be_bytes());
+        return buf;
+    }
+
+    let (negative, unsigned) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let (int_part, frac_part) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let int_part = int_part.trim_start_matches('0');
+
+    // Pad both parts to whole groups of four digits around the decimal point
+    let int_pad = (4 - int_part.len() % 4) % 4;
+    let frac_pad = (4 - frac_part.len() % 4) % 4;
+    let padded: Vec<u8> = std::iter::repeat_n(b'0', int_pad)
+        .chain(int_part.bytes())
+        .chain(frac_part.bytes())
+        .chain(std::iter::repeat_n(b'0', frac_pad))
+        .collect();
+    let mut groups: Vec<i16> = padded
+        .chunks(4)
+        .map(|chunk| {
+            chunk
+                .iter()
+                .fold(0i16, |acc, d| acc * 10 + (d - b'0') as i16)
+        })
+        .collect();
+
+    let mut weight = ((int_pad + int_part.len()) / 4) as i16 - 1;
+    while groups.first() == Some(&0) {
+        groups.remove(0);
+        weight -= 1;
+    }
+    while groups.last() == Some(&0) {
+        groups.pop();
+    }
+    if groups.is_empty() {
+        weight = 0;
+    }
+
+    let sign = if negative && !groups.is_empty() {
+        NUMERIC_NEG
+    } else {
+        NUMERIC_POS
+    };
+
+    let mut buf = Vec::with_capacity(8 + groups.len() * 2);
+    buf.extend_from_slice(&(groups.len() as i16).to_be_bytes());
+    buf.extend_from_slice(&weight.to_be_bytes());
+    buf.extend_from_slice(&sign.to_be_bytes());
+    buf.extend_from_slice(&(frac_part.len() as u16).to_be_bytes());
+    for group in groups {
+        buf.extend_from_slice(&group.to_be_bytes());
+    }
+    buf
+}
+
+/// Decode a binary NUMERIC to plain decimal text ("-1234.56", "NaN",
+/// "Infinity"), keeping the display scale's trailing zeros.
+fn decode_binary_numeric(data: &[u8]) -> PgResult<String> {
+    const NUMERIC_NEG: u16 = 0x4000;
+    const NUMERIC_NAN: u16 = 0xC000;
+    const NUMERIC_PINF: u16 = 0xD000;
+    const NUMERIC_NINF: u16 = 0xF000;
+
+    if data.len() < 8 {
+        return Err(PgError::Type(format!(
+            "Invalid NUMERIC length: {}",
+            data.len()
+        )));
+    }
+    let ndigits = i16::from_be_bytes([data[0], data[1]]).max(0) as usize;
+    let weight = i16::from_be_bytes([data[2], data[3]]) as isize;
+    let sign = u16::from_be_bytes([data[4], data[5]]);
+    let dscale = u16::from_be_bytes([data[6], data[7]]) as usize;
+    if data.len() != 8 + ndigits * 2 {
+        return Err(PgError::Type(format!(
+            "Invalid NUMERIC length {} for {} digits",
+            data.len(),
+            ndigits
+        )));
+    }
+
+    match sign {
+        NUMERIC_NAN => return Ok("NaN".to_string()),
+        NUMERIC_PINF => return Ok("Infinity".to_string()),
+        NUMERIC_NINF => return Ok("-Infinity".to_string()),
+        _ => {}
+    }
+
+    let digits: Vec<i16> = data[8..]
+        .chunks(2)
+        .map(|c| i16::from_be_bytes([c[0], c[1]]))
+        .collect();
+    // Base-10000 group at position `i` (group 0 has exponent `weight`)
+    let group = |i: isize| -> i16 {
+        if i < 0 {
+            0
+        } else {
+            digits.get(i as usize).copied().unwrap_or(0)
+        }
+    };
+
+    let mut out = String::new();
+    if sign == NUMERIC_NEG {
+        out.push('-');
+    }
+    if weight < 0 {
+        out.push('0');
+    } else {
+        out.push_str(&group(0).to_string());
+        for i in 1..=weight {
+            out.push_str(&format!("{:04}", group(i)));
+        }
+    }
+
+    if dscale > 0 {
+        let mut frac = String::with_capacity(dscale + 4);
+        let mut i = weight + 1;
+        while frac.len() < dscale {
+            frac.push_str(&format!("{:04}", group(i)));
+            i += 1;
+        }
+        frac.truncate(dscale);
+        out.push('.');
+        out.push_str(&frac);
+    }
+
+    Ok(out)
+}
+
+/// Normalize MONEY text output to plain decimal text ("-1234.56").
+///
+/// The server formats MONEY per `lc_monetary`, so currency symbols, grouping
+/// separators, the decimal point and how negatives are shown all vary. The
+/// last `.` or `,` is taken as the decimal point, unless exactly three digits
+/// follow it (a grouping separator in a locale without fractional digits).
+fn normalize_money_text(text: &str) -> PgResult<String> {
+    let negative = text.contains(['-', '(', '\u{2212}']);
+    let kept: String = text
+        .chars()
+        .filter(|c| c.is_ascii_digit() || *c == '.' || *c == ',')
+        .collect();
+    if !kept.bytes().any(|b| b.is_ascii_digit()) {
+        return Err(PgError::Type(format!("Invalid MONEY: {}", text)));
+    }
+
+    let (int_part, frac_part) = match kept.rfind(['.', ',']) {
+        Some(pos) if kept.len() - pos - 1 != 3 => (&kept[..pos], &kept[pos + 1..]),
+        _ => (kept.as_str(), ""),
+    };
+    let int_digits: String = int_part.chars().filter(char::is_ascii_digit).collect();
+    let int_digits = int_digits.trim_start_matches('0');
+
+    let mut out = String::new();
+    if negative {
+        out.push('-');
+    }
+    out.push_str(if int_digits.is_empty() {
+        "0"
+    } else {
+        int_digits
+    });
+    if !frac_part.is_empty() {
+        out.push('.');
+        out.push_str(frac_part);
+    }
+    Ok(out)
+}
+
+// ============================================================================
+// Binary Array Format
+// ============================================================================
+
+/// Array type OID for an element type (TEXT[] when there is no better match).
+fn array_oid(element: Oid) -> Oid {
+    match element {
+        Oid::INT4 => Oid::INT4_ARRAY,
+        Oid::INT2 => Oid::INT2_ARRAY,
+        Oid::INT8 => Oid::INT8_ARRAY,
+        Oid::BOOL => Oid::BOOL_ARRAY,
+        Oid::FLOAT4 => Oid::FLOAT4_ARRAY,
+        Oid::FLOAT8 => Oid::FLOAT8_ARRAY,
+        Oid::UUID => Oid::UUID_ARRAY,
+        Oid::RECORD => Oid::RECORD_ARRAY,
+        _ => Oid::TEXT_ARRAY,
+    }
+}
+
+/// Element type of an array: the type of its first non-NULL element.
+fn array_element_oid(items: &[PgValue]) -> Oid {
+    items
+        .iter()
+        .find(|v| !v.is_null())
+        .map(PgValue::type_oid)
+        .unwrap_or(Oid::TEXT)
+}
+
+/// Encode a one-dimensional array in the binary array format.
+fn encode_binary_array(items: &[PgValue]) -> Vec<u8> {
+    let element_oid = array_element_oid(items);
+    let has_nulls = items.iter().any(PgValue::is_null);
+
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&1i32.to_be_bytes()); // ndim
+    buf.extend_from_slice(&(has_nulls as i32).to_be_bytes());
+    buf.extend_from_slice(&element_oid.as_i32().to_be_bytes());
+    buf.extend_from_slice(&(items.len() as i32).to_be_bytes());
+    buf.extend_from_slice(&1i32.to_be_bytes()); // lower bound
+    for item in items {
+        if item.is_null() {
+            buf.extend_from_slice(&(-1i32).to_be_bytes());
+        } else {
+            let data = item.encode_binary();
+            buf.extend_from_slice(&(data.len() as i32).to_be_bytes());
+            buf.extend_from_slice(&data);
+        }
+    }
+    buf
+}
+
+/// Read a big-endian i32 from binary array or composite data.
+fn read_i32(data: &[u8], pos: &mut usize) -> PgResult<i32> {
+    let bytes = data
+        .get(*pos..*pos + 4)
+        .ok_or_else(|| PgError::Type("Truncated array data".to_string()))?;
+    *pos += 4;
+    Ok(i32::from_be_bytes(bytes.try_into().unwrap()))
+}
+
+/// Read a length-prefixed value (-1 = NULL) from binary array or composite
+/// data.
+fn read_value<'a>(data: &'a [u8], pos: &mut usize) -> PgResult<Option<&'a [u8]>> {
+    let len = read_i32(data, pos)?;
+    if len < 0 {
+        return Ok(None);
+    }
+    let value = data
+        .get(*pos..*pos + len as usize)
+        .ok_or_else(|| PgError::Type("Truncated array element".to_string()))?;
+    *pos += len as usize;
+    Ok(Some(value))
+}
+
+/// Decode an array from the binary array format.
+///
+/// Layout: ndim, has-nulls flag, element OID, then (length, lower bound)
+/// per dimension, then each element in row-major order as a length-prefixed
+/// value (-1 = NULL). Multi-dimensional arrays come back nested, one
+/// `PgValue::Array` per dimension.
+fn decode_binary_array(data: &[u8]) -> PgResult<Vec<PgValue>> {
+    let mut pos = 0;
+    let ndim = read_i32(data, &mut pos)?;
+    let _has_nulls = read_i32(data, &mut pos)?;
+    let element_oid = Oid(read_i32(data, &mut pos)?);
+    // PostgreSQL's MAXDIM
+    if !(0..=6).contains(&ndim) {
+        return Err(PgError::Type(format!("Invalid array dimensions: {}", ndim)));
+    }
+    if ndim == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut dims = Vec::with_capacity(ndim as usize);
+    for _ in 0..ndim {
+        dims.push(read_i32(data, &mut pos)?.max(0) as usize);
+        let _lower_bound = read_i32(data, &mut pos)?;
+    }
+
+    let total = dims
+        .iter()
+        .try_fold(1usize, |total, &len| total.checked_mul(len))
+        .ok_or_else(|| PgError::Protocol(format!("Array dimensions overflow: {:?}", dims)))?;
+    // Every element takes at least its 4-byte length, so a bogus header
+    // can't make us reserve more than the data could hold
+    let mut items = Vec::with_capacity(total.min((data.len() - pos) / 4));
+    for _ in 0..total {
+        items.push(match read_value(data, &mut pos)? {
+            None => PgValue::Null,
+            // oid is unsigned 32-bit, so widen to INT8
+            Some(item) if element_oid == Oid::OID_TYPE && item.len() == 4 => {
+                PgValue::Int8(u32::from_be_bytes(item.try_into().unwrap()) as i64)
+            }
+            Some(item) => PgValue::decode_binary(element_oid, item)?,
+        });
+    }
+
+    // Fold the flat elements into sub-arrays, innermost dimension first
+    for &len in dims[1..].iter().rev() {
+        let mut rows = Vec::with_capacity(items.len() / len.max(1));
+        let mut rest = items.into_iter();
+        loop {
+            let row: Vec<PgValue> = rest.by_ref().take(len).collect();
+            if row.is_empty() {
+                break;
+            }
+            rows.push(PgValue::Array(row));
+        }
+        items = rows;
+    }
+    Ok(items)
+}
+
+/// Encode a composite value in the binary record format.
+fn encode_binary_record(fields: &[PgValue]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&(fields.len() as i32).to_be_bytes());
+    for field in fields {
+        buf.extend_from_slice(&field.type_oid().as_i32().to_be_bytes());
+        if field.is_null() {
+            buf.extend_from_slice(&(-1i32).to_be_bytes());
+        } else {
+            let data = field.encode_binary();
+            buf.extend_from_slice(&(data.len() as i32).to_be_bytes());
+            buf.extend_from_slice(&data);
+        }
+    }
+    buf
+}
+
+/// Decode a composite value from the binary record format.
+///
+/// Layout: field count, then per field its type OID and a length-prefixed
+/// value (-1 = NULL). Fields may themselves be arrays or composites.
+fn decode_binary_record(data: &[u8]) -> PgResult<Vec<PgValue>> {
+    let mut pos = 0;
+    let count = read_i32(data, &mut pos)?;
+    let mut fields = Vec::with_capacity(count.max(0) as usize);
+    for _ in 0..count {
+        let oid = Oid(read_i32(data, &mut pos)?);
+        fields.push(match read_value(data, &mut pos)? {
+            None => PgValue::Null,
+            Some(value) => PgValue::decode_binary(oid, value)?,
+        });
+    }
+    Ok(fields)
+}
+
+// ============================================================================
+// Binary Range Format
+// ============================================================================
+
+/// Range flag bits (from the server's rangetypes.h).
+const RANGE_EMPTY: u8 = 0x01;
+const RANGE_LB_INC: u8 = 0x02;
+const RANGE_UB_INC: u8 = 0x04;
+const RANGE_LB_INF: u8 = 0x08;
+const RANGE_UB_INF: u8 = 0x10;
+
+/// Range type OID for a bound type (INT4RANGE when there is no better match).
+fn range_oid(element: Oid) -> Oid {
+    match element {
+        Oid::INT8 => Oid::INT8RANGE,
+        Oid::NUMERIC => Oid::NUMRANGE,
+        Oid::TIMESTAMP => Oid::TSRANGE,
+        Oid::TIMESTAMPTZ => Oid::TSTZRANGE,
+        Oid::DATE => Oid::DATERANGE,
+        _ => Oid::INT4RANGE,
+    }
+}
+
+/// Bound type of a range type.
+fn range_element_oid(range: Oid) -> Oid {
+    match range {
+        Oid::INT8RANGE => Oid::INT8,
+        Oid::NUMRANGE => Oid::NUMERIC,
+        Oid::TSRANGE => Oid::TIMESTAMP,
+        Oid::TSTZRANGE => Oid::TIMESTAMPTZ,
+        Oid::DATERANGE => Oid::DATE,
+        _ => Oid::INT4,
+    }
+}
+
+/// Encode a range in the binary range format.
+fn encode_binary_range(
+    lower: Option<&PgValue>,
+    upper: Option<&PgValue>,
+    lower_inc: bool,
+    upper_inc: bool,
+    empty: bool,
+) -> Vec<u8> {
+    if empty {
+        return vec![RANGE_EMPTY];
+    }
+
+    let mut flags = 0;
+    if lower_inc {
+        flags |= RANGE_LB_INC;
+    }
+    if upper_inc {
+        flags |= RANGE_UB_INC;
+    }
+    if lower.is_none() {
+        flags |= RANGE_LB_INF;
+    }
+    if upper.is_none() {
+        flags |= RANGE_UB_INF;
+    }
+
+    let mut buf = vec![flags];
+    for bound in [lower, upper].into_iter().flatten() {
+        let data = bound.encode_binary();
+        buf.extend_from_slice(&(data.len() as i32).to_be_bytes());
+        buf.extend_from_slice(&data);
+    }
+    buf
+}
+
+/// Decode a range from the binary range format.
+///
+/// Layout: a flags byte, then the lower and upper bounds as length-prefixed
+/// values, each omitted when the range is empty or that bound is infinite.
+fn decode_binary_range(element_oid: Oid, data: &[u8]) -> PgResult<PgValue> {
+    let flags = *data
+        .first()
+        .ok_or_else(|| PgError::Type("Empty data for range".to_string()))?;
+    if flags & RANGE_EMPTY != 0 {
+        return Ok(PgValue::Range {
+            lower: None,
+            upper: None,
+            lower_inc: false,
+            upper_inc: false,
+            empty: true,
+        });
+    }
+
+    let mut pos = 1;
+    let mut read_bound = |infinite: bool| -> PgResult<Option<Box<PgValue>>> {
+        if infinite {
+            return Ok(None);
+        }
+        let value = read_value(data, &mut pos)?
+            .ok_or_else(|| PgError::Type("NULL range bound".to_string()))?;
+        Ok(Some(Box::new(PgValue::decode_binary(element_oid, value)?)))
+    };
+    let lower = read_bound(flags & RANGE_LB_INF != 0)?;
+    let upper = read_bound(flags & RANGE_UB_INF != 0)?;
+
+    Ok(PgValue::Range {
+        lower,
+        upper,
+        lower_inc: flags & RANGE_LB_INC != 0,
+        upper_inc: flags & RANGE_UB_INC != 0,
+        empty: false,
+    })
+}
+
+/// Multirange type OID for a range type.
+fn multirange_oid(range: Oid) -> Oid {
+    match range {
+        Oid::INT8RANGE => Oid::INT8MULTIRANGE,
+        Oid::NUMRANGE => Oid::NUMMULTIRANGE,
+        Oid::TSRANGE => Oid::TSMULTIRANGE,
+        Oid::TSTZRANGE => Oid::TSTZMULTIRANGE,
+        Oid::DATERANGE => Oid::DATEMULTIRANGE,
+        _ => Oid::INT4MULTIRANGE,
+    }
+}
+
+/// Range type of a multirange type.
+fn multirange_range_oid(multirange: Oid) -> Oid {
+    match multirange {
+        Oid::INT8MULTIRANGE => Oid::INT8RANGE,
+        Oid::NUMMULTIRANGE => Oid::NUMRANGE,
+        Oid::TSMULTIRANGE => Oid::TSRANGE,
+        Oid::TSTZMULTIRANGE => Oid::TSTZRANGE,
+        Oid::DATEMULTIRANGE => Oid::DATERANGE,
+        _ => Oid::INT4RANGE,
+    }
+}
+
+/// Encode ranges in the binary multirange format.
+fn encode_binary_multirange(ranges: &[PgValue]) -> Vec<u8> {
+    let mut buf = (ranges.len() as i32).to_be_bytes().to_vec();
+    for range in ranges {
+        let data = range.encode_binary();
+        buf.extend_from_slice(&(data.len() as i32).to_be_bytes());
+        buf.extend_from_slice(&data);
+    }
+    buf
+}
+
+/// Decode a multirange from the binary multirange format.
+///
+/// Layout: a range count, then each range as a length-prefixed value in the
+/// binary range format.
+fn decode_binary_multirange(element_oid: Oid, data: &[u8]) -> PgResult<Vec<PgValue>> {
+    let mut pos = 0;
+    let count = read_i32(data, &mut pos)?;
+    let mut ranges = Vec::with_capacity(count.max(0) as usize);
+    for _ in 0..count {
+        let range = read_value(data, &mut pos)?
+            .ok_or_else(|| PgError::Type("NULL range in multirange".to_string()))?;
+        ranges.push(decode_binary_range(element_oid, range)?);
+    }
+    Ok(ranges)
+}
+
+// ============================================================================
+// bytea Text Format
+// ============================================================================
+
+/// Text output format for bytea values (the server's `bytea_output` setting).
+///
+/// The server doesn't report `bytea_output` changes, so text results are
+/// decoded in the format detected from each value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ByteaOutput {
+    /// `\x` followed by two hex digits per byte (the default since 9.0)
+    Hex,
+    /// Printable ASCII as-is, everything else as `\ooo` octal escapes
+    Escape,
+}
+
+impl ByteaOutput {
+    /// Infer the format from a value.
+    ///
+    /// Escape output writes a literal backslash as `\\`, so only hex output
+    /// can start with `\x`.
+    pub fn detect(data: &[u8]) -> Self {
+        if data.starts_with(b"\\x") {
+            ByteaOutput::Hex
+        } else {
+            ByteaOutput::Escape
+        }
+    }
+}
+
+/// Decode a bytea value from text format.
+pub fn decode_bytea_text(data: &[u8], format: ByteaOutput) -> PgResult<Vec<u8>> {
+    match format {
+        ByteaOutput::Hex => {
+            let hex = data.strip_prefix(b"\\x").ok_or_else(|| {
+                PgError::Type("Invalid hex BYTEA: missing \\x prefix".to_string())
+            })?;
+            if hex.len() % 2 != 0 {
+                return Err(PgError::Type("Invalid hex BYTEA: odd length".to_string()));
+            }
+            hex.chunks(2)
+                .map(|pair| {
+                    std::str::from_utf8(pair)
+                        .ok()
+                        .and_then(|s| u8::from_str_radix(s, 16).ok())
+                        .ok_or_else(|| PgError::Type("Invalid hex BYTEA digit".to_string()))
+                })
+                .collect()
+        }
+        ByteaOutput::Escape => {
+            let mut out = Vec::with_capacity(data.len());
+            let mut i = 0;
+            while i < data.len() {
+                if data[i] != b'\\' {
+                    out.push(data[i]);
+                    i += 1;
+                } else if data.get(i + 1) == Some(&b'\\') {
+                    out.push(b'\\');
+                    i += 2;
+                } else {
+                    let octal = data
+                        .get(i + 1..i + 4)
+                        .and_then(|o| std::str::from_utf8(o).ok())
+                        .and_then(|o| u8::from_str_radix(o, 8).ok())
+                        .ok_or_else(|| PgError::Type("Invalid escape BYTEA".to_string()))?;
+                    out.push(octal);
+                    i += 4;
+                }
+            }
+            Ok(out)
+        }
+    }
+}
+
+/// Write an array literal such as `{"a","b c",NULL}`. Nested arrays are
+/// further dimensions; every other element is quoted.
+fn encode_text_array(items: &[PgValue], out: &mut String) -> PgResult<()> {
+    out.push('{');
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        match item {
+            PgValue::Null => out.push_str("NULL"),
+            PgValue::Array(inner) => encode_text_array(inner, out)?,
+            value => {
+                out.push('"');
+                for c in value.encode_text()?.chars() {
+                    if matches!(c, '"' | '\\') {
+                        out.push('\\');
+                    }
+                    out.push(c);
+                }
+                out.push('"');
+            }
+        }
+    }
+    out.push('}');
+    Ok(())
+}
+
+/// Escape bytes for a COPY text-format field.
+fn escape_copy_text(data: &[u8], buf: &mut Vec<u8>) {
+    for &byte in data {
+        match byte {
+            b'\\' => buf.extend_from_slice(b"\\\\"),
+            b'\t' => buf.extend_from_slice(b"\\t"),
+            b'\n' => buf.extend_from_slice(b"\\n"),
+            b'\r' => buf.extend_from_slice(b"\\r"),
+            _ => buf.push(byte),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -384,6 +1447,7 @@ mod tests {
     fn test_oid_classification() {
         assert!(Oid::TEXT.is_text_like());
         assert!(Oid::VARCHAR.is_text_like());
+        assert!(Oid::UNKNOWN.is_text_like());
         assert!(!Oid::INT4.is_text_like());
 
         assert!(Oid::INT4.is_integer());
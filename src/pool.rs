@@ -2,20 +2,75 @@
 //!
 //! No sqlx. Pure Rust. Maximum performance.
 
+use chrono::{NaiveDate, NaiveTime};
 use pyo3::prelude::*;
+use pyo3::types::{
+    PyBytes, PyDate, PyDateAccess, PyDateTime, PyDelta, PyDeltaAccess, PyDict, PyIterator, PyTime,
+    PyTimeAccess,
+};
 use smallvec::SmallVec;
+use std::future::Future;
+use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::Arc;
 
-use crate::error::{ForeignKeyError, Result};
-use crate::executor::{LazyRow, QueryResult, RowValue};
-use crate::pg::{PgPool, PgPoolConfig, PgValue, PooledConnection as PgPooledConnection};
-use crate::schema::{ColumnInfo, ConstraintInfo, IndexInfo, TableInfo};
-use crate::sqlite::{SqlitePool, SqlitePoolConfig, SqliteValue};
+use crate::csv::{ColumnKind, CsvReader};
+use crate::error::{pg_error_to_py, sqlite_error_to_py, ForeignKeyError, Result};
+use crate::executor::{
+    BytesFormat, ColumnCase, LazyRow, QueryResult, RowFactory, RowValue, StringInterner,
+};
+use crate::params::{rewrite_named_params, PlaceholderStyle};
+use crate::pg::datetime::{
+    date_to_days, datetime_to_micros, days_to_date, micros_to_datetime, Interval,
+};
+use crate::pg::pool::{command_tag_rows, parse_rows_affected};
+use crate::pg::protocol::{
+    copy_binary_header, encode_copy_binary_row, TransactionStatus, COPY_BINARY_TRAILER,
+};
+use crate::pg::{
+    ListenerEvent, Oid, PgError, PgListener, PgPool, PgPoolConfig, PgPreparedQuery, PgResult,
+    PgValue, PooledConnection as PgPooledConnection,
+};
+use crate::schema::{
+    BlockedQuery, ColumnInfo, ConstraintInfo, EnumInfo, ForeignKeyViolation, IndexColumn,
+    IndexInfo, IndexStats, TableInfo, TableSize,
+};
+use crate::sql::{pg_vacuum_sql, quote_ident, quote_qualified, Dialect};
+use crate::sqlite::{
+    PooledConnection as SqlitePooledConnection, RowStream as SqliteRowStream, SqliteError,
+    SqlitePool, SqlitePoolConfig, SqliteValue, UpsertResult,
+};
+use crate::typed::typed_param;
+
+/// Rows pulled from the Python iterator per chunk in `bulk_load`
+const BULK_LOAD_CHUNK_ROWS: usize = 1000;
+
+/// Converted chunks the CSV reader in `import_csv` may get ahead by
+const CSV_BUFFER_CHUNKS: usize = 2;
+
+/// Rows per batch yielded by `stream` unless the caller picks a size
+const STREAM_BATCH_ROWS: usize = 1000;
+
+/// Bytes of COPY data gathered into each chunk `copy_out_stream` yields
+const COPY_OUT_CHUNK_BYTES: usize = 64 * 1024;
 
 pub struct PoolConfig {
     pub url: String,
     pub min_connections: u32,
     pub max_connections: u32,
+    pub test_before_acquire: bool,
+    pub validation_query: Option<String>,
+    pub heartbeat_interval: Option<std::time::Duration>,
+    pub statement_timeout: Option<std::time::Duration>,
+    pub statement_cache_memory_limit: Option<usize>,
+    pub reset_on_return: bool,
+    pub acquire_timeout: Option<std::time::Duration>,
+    pub empty_string_as_null: bool,
+    pub pipeline_depth: usize,
+    pub column_case: ColumnCase,
+    pub bytes_format: BytesFormat,
+    pub intern_strings: bool,
+    pub uuid_as_object: bool,
+    pub default_row_factory: Option<RowFactory>,
 }
 
 #[derive(Clone)]
@@ -30,16 +85,39 @@ enum PoolInner {
 pub struct ConnectionPool {
     inner: Arc<PoolInner>,
     url: String,
+    /// Casing applied to result column names
+    column_case: ColumnCase,
+    /// Format of bytes values in results
+    bytes_format: BytesFormat,
+    /// Whether equal strings in results share one allocation
+    intern_strings: bool,
+    /// Whether UUID columns come back as `uuid.UUID` objects
+    uuid_as_object: bool,
+    /// Row factory `execute` uses when none is given
+    default_row_factory: Option<Arc<RowFactory>>,
 }
 
 impl ConnectionPool {
     pub async fn connect(config: PoolConfig) -> Result<Self> {
         let url = config.url.clone();
+        let default_row_factory = config.default_row_factory.map(Arc::new);
 
         if url.starts_with("postgresql://") || url.starts_with("postgres://") {
             let pg_config = PgPoolConfig::new(&url)
                 .min_connections(config.min_connections)
-                .max_connections(config.max_connections);
+                .max_connections(config.max_connections)
+                .test_before_acquire(config.test_before_acquire)
+                .validation_query(config.validation_query)
+                .heartbeat_interval(config.heartbeat_interval)
+                .statement_timeout(config.statement_timeout)
+                .statement_cache_memory_limit(config.statement_cache_memory_limit)
+                .reset_on_return(config.reset_on_return)
+                .pipeline_depth(config.pipeline_depth);
+            let pg_config = match config.acquire_timeout {
+                Some(timeout) => pg_config.acquire_timeout(Some(timeout)),
+                // Leave it to the URL's pool_timeout/connect_timeout
+                None => pg_config,
+            };
 
             let pool = PgPool::connect(pg_config)
                 .await
@@ -48,6 +126,11 @@ impl ConnectionPool {
             Ok(Self {
                 inner: Arc::new(PoolInner::Postgres(pool)),
                 url,
+                column_case: config.column_case,
+                bytes_format: config.bytes_format,
+                intern_strings: config.intern_strings,
+                uuid_as_object: config.uuid_as_object,
+                default_row_factory,
             })
         } else if url.starts_with("sqlite://") || url.starts_with("sqlite:") {
             // Parse SQLite URL: sqlite://:memory: or sqlite://path/to/db
@@ -56,8 +139,12 @@ impl ConnectionPool {
                 .or_else(|| url.strip_prefix("sqlite:"))
                 .unwrap_or(":memory:");
 
-            let sqlite_config =
-                SqlitePoolConfig::new(path).max_read_connections(config.max_connections);
+            let sqlite_config = SqlitePoolConfig::new(path)
+                .max_read_connections(config.max_connections)
+                .test_before_acquire(config.test_before_acquire)
+                .statement_timeout(config.statement_timeout)
+                .acquire_timeout(config.acquire_timeout)
+                .empty_string_as_null(config.empty_string_as_null);
 
             let pool = SqlitePool::connect(sqlite_config)
                 .await
@@ -66,6 +153,11 @@ impl ConnectionPool {
             Ok(Self {
                 inner: Arc::new(PoolInner::Sqlite(pool)),
                 url,
+                column_case: config.column_case,
+                bytes_format: config.bytes_format,
+                intern_strings: config.intern_strings,
+                uuid_as_object: config.uuid_as_object,
+                default_row_factory,
             })
         } else {
             Err(ForeignKeyError::ConfigError(format!(
@@ -75,14 +167,55 @@ impl ConnectionPool {
         }
     }
 
-    /// Execute a raw SQL query and return results
-    pub async fn execute_query(&self, sql: &str, params: Vec<SqlParam>) -> Result<QueryResult> {
+    /// Placeholder style used when rewriting named parameters
+    fn placeholder_style(&self) -> PlaceholderStyle {
+        match self.inner.as_ref() {
+            PoolInner::Postgres(_) => PlaceholderStyle::Dollar,
+            PoolInner::Sqlite(_) => PlaceholderStyle::Question,
+        }
+    }
+
+    /// Execute a SQL query with already-converted parameters.
+    ///
+    /// This is the entry point under `execute`, without the Python object
+    /// conversion: each `SqlParam` binds as-is, so `SqlParam::Int4(5)` is
+    /// sent as INT4 where a Python int would be INT8. Placeholders must
+    /// already be positional (`$1` or `?`); named parameters are rewritten
+    /// before this is called.
+    pub async fn execute_raw(&self, sql: &str, params: Vec<SqlParam>) -> Result<QueryResult> {
         match self.inner.as_ref() {
             PoolInner::Postgres(pool) => self.execute_pg(pool, sql, params).await,
             PoolInner::Sqlite(pool) => self.execute_sqlite(pool, sql, params).await,
         }
     }
 
+    /// Execute a prepared query handle (PostgreSQL only)
+    async fn execute_prepared(
+        &self,
+        query: &PgPreparedQuery,
+        params: Vec<SqlParam>,
+    ) -> Result<QueryResult> {
+        let PoolInner::Postgres(pool) = self.inner.as_ref() else {
+            return Err(ForeignKeyError::QueryError(
+                "Prepared queries are only supported for PostgreSQL".to_string(),
+            ));
+        };
+        let pg_params: Vec<PgValue> = params.into_iter().map(sql_param_to_pg).collect();
+
+        let result = pool
+            .execute_prepared(query, &pg_params)
+            .await
+            .map_err(ForeignKeyError::from)?;
+
+        Ok(pg_query_result(
+            result,
+            self.column_case,
+            self.bytes_format,
+            self.intern_strings,
+            self.uuid_as_object,
+        ))
+    }
+
     /// Execute PostgreSQL query - optimized path
     async fn execute_pg(
         &self,
@@ -95,23 +228,15 @@ impl ConnectionPool {
         let result = pool
             .query(sql, &pg_params)
             .await
-            .map_err(|e| ForeignKeyError::QueryError(e.to_string()))?;
-
-        // Convert to our QueryResult format - extract column names from Arc<Vec<FieldDescription>>
-        let columns: Vec<String> = result.columns.iter().map(|f| f.name.clone()).collect();
-
-        let lazy_rows: Vec<LazyRow> = result
-            .rows
-            .into_iter()
-            .map(|row| {
-                // Use SmallVec::from_iter for efficient inline storage (avoids heap for ≤16 columns)
-                let values: SmallVec<[RowValue; 16]> =
-                    row.into_iter().map(pg_value_to_row).collect();
-                LazyRow { values }
-            })
-            .collect();
+            .map_err(ForeignKeyError::from)?;
 
-        Ok(QueryResult::from_lazy(lazy_rows, columns))
+        Ok(pg_query_result(
+            result,
+            self.column_case,
+            self.bytes_format,
+            self.intern_strings,
+            self.uuid_as_object,
+        ))
     }
 
     /// Execute SQLite query - optimized path
@@ -124,24 +249,49 @@ impl ConnectionPool {
         let sqlite_params: Vec<SqliteValue> = params.into_iter().map(sql_param_to_sqlite).collect();
 
         let result = pool
-            .query(sql, &sqlite_params)
+            .query(sql, sqlite_params)
             .await
-            .map_err(|e| ForeignKeyError::QueryError(e.to_string()))?;
-
-        let columns = result.columns;
+            .map_err(ForeignKeyError::from)?;
 
-        let lazy_rows: Vec<LazyRow> = result
-            .rows
-            .into_iter()
-            .map(|row| {
-                // Use SmallVec::from_iter for efficient inline storage (avoids heap for ≤16 columns)
-                let values: SmallVec<[RowValue; 16]> =
-                    row.into_iter().map(sqlite_value_to_row).collect();
-                LazyRow { values }
-            })
-            .collect();
+        Ok(sqlite_query_result(
+            result,
+            self.column_case,
+            self.bytes_format,
+            self.intern_strings,
+        ))
+    }
 
-        Ok(QueryResult::from_lazy(lazy_rows, columns))
+    /// Execute a query, returning its rows and the number of rows it affected
+    pub async fn execute_returning_impl(
+        &self,
+        sql: &str,
+        params: Vec<SqlParam>,
+    ) -> Result<(QueryResult, u64)> {
+        match self.inner.as_ref() {
+            PoolInner::Postgres(pool) => {
+                let result = self.execute_pg(pool, sql, params).await?;
+                let rows_affected = result.rowcount_affected().unwrap_or(0);
+                Ok((result, rows_affected))
+            }
+            PoolInner::Sqlite(pool) => {
+                let sqlite_params: Vec<SqliteValue> =
+                    params.into_iter().map(sql_param_to_sqlite).collect();
+                let result = pool
+                    .query(sql, sqlite_params)
+                    .await
+                    .map_err(ForeignKeyError::from)?;
+                let rows_affected = result.rows_affected;
+                Ok((
+                    sqlite_query_result(
+                        result,
+                        self.column_case,
+                        self.bytes_format,
+                        self.intern_strings,
+                    ),
+                    rows_affected,
+                ))
+            }
+        }
     }
 
     /// Execute a statement that doesn't return rows (INSERT, UPDATE, DELETE)
@@ -151,14 +301,530 @@ impl ConnectionPool {
                 let pg_params: Vec<PgValue> = params.into_iter().map(sql_param_to_pg).collect();
                 pool.execute(sql, &pg_params)
                     .await
-                    .map_err(|e| ForeignKeyError::QueryError(e.to_string()))
+                    .map_err(ForeignKeyError::from)
+            }
+            PoolInner::Sqlite(pool) => {
+                let sqlite_params: Vec<SqliteValue> =
+                    params.into_iter().map(sql_param_to_sqlite).collect();
+                pool.execute(sql, sqlite_params)
+                    .await
+                    .map_err(ForeignKeyError::from)
+            }
+        }
+    }
+
+    /// Execute a statement once per parameter set, atomically
+    pub async fn execute_many_impl(
+        &self,
+        sql: &str,
+        params_list: Vec<Vec<SqlParam>>,
+    ) -> Result<u64> {
+        match self.inner.as_ref() {
+            PoolInner::Postgres(pool) => {
+                let pg_params: Vec<Vec<PgValue>> = params_list
+                    .into_iter()
+                    .map(|params| params.into_iter().map(sql_param_to_pg).collect())
+                    .collect();
+                pool.execute_many(sql, &pg_params)
+                    .await
+                    .map_err(ForeignKeyError::from)
             }
+            PoolInner::Sqlite(pool) => {
+                let sqlite_params: Vec<Vec<SqliteValue>> = params_list
+                    .into_iter()
+                    .map(|params| params.into_iter().map(sql_param_to_sqlite).collect())
+                    .collect();
+                pool.execute_many(sql, sqlite_params)
+                    .await
+                    .map_err(ForeignKeyError::from)
+            }
+        }
+    }
+
+    /// Execute an `INSERT ... ON CONFLICT` and report whether it inserted or updated
+    pub async fn execute_upsert_impl(
+        &self,
+        sql: &str,
+        params: Vec<SqlParam>,
+    ) -> Result<UpsertResult> {
+        match self.inner.as_ref() {
+            PoolInner::Postgres(_) => Err(ForeignKeyError::QueryError(
+                "execute_upsert is only supported for SQLite".to_string(),
+            )),
             PoolInner::Sqlite(pool) => {
                 let sqlite_params: Vec<SqliteValue> =
                     params.into_iter().map(sql_param_to_sqlite).collect();
-                pool.execute(sql, &sqlite_params)
+                pool.execute_upsert(sql, sqlite_params)
+                    .await
+                    .map_err(ForeignKeyError::from)
+            }
+        }
+    }
+
+    /// Insert a row unless it conflicts on `conflict_columns`, returning
+    /// whether it was inserted.
+    ///
+    /// Both backends use `ON CONFLICT (...) DO NOTHING` rather than SQLite's
+    /// `INSERT OR IGNORE`, which would also swallow NOT NULL and CHECK
+    /// violations.
+    pub async fn insert_if_absent_impl(
+        &self,
+        table: &str,
+        columns: &[String],
+        values: Vec<SqlParam>,
+        conflict_columns: &[String],
+    ) -> Result<bool> {
+        let dialect = match self.inner.as_ref() {
+            PoolInner::Postgres(_) => Dialect::Postgres,
+            PoolInner::Sqlite(_) => Dialect::Sqlite,
+        };
+        let quote_all = |names: &[String]| {
+            names
+                .iter()
+                .map(|name| quote_ident(name, dialect))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        let placeholders = (1..=columns.len())
+            .map(|i| match dialect {
+                Dialect::Postgres => format!("${}", i),
+                Dialect::Sqlite => "?".to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!(
+            "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT ({}) DO NOTHING",
+            quote_ident(table, dialect),
+            quote_all(columns),
+            placeholders,
+            quote_all(conflict_columns)
+        );
+
+        Ok(self.execute_statement(&sql, values).await? > 0)
+    }
+
+    /// Rows changed by INSERT/UPDATE/DELETE since the pool's connections opened
+    pub async fn total_changes_impl(&self) -> Result<u64> {
+        match self.inner.as_ref() {
+            PoolInner::Postgres(_) => Err(ForeignKeyError::QueryError(
+                "total_changes is only supported for SQLite".to_string(),
+            )),
+            PoolInner::Sqlite(pool) => pool.total_changes().await.map_err(ForeignKeyError::from),
+        }
+    }
+
+    /// List rows whose foreign keys have no parent, in `table` or every table
+    pub async fn foreign_key_check_impl(
+        &self,
+        table: Option<&str>,
+    ) -> Result<Vec<ForeignKeyViolation>> {
+        match self.inner.as_ref() {
+            PoolInner::Postgres(_) => Err(ForeignKeyError::QueryError(
+                "foreign_key_check is only supported for SQLite".to_string(),
+            )),
+            PoolInner::Sqlite(pool) => {
+                let pragma = crate::schema::sqlite_foreign_key_check_pragma(table);
+                let result = pool
+                    .query(&pragma, &[])
+                    .await
+                    .map_err(ForeignKeyError::from)?;
+
+                // PRAGMA foreign_key_check returns: table, rowid, parent, fkid
+                let violations = result
+                    .rows
+                    .into_iter()
+                    .filter_map(|row| {
+                        let mut iter = row.into_iter();
+                        let table = match iter.next() {
+                            Some(SqliteValue::Text(s)) => s,
+                            _ => return None,
+                        };
+                        let rowid = match iter.next() {
+                            Some(SqliteValue::Integer(i)) => Some(i),
+                            _ => None,
+                        };
+                        let parent = match iter.next() {
+                            Some(SqliteValue::Text(s)) => s,
+                            _ => return None,
+                        };
+                        let fk_index = match iter.next() {
+                            Some(SqliteValue::Integer(i)) => i,
+                            _ => return None,
+                        };
+                        Some(ForeignKeyViolation {
+                            table,
+                            rowid,
+                            parent,
+                            fk_index,
+                        })
+                    })
+                    .collect();
+                Ok(violations)
+            }
+        }
+    }
+
+    /// Look up the name of the relation with the given OID
+    pub async fn regclass_name_impl(&self, oid: i64) -> Result<Option<String>> {
+        match self.inner.as_ref() {
+            PoolInner::Postgres(pool) => {
+                let result = pool
+                    .query(crate::schema::PG_REGCLASS_NAME_QUERY, &[PgValue::Int8(oid)])
+                    .await
+                    .map_err(ForeignKeyError::from)?;
+                Ok(result
+                    .rows
+                    .into_iter()
+                    .next()
+                    .and_then(|row| match row.into_iter().next() {
+                        Some(PgValue::Text(name)) => Some(name),
+                        _ => None,
+                    }))
+            }
+            PoolInner::Sqlite(_) => Err(ForeignKeyError::QueryError(
+                "regclass_name is only supported for PostgreSQL".to_string(),
+            )),
+        }
+    }
+
+    /// List sessions waiting on locks, paired with the sessions blocking them
+    pub async fn blocking_queries_impl(&self) -> Result<Vec<BlockedQuery>> {
+        match self.inner.as_ref() {
+            PoolInner::Postgres(pool) => {
+                let result = pool
+                    .query(crate::schema::PG_BLOCKING_QUERIES_QUERY, &[])
+                    .await
+                    .map_err(ForeignKeyError::from)?;
+
+                let text = |value: Option<PgValue>| match value {
+                    Some(PgValue::Text(s)) => Some(s),
+                    _ => None,
+                };
+                let blocked = result
+                    .rows
+                    .into_iter()
+                    .map(|row| {
+                        let mut iter = row.into_iter();
+                        let blocked_pid = match iter.next() {
+                            Some(PgValue::Int4(pid)) => pid,
+                            _ => 0,
+                        };
+                        let blocked_query = text(iter.next()).unwrap_or_default();
+                        let blocking_pid = match iter.next() {
+                            Some(PgValue::Int4(pid)) => pid,
+                            _ => 0,
+                        };
+                        BlockedQuery {
+                            blocked_pid,
+                            blocked_query,
+                            blocking_pid,
+                            blocking_query: text(iter.next()).unwrap_or_default(),
+                            blocking_state: text(iter.next()).unwrap_or_default(),
+                            lock_type: text(iter.next()),
+                            lock_mode: text(iter.next()),
+                            relation: text(iter.next()),
+                            wait_seconds: match iter.next() {
+                                Some(PgValue::Float8(secs)) => secs,
+                                _ => 0.0,
+                            },
+                        }
+                    })
+                    .collect();
+                Ok(blocked)
+            }
+            PoolInner::Sqlite(_) => Err(ForeignKeyError::QueryError(
+                "blocking_queries is only supported for PostgreSQL".to_string(),
+            )),
+        }
+    }
+
+    /// Run VACUUM on a connection of its own, outside any transaction block
+    pub async fn vacuum_impl(&self, table: Option<&str>, full: bool, analyze: bool) -> Result<()> {
+        match self.inner.as_ref() {
+            PoolInner::Postgres(pool) => {
+                let mut conn = pool.acquire().await.map_err(ForeignKeyError::from)?;
+                // Pooled connections come back idle; never let VACUUM land
+                // in a transaction block left open regardless
+                if conn.transaction_status() != TransactionStatus::Idle {
+                    conn.simple_query("ROLLBACK")
+                        .await
+                        .map_err(ForeignKeyError::from)?;
+                }
+                // The simple protocol sends it as is: no deferred BEGIN or
+                // prepared statement around it
+                conn.simple_query(&pg_vacuum_sql(table, full, analyze))
+                    .await
+                    .map_err(ForeignKeyError::from)?;
+                Ok(())
+            }
+            PoolInner::Sqlite(pool) => {
+                if table.is_some() {
+                    return Err(ForeignKeyError::QueryError(
+                        "SQLite can only VACUUM the whole database".to_string(),
+                    ));
+                }
+                let conn = pool.acquire().await.map_err(ForeignKeyError::from)?;
+                conn.execute_batch("VACUUM")
+                    .await
+                    .map_err(ForeignKeyError::from)?;
+                if analyze {
+                    conn.execute_batch("ANALYZE")
+                        .await
+                        .map_err(ForeignKeyError::from)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Stream rows from a Python iterator into a table, returning the row count.
+    ///
+    /// PostgreSQL uses COPY FROM STDIN; SQLite inserts each chunk in its own
+    /// transaction. Only one chunk of rows is held in memory at a time.
+    pub async fn bulk_load_impl(
+        &self,
+        table: &str,
+        columns: &[String],
+        rows: Py<PyIterator>,
+    ) -> PyResult<u64> {
+        let chunks = PyChunks {
+            rows: &rows,
+            width: columns.len(),
+        };
+        self.load_chunks(table, columns, chunks).await
+    }
+
+    /// Copy rows from a Python iterator into a table with binary COPY,
+    /// returning the row count (PostgreSQL only).
+    ///
+    /// Binary data gets no server-side casts, so each value is converted to
+    /// its column's type first; the types come from a `LIMIT 0` probe of
+    /// the columns. An unconvertible value aborts the COPY.
+    pub async fn copy_in_impl(
+        &self,
+        table: &str,
+        columns: &[String],
+        rows: Py<PyIterator>,
+    ) -> PyResult<u64> {
+        let PoolInner::Postgres(pool) = self.inner.as_ref() else {
+            return Err(ForeignKeyError::QueryError(
+                "copy_in is only supported for PostgreSQL".to_string(),
+            )
+            .into());
+        };
+        let table = quote_qualified(table, Dialect::Postgres);
+        let column_list = columns
+            .iter()
+            .map(|c| quote_ident(c, Dialect::Postgres))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut conn = pool.acquire().await.map_err(ForeignKeyError::from)?;
+        let probe = conn
+            .simple_query(&format!("SELECT {} FROM {} LIMIT 0", column_list, table))
+            .await
+            .map_err(ForeignKeyError::from)?;
+        let types: Vec<Oid> = probe
+            .first()
+            .map(|result| result.columns.iter().map(|c| c.type_oid).collect())
+            .unwrap_or_default();
+
+        let copy_sql = format!(
+            "COPY {} ({}) FROM STDIN WITH (FORMAT binary)",
+            table, column_list
+        );
+        conn.copy_in_start(&copy_sql)
+            .await
+            .map_err(ForeignKeyError::from)?;
+
+        let mut data = copy_binary_header();
+        loop {
+            let encoded = next_bulk_chunk(&rows, columns.len()).and_then(|chunk| {
+                let done = chunk.is_empty();
+                encode_copy_binary_rows(chunk, &types, &mut data)
+                    .map_err(|e| PyErr::from(ForeignKeyError::from(e)))?;
+                Ok(done)
+            });
+            match encoded {
+                Ok(done) => {
+                    if done {
+                        data.extend_from_slice(&COPY_BINARY_TRAILER);
+                    }
+                    conn.copy_in_send(std::mem::take(&mut data))
+                        .await
+                        .map_err(ForeignKeyError::from)?;
+                    if done {
+                        break;
+                    }
+                }
+                Err(e) => {
+                    // Abort so the server discards the partial load
+                    let _ = conn.copy_in_fail("copy aborted").await;
+                    return Err(e);
+                }
+            }
+        }
+
+        Ok(conn.copy_in_finish().await.map_err(ForeignKeyError::from)?)
+    }
+
+    /// Start `COPY (query) TO STDOUT` as CSV on a pooled connection
+    /// (PostgreSQL only).
+    async fn copy_out_start(&self, query: &str, header: bool) -> Result<PgPooledConnection> {
+        let PoolInner::Postgres(pool) = self.inner.as_ref() else {
+            return Err(ForeignKeyError::QueryError(
+                "copy_out is only supported for PostgreSQL".to_string(),
+            ));
+        };
+        let mut conn = pool.acquire().await?;
+        conn.copy_out_start(&copy_out_sql(query, header)).await?;
+        Ok(conn)
+    }
+
+    /// Export a query's rows as CSV in one buffer (PostgreSQL only).
+    pub async fn copy_out_impl(&self, query: &str, header: bool) -> Result<Vec<u8>> {
+        let mut conn = self.copy_out_start(query, header).await?;
+        let mut data = Vec::new();
+        while let Some(chunk) = conn.copy_out_next().await? {
+            data.extend_from_slice(&chunk);
+        }
+        Ok(data)
+    }
+
+    /// Import a CSV file into a table, converting fields by column type.
+    ///
+    /// With `header`, the first record names the columns to load; otherwise
+    /// every column of the table is loaded in order. A field that doesn't
+    /// convert fails the import with its line and column, and no rows are
+    /// kept on PostgreSQL. Returns the number of rows loaded.
+    pub async fn import_csv_impl(&self, table: &str, path: &str, header: bool) -> PyResult<u64> {
+        let dialect = match self.inner.as_ref() {
+            PoolInner::Postgres(_) => Dialect::Postgres,
+            PoolInner::Sqlite(_) => Dialect::Sqlite,
+        };
+        let table_columns = self.get_columns_impl(table).await?;
+        if table_columns.is_empty() {
+            return Err(ForeignKeyError::QueryError(format!("table {} not found", table)).into());
+        }
+
+        // The file is read on a blocking thread, which hands over the column
+        // names and then one converted chunk at a time
+        let (columns_tx, columns_rx) = tokio::sync::oneshot::channel();
+        let (chunk_tx, chunks) = tokio::sync::mpsc::channel(CSV_BUFFER_CHUNKS);
+        let csv_path = path.to_string();
+        let csv_table = table.to_string();
+        tokio::task::spawn_blocking(move || {
+            let opened = open_csv(&csv_path, header, &csv_table, &table_columns, dialect);
+            let (mut reader, columns, kinds) = match opened {
+                Ok(Some(opened)) => opened,
+                Ok(None) => {
+                    let _ = columns_tx.send(Ok(None));
+                    return;
+                }
+                Err(e) => {
+                    let _ = columns_tx.send(Err(e));
+                    return;
+                }
+            };
+            if columns_tx.send(Ok(Some(columns.clone()))).is_err() {
+                return;
+            }
+            loop {
+                let chunk = next_csv_chunk(&mut reader, &columns, &kinds, &csv_path);
+                let done = !matches!(&chunk, Ok(rows) if !rows.is_empty());
+                // A closed channel means the load gave up early
+                if chunk_tx.blocking_send(chunk).is_err() || done {
+                    return;
+                }
+            }
+        });
+
+        let columns = columns_rx.await.map_err(|_| csv_reader_stopped(path))??;
+        let Some(columns) = columns else {
+            return Ok(0);
+        };
+        let chunks = ChannelChunks {
+            chunks,
+            path: path.to_string(),
+        };
+        self.load_chunks(table, &columns, chunks).await
+    }
+
+    /// Load rows into a table one chunk at a time, returning the row count.
+    ///
+    /// `rows` yields an empty chunk once they run out. On PostgreSQL an
+    /// error from it aborts the COPY so nothing is kept.
+    async fn load_chunks(
+        &self,
+        table: &str,
+        columns: &[String],
+        mut rows: impl ChunkSource,
+    ) -> PyResult<u64> {
+        let dialect = match self.inner.as_ref() {
+            PoolInner::Postgres(_) => Dialect::Postgres,
+            PoolInner::Sqlite(_) => Dialect::Sqlite,
+        };
+        let table = quote_qualified(table, dialect);
+        let column_list = columns
+            .iter()
+            .map(|c| quote_ident(c, dialect))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        match self.inner.as_ref() {
+            PoolInner::Postgres(pool) => {
+                let mut conn = pool.acquire().await.map_err(ForeignKeyError::from)?;
+                let copy_sql = format!("COPY {} ({}) FROM STDIN", table, column_list);
+                conn.copy_in_start(&copy_sql)
                     .await
-                    .map_err(|e| ForeignKeyError::QueryError(e.to_string()))
+                    .map_err(ForeignKeyError::from)?;
+
+                loop {
+                    let data = match rows.next_chunk().await {
+                        Ok(chunk) if chunk.is_empty() => break,
+                        Ok(chunk) => encode_copy_rows(chunk)
+                            .map_err(|e| PyErr::from(ForeignKeyError::from(e))),
+                        Err(e) => Err(e),
+                    };
+                    match data {
+                        Ok(data) => conn
+                            .copy_in_send(data)
+                            .await
+                            .map_err(ForeignKeyError::from)?,
+                        Err(e) => {
+                            // Abort so the server discards the partial load
+                            let _ = conn.copy_in_fail("load aborted").await;
+                            return Err(e);
+                        }
+                    }
+                }
+
+                Ok(conn.copy_in_finish().await.map_err(ForeignKeyError::from)?)
+            }
+            PoolInner::Sqlite(pool) => {
+                let placeholders = vec!["?"; columns.len()].join(", ");
+                let insert_sql = format!(
+                    "INSERT INTO {} ({}) VALUES ({})",
+                    table, column_list, placeholders
+                );
+                let conn = pool.acquire().await.map_err(ForeignKeyError::from)?;
+
+                let mut total = 0;
+                loop {
+                    let chunk = rows.next_chunk().await?;
+                    if chunk.is_empty() {
+                        break;
+                    }
+                    let values: Vec<Vec<SqliteValue>> = chunk
+                        .into_iter()
+                        .map(|row| row.into_iter().map(sql_param_to_sqlite).collect())
+                        .collect();
+                    total += conn
+                        .insert_rows(&insert_sql, values)
+                        .await
+                        .map_err(ForeignKeyError::from)?;
+                }
+                Ok(total)
             }
         }
     }
@@ -174,7 +840,7 @@ impl ConnectionPool {
                 let result = pool
                     .query(crate::schema::PG_TABLES_QUERY, &[])
                     .await
-                    .map_err(|e| ForeignKeyError::QueryError(e.to_string()))?;
+                    .map_err(ForeignKeyError::from)?;
 
                 let tables: Vec<String> = result
                     .rows
@@ -192,7 +858,7 @@ impl ConnectionPool {
                 let result = pool
                     .query(crate::schema::SQLITE_TABLES_QUERY, &[])
                     .await
-                    .map_err(|e| ForeignKeyError::QueryError(e.to_string()))?;
+                    .map_err(ForeignKeyError::from)?;
 
                 let tables: Vec<String> = result
                     .rows
@@ -209,30 +875,141 @@ impl ConnectionPool {
         }
     }
 
-    /// Get column information for a table
-    pub async fn get_columns_impl(&self, table: &str) -> Result<Vec<ColumnInfo>> {
+    /// Get the enum types with their labels in sort order.
+    ///
+    /// SQLite has no enum types, so there it is always empty.
+    pub async fn get_enums_impl(&self) -> Result<Vec<EnumInfo>> {
+        let PoolInner::Postgres(pool) = self.inner.as_ref() else {
+            return Ok(Vec::new());
+        };
+        let result = pool
+            .query(crate::schema::PG_ENUMS_QUERY, &[])
+            .await
+            .map_err(ForeignKeyError::from)?;
+
+        let enums = result
+            .rows
+            .into_iter()
+            .map(|row| {
+                let mut iter = row.into_iter();
+                let name = match iter.next() {
+                    Some(PgValue::Text(s)) => s,
+                    _ => String::new(),
+                };
+                let labels = match iter.next() {
+                    Some(PgValue::Array(items)) => items
+                        .into_iter()
+                        .filter_map(|v| match v {
+                            PgValue::Text(s) => Some(s),
+                            _ => None,
+                        })
+                        .collect(),
+                    _ => vec![],
+                };
+                EnumInfo { name, labels }
+            })
+            .collect();
+        Ok(enums)
+    }
+
+    /// Get the storage size of each table, largest first.
+    ///
+    /// `schema` picks the PostgreSQL schema; SQLite always reports the main
+    /// database.
+    pub async fn table_sizes_impl(&self, schema: &str) -> Result<Vec<TableSize>> {
         match self.inner.as_ref() {
             PoolInner::Postgres(pool) => {
                 let result = pool
                     .query(
-                        crate::schema::PG_COLUMNS_QUERY,
-                        &[PgValue::Text(table.to_string())],
+                        crate::schema::PG_TABLE_SIZES_QUERY,
+                        &[PgValue::Text(schema.to_string())],
                     )
                     .await
-                    .map_err(|e| ForeignKeyError::QueryError(e.to_string()))?;
+                    .map_err(ForeignKeyError::from)?;
 
-                let columns: Vec<ColumnInfo> = result
+                let sizes = result
                     .rows
                     .into_iter()
                     .map(|row| {
                         let mut iter = row.into_iter();
-                        let name = match iter.next() {
+                        let table = match iter.next() {
                             Some(PgValue::Text(s)) => s,
                             _ => String::new(),
                         };
-                        let data_type = match iter.next() {
-                            Some(PgValue::Text(s)) => s,
-                            _ => String::new(),
+                        let mut bytes = iter.map(|v| match v {
+                            PgValue::Int8(n) => n,
+                            _ => 0,
+                        });
+                        TableSize {
+                            table,
+                            total_bytes: bytes.next().unwrap_or(0),
+                            table_bytes: bytes.next().unwrap_or(0),
+                            index_bytes: bytes.next().unwrap_or(0),
+                            toast_bytes: bytes.next().unwrap_or(0),
+                        }
+                    })
+                    .collect();
+                Ok(sizes)
+            }
+            PoolInner::Sqlite(pool) => {
+                let result = pool
+                    .query(crate::schema::SQLITE_TABLE_SIZES_QUERY, &[])
+                    .await
+                    .map_err(ForeignKeyError::from)?;
+
+                let sizes = result
+                    .rows
+                    .into_iter()
+                    .map(|row| {
+                        let mut iter = row.into_iter();
+                        let table = match iter.next() {
+                            Some(SqliteValue::Text(s)) => s,
+                            _ => String::new(),
+                        };
+                        let mut bytes = iter.map(|v| match v {
+                            SqliteValue::Integer(n) => n,
+                            _ => 0,
+                        });
+                        let total_bytes = bytes.next().unwrap_or(0);
+                        let index_bytes = bytes.next().unwrap_or(0);
+                        TableSize {
+                            table,
+                            total_bytes,
+                            table_bytes: total_bytes - index_bytes,
+                            index_bytes,
+                            toast_bytes: 0,
+                        }
+                    })
+                    .collect();
+                Ok(sizes)
+            }
+        }
+    }
+
+    /// Get column information for a table
+    pub async fn get_columns_impl(&self, table: &str) -> Result<Vec<ColumnInfo>> {
+        match self.inner.as_ref() {
+            PoolInner::Postgres(pool) => {
+                let result = pool
+                    .query(
+                        crate::schema::PG_COLUMNS_QUERY,
+                        &[PgValue::Text(table.to_string())],
+                    )
+                    .await
+                    .map_err(ForeignKeyError::from)?;
+
+                let columns: Vec<ColumnInfo> = result
+                    .rows
+                    .into_iter()
+                    .map(|row| {
+                        let mut iter = row.into_iter();
+                        let name = match iter.next() {
+                            Some(PgValue::Text(s)) => s,
+                            _ => String::new(),
+                        };
+                        let data_type = match iter.next() {
+                            Some(PgValue::Text(s)) => s,
+                            _ => String::new(),
                         };
                         let nullable = match iter.next() {
                             Some(PgValue::Bool(b)) => b,
@@ -264,7 +1041,7 @@ impl ConnectionPool {
                 let result = pool
                     .query(&pragma, &[])
                     .await
-                    .map_err(|e| ForeignKeyError::QueryError(e.to_string()))?;
+                    .map_err(ForeignKeyError::from)?;
 
                 // PRAGMA table_info returns: cid, name, type, notnull, dflt_value, pk
                 let columns: Vec<ColumnInfo> = result
@@ -318,7 +1095,7 @@ impl ConnectionPool {
                         &[PgValue::Text(table.to_string())],
                     )
                     .await
-                    .map_err(|e| ForeignKeyError::QueryError(e.to_string()))?;
+                    .map_err(ForeignKeyError::from)?;
 
                 let indexes: Vec<IndexInfo> = result
                     .rows
@@ -360,7 +1137,7 @@ impl ConnectionPool {
                 let result = pool
                     .query(&index_list_pragma, &[])
                     .await
-                    .map_err(|e| ForeignKeyError::QueryError(e.to_string()))?;
+                    .map_err(ForeignKeyError::from)?;
 
                 // PRAGMA index_list returns: seq, name, unique, origin, partial
                 let mut indexes = Vec::new();
@@ -390,7 +1167,7 @@ impl ConnectionPool {
                     let col_result = pool
                         .query(&index_info_pragma, &[])
                         .await
-                        .map_err(|e| ForeignKeyError::QueryError(e.to_string()))?;
+                        .map_err(ForeignKeyError::from)?;
 
                     // PRAGMA index_info returns: seqno, cid, name
                     let columns: Vec<String> = col_result
@@ -418,6 +1195,98 @@ impl ConnectionPool {
         }
     }
 
+    /// Get detailed index information for a SQLite table
+    pub async fn index_stats_impl(&self, table: &str) -> Result<Vec<IndexStats>> {
+        let PoolInner::Sqlite(pool) = self.inner.as_ref() else {
+            return Err(ForeignKeyError::QueryError(
+                "index_stats is only supported for SQLite".to_string(),
+            ));
+        };
+        let result = pool
+            .query(&crate::schema::sqlite_index_list_pragma(table), &[])
+            .await
+            .map_err(ForeignKeyError::from)?;
+
+        // PRAGMA index_list returns: seq, name, unique, origin, partial
+        let mut indexes = Vec::new();
+        for row in result.rows {
+            let mut iter = row.into_iter().skip(1);
+            let name = match iter.next() {
+                Some(SqliteValue::Text(s)) => s,
+                _ => continue,
+            };
+            let unique = matches!(iter.next(), Some(SqliteValue::Integer(i)) if i != 0);
+            let origin = match iter.next() {
+                Some(SqliteValue::Text(s)) => s,
+                _ => String::new(),
+            };
+            let partial = matches!(iter.next(), Some(SqliteValue::Integer(i)) if i != 0);
+
+            // PRAGMA index_xinfo returns: seqno, cid, name, desc, coll, key
+            let xinfo = pool
+                .query(&crate::schema::sqlite_index_xinfo_pragma(&name), &[])
+                .await
+                .map_err(ForeignKeyError::from)?;
+            let columns = xinfo
+                .rows
+                .into_iter()
+                .filter_map(|row| {
+                    let mut iter = row.into_iter().skip(2);
+                    let name = match iter.next() {
+                        Some(SqliteValue::Text(s)) => Some(s),
+                        _ => None,
+                    };
+                    let descending = matches!(iter.next(), Some(SqliteValue::Integer(i)) if i != 0);
+                    let collation = match iter.next() {
+                        Some(SqliteValue::Text(s)) => s,
+                        _ => String::new(),
+                    };
+                    // Non-key entries are the rowid or primary key the index stores
+                    let key = matches!(iter.next(), Some(SqliteValue::Integer(i)) if i != 0);
+                    key.then_some(IndexColumn {
+                        name,
+                        descending,
+                        collation,
+                    })
+                })
+                .collect();
+
+            let predicate = if partial {
+                let create = pool
+                    .query(
+                        crate::schema::SQLITE_INDEX_SQL_QUERY,
+                        &[SqliteValue::Text(name.clone())],
+                    )
+                    .await
+                    .map_err(ForeignKeyError::from)?;
+                match create
+                    .rows
+                    .into_iter()
+                    .next()
+                    .and_then(|r| r.into_iter().next())
+                {
+                    Some(SqliteValue::Text(sql)) => {
+                        crate::schema::sqlite_partial_index_predicate(&sql)
+                    }
+                    _ => None,
+                }
+            } else {
+                None
+            };
+
+            indexes.push(IndexStats {
+                name,
+                columns,
+                unique,
+                partial,
+                auto_created: origin != "c",
+                origin,
+                predicate,
+            });
+        }
+        Ok(indexes)
+    }
+
     /// Get constraint information for a table
     pub async fn get_constraints_impl(&self, table: &str) -> Result<Vec<ConstraintInfo>> {
         match self.inner.as_ref() {
@@ -428,7 +1297,7 @@ impl ConnectionPool {
                         &[PgValue::Text(table.to_string())],
                     )
                     .await
-                    .map_err(|e| ForeignKeyError::QueryError(e.to_string()))?;
+                    .map_err(ForeignKeyError::from)?;
 
                 let constraints: Vec<ConstraintInfo> = result
                     .rows
@@ -480,7 +1349,7 @@ impl ConnectionPool {
                 let result = pool
                     .query(&fk_pragma, &[])
                     .await
-                    .map_err(|e| ForeignKeyError::QueryError(e.to_string()))?;
+                    .map_err(ForeignKeyError::from)?;
 
                 // PRAGMA foreign_key_list returns: id, seq, table, from, to, on_update, on_delete, match
                 let mut constraints = Vec::new();
@@ -533,7 +1402,7 @@ impl ConnectionPool {
                 let pk_result = pool
                     .query(&pragma, &[])
                     .await
-                    .map_err(|e| ForeignKeyError::QueryError(e.to_string()))?;
+                    .map_err(ForeignKeyError::from)?;
 
                 let pk_columns: Vec<String> = pk_result
                     .rows
@@ -603,7 +1472,7 @@ const HEX_CHARS: &[u8; 16] = b"0123456789abcdef";
 /// Fast UUID formatting using pre-allocated buffer and lookup table.
 /// This is significantly faster than format!() with 16 specifiers.
 #[inline(always)]
-fn format_uuid(u: &[u8; 16]) -> String {
+pub(crate) fn format_uuid(u: &[u8; 16]) -> String {
     // UUID format: xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx (36 chars)
     let mut buf = [0u8; 36];
     let mut pos = 0;
@@ -655,8 +1524,11 @@ fn format_uuid(u: &[u8; 16]) -> String {
 }
 
 /// Convert PgValue to RowValue (hot path)
+///
+/// UUIDs become `RowValue::Uuid` when `uuid_as_object` is set, and
+/// hyphenated strings otherwise.
 #[inline(always)]
-fn pg_value_to_row(value: PgValue) -> RowValue {
+fn pg_value_to_row(value: PgValue, uuid_as_object: bool) -> RowValue {
     match value {
         PgValue::Null => RowValue::Null,
         PgValue::Bool(b) => RowValue::Bool(b),
@@ -667,17 +1539,30 @@ fn pg_value_to_row(value: PgValue) -> RowValue {
         PgValue::Float8(f) => RowValue::Float(f),
         PgValue::Text(s) => RowValue::String(s),
         PgValue::Bytea(b) => RowValue::Bytes(b),
+        PgValue::Uuid(u) if uuid_as_object => RowValue::Uuid(u),
         PgValue::Uuid(u) => {
             // Fast UUID formatting using lookup table
             RowValue::String(format_uuid(&u))
         }
-        PgValue::Timestamp(ts) => {
-            // Convert PostgreSQL timestamp (microseconds since 2000-01-01) to string
-            // For now, just return as integer - can improve later
-            RowValue::Int(ts)
-        }
-        PgValue::Date(d) => RowValue::Int(d as i64),
-        PgValue::Time(t) => RowValue::Int(t),
+        PgValue::Timestamp(micros) => RowValue::DateTime { micros, utc: false },
+        PgValue::TimestampTz(micros) => RowValue::DateTime { micros, utc: true },
+        PgValue::Date(d) => RowValue::Date(d),
+        PgValue::Time(t) => RowValue::Time(t),
+        PgValue::TimeTz {
+            micros,
+            tz_offset_secs,
+        } => RowValue::TimeTz {
+            micros,
+            tz_offset_secs,
+        },
+        // A month has no fixed length, so only month-free intervals are
+        // timedeltas
+        PgValue::Interval(i) if i.months == 0 => RowValue::TimeDelta {
+            days: i.days,
+            micros: i.microseconds,
+        },
+        PgValue::Interval(i) => RowValue::String(i.to_iso8601()),
+        PgValue::Numeric(s) => RowValue::Decimal(s),
         PgValue::Json(s) => {
             // Parse JSON string into serde_json::Value for proper Python conversion
             match serde_json::from_str(&s) {
@@ -685,6 +1570,37 @@ fn pg_value_to_row(value: PgValue) -> RowValue {
                 Err(_) => RowValue::String(s), // Fallback to string if parse fails
             }
         }
+        PgValue::Array(items) => RowValue::Array(
+            items
+                .into_iter()
+                .map(|v| pg_value_to_row(v, uuid_as_object))
+                .collect(),
+        ),
+        PgValue::Record(fields) => RowValue::Record(
+            fields
+                .into_iter()
+                .map(|v| pg_value_to_row(v, uuid_as_object))
+                .collect(),
+        ),
+        PgValue::Range {
+            lower,
+            upper,
+            lower_inc,
+            upper_inc,
+            empty,
+        } => RowValue::Range {
+            lower: lower.map(|v| Box::new(pg_value_to_row(*v, uuid_as_object))),
+            upper: upper.map(|v| Box::new(pg_value_to_row(*v, uuid_as_object))),
+            lower_inc,
+            upper_inc,
+            empty,
+        },
+        PgValue::MultiRange(ranges) => RowValue::Array(
+            ranges
+                .into_iter()
+                .map(|v| pg_value_to_row(v, uuid_as_object))
+                .collect(),
+        ),
         PgValue::Raw { data, .. } => RowValue::Bytes(data),
     }
 }
@@ -701,17 +1617,314 @@ fn sqlite_value_to_row(value: SqliteValue) -> RowValue {
     }
 }
 
+/// Convert a PostgreSQL result to our QueryResult format
+fn pg_query_result(
+    result: crate::pg::connection::QueryResult,
+    case: ColumnCase,
+    bytes_format: BytesFormat,
+    intern_strings: bool,
+    uuid_as_object: bool,
+) -> QueryResult {
+    // Extract column names from Arc<Vec<FieldDescription>>
+    let columns: Vec<String> = result.columns.iter().map(|f| f.name.clone()).collect();
+    let rows_affected = command_tag_rows(&result.command_tag);
+    let mut interner = intern_strings.then(StringInterner::default);
+
+    let lazy_rows: Vec<LazyRow> = result
+        .rows
+        .into_iter()
+        .map(|row| {
+            // Use SmallVec::from_iter for efficient inline storage (avoids heap for ≤16 columns)
+            let values: SmallVec<[RowValue; 16]> = row
+                .into_iter()
+                .map(|value| intern_value(&mut interner, pg_value_to_row(value, uuid_as_object)))
+                .collect();
+            LazyRow { values }
+        })
+        .collect();
+
+    QueryResult::from_lazy(lazy_rows, columns, case)
+        .with_bytes_format(bytes_format)
+        .with_rows_affected(rows_affected)
+}
+
+/// Convert a SQLite result to our QueryResult format
+fn sqlite_query_result(
+    result: crate::sqlite::connection::QueryResult,
+    case: ColumnCase,
+    bytes_format: BytesFormat,
+    intern_strings: bool,
+) -> QueryResult {
+    let mut interner = intern_strings.then(StringInterner::default);
+
+    let lazy_rows: Vec<LazyRow> = result
+        .rows
+        .into_iter()
+        .map(|row| {
+            // Use SmallVec::from_iter for efficient inline storage (avoids heap for ≤16 columns)
+            let values: SmallVec<[RowValue; 16]> = row
+                .into_iter()
+                .map(|value| intern_value(&mut interner, sqlite_value_to_row(value)))
+                .collect();
+            LazyRow { values }
+        })
+        .collect();
+
+    QueryResult::from_lazy(lazy_rows, result.columns, case).with_bytes_format(bytes_format)
+}
+
+/// Pass a value through the result's interner, when interning is on
+#[inline(always)]
+fn intern_value(interner: &mut Option<StringInterner>, value: RowValue) -> RowValue {
+    match interner {
+        Some(interner) => interner.intern(value),
+        None => value,
+    }
+}
+
+/// Pull up to `BULK_LOAD_CHUNK_ROWS` rows from a Python iterator.
+///
+/// Each row must be a sequence of exactly `width` values.
+fn next_bulk_chunk(rows: &Py<PyIterator>, width: usize) -> PyResult<Vec<Vec<SqlParam>>> {
+    Python::with_gil(|py| {
+        let mut iter = rows.bind(py).clone();
+        let mut chunk = Vec::with_capacity(BULK_LOAD_CHUNK_ROWS);
+
+        while chunk.len() < BULK_LOAD_CHUNK_ROWS {
+            let Some(row) = iter.next() else {
+                break;
+            };
+            let values: Vec<PyObject> = row?.extract()?;
+            if values.len() != width {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "row has {} values, expected {}",
+                    values.len(),
+                    width
+                )));
+            }
+            chunk.push(convert_py_params(py, values)?);
+        }
+
+        Ok(chunk)
+    })
+}
+
+/// Rows for `load_chunks`, pulled one chunk at a time.
+trait ChunkSource {
+    /// The next chunk of rows; empty once they run out.
+    fn next_chunk(&mut self) -> impl Future<Output = PyResult<Vec<Vec<SqlParam>>>> + Send;
+}
+
+/// Rows from a Python iterator, converted under the GIL.
+struct PyChunks<'a> {
+    rows: &'a Py<PyIterator>,
+    width: usize,
+}
+
+impl ChunkSource for PyChunks<'_> {
+    fn next_chunk(&mut self) -> impl Future<Output = PyResult<Vec<Vec<SqlParam>>>> + Send {
+        std::future::ready(next_bulk_chunk(self.rows, self.width))
+    }
+}
+
+/// Rows read on a blocking thread and handed over a channel.
+struct ChannelChunks {
+    chunks: tokio::sync::mpsc::Receiver<PyResult<Vec<Vec<SqlParam>>>>,
+    /// File the rows come from, for the error if the reader dies
+    path: String,
+}
+
+impl ChunkSource for ChannelChunks {
+    async fn next_chunk(&mut self) -> PyResult<Vec<Vec<SqlParam>>> {
+        // The reader always ends with an empty chunk or an error, so a
+        // closed channel means it stopped partway
+        self.chunks
+            .recv()
+            .await
+            .unwrap_or_else(|| Err(csv_reader_stopped(&self.path)))
+    }
+}
+
+fn csv_reader_stopped(path: &str) -> PyErr {
+    pyo3::exceptions::PyRuntimeError::new_err(format!("{}: reader stopped unexpectedly", path))
+}
+
+fn csv_error(path: &str, msg: String) -> PyErr {
+    pyo3::exceptions::PyValueError::new_err(format!("{}: {}", path, msg))
+}
+
+/// An open CSV file with the columns its fields load into, in order, and
+/// how each is converted.
+type OpenCsv = (
+    CsvReader<std::io::BufReader<std::fs::File>>,
+    Vec<String>,
+    Vec<ColumnKind>,
+);
+
+/// Open a CSV file and map its fields to the table's columns, by the header
+/// line if there is one. None for a file without even a header.
+fn open_csv(
+    path: &str,
+    header: bool,
+    table: &str,
+    table_columns: &[ColumnInfo],
+    dialect: Dialect,
+) -> PyResult<Option<OpenCsv>> {
+    let file = std::fs::File::open(path).map_err(|e| {
+        pyo3::exceptions::PyOSError::new_err(format!("cannot open {}: {}", path, e))
+    })?;
+    let mut reader = CsvReader::new(std::io::BufReader::new(file));
+
+    let mut columns = Vec::new();
+    let mut kinds = Vec::new();
+    if header {
+        let Some((_, names)) = reader.next_record().map_err(|e| csv_error(path, e))? else {
+            return Ok(None);
+        };
+        for name in names {
+            let name = name.unwrap_or_default();
+            let info = table_columns
+                .iter()
+                .find(|c| c.name == name)
+                .ok_or_else(|| {
+                    csv_error(path, format!("column {:?} is not in table {}", name, table))
+                })?;
+            kinds.push(ColumnKind::from_data_type(&info.data_type, dialect));
+            columns.push(name);
+        }
+    } else {
+        for info in table_columns {
+            kinds.push(ColumnKind::from_data_type(&info.data_type, dialect));
+            columns.push(info.name.clone());
+        }
+    }
+    Ok(Some((reader, columns, kinds)))
+}
+
+/// Read and convert up to `BULK_LOAD_CHUNK_ROWS` CSV records.
+fn next_csv_chunk(
+    reader: &mut CsvReader<std::io::BufReader<std::fs::File>>,
+    columns: &[String],
+    kinds: &[ColumnKind],
+    path: &str,
+) -> PyResult<Vec<Vec<SqlParam>>> {
+    let mut chunk = Vec::with_capacity(BULK_LOAD_CHUNK_ROWS);
+    while chunk.len() < BULK_LOAD_CHUNK_ROWS {
+        let Some((line, fields)) = reader.next_record().map_err(|e| csv_error(path, e))? else {
+            break;
+        };
+        if fields.len() != columns.len() {
+            return Err(csv_error(
+                path,
+                format!(
+                    "line {}: {} fields, expected {}",
+                    line,
+                    fields.len(),
+                    columns.len()
+                ),
+            ));
+        }
+        let row = fields
+            .into_iter()
+            .zip(kinds)
+            .zip(columns)
+            .map(|((field, kind), column)| {
+                kind.convert(field).map_err(|msg| {
+                    csv_error(path, format!("line {}, column {:?}: {}", line, column, msg))
+                })
+            })
+            .collect::<PyResult<Vec<_>>>()?;
+        chunk.push(row);
+    }
+    Ok(chunk)
+}
+
+/// `COPY (query) TO STDOUT` in CSV format, with a header line if asked.
+fn copy_out_sql(query: &str, header: bool) -> String {
+    let query = query.trim_end().trim_end_matches(';');
+    let header = if header { ", HEADER" } else { "" };
+    format!("COPY ({}) TO STDOUT WITH (FORMAT csv{})", query, header)
+}
+
+/// Append rows as binary COPY tuples, converting each value to its
+/// column's type.
+fn encode_copy_binary_rows(
+    rows: Vec<Vec<SqlParam>>,
+    types: &[Oid],
+    buf: &mut Vec<u8>,
+) -> PgResult<()> {
+    for row in rows {
+        let values = row
+            .into_iter()
+            .zip(types)
+            .map(|(param, &oid)| sql_param_to_pg(param).coerce_to(oid))
+            .collect::<PgResult<Vec<_>>>()?;
+        encode_copy_binary_row(buf, &values);
+    }
+    Ok(())
+}
+
+/// Encode rows as tab-separated COPY text data.
+fn encode_copy_rows(rows: Vec<Vec<SqlParam>>) -> PgResult<Vec<u8>> {
+    let mut buf = Vec::new();
+    for row in rows {
+        for (i, param) in row.into_iter().enumerate() {
+            if i > 0 {
+                buf.push(b'\t');
+            }
+            sql_param_to_pg(param).encode_copy_text(&mut buf)?;
+        }
+        buf.push(b'\n');
+    }
+    Ok(buf)
+}
+
 /// Convert SqlParam to PgValue
 #[inline]
 fn sql_param_to_pg(param: SqlParam) -> PgValue {
     match param {
         SqlParam::Null => PgValue::Null,
         SqlParam::Bool(b) => PgValue::Bool(b),
+        SqlParam::Int2(i) => PgValue::Int2(i),
+        SqlParam::Int4(i) => PgValue::Int4(i),
         SqlParam::Int(i) => PgValue::Int8(i),
+        SqlParam::Numeric(s) => PgValue::Numeric(s),
+        SqlParam::Float4(f) => PgValue::Float4(f),
         SqlParam::Float(f) => PgValue::Float8(f),
         SqlParam::String(s) => PgValue::Text(s),
         SqlParam::Bytes(b) => PgValue::Bytea(b),
+        SqlParam::Uuid(u) => PgValue::Uuid(u),
+        SqlParam::Date(days) => PgValue::Date(days),
+        SqlParam::DateTime { micros, utc: false } => PgValue::Timestamp(micros),
+        SqlParam::DateTime { micros, utc: true } => PgValue::TimestampTz(micros),
+        SqlParam::Time {
+            micros,
+            tz_offset_secs: None,
+        } => PgValue::Time(micros),
+        SqlParam::Time {
+            micros,
+            tz_offset_secs: Some(tz_offset_secs),
+        } => PgValue::TimeTz {
+            micros,
+            tz_offset_secs,
+        },
+        SqlParam::Interval { days, microseconds } => PgValue::Interval(Interval {
+            months: 0,
+            days,
+            microseconds,
+        }),
         SqlParam::Json(s) => PgValue::Json(s),
+        SqlParam::Array(items) => PgValue::Array(items.into_iter().map(sql_param_to_pg).collect()),
+    }
+}
+
+/// Time-of-day format with microseconds only when there are any, as in
+/// Python's `isoformat()`.
+fn iso_time_format(micros: i64) -> &'static str {
+    if micros.rem_euclid(1_000_000) == 0 {
+        "%H:%M:%S"
+    } else {
+        "%H:%M:%S%.6f"
     }
 }
 
@@ -721,12 +1934,82 @@ fn sql_param_to_sqlite(param: SqlParam) -> SqliteValue {
     match param {
         SqlParam::Null => SqliteValue::Null,
         SqlParam::Bool(b) => SqliteValue::Integer(if b { 1 } else { 0 }),
+        SqlParam::Int2(i) => SqliteValue::Integer(i as i64),
+        SqlParam::Int4(i) => SqliteValue::Integer(i as i64),
         SqlParam::Int(i) => SqliteValue::Integer(i),
+        // Too big for an INTEGER; TEXT keeps every digit
+        SqlParam::Numeric(s) => SqliteValue::Text(s),
+        SqlParam::Float4(f) => SqliteValue::Real(f as f64),
         SqlParam::Float(f) => SqliteValue::Real(f),
         SqlParam::String(s) => SqliteValue::Text(s),
         SqlParam::Bytes(b) => SqliteValue::Blob(b),
+        // Hyphenated text, matching str(uuid)
+        SqlParam::Uuid(u) => SqliteValue::Text(format_uuid(&u)),
+        // No date/time types: ISO 8601 text laid out like Python's
+        // isoformat(" ") and SQLite's own date functions, so values compare
+        // correctly as strings. Aware datetimes are already in UTC.
+        SqlParam::Date(days) => SqliteValue::Text(
+            days_to_date(days)
+                .map(|date| date.format("%Y-%m-%d").to_string())
+                .unwrap_or_default(),
+        ),
+        SqlParam::DateTime { micros, .. } => SqliteValue::Text(
+            micros_to_datetime(micros)
+                .map(|dt| {
+                    dt.format(&format!("%Y-%m-%d {}", iso_time_format(micros)))
+                        .to_string()
+                })
+                .unwrap_or_default(),
+        ),
+        SqlParam::Time {
+            micros,
+            tz_offset_secs,
+        } => {
+            let time = NaiveTime::from_num_seconds_from_midnight_opt(
+                (micros / 1_000_000) as u32,
+                (micros % 1_000_000 * 1000) as u32,
+            )
+            .map(|t| t.format(iso_time_format(micros)).to_string())
+            .unwrap_or_default();
+            SqliteValue::Text(match tz_offset_secs {
+                Some(offset) => {
+                    let sign = if offset < 0 { '-' } else { '+' };
+                    let offset = offset.unsigned_abs();
+                    format!(
+                        "{}{}{:02}:{:02}",
+                        time,
+                        sign,
+                        offset / 3600,
+                        offset % 3600 / 60
+                    )
+                }
+                None => time,
+            })
+        }
+        // No interval type: ISO 8601 duration text, as PG intervals decode
+        SqlParam::Interval { days, microseconds } => SqliteValue::Text(
+            Interval {
+                months: 0,
+                days,
+                microseconds,
+            }
+            .to_iso8601(),
+        ),
         // SQLite stores JSON as TEXT
         SqlParam::Json(s) => SqliteValue::Text(s),
+        // No arrays either: the same JSON text the list bound as before
+        SqlParam::Array(items) => {
+            let json: Vec<serde_json::Value> = items
+                .into_iter()
+                .map(|item| match item {
+                    SqlParam::Int(i) => i.into(),
+                    SqlParam::String(s) => s.into(),
+                    SqlParam::Uuid(u) => format_uuid(&u).into(),
+                    _ => serde_json::Value::Null,
+                })
+                .collect();
+            SqliteValue::Text(serde_json::Value::Array(json).to_string())
+        }
     }
 }
 
@@ -761,114 +2044,701 @@ impl ConnectionPool {
         matches!(self.inner.as_ref(), PoolInner::Sqlite(_))
     }
 
+    /// Get query counters summed over the pool's connections (PostgreSQL only)
+    ///
+    /// Returns a dict with `queries_executed`, `rows_returned`,
+    /// `statements_prepared`, `errors` and `last_error` (the most recent
+    /// error message, or None).
+    fn metrics<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyDict>> {
+        let metrics = match self.inner.as_ref() {
+            PoolInner::Postgres(pool) => pool.metrics(),
+            PoolInner::Sqlite(_) => {
+                return Err(ForeignKeyError::QueryError(
+                    "metrics is only supported for PostgreSQL".to_string(),
+                )
+                .into())
+            }
+        };
+
+        let dict = PyDict::new(py);
+        dict.set_item("queries_executed", metrics.queries_executed)?;
+        dict.set_item("rows_returned", metrics.rows_returned)?;
+        dict.set_item("statements_prepared", metrics.statements_prepared)?;
+        dict.set_item("errors", metrics.errors)?;
+        dict.set_item("last_error", metrics.last_error)?;
+        Ok(dict)
+    }
+
+    /// Prepare a query for repeated execution (PostgreSQL only)
+    ///
+    /// Returns a `PreparedQuery` to pass to `execute` in place of the SQL
+    /// string. It skips the statement cache lookup and binds directly; the
+    /// statement is prepared on each connection the first time it runs
+    /// there, and again after DDL deallocates it.
+    fn prepare(&self, sql: &str) -> PyResult<PreparedQuery> {
+        if self.is_sqlite() {
+            return Err(ForeignKeyError::QueryError(
+                "Prepared queries are only supported for PostgreSQL".to_string(),
+            )
+            .into());
+        }
+        let (sql, names) = rewrite_named_params(sql, PlaceholderStyle::Dollar);
+        Ok(PreparedQuery {
+            inner: Arc::new(PgPreparedQuery::new(&sql)),
+            names,
+        })
+    }
+
     /// Execute a SQL query and return results
-    #[pyo3(signature = (sql, params=None))]
+    ///
+    /// `sql` may also be a `PreparedQuery` from `prepare`. Returns a
+    /// `QueryResult` by default. With `row_factory` set to `"dict"`,
+    /// `"tuple"` or a callable (invoked with each row dict), returns a list
+    /// of rows materialized in that format instead. Without one, the pool's
+    /// `default_row_factory` applies; `"result"` always returns a
+    /// `QueryResult`. `bytes_format` (`"raw"`, `"hex"` or `"base64"`)
+    /// overrides the pool's format for bytes values. `param_types` gives a
+    /// PostgreSQL type name (or None) per positional parameter to coerce
+    /// the bound value to; currently `"bool"`, which binds an int 0 or 1 as
+    /// a boolean.
+    ///
+    /// On PostgreSQL, cancelling the awaiting task (e.g. through
+    /// `asyncio.wait_for`) sends a CancelRequest for the running query and
+    /// discards its connection.
+    #[pyo3(signature = (sql, params=None, row_factory=None, bytes_format=None, param_types=None))]
     fn execute<'py>(
         &self,
         py: Python<'py>,
-        sql: String,
-        params: Option<Vec<PyObject>>,
+        sql: Bound<'py, PyAny>,
+        params: Option<Bound<'py, PyAny>>,
+        row_factory: Option<Bound<'py, PyAny>>,
+        bytes_format: Option<&str>,
+        param_types: Option<Vec<Option<String>>>,
     ) -> PyResult<Bound<'py, PyAny>> {
         let pool = self.clone();
-        let sql_params = convert_py_params(py, params.unwrap_or_default())?;
+        let bytes_format = match bytes_format {
+            Some(name) => BytesFormat::from_name(name)?,
+            None => self.bytes_format,
+        };
+        let factory = match row_factory {
+            // An explicit "result" opts out of the pool default
+            Some(row_factory)
+                if matches!(row_factory.extract::<String>().as_deref(), Ok("result")) =>
+            {
+                None
+            }
+            Some(row_factory) => Some(Arc::new(RowFactory::from_py(&row_factory)?)),
+            None => self.default_row_factory.clone(),
+        };
+
+        if let Ok(prepared) = sql.downcast::<PreparedQuery>() {
+            let prepared = prepared.borrow();
+            let mut sql_params = prepared.bind(py, params)?;
+            if let Some(param_types) = &param_types {
+                apply_param_types(&mut sql_params, param_types)?;
+            }
+            let query = Arc::clone(&prepared.inner);
+
+            return pyo3_async_runtimes::tokio::future_into_py(py, async move {
+                let result = pool
+                    .execute_prepared(&query, sql_params)
+                    .await?
+                    .with_bytes_format(bytes_format);
+                Python::with_gil(|py| match factory {
+                    Some(factory) => Ok(result.materialize(py, &factory)?.into_any().unbind()),
+                    None => Ok(Py::new(py, result)?.into_any()),
+                })
+            });
+        }
+
+        let (sql, mut sql_params) =
+            bind_py_params(py, sql.extract()?, params, self.placeholder_style())?;
+        if let Some(param_types) = &param_types {
+            apply_param_types(&mut sql_params, param_types)?;
+        }
 
         pyo3_async_runtimes::tokio::future_into_py(py, async move {
             let result = pool
-                .execute_query(&sql, sql_params)
-                .await
-                .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
-            Ok(result)
+                .execute_raw(&sql, sql_params)
+                .await?
+                .with_bytes_format(bytes_format);
+            Python::with_gil(|py| match factory {
+                Some(factory) => Ok(result.materialize(py, &factory)?.into_any().unbind()),
+                None => Ok(Py::new(py, result)?.into_any()),
+            })
         })
     }
 
-    /// Execute a statement that doesn't return rows
+    /// Execute a query and return `(result, rows_affected)`
+    ///
+    /// For statements such as `UPDATE ... RETURNING` that both return rows
+    /// and change them: `result` is the `QueryResult` of returned rows and
+    /// `rows_affected` the number of rows the statement inserted, updated or
+    /// deleted (0 for statements that change nothing).
     #[pyo3(signature = (sql, params=None))]
-    fn execute_statement_py<'py>(
+    fn execute_returning<'py>(
         &self,
         py: Python<'py>,
         sql: String,
-        params: Option<Vec<PyObject>>,
+        params: Option<Bound<'py, PyAny>>,
     ) -> PyResult<Bound<'py, PyAny>> {
         let pool = self.clone();
-        let sql_params = convert_py_params(py, params.unwrap_or_default())?;
+        let (sql, sql_params) = bind_py_params(py, sql, params, self.placeholder_style())?;
 
         pyo3_async_runtimes::tokio::future_into_py(py, async move {
-            let rows_affected = pool
-                .execute_statement(&sql, sql_params)
-                .await
-                .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
-            Ok(rows_affected)
+            Ok(pool.execute_returning_impl(&sql, sql_params).await?)
         })
     }
 
-    /// Start a new transaction - returns a Transaction context manager
-    fn transaction<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
-        let pool_inner = Arc::clone(&self.inner);
+    /// Execute a SQL query and return rows as a list of tuples
+    #[pyo3(signature = (sql, params=None))]
+    fn execute_tuples<'py>(
+        &self,
+        py: Python<'py>,
+        sql: String,
+        params: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let pool = self.clone();
+        let (sql, sql_params) = bind_py_params(py, sql, params, self.placeholder_style())?;
 
         pyo3_async_runtimes::tokio::future_into_py(py, async move {
-            match pool_inner.as_ref() {
-                PoolInner::Postgres(pool) => {
-                    let mut conn = pool
-                        .acquire()
-                        .await
-                        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
-
-                    // Buffer BEGIN without flushing - will be sent with first query
-                    conn.begin_deferred()
-                        .await
-                        .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
-
-                    Ok(Transaction {
-                        conn: Arc::new(tokio::sync::Mutex::new(Some(conn))),
-                        begun: Arc::new(std::sync::atomic::AtomicBool::new(false)),
-                    })
-                }
-                PoolInner::Sqlite(_) => Err(pyo3::exceptions::PyRuntimeError::new_err(
-                    "SQLite transactions not yet implemented",
-                )),
-            }
+            let result = pool.execute_raw(&sql, sql_params).await?;
+            Python::with_gil(|py| {
+                Ok(result
+                    .materialize(py, &RowFactory::Tuple)?
+                    .into_any()
+                    .unbind())
+            })
         })
     }
 
-    /// Close the connection pool
-    fn close<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    /// Execute a statement that doesn't return rows
+    #[pyo3(signature = (sql, params=None))]
+    fn execute_statement_py<'py>(
+        &self,
+        py: Python<'py>,
+        sql: String,
+        params: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         let pool = self.clone();
+        let (sql, sql_params) = bind_py_params(py, sql, params, self.placeholder_style())?;
 
         pyo3_async_runtimes::tokio::future_into_py(py, async move {
-            match pool.inner.as_ref() {
-                PoolInner::Postgres(p) => p.close().await,
-                PoolInner::Sqlite(p) => p.close().await,
-            }
-            Ok(())
+            let rows_affected = pool.execute_statement(&sql, sql_params).await?;
+            Ok(rows_affected)
         })
     }
 
-    // ========================================================================
-    // Schema Introspection - Python Interface
-    // ========================================================================
-
-    /// Get all table names in the database
-    fn get_tables<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+    /// Execute a statement once per parameter set and return the total rows affected
+    ///
+    /// The batch is atomic: if any execution fails, none of them take effect.
+    /// PostgreSQL pipelines the whole batch with a single Sync; SQLite runs it
+    /// in an immediate transaction with one cached statement.
+    #[pyo3(signature = (sql, params_list))]
+    fn execute_many<'py>(
+        &self,
+        py: Python<'py>,
+        sql: String,
+        params_list: Vec<Vec<PyObject>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         let pool = self.clone();
+        let all_params = convert_py_param_batch(py, params_list)?;
 
         pyo3_async_runtimes::tokio::future_into_py(py, async move {
-            let tables = pool
-                .get_tables_impl()
-                .await
-                .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
-            Ok(tables)
+            Ok(pool.execute_many_impl(&sql, all_params).await?)
         })
     }
 
-    /// Get column information for a table
-    fn get_columns<'py>(&self, py: Python<'py>, table: String) -> PyResult<Bound<'py, PyAny>> {
+    /// Execute an upsert and return `{"action": "insert"|"update"|"none", "rowid": ...}`
+    #[pyo3(signature = (sql, params=None))]
+    fn execute_upsert<'py>(
+        &self,
+        py: Python<'py>,
+        sql: String,
+        params: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
         let pool = self.clone();
+        let (sql, sql_params) = bind_py_params(py, sql, params, self.placeholder_style())?;
 
         pyo3_async_runtimes::tokio::future_into_py(py, async move {
-            let columns = pool
-                .get_columns_impl(&table)
-                .await
-                .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+            let result = pool.execute_upsert_impl(&sql, sql_params).await?;
+            Python::with_gil(|py| {
+                let dict = pyo3::types::PyDict::new(py);
+                dict.set_item("action", result.action.as_str())?;
+                dict.set_item("rowid", result.rowid)?;
+                Ok(dict.into_any().unbind())
+            })
+        })
+    }
+
+    /// Insert a row from a dict of column values unless it already exists
+    ///
+    /// A row conflicting on `conflict_columns` (which must have a unique
+    /// index) is left untouched. Returns True if the row was inserted.
+    fn insert_if_absent<'py>(
+        &self,
+        py: Python<'py>,
+        table: String,
+        values: Bound<'py, PyDict>,
+        conflict_columns: Vec<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        if values.is_empty() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "insert_if_absent needs at least one column value",
+            ));
+        }
+        if conflict_columns.is_empty() {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "insert_if_absent needs at least one conflict column",
+            ));
+        }
+        let pool = self.clone();
+        let mut columns = Vec::with_capacity(values.len());
+        let mut params = Vec::with_capacity(values.len());
+        for (column, value) in values.iter() {
+            columns.push(column.extract::<String>()?);
+            params.push(value.unbind());
+        }
+        let params = convert_py_params(py, params)?;
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            Ok(pool
+                .insert_if_absent_impl(&table, &columns, params, &conflict_columns)
+                .await?)
+        })
+    }
+
+    /// Get the cumulative number of rows changed (SQLite only)
+    ///
+    /// Sums SQLite's `total_changes()` over the pool's idle connections.
+    fn total_changes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let pool = self.clone();
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            Ok(pool.total_changes_impl().await?)
+        })
+    }
+
+    /// List rows violating foreign keys (SQLite only)
+    ///
+    /// Runs `PRAGMA foreign_key_check` on `table`, or on every table when
+    /// omitted, and returns a list of `ForeignKeyViolation`. Useful for
+    /// auditing data loaded with enforcement off before turning it back on.
+    #[pyo3(signature = (table=None))]
+    fn foreign_key_check<'py>(
+        &self,
+        py: Python<'py>,
+        table: Option<String>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let pool = self.clone();
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let violations = pool.foreign_key_check_impl(table.as_deref()).await?;
+            Ok(violations)
+        })
+    }
+
+    /// Resolve a relation OID to its name (PostgreSQL only)
+    ///
+    /// `regclass` columns come back as the bare OID integer; this looks the
+    /// name up in `pg_class`. Returns None when no relation has that OID.
+    fn regclass_name<'py>(&self, py: Python<'py>, oid: i64) -> PyResult<Bound<'py, PyAny>> {
+        let pool = self.clone();
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            Ok(pool.regclass_name_impl(oid).await?)
+        })
+    }
+
+    /// List queries waiting on locks and what blocks them (PostgreSQL only)
+    ///
+    /// Returns a list of `BlockedQuery`, one per blocked/blocking pair, from
+    /// `pg_stat_activity` and `pg_locks`. Useful when a transaction hangs:
+    /// a `blocking_state` of "idle in transaction" usually means an
+    /// application forgot to commit.
+    fn blocking_queries<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let pool = self.clone();
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            Ok(pool.blocking_queries_impl().await?)
+        })
+    }
+
+    /// Run VACUUM outside any transaction
+    ///
+    /// VACUUM can't run inside a transaction block, so this uses a
+    /// connection of its own in autocommit mode and the simple query
+    /// protocol, composing `VACUUM [FULL] [ANALYZE] [table]`. SQLite always
+    /// rebuilds the whole database: `table` isn't supported there, `full`
+    /// has no effect and `analyze=True` runs `ANALYZE` afterwards.
+    #[pyo3(signature = (table=None, full=false, analyze=false))]
+    fn vacuum<'py>(
+        &self,
+        py: Python<'py>,
+        table: Option<String>,
+        full: bool,
+        analyze: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let pool = self.clone();
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            pool.vacuum_impl(table.as_deref(), full, analyze).await?;
+            Ok(())
+        })
+    }
+
+    /// Bulk load rows into a table from an iterable of tuples
+    ///
+    /// Uses COPY on PostgreSQL and chunked INSERT transactions on SQLite.
+    /// Rows are pulled lazily, so a generator can stream data from a file.
+    /// `table` may be schema-qualified (`schema.table`); each part and each
+    /// column is quoted as an identifier. Returns the number of rows loaded.
+    fn bulk_load<'py>(
+        &self,
+        py: Python<'py>,
+        table: String,
+        columns: Vec<String>,
+        rows: Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let pool = self.clone();
+        let rows = rows.try_iter()?.unbind();
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            pool.bulk_load_impl(&table, &columns, rows).await
+        })
+    }
+
+    /// Copy rows into a table with binary COPY (PostgreSQL only)
+    ///
+    /// `rows` is an iterable of tuples, one value per name in `columns`.
+    /// Rows are pulled lazily and sent in chunks with `COPY ... FROM STDIN
+    /// WITH (FORMAT binary)`, which skips the server's text parsing. Each
+    /// value must fit its column's type: ints convert to any numeric
+    /// column, floats to float or numeric columns and strings to text or
+    /// JSON columns. A bad value or row aborts the COPY, keeping no rows.
+    /// `table` (each part, if schema-qualified) and `columns` are quoted as
+    /// identifiers, so they match case-sensitively. Returns the number of
+    /// rows copied.
+    fn copy_in<'py>(
+        &self,
+        py: Python<'py>,
+        table: String,
+        columns: Vec<String>,
+        rows: Bound<'py, PyAny>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let pool = self.clone();
+        let rows = rows.try_iter()?.unbind();
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            pool.copy_in_impl(&table, &columns, rows).await
+        })
+    }
+
+    /// Export a query's rows as CSV bytes (PostgreSQL only)
+    ///
+    /// Runs `COPY (query) TO STDOUT WITH (FORMAT csv)`, which is much faster
+    /// than fetching rows for large exports. `query` is any SELECT, or
+    /// `TABLE name` for a whole table; `header=True` adds a line of column
+    /// names. The whole export is held in memory; see `copy_out_stream`.
+    #[pyo3(signature = (query, header=false))]
+    fn copy_out<'py>(
+        &self,
+        py: Python<'py>,
+        query: String,
+        header: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let pool = self.clone();
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let data = pool.copy_out_impl(&query, header).await?;
+            Ok(Python::with_gil(|py| PyBytes::new(py, &data).unbind()))
+        })
+    }
+
+    /// Export a query's rows as a stream of CSV chunks (PostgreSQL only)
+    ///
+    /// Like `copy_out`, but returns a `CopyOutStream`; iterate it with
+    /// `async for` to get `bytes` chunks of whole rows, about 64 KiB each.
+    /// The stream holds a pooled connection until it is exhausted or closed;
+    /// closing it early cancels the COPY.
+    #[pyo3(signature = (query, header=false))]
+    fn copy_out_stream<'py>(
+        &self,
+        py: Python<'py>,
+        query: String,
+        header: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let pool = self.clone();
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let conn = pool.copy_out_start(&query, header).await?;
+            Ok(CopyOutStream {
+                inner: Arc::new(tokio::sync::Mutex::new(Some(Box::new(conn)))),
+            })
+        })
+    }
+
+    /// Import a CSV file into a table
+    ///
+    /// Fields are converted using the table's column types: integer, float,
+    /// numeric and boolean columns are checked here, other types are parsed
+    /// by the database. An unquoted empty field is NULL. With `header=True`
+    /// the first line names the columns; otherwise fields map to the
+    /// table's columns in order. A bad value raises ValueError naming its
+    /// line and column. Uses COPY on PostgreSQL and chunked INSERT
+    /// transactions on SQLite. Returns the number of rows loaded.
+    #[pyo3(signature = (table, csv_path, header=true))]
+    fn import_csv<'py>(
+        &self,
+        py: Python<'py>,
+        table: String,
+        csv_path: std::path::PathBuf,
+        header: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let pool = self.clone();
+        let path = csv_path.to_string_lossy().into_owned();
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            pool.import_csv_impl(&table, &path, header).await
+        })
+    }
+
+    /// Start a new transaction - returns a Transaction context manager
+    ///
+    /// On PostgreSQL BEGIN is deferred by default and sent with the first
+    /// query, saving a round trip. With `deferred=False` BEGIN runs before
+    /// this returns, so the transaction is already open (e.g. to take locks
+    /// or set session state before the first query). On SQLite BEGIN always
+    /// runs here; `deferred=False` issues `BEGIN IMMEDIATE`, taking the write
+    /// lock up front. SQLite has a single writer, so the pool's transactions
+    /// run one at a time: this waits until any other transaction from the
+    /// pool has exited.
+    ///
+    /// `cache_statements=False` runs the transaction's PostgreSQL queries
+    /// through the unnamed statement instead of the connection's statement
+    /// cache, so one-off SQL such as generated migration steps doesn't evict
+    /// the statements normal traffic reuses. It has no effect on SQLite.
+    #[pyo3(signature = (deferred=true, cache_statements=true))]
+    fn transaction<'py>(
+        &self,
+        py: Python<'py>,
+        deferred: bool,
+        cache_statements: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let pool_inner = Arc::clone(&self.inner);
+        let column_case = self.column_case;
+        let bytes_format = self.bytes_format;
+        let intern_strings = self.intern_strings;
+        let uuid_as_object = self.uuid_as_object;
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let mut conn = acquire_dedicated(&pool_inner, true).await?;
+            begin_transaction(&mut conn, deferred).await?;
+            Ok(Transaction::new(
+                conn,
+                column_case,
+                bytes_format,
+                intern_strings,
+                uuid_as_object,
+                cache_statements,
+            ))
+        })
+    }
+
+    /// Pin one pool connection for a sequence of statements - returns a
+    /// Session
+    ///
+    /// Use for session-scoped state such as temp tables, `SET` or advisory
+    /// locks that later statements must see. No BEGIN is issued. The
+    /// connection goes back to the pool when the session is closed (or its
+    /// `async with` block exits).
+    fn session<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let pool_inner = Arc::clone(&self.inner);
+        let column_case = self.column_case;
+        let bytes_format = self.bytes_format;
+        let intern_strings = self.intern_strings;
+        let uuid_as_object = self.uuid_as_object;
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let conn = acquire_dedicated(&pool_inner, false).await?;
+            Ok(Session {
+                placeholder_style: placeholder_style_of(&conn),
+                connection_id: connection_id_of(&conn),
+                conn: Arc::new(tokio::sync::Mutex::new(Some(conn))),
+                column_case,
+                bytes_format,
+                intern_strings,
+                uuid_as_object,
+            })
+        })
+    }
+
+    /// Stream a query's rows in batches without loading them all
+    ///
+    /// Returns a `RowStream`; iterate it with `async for` to get a
+    /// `QueryResult` of up to `batch_size` rows at a time. The stream holds a
+    /// pooled connection until it is exhausted or closed. On PostgreSQL the
+    /// rows are read through a named portal, whose implicit transaction stays
+    /// open until then.
+    #[pyo3(signature = (sql, params=None, batch_size=STREAM_BATCH_ROWS))]
+    fn stream<'py>(
+        &self,
+        py: Python<'py>,
+        sql: String,
+        params: Option<Bound<'py, PyAny>>,
+        batch_size: usize,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        if batch_size == 0 {
+            return Err(pyo3::exceptions::PyValueError::new_err(
+                "batch_size must be positive",
+            ));
+        }
+        let pool_inner = Arc::clone(&self.inner);
+        let column_case = self.column_case;
+        let bytes_format = self.bytes_format;
+        let intern_strings = self.intern_strings;
+        let uuid_as_object = self.uuid_as_object;
+        let (sql, sql_params) = bind_py_params(py, sql, params, self.placeholder_style())?;
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            match pool_inner.as_ref() {
+                PoolInner::Sqlite(pool) => {
+                    let conn = pool.acquire().await.map_err(ForeignKeyError::from)?;
+                    let sqlite_params: Vec<SqliteValue> =
+                        sql_params.into_iter().map(sql_param_to_sqlite).collect();
+                    let stream = conn
+                        .query_stream(&sql, sqlite_params, batch_size)
+                        .await
+                        .map_err(ForeignKeyError::from)?;
+                    Ok(RowStream {
+                        inner: Arc::new(tokio::sync::Mutex::new(Some(StreamSource::Sqlite(
+                            stream, conn,
+                        )))),
+                        column_case,
+                        bytes_format,
+                        intern_strings,
+                        uuid_as_object,
+                    })
+                }
+                PoolInner::Postgres(pool) => {
+                    let mut conn = pool.acquire().await.map_err(ForeignKeyError::from)?;
+                    let pg_params: Vec<PgValue> =
+                        sql_params.into_iter().map(sql_param_to_pg).collect();
+                    let batch_size = u32::try_from(batch_size).unwrap_or(u32::MAX);
+                    let first = conn
+                        .query_portal(&sql, &pg_params, batch_size)
+                        .await
+                        .map_err(ForeignKeyError::from)?;
+                    Ok(RowStream {
+                        inner: Arc::new(tokio::sync::Mutex::new(Some(StreamSource::Postgres {
+                            conn: Box::new(conn),
+                            first: Some(first),
+                            batch_size,
+                        }))),
+                        column_case,
+                        bytes_format,
+                        intern_strings,
+                        uuid_as_object,
+                    })
+                }
+            }
+        })
+    }
+
+    /// Subscribe to NOTIFY channels on a dedicated connection (PostgreSQL only)
+    ///
+    /// Returns a `Listener`; iterate it with `async for` or call `recv()`.
+    #[pyo3(signature = (*channels))]
+    fn listen<'py>(&self, py: Python<'py>, channels: Vec<String>) -> PyResult<Bound<'py, PyAny>> {
+        let pool_inner = Arc::clone(&self.inner);
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            match pool_inner.as_ref() {
+                PoolInner::Postgres(pool) => {
+                    let mut listener = pool.listener().await.map_err(ForeignKeyError::from)?;
+                    for channel in &channels {
+                        listener
+                            .listen(channel)
+                            .await
+                            .map_err(ForeignKeyError::from)?;
+                    }
+                    Ok(Listener {
+                        inner: Arc::new(tokio::sync::Mutex::new(listener)),
+                        interrupt: Arc::new(tokio::sync::Notify::new()),
+                    })
+                }
+                PoolInner::Sqlite(_) => Err(ForeignKeyError::QueryError(
+                    "listen is only supported for PostgreSQL".to_string(),
+                )
+                .into()),
+            }
+        })
+    }
+
+    /// Close the connection pool
+    fn close<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let pool = self.clone();
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            match pool.inner.as_ref() {
+                PoolInner::Postgres(p) => p.close().await,
+                PoolInner::Sqlite(p) => p.close().await,
+            }
+            Ok(())
+        })
+    }
+
+    // ========================================================================
+    // Schema Introspection - Python Interface
+    // ========================================================================
+
+    /// Get all table names in the database
+    fn get_tables<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let pool = self.clone();
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let tables = pool.get_tables_impl().await?;
+            Ok(tables)
+        })
+    }
+
+    /// Get the enum types and their labels
+    ///
+    /// Returns a list of `EnumInfo`, one per enum type in the `public`
+    /// schema, with its labels in sort order. Always empty on SQLite, which
+    /// has no enum types.
+    fn get_enums<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let pool = self.clone();
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let enums = pool.get_enums_impl().await?;
+            Ok(enums)
+        })
+    }
+
+    /// Get the storage size of each table, largest first
+    ///
+    /// Returns a list of `TableSize` with the bytes used by each table in
+    /// total and split into its own data, its indexes and (on PostgreSQL)
+    /// TOAST storage. PostgreSQL covers the tables of `schema` (default
+    /// `public`); SQLite ignores it and counts the pages of the main
+    /// database through `dbstat`.
+    #[pyo3(signature = (schema="public".to_string()))]
+    fn table_sizes<'py>(&self, py: Python<'py>, schema: String) -> PyResult<Bound<'py, PyAny>> {
+        let pool = self.clone();
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let sizes = pool.table_sizes_impl(&schema).await?;
+            Ok(sizes)
+        })
+    }
+
+    /// Get column information for a table
+    fn get_columns<'py>(&self, py: Python<'py>, table: String) -> PyResult<Bound<'py, PyAny>> {
+        let pool = self.clone();
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let columns = pool.get_columns_impl(&table).await?;
             Ok(columns)
         })
     }
@@ -878,23 +2748,31 @@ impl ConnectionPool {
         let pool = self.clone();
 
         pyo3_async_runtimes::tokio::future_into_py(py, async move {
-            let indexes = pool
-                .get_indexes_impl(&table)
-                .await
-                .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+            let indexes = pool.get_indexes_impl(&table).await?;
             Ok(indexes)
         })
     }
 
+    /// Get detailed index information for a table (SQLite only)
+    ///
+    /// Returns a list of `IndexStats`, one per index including those SQLite
+    /// creates for UNIQUE and PRIMARY KEY constraints, with each key
+    /// column's sort order and collation and a partial index's `WHERE`
+    /// predicate.
+    fn index_stats<'py>(&self, py: Python<'py>, table: String) -> PyResult<Bound<'py, PyAny>> {
+        let pool = self.clone();
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            Ok(pool.index_stats_impl(&table).await?)
+        })
+    }
+
     /// Get constraint information for a table
     fn get_constraints<'py>(&self, py: Python<'py>, table: String) -> PyResult<Bound<'py, PyAny>> {
         let pool = self.clone();
 
         pyo3_async_runtimes::tokio::future_into_py(py, async move {
-            let constraints = pool
-                .get_constraints_impl(&table)
-                .await
-                .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+            let constraints = pool.get_constraints_impl(&table).await?;
             Ok(constraints)
         })
     }
@@ -904,10 +2782,7 @@ impl ConnectionPool {
         let pool = self.clone();
 
         pyo3_async_runtimes::tokio::future_into_py(py, async move {
-            let info = pool
-                .get_table_info_impl(&table)
-                .await
-                .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+            let info = pool.get_table_info_impl(&table).await?;
             Ok(info)
         })
     }
@@ -918,19 +2793,62 @@ impl ConnectionPool {
 pub enum SqlParam {
     Null,
     Bool(bool),
+    /// `ormkit.Int2` - bound as INT2 rather than INT8
+    Int2(i16),
+    /// `ormkit.Int4` - bound as INT4 rather than INT8
+    Int4(i32),
     Int(i64),
+    /// Decimal text: a Python int outside the i64 range, a `Decimal`, or
+    /// `ormkit.Numeric`; bound as NUMERIC
+    Numeric(String),
+    /// `ormkit.Float4` - bound as FLOAT4 rather than FLOAT8
+    Float4(f32),
     Float(f64),
     String(String),
     Bytes(Vec<u8>),
+    /// `uuid.UUID`, as its 16 bytes
+    Uuid([u8; 16]),
+    /// Python date (not datetime), as days since 2000-01-01
+    Date(i32),
+    /// Python datetime, as microseconds since 2000-01-01; an aware datetime
+    /// is normalized to UTC and bound as TIMESTAMPTZ
+    DateTime {
+        micros: i64,
+        utc: bool,
+    },
+    /// Python time, as microseconds since midnight; an aware time keeps its
+    /// UTC offset (seconds east) and is bound as TIMETZ
+    Time {
+        micros: i64,
+        tz_offset_secs: Option<i32>,
+    },
+    /// Python timedelta: whole days plus the sub-day remainder
+    Interval {
+        days: i32,
+        microseconds: i64,
+    },
     /// JSON value - pre-serialized string from Python dict/list
     /// We serialize directly to string to avoid the intermediate serde_json::Value
     Json(String),
+    /// Python list of ints, strs or UUIDs (and None) - bound as a
+    /// one-dimensional array; SQLite has no arrays and gets JSON text
+    Array(Vec<SqlParam>),
 }
 
 // ============================================================================
 // Transaction Support
 // ============================================================================
 
+/// The connection a transaction or session runs on.
+enum TransactionConn {
+    Postgres {
+        conn: Box<PgPooledConnection>,
+        /// Whether BEGIN response has been consumed
+        begun: bool,
+    },
+    Sqlite(SqlitePooledConnection),
+}
+
 /// A database transaction context manager.
 ///
 /// This is used as an async context manager in Python:
@@ -940,8 +2858,10 @@ pub enum SqlParam {
 ///     await tx.execute("UPDATE ...")
 /// ```
 ///
-/// Performance optimization: Uses deferred BEGIN - the BEGIN is buffered
-/// but not sent until the first query, saving a round trip. This means:
+/// The API is the same for both backends; only the placeholder style differs.
+///
+/// Performance optimization (PostgreSQL): Uses deferred BEGIN - the BEGIN is
+/// buffered but not sent until the first query, saving a round trip. This means:
 /// - transaction() returns immediately (no network I/O)
 /// - First execute() sends BEGIN + query together
 /// - Subsequent queries skip ReadyForQuery wait (use Flush not Sync)
@@ -949,210 +2869,1731 @@ pub enum SqlParam {
 #[pyclass]
 pub struct Transaction {
     /// The dedicated connection for this transaction
-    conn: Arc<tokio::sync::Mutex<Option<PgPooledConnection>>>,
-    /// Whether BEGIN response has been consumed
-    begun: Arc<std::sync::atomic::AtomicBool>,
+    conn: Arc<tokio::sync::Mutex<Option<TransactionConn>>>,
+    /// Placeholder style of the backend, for binding named params
+    placeholder_style: PlaceholderStyle,
+    /// Casing applied to result column names
+    column_case: ColumnCase,
+    /// Format of bytes values in results
+    bytes_format: BytesFormat,
+    /// Whether equal strings in results share one allocation
+    intern_strings: bool,
+    /// Whether UUID columns come back as `uuid.UUID` objects
+    uuid_as_object: bool,
+    /// Whether PostgreSQL queries go through the connection's statement
+    /// cache; when false they use the unnamed statement
+    cache_statements: bool,
+    /// Savepoints created by `nested()`, for generating unique names
+    savepoints: Arc<AtomicU32>,
+    /// Whether the connection goes back to the pool on exit; false when it
+    /// belongs to a `Session`, which keeps it
+    owns_conn: bool,
+    /// Id of the connection, for correlating logs and errors
+    connection_id: Option<u64>,
 }
 
-#[pymethods]
 impl Transaction {
-    /// Enter the async context manager
-    fn __aenter__<'py>(slf: PyRef<'py, Self>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
-        // Return self - BEGIN is buffered but not sent yet
-        let tx = Transaction {
-            conn: Arc::clone(&slf.conn),
-            begun: Arc::clone(&slf.begun),
-        };
-        pyo3_async_runtimes::tokio::future_into_py(py, async move { Ok(tx) })
+    fn new(
+        conn: TransactionConn,
+        column_case: ColumnCase,
+        bytes_format: BytesFormat,
+        intern_strings: bool,
+        uuid_as_object: bool,
+        cache_statements: bool,
+    ) -> Self {
+        let placeholder_style = placeholder_style_of(&conn);
+        let connection_id = connection_id_of(&conn);
+        Self {
+            conn: Arc::new(tokio::sync::Mutex::new(Some(conn))),
+            placeholder_style,
+            column_case,
+            bytes_format,
+            intern_strings,
+            uuid_as_object,
+            cache_statements,
+            savepoints: Arc::new(AtomicU32::new(0)),
+            owns_conn: true,
+            connection_id,
+        }
     }
 
-    /// Exit the async context manager - commits or rolls back
-    #[pyo3(signature = (exc_type, _exc_val, _exc_tb))]
-    fn __aexit__<'py>(
+    /// Another handle to the same transaction.
+    fn share(&self) -> Self {
+        Self {
+            conn: Arc::clone(&self.conn),
+            placeholder_style: self.placeholder_style,
+            column_case: self.column_case,
+            bytes_format: self.bytes_format,
+            intern_strings: self.intern_strings,
+            uuid_as_object: self.uuid_as_object,
+            cache_statements: self.cache_statements,
+            savepoints: Arc::clone(&self.savepoints),
+            owns_conn: self.owns_conn,
+            connection_id: self.connection_id,
+        }
+    }
+
+    /// Run a parameterless command such as `SAVEPOINT` on the transaction's
+    /// connection, syncing first (see `transaction_command`) or not (see
+    /// `savepoint_command`).
+    fn run_command<'py>(
         &self,
         py: Python<'py>,
-        exc_type: Option<PyObject>,
-        _exc_val: Option<PyObject>,
-        _exc_tb: Option<PyObject>,
+        sql: String,
+        sync: bool,
     ) -> PyResult<Bound<'py, PyAny>> {
-        let has_exception = exc_type.is_some();
         let conn = Arc::clone(&self.conn);
 
         pyo3_async_runtimes::tokio::future_into_py(py, async move {
             let mut guard = conn.lock().await;
-            if let Some(ref mut c) = *guard {
-                if has_exception {
-                    // Rollback on exception - includes Sync
-                    let _ = c.rollback().await;
-                } else {
-                    // Commit - includes Sync
-                    c.commit().await.map_err(|e| {
-                        pyo3::exceptions::PyRuntimeError::new_err(format!(
-                            "Failed to commit: {}",
-                            e
-                        ))
-                    })?;
-                }
+            let conn = guard.as_mut().ok_or_else(transaction_not_active)?;
+            if sync {
+                transaction_command(conn, &sql).await
+            } else {
+                savepoint_command(conn, &sql).await
             }
-            // Take the connection out of the Option so it gets dropped,
-            // returning the semaphore permit to the pool
-            let _ = guard.take();
-            // Return False to not suppress exceptions
-            Ok(false)
         })
     }
+}
 
-    /// Execute a query within the transaction
-    ///
-    /// First call sends buffered BEGIN + query together (deferred BEGIN).
-    /// Subsequent calls use query_no_sync() - skips ReadyForQuery wait.
-    #[pyo3(signature = (sql, params=None))]
-    fn execute<'py>(
-        &self,
-        py: Python<'py>,
-        sql: String,
-        params: Option<Vec<PyObject>>,
-    ) -> PyResult<Bound<'py, PyAny>> {
-        let sql_params = convert_py_params(py, params.unwrap_or_default())?;
-        let conn = Arc::clone(&self.conn);
-        let begun = Arc::clone(&self.begun);
+/// Placeholder style of the backend a connection belongs to.
+fn placeholder_style_of(conn: &TransactionConn) -> PlaceholderStyle {
+    match conn {
+        TransactionConn::Postgres { .. } => PlaceholderStyle::Dollar,
+        TransactionConn::Sqlite(_) => PlaceholderStyle::Question,
+    }
+}
+
+fn connection_id_of(conn: &TransactionConn) -> Option<u64> {
+    match conn {
+        TransactionConn::Postgres { conn, .. } => conn.id(),
+        TransactionConn::Sqlite(conn) => conn.id(),
+    }
+}
+
+/// Acquire a connection to hold for a transaction or session.
+///
+/// A SQLite transaction also takes the pool's write lock until the
+/// connection is returned, so the pool's transactions run one at a time
+/// instead of contending for SQLite's single writer.
+async fn acquire_dedicated(pool: &PoolInner, transaction: bool) -> PyResult<TransactionConn> {
+    Ok(match pool {
+        PoolInner::Postgres(pool) => TransactionConn::Postgres {
+            conn: Box::new(pool.acquire().await.map_err(ForeignKeyError::from)?),
+            begun: true,
+        },
+        PoolInner::Sqlite(pool) => {
+            let conn = if transaction {
+                pool.acquire_writer().await
+            } else {
+                pool.acquire().await
+            };
+            TransactionConn::Sqlite(conn.map_err(ForeignKeyError::from)?)
+        }
+    })
+}
+
+/// Start a transaction on a dedicated connection.
+///
+/// On PostgreSQL a deferred BEGIN is buffered and sent with the first query;
+/// on SQLite `deferred=false` issues `BEGIN IMMEDIATE`.
+async fn begin_transaction(conn: &mut TransactionConn, deferred: bool) -> PyResult<()> {
+    match conn {
+        TransactionConn::Postgres { conn, begun } => {
+            let begin = if deferred {
+                // Buffer BEGIN without flushing - will be sent with first query
+                conn.begin_deferred().await
+            } else {
+                conn.begin().await
+            };
+            begin.map_err(pg_transaction_error)?;
+            // An immediate BEGIN has no pending response to consume
+            *begun = !deferred;
+        }
+        TransactionConn::Sqlite(conn) => {
+            let begin = if deferred { "BEGIN" } else { "BEGIN IMMEDIATE" };
+            conn.execute_batch(begin).await.map_err(transaction_error)?;
+        }
+    }
+    Ok(())
+}
+
+fn transaction_not_active() -> PyErr {
+    pyo3::exceptions::PyRuntimeError::new_err("Transaction not active")
+}
+
+fn transaction_error(e: SqliteError) -> PyErr {
+    let message = e.to_string();
+    sqlite_error_to_py(&e, message)
+}
+
+fn pg_transaction_error(e: PgError) -> PyErr {
+    let message = e.to_string();
+    pg_error_to_py(&e, message)
+}
+
+/// Run a parameterless command on a transaction's connection.
+///
+/// On PostgreSQL the connection is synced first: that sends a deferred
+/// BEGIN, and clears the error state a failed statement leaves behind so
+/// that `ROLLBACK TO SAVEPOINT` can recover the transaction.
+async fn transaction_command(conn: &mut TransactionConn, sql: &str) -> PyResult<()> {
+    match conn {
+        TransactionConn::Postgres { conn, begun } => {
+            conn.sync().await.map_err(pg_transaction_error)?;
+            *begun = true;
+            conn.simple_query(sql).await.map_err(pg_transaction_error)?;
+        }
+        TransactionConn::Sqlite(conn) => {
+            conn.execute_batch(sql).await.map_err(transaction_error)?;
+        }
+    }
+    Ok(())
+}
+
+/// Run `SAVEPOINT` or `RELEASE SAVEPOINT` on a transaction's connection.
+///
+/// On PostgreSQL this goes through the unnamed statement without a Sync,
+/// like the transaction's other statements, and carries a deferred BEGIN
+/// with it: one round trip. A failed statement makes the server skip
+/// everything up to the next Sync, so recovering from one is left to
+/// `ROLLBACK TO SAVEPOINT` through `transaction_command`.
+async fn savepoint_command(conn: &mut TransactionConn, sql: &str) -> PyResult<()> {
+    match conn {
+        TransactionConn::Postgres { conn, begun } => {
+            let is_first = !std::mem::replace(begun, true);
+            conn.query_in_transaction_uncached(sql, &[], is_first)
+                .await
+                .map_err(pg_transaction_error)?;
+        }
+        TransactionConn::Sqlite(conn) => {
+            conn.execute_batch(sql).await.map_err(transaction_error)?;
+        }
+    }
+    Ok(())
+}
+
+/// Quote a savepoint name as an SQL identifier.
+fn quote_savepoint(name: &str) -> String {
+    // Savepoint names are quoted the same way on both backends
+    quote_ident(name, Dialect::Postgres)
+}
+
+#[pymethods]
+impl Transaction {
+    /// Enter the async context manager
+    fn __aenter__<'py>(slf: PyRef<'py, Self>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        // Return self - BEGIN is buffered but not sent yet
+        let tx = slf.share();
+        pyo3_async_runtimes::tokio::future_into_py(py, async move { Ok(tx) })
+    }
+
+    /// Exit the async context manager - commits or rolls back
+    #[pyo3(signature = (exc_type, _exc_val, _exc_tb))]
+    fn __aexit__<'py>(
+        &self,
+        py: Python<'py>,
+        exc_type: Option<PyObject>,
+        _exc_val: Option<PyObject>,
+        _exc_tb: Option<PyObject>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let has_exception = exc_type.is_some();
+        let conn = Arc::clone(&self.conn);
+        let owns_conn = self.owns_conn;
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let mut guard = conn.lock().await;
+            let result = match guard.as_mut() {
+                Some(TransactionConn::Postgres { conn, .. }) => {
+                    if has_exception {
+                        // Rollback on exception - includes Sync
+                        let _ = conn.rollback().await;
+                        Ok(())
+                    } else {
+                        // Commit - includes Sync
+                        conn.commit().await.map_err(|e| {
+                            pyo3::exceptions::PyRuntimeError::new_err(format!(
+                                "Failed to commit: {}",
+                                e
+                            ))
+                        })
+                    }
+                }
+                Some(TransactionConn::Sqlite(conn)) => {
+                    if has_exception {
+                        let _ = conn.execute_batch("ROLLBACK").await;
+                        Ok(())
+                    } else if let Err(e) = conn.execute_batch("COMMIT").await {
+                        // A failed COMMIT leaves the transaction open; don't
+                        // hand the connection back mid-transaction
+                        let _ = conn.execute_batch("ROLLBACK").await;
+                        Err(pyo3::exceptions::PyRuntimeError::new_err(format!(
+                            "Failed to commit: {}",
+                            e
+                        )))
+                    } else {
+                        Ok(())
+                    }
+                }
+                None => Ok(()),
+            };
+            if owns_conn {
+                // Drop the connection, returning it to the pool
+                guard.take();
+            }
+            result?;
+            // Return False to not suppress exceptions
+            Ok(false)
+        })
+    }
+
+    /// Execute a query within the transaction
+    ///
+    /// On PostgreSQL the first call sends buffered BEGIN + query together
+    /// (deferred BEGIN), and later calls skip the ReadyForQuery wait.
+    #[pyo3(signature = (sql, params=None))]
+    fn execute<'py>(
+        &self,
+        py: Python<'py>,
+        sql: String,
+        params: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let (sql, sql_params) = bind_py_params(py, sql, params, self.placeholder_style)?;
+        let conn = Arc::clone(&self.conn);
+        let column_case = self.column_case;
+        let bytes_format = self.bytes_format;
+        let intern_strings = self.intern_strings;
+        let uuid_as_object = self.uuid_as_object;
+        let cache_statements = self.cache_statements;
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let mut guard = conn.lock().await;
+            match guard.as_mut().ok_or_else(transaction_not_active)? {
+                TransactionConn::Postgres { conn, begun } => {
+                    let pg_params: Vec<PgValue> =
+                        sql_params.into_iter().map(sql_param_to_pg).collect();
+                    // On first query, we need to consume BEGIN response after flush
+                    let is_first = !std::mem::replace(begun, true);
+                    let result = if cache_statements {
+                        conn.query_in_transaction(&sql, &pg_params, is_first).await
+                    } else {
+                        conn.query_in_transaction_uncached(&sql, &pg_params, is_first)
+                            .await
+                    }
+                    .map_err(pg_transaction_error)?;
+                    Ok(pg_query_result(
+                        result,
+                        column_case,
+                        bytes_format,
+                        intern_strings,
+                        uuid_as_object,
+                    ))
+                }
+                TransactionConn::Sqlite(conn) => {
+                    let sqlite_params: Vec<SqliteValue> =
+                        sql_params.into_iter().map(sql_param_to_sqlite).collect();
+                    let result = conn
+                        .query(&sql, sqlite_params)
+                        .await
+                        .map_err(transaction_error)?;
+                    Ok(sqlite_query_result(
+                        result,
+                        column_case,
+                        bytes_format,
+                        intern_strings,
+                    ))
+                }
+            }
+        })
+    }
+
+    /// Execute a statement within the transaction, returning rows affected
+    #[pyo3(signature = (sql, params=None))]
+    fn execute_statement<'py>(
+        &self,
+        py: Python<'py>,
+        sql: String,
+        params: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let (sql, sql_params) = bind_py_params(py, sql, params, self.placeholder_style)?;
+        let conn = Arc::clone(&self.conn);
+        let cache_statements = self.cache_statements;
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let mut guard = conn.lock().await;
+            match guard.as_mut().ok_or_else(transaction_not_active)? {
+                TransactionConn::Postgres { conn, begun } => {
+                    let pg_params: Vec<PgValue> =
+                        sql_params.into_iter().map(sql_param_to_pg).collect();
+                    let is_first = !std::mem::replace(begun, true);
+                    let result = if cache_statements {
+                        conn.query_in_transaction(&sql, &pg_params, is_first).await
+                    } else {
+                        conn.query_in_transaction_uncached(&sql, &pg_params, is_first)
+                            .await
+                    }
+                    .map_err(pg_transaction_error)?;
+                    Ok(parse_rows_affected(&result.command_tag))
+                }
+                TransactionConn::Sqlite(conn) => {
+                    let sqlite_params: Vec<SqliteValue> =
+                        sql_params.into_iter().map(sql_param_to_sqlite).collect();
+                    conn.execute(&sql, sqlite_params)
+                        .await
+                        .map_err(transaction_error)
+                }
+            }
+        })
+    }
+
+    /// Execute a statement once per parameter set, returning total rows
+    /// affected.
+    ///
+    /// Both backends look the statement up once and reuse it for every
+    /// parameter set. PostgreSQL pipelines the executions, keeping at most
+    /// the pool's `pipeline_depth` unanswered, and syncs once at the end;
+    /// SQLite runs them under a savepoint.
+    #[pyo3(signature = (sql, params_list))]
+    fn execute_many<'py>(
+        &self,
+        py: Python<'py>,
+        sql: String,
+        params_list: Vec<Vec<PyObject>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        // Convert all params upfront
+        let all_params = convert_py_param_batch(py, params_list)?;
+
+        let conn = Arc::clone(&self.conn);
+        let cache_statements = self.cache_statements;
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let mut guard = conn.lock().await;
+            match guard.as_mut().ok_or_else(transaction_not_active)? {
+                TransactionConn::Postgres { conn, begun } => {
+                    if all_params.is_empty() {
+                        return Ok(0);
+                    }
+                    let params_list: Vec<Vec<PgValue>> = all_params
+                        .into_iter()
+                        .map(|params| params.into_iter().map(sql_param_to_pg).collect())
+                        .collect();
+
+                    // Send a deferred BEGIN on its own so its reply isn't
+                    // mistaken for the first execution's
+                    if !std::mem::replace(begun, true) {
+                        conn.sync().await.map_err(pg_transaction_error)?;
+                    }
+
+                    if !cache_statements {
+                        let mut affected = 0u64;
+                        for params in &params_list {
+                            let result = conn
+                                .query_in_transaction_uncached(&sql, params, false)
+                                .await
+                                .map_err(pg_transaction_error)?;
+                            affected += parse_rows_affected(&result.command_tag);
+                        }
+                        conn.sync().await.map_err(pg_transaction_error)?;
+                        return Ok(affected);
+                    }
+
+                    // Every set shares the SQL: look it up once, then bind
+                    // the statement directly for each set
+                    let stmt = conn
+                        .prepare_batch(&sql, &params_list)
+                        .await
+                        .map_err(pg_transaction_error)?;
+                    let tags = conn
+                        .execute_batch(&stmt, &params_list)
+                        .await
+                        .map_err(pg_transaction_error)?;
+
+                    Ok(tags.iter().map(|tag| parse_rows_affected(tag)).sum::<u64>())
+                }
+                TransactionConn::Sqlite(conn) => {
+                    let params_list: Vec<Vec<SqliteValue>> = all_params
+                        .into_iter()
+                        .map(|params| params.into_iter().map(sql_param_to_sqlite).collect())
+                        .collect();
+                    conn.execute_many(&sql, params_list)
+                        .await
+                        .map_err(transaction_error)
+                }
+            }
+        })
+    }
+
+    /// Create a savepoint
+    ///
+    /// The name is quoted as an identifier, so any string is safe to pass.
+    /// On PostgreSQL this is pipelined like the transaction's statements,
+    /// without waiting for a Sync.
+    fn savepoint<'py>(&self, py: Python<'py>, name: &str) -> PyResult<Bound<'py, PyAny>> {
+        self.run_command(py, format!("SAVEPOINT {}", quote_savepoint(name)), false)
+    }
+
+    /// Release a savepoint, keeping its changes
+    fn release<'py>(&self, py: Python<'py>, name: &str) -> PyResult<Bound<'py, PyAny>> {
+        self.run_command(
+            py,
+            format!("RELEASE SAVEPOINT {}", quote_savepoint(name)),
+            false,
+        )
+    }
+
+    /// Roll back to a savepoint, discarding changes made since it was created
+    ///
+    /// The savepoint is kept, so it can be rolled back to again. This also
+    /// recovers the transaction after a failed statement.
+    fn rollback_to<'py>(&self, py: Python<'py>, name: &str) -> PyResult<Bound<'py, PyAny>> {
+        self.run_command(
+            py,
+            format!("ROLLBACK TO SAVEPOINT {}", quote_savepoint(name)),
+            true,
+        )
+    }
+
+    /// Id of the connection this runs on
+    ///
+    /// Process-unique and never reused, so it tells connections apart in
+    /// logs. On PostgreSQL a `DatabaseError` raised on the connection
+    /// carries the same `connection_id`.
+    #[getter]
+    fn connection_id(&self) -> Option<u64> {
+        self.connection_id
+    }
+
+    /// Start a nested transaction backed by a savepoint
+    ///
+    /// Use as `async with tx.nested():` - entering creates a uniquely named
+    /// savepoint, a clean exit releases it and an exception rolls back to
+    /// it, leaving the rest of the outer transaction intact.
+    fn nested(&self) -> NestedTransaction {
+        let n = self.savepoints.fetch_add(1, Ordering::Relaxed) + 1;
+        NestedTransaction {
+            tx: self.share(),
+            name: format!("ormkit_sp_{}", n),
+        }
+    }
+
+    /// Transaction status: "idle", "in_transaction" or "failed"
+    ///
+    /// On PostgreSQL this is the status from the server's last
+    /// ReadyForQuery. Queries in a transaction don't wait for one, so with a
+    /// deferred BEGIN it stays "idle" until commit; with `deferred=False` it
+    /// is "in_transaction" from the start. SQLite runs BEGIN up front, so it
+    /// is always "in_transaction".
+    #[getter]
+    fn transaction_status(&self) -> PyResult<&'static str> {
+        let guard = self
+            .conn
+            .try_lock()
+            .map_err(|_| pyo3::exceptions::PyRuntimeError::new_err("Transaction is busy"))?;
+        match guard.as_ref().ok_or_else(transaction_not_active)? {
+            TransactionConn::Postgres { conn, .. } => Ok(conn.transaction_status().as_str()),
+            TransactionConn::Sqlite(_) => Ok("in_transaction"),
+        }
+    }
+
+    /// List the transaction connection's cached prepared statements
+    /// (PostgreSQL only)
+    ///
+    /// Returns `(sql, statement_name, param_oids)` tuples, most recently used
+    /// first. Useful for diagnosing why a query is being re-prepared.
+    fn prepared_statements<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let conn = Arc::clone(&self.conn);
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let guard = conn.lock().await;
+            match guard.as_ref().ok_or_else(transaction_not_active)? {
+                TransactionConn::Postgres { conn, .. } => Ok(conn
+                    .prepared_statements()
+                    .into_iter()
+                    .map(|(sql, name, oids)| {
+                        let oids: Vec<i32> = oids.into_iter().map(|oid| oid.as_i32()).collect();
+                        (sql, name, oids)
+                    })
+                    .collect::<Vec<_>>()),
+                TransactionConn::Sqlite(_) => Err(ForeignKeyError::QueryError(
+                    "prepared_statements is only supported for PostgreSQL".to_string(),
+                )
+                .into()),
+            }
+        })
+    }
+}
+
+/// A connection pinned for a sequence of statements, from
+/// `ConnectionPool.session()`.
+///
+/// ```python
+/// async with await pool.session() as session:
+///     await session.execute("CREATE TEMP TABLE scratch (id int)")
+///     await session.execute("SELECT * FROM scratch")
+/// ```
+#[pyclass]
+pub struct Session {
+    /// The pinned connection (None once closed)
+    conn: Arc<tokio::sync::Mutex<Option<TransactionConn>>>,
+    /// Placeholder style of the backend, for binding named params
+    placeholder_style: PlaceholderStyle,
+    /// Casing applied to result column names
+    column_case: ColumnCase,
+    /// Format of bytes values in results
+    bytes_format: BytesFormat,
+    /// Whether equal strings in results share one allocation
+    intern_strings: bool,
+    /// Whether UUID columns come back as `uuid.UUID` objects
+    uuid_as_object: bool,
+    /// Id of the pinned connection, for correlating logs and errors
+    connection_id: Option<u64>,
+}
+
+fn session_closed() -> PyErr {
+    pyo3::exceptions::PyRuntimeError::new_err("Session is closed")
+}
+
+#[pymethods]
+impl Session {
+    /// Enter the async context manager
+    fn __aenter__<'py>(slf: PyRef<'py, Self>, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let session = Session {
+            conn: Arc::clone(&slf.conn),
+            placeholder_style: slf.placeholder_style,
+            column_case: slf.column_case,
+            bytes_format: slf.bytes_format,
+            intern_strings: slf.intern_strings,
+            uuid_as_object: slf.uuid_as_object,
+            connection_id: slf.connection_id,
+        };
+        pyo3_async_runtimes::tokio::future_into_py(py, async move { Ok(session) })
+    }
+
+    /// Id of the connection this runs on
+    ///
+    /// Process-unique and never reused, so it tells connections apart in
+    /// logs. On PostgreSQL a `DatabaseError` raised on the connection
+    /// carries the same `connection_id`.
+    #[getter]
+    fn connection_id(&self) -> Option<u64> {
+        self.connection_id
+    }
+
+    /// Exit the async context manager - returns the connection to the pool
+    #[pyo3(signature = (_exc_type, _exc_val, _exc_tb))]
+    fn __aexit__<'py>(
+        &self,
+        py: Python<'py>,
+        _exc_type: Option<PyObject>,
+        _exc_val: Option<PyObject>,
+        _exc_tb: Option<PyObject>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let conn = Arc::clone(&self.conn);
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            conn.lock().await.take();
+            // Return False to not suppress exceptions
+            Ok(false)
+        })
+    }
+
+    /// Return the connection to the pool
+    fn close<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let conn = Arc::clone(&self.conn);
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            conn.lock().await.take();
+            Ok(())
+        })
+    }
+
+    /// Execute a query on the session's connection
+    #[pyo3(signature = (sql, params=None))]
+    fn execute<'py>(
+        &self,
+        py: Python<'py>,
+        sql: String,
+        params: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let (sql, sql_params) = bind_py_params(py, sql, params, self.placeholder_style)?;
+        let conn = Arc::clone(&self.conn);
+        let column_case = self.column_case;
+        let bytes_format = self.bytes_format;
+        let intern_strings = self.intern_strings;
+        let uuid_as_object = self.uuid_as_object;
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let mut guard = conn.lock().await;
+            match guard.as_mut().ok_or_else(session_closed)? {
+                TransactionConn::Postgres { conn, .. } => {
+                    let pg_params: Vec<PgValue> =
+                        sql_params.into_iter().map(sql_param_to_pg).collect();
+                    let result = conn
+                        .query(&sql, &pg_params)
+                        .await
+                        .map_err(pg_transaction_error)?;
+                    Ok(pg_query_result(
+                        result,
+                        column_case,
+                        bytes_format,
+                        intern_strings,
+                        uuid_as_object,
+                    ))
+                }
+                TransactionConn::Sqlite(conn) => {
+                    let sqlite_params: Vec<SqliteValue> =
+                        sql_params.into_iter().map(sql_param_to_sqlite).collect();
+                    let result = conn
+                        .query(&sql, sqlite_params)
+                        .await
+                        .map_err(transaction_error)?;
+                    Ok(sqlite_query_result(
+                        result,
+                        column_case,
+                        bytes_format,
+                        intern_strings,
+                    ))
+                }
+            }
+        })
+    }
+
+    /// Execute a statement on the session's connection, returning rows
+    /// affected
+    #[pyo3(signature = (sql, params=None))]
+    fn execute_statement<'py>(
+        &self,
+        py: Python<'py>,
+        sql: String,
+        params: Option<Bound<'py, PyAny>>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let (sql, sql_params) = bind_py_params(py, sql, params, self.placeholder_style)?;
+        let conn = Arc::clone(&self.conn);
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let mut guard = conn.lock().await;
+            match guard.as_mut().ok_or_else(session_closed)? {
+                TransactionConn::Postgres { conn, .. } => {
+                    let pg_params: Vec<PgValue> =
+                        sql_params.into_iter().map(sql_param_to_pg).collect();
+                    let result = conn
+                        .query(&sql, &pg_params)
+                        .await
+                        .map_err(pg_transaction_error)?;
+                    Ok(parse_rows_affected(&result.command_tag))
+                }
+                TransactionConn::Sqlite(conn) => {
+                    let sqlite_params: Vec<SqliteValue> =
+                        sql_params.into_iter().map(sql_param_to_sqlite).collect();
+                    conn.execute(&sql, sqlite_params)
+                        .await
+                        .map_err(transaction_error)
+                }
+            }
+        })
+    }
+
+    /// Start a transaction on the session's connection - returns a
+    /// Transaction context manager
+    ///
+    /// The connection stays with the session after the transaction ends.
+    /// BEGIN always runs before this returns on PostgreSQL, so statements
+    /// run through the session itself also land inside the transaction. On
+    /// SQLite `deferred=False` issues `BEGIN IMMEDIATE`.
+    #[pyo3(signature = (deferred=true))]
+    fn transaction<'py>(&self, py: Python<'py>, deferred: bool) -> PyResult<Bound<'py, PyAny>> {
+        let conn = Arc::clone(&self.conn);
+        let placeholder_style = self.placeholder_style;
+        let column_case = self.column_case;
+        let bytes_format = self.bytes_format;
+        let intern_strings = self.intern_strings;
+        let uuid_as_object = self.uuid_as_object;
+        let connection_id = self.connection_id;
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            {
+                let mut guard = conn.lock().await;
+                let pinned = guard.as_mut().ok_or_else(session_closed)?;
+                let deferred = deferred && matches!(pinned, TransactionConn::Sqlite(_));
+                begin_transaction(pinned, deferred).await?;
+            }
+            Ok(Transaction {
+                conn,
+                placeholder_style,
+                column_case,
+                bytes_format,
+                intern_strings,
+                uuid_as_object,
+                cache_statements: true,
+                savepoints: Arc::new(AtomicU32::new(0)),
+                owns_conn: false,
+                connection_id,
+            })
+        })
+    }
+}
+
+/// A savepoint-backed nested transaction, from `Transaction.nested()`.
+#[pyclass]
+pub struct NestedTransaction {
+    /// The enclosing transaction
+    tx: Transaction,
+    /// Savepoint name, unique within the enclosing transaction
+    name: String,
+}
+
+#[pymethods]
+impl NestedTransaction {
+    /// The savepoint's name
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Create the savepoint, returning the enclosing transaction
+    fn __aenter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let tx = self.tx.share();
+        let sql = format!("SAVEPOINT {}", quote_savepoint(&self.name));
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            {
+                let mut guard = tx.conn.lock().await;
+                savepoint_command(guard.as_mut().ok_or_else(transaction_not_active)?, &sql).await?;
+            }
+            Ok(tx)
+        })
+    }
+
+    /// Release the savepoint, or roll back to it on exception
+    #[pyo3(signature = (exc_type, _exc_val, _exc_tb))]
+    fn __aexit__<'py>(
+        &self,
+        py: Python<'py>,
+        exc_type: Option<PyObject>,
+        _exc_val: Option<PyObject>,
+        _exc_tb: Option<PyObject>,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let conn = Arc::clone(&self.tx.conn);
+        let name = quote_savepoint(&self.name);
+        let has_exception = exc_type.is_some();
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let mut guard = conn.lock().await;
+            let conn = guard.as_mut().ok_or_else(transaction_not_active)?;
+            if has_exception {
+                // Rolling back keeps the savepoint; release it so it doesn't
+                // linger for the rest of the outer transaction
+                transaction_command(conn, &format!("ROLLBACK TO SAVEPOINT {}", name)).await?;
+            }
+            savepoint_command(conn, &format!("RELEASE SAVEPOINT {}", name)).await?;
+            // Return False to not suppress exceptions
+            Ok(false)
+        })
+    }
+}
+
+/// A query prepared for repeated execution, from `ConnectionPool.prepare`.
+///
+/// Pass it to `ConnectionPool.execute` in place of the SQL string.
+#[pyclass]
+pub struct PreparedQuery {
+    inner: Arc<PgPreparedQuery>,
+    /// Named parameters in positional order (empty for positional SQL)
+    names: Vec<String>,
+}
+
+impl PreparedQuery {
+    /// Convert params for execution: a dict for named parameters, or a
+    /// positional sequence.
+    fn bind(&self, py: Python<'_>, params: Option<Bound<'_, PyAny>>) -> PyResult<Vec<SqlParam>> {
+        let Some(params) = params else {
+            return Ok(Vec::new());
+        };
+
+        if let Ok(named) = params.downcast::<PyDict>() {
+            let values = named_param_values(named, &self.names)?;
+            return convert_py_params(py, values);
+        }
+
+        convert_py_params(py, params.extract()?)
+    }
+}
+
+#[pymethods]
+impl PreparedQuery {
+    /// The SQL text as sent to the server
+    #[getter]
+    fn sql(&self) -> &str {
+        self.inner.sql()
+    }
+
+    /// Number of pooled connections the query is currently prepared on
+    #[getter]
+    fn prepared_count(&self) -> usize {
+        self.inner.prepared_count()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("PreparedQuery({:?})", self.inner.sql())
+    }
+}
+
+/// A LISTEN subscription on a dedicated PostgreSQL connection.
+///
+/// Each event is a dict: `{"type": "notification", "channel", "payload",
+/// "pid"}`, or `{"type": "reconnected"}` after the connection dropped and
+/// was re-established (notifications sent in the gap were missed).
+#[pyclass]
+pub struct Listener {
+    inner: Arc<tokio::sync::Mutex<PgListener>>,
+    /// Wakes a waiting `recv` so it lets go of `inner` for a `listen`,
+    /// `unlisten` or `close`
+    interrupt: Arc<tokio::sync::Notify>,
+}
+
+impl Listener {
+    /// Lock the listener, asking a waiting `recv` to step aside first.
+    async fn lock_interrupting<'a>(
+        inner: &'a tokio::sync::Mutex<PgListener>,
+        interrupt: &tokio::sync::Notify,
+    ) -> tokio::sync::MutexGuard<'a, PgListener> {
+        // recv registers for the interrupt before it locks, and the mutex
+        // is fair, so this lock is queued ahead of its retry
+        interrupt.notify_waiters();
+        inner.lock().await
+    }
+}
+
+/// Convert a listener event to its Python dict form.
+fn listener_event_to_py(py: Python<'_>, event: ListenerEvent) -> PyResult<PyObject> {
+    let dict = PyDict::new(py);
+    match event {
+        ListenerEvent::Notification(n) => {
+            dict.set_item("type", "notification")?;
+            dict.set_item("channel", n.channel)?;
+            dict.set_item("payload", n.payload)?;
+            dict.set_item("pid", n.process_id)?;
+        }
+        ListenerEvent::Reconnected => dict.set_item("type", "reconnected")?,
+    }
+    Ok(dict.into_any().unbind())
+}
+
+#[pymethods]
+impl Listener {
+    /// Wait for the next event
+    ///
+    /// `listen`, `unlisten` and `close` don't wait for this to return; a
+    /// pending `recv` pauses while they run, then keeps waiting (or fails
+    /// after `close`).
+    fn recv<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let inner = Arc::clone(&self.inner);
+        let interrupt = Arc::clone(&self.interrupt);
 
         pyo3_async_runtimes::tokio::future_into_py(py, async move {
-            let mut guard = conn.lock().await;
-            let c = guard.as_mut().ok_or_else(|| {
-                pyo3::exceptions::PyRuntimeError::new_err("Transaction not active")
-            })?;
+            let event = loop {
+                // Register before locking, so an interrupt sent while this
+                // holds the lock is never missed
+                let interrupted = interrupt.notified();
+                tokio::pin!(interrupted);
+                interrupted.as_mut().enable();
+
+                let mut listener = inner.lock().await;
+                tokio::select! {
+                    event = listener.recv() => break event.map_err(ForeignKeyError::from)?,
+                    _ = interrupted => {}
+                }
+            };
+            Python::with_gil(|py| listener_event_to_py(py, event))
+        })
+    }
+
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
 
-            // On first query, we need to consume BEGIN response after flush
-            let is_first = !begun.swap(true, std::sync::atomic::Ordering::SeqCst);
+    fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.recv(py)
+    }
 
-            let pg_params: Vec<PgValue> = sql_params.into_iter().map(sql_param_to_pg).collect();
+    /// Subscribe to another channel
+    fn listen<'py>(&self, py: Python<'py>, channel: String) -> PyResult<Bound<'py, PyAny>> {
+        let inner = Arc::clone(&self.inner);
+        let interrupt = Arc::clone(&self.interrupt);
 
-            // Execute query, consuming deferred BEGIN on first call
-            let result = c
-                .query_in_transaction(&sql, &pg_params, is_first)
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            Self::lock_interrupting(&inner, &interrupt)
+                .await
+                .listen(&channel)
                 .await
-                .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+                .map_err(ForeignKeyError::from)?;
+            Ok(())
+        })
+    }
 
-            // Convert to our QueryResult format - extract column names from Arc<Vec<FieldDescription>>
-            let columns: Vec<String> = result.columns.iter().map(|f| f.name.clone()).collect();
+    /// Unsubscribe from a channel
+    fn unlisten<'py>(&self, py: Python<'py>, channel: String) -> PyResult<Bound<'py, PyAny>> {
+        let inner = Arc::clone(&self.inner);
+        let interrupt = Arc::clone(&self.interrupt);
 
-            let lazy_rows: Vec<LazyRow> = result
-                .rows
-                .into_iter()
-                .map(|row| {
-                    let values: SmallVec<[RowValue; 16]> =
-                        row.into_iter().map(pg_value_to_row).collect();
-                    LazyRow { values }
-                })
-                .collect();
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            Self::lock_interrupting(&inner, &interrupt)
+                .await
+                .unlisten(&channel)
+                .await
+                .map_err(ForeignKeyError::from)?;
+            Ok(())
+        })
+    }
+
+    /// Subscribed channels
+    #[getter]
+    fn channels(&self) -> PyResult<Vec<String>> {
+        let inner = self
+            .inner
+            .try_lock()
+            .map_err(|_| pyo3::exceptions::PyRuntimeError::new_err("Listener is busy receiving"))?;
+        Ok(inner.channels().to_vec())
+    }
+
+    /// Backend PID of the listening connection (None while disconnected)
+    #[getter]
+    fn backend_pid(&self) -> PyResult<Option<i32>> {
+        let inner = self
+            .inner
+            .try_lock()
+            .map_err(|_| pyo3::exceptions::PyRuntimeError::new_err("Listener is busy receiving"))?;
+        Ok(inner.backend_pid())
+    }
+
+    /// Close the listening connection
+    fn close<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let inner = Arc::clone(&self.inner);
+        let interrupt = Arc::clone(&self.interrupt);
 
-            Ok(QueryResult::from_lazy(lazy_rows, columns))
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            let _ = Self::lock_interrupting(&inner, &interrupt)
+                .await
+                .close()
+                .await;
+            Ok(())
         })
     }
+}
 
-    /// Execute many queries in a pipelined fashion (for bulk operations).
-    ///
-    /// This is much faster than calling execute() in a loop because it
-    /// sends all queries without waiting for responses, then collects
-    /// all results at once.
-    #[pyo3(signature = (sql, params_list))]
-    fn execute_many<'py>(
-        &self,
-        py: Python<'py>,
-        sql: String,
-        params_list: Vec<Vec<PyObject>>,
-    ) -> PyResult<Bound<'py, PyAny>> {
-        // Convert all params upfront
-        let all_params: Vec<Vec<SqlParam>> = params_list
-            .into_iter()
-            .map(|params| convert_py_params(py, params))
-            .collect::<PyResult<Vec<_>>>()?;
+/// Where a `RowStream` reads its rows from.
+enum StreamSource {
+    Sqlite(SqliteRowStream, SqlitePooledConnection),
+    /// A connection reading through an open portal
+    Postgres {
+        conn: Box<PgPooledConnection>,
+        /// First batch, read when the stream was opened
+        first: Option<crate::pg::connection::QueryResult>,
+        batch_size: u32,
+    },
+}
 
-        let conn = Arc::clone(&self.conn);
+/// Rows of a query read incrementally, from `ConnectionPool.stream`
+///
+/// Each step of `async for` yields a `QueryResult` holding the next batch.
+/// The pooled connection goes back to the pool once the rows run out or
+/// `close()` is called.
+#[pyclass]
+pub struct RowStream {
+    inner: Arc<tokio::sync::Mutex<Option<StreamSource>>>,
+    column_case: ColumnCase,
+    bytes_format: BytesFormat,
+    intern_strings: bool,
+    uuid_as_object: bool,
+}
+
+impl RowStream {
+    /// Read the next batch, dropping the stream (and its connection) once
+    /// it's finished or has failed.
+    async fn read_batch(
+        inner: Arc<tokio::sync::Mutex<Option<StreamSource>>>,
+        column_case: ColumnCase,
+        bytes_format: BytesFormat,
+        intern_strings: bool,
+        uuid_as_object: bool,
+    ) -> PyResult<Option<QueryResult>> {
+        let mut guard = inner.lock().await;
+        match guard.as_mut() {
+            None => Ok(None),
+            Some(StreamSource::Sqlite(stream, _)) => match stream.next_batch().await {
+                Some(Ok(rows)) => {
+                    let result = crate::sqlite::connection::QueryResult {
+                        columns: stream.columns.clone(),
+                        rows,
+                        rows_affected: 0,
+                    };
+                    Ok(Some(sqlite_query_result(
+                        result,
+                        column_case,
+                        bytes_format,
+                        intern_strings,
+                    )))
+                }
+                Some(Err(e)) => {
+                    *guard = None;
+                    Err(ForeignKeyError::from(e).into())
+                }
+                None => {
+                    *guard = None;
+                    Ok(None)
+                }
+            },
+            Some(StreamSource::Postgres {
+                conn,
+                first,
+                batch_size,
+            }) => {
+                let batch = match first.take() {
+                    Some(batch) => Ok(batch),
+                    None => conn.fetch_portal(*batch_size).await,
+                };
+                match batch {
+                    Ok(batch) => {
+                        // The portal closes itself once it completes
+                        if !batch.suspended {
+                            *guard = None;
+                            if batch.rows.is_empty() {
+                                return Ok(None);
+                            }
+                        }
+                        Ok(Some(pg_query_result(
+                            batch,
+                            column_case,
+                            bytes_format,
+                            intern_strings,
+                            uuid_as_object,
+                        )))
+                    }
+                    Err(e) => {
+                        *guard = None;
+                        Err(ForeignKeyError::from(e).into())
+                    }
+                }
+            }
+        }
+    }
+
+    /// Drop the stream, closing a PostgreSQL portal that still has rows.
+    async fn close_source(inner: Arc<tokio::sync::Mutex<Option<StreamSource>>>) {
+        if let Some(StreamSource::Postgres { mut conn, .. }) = inner.lock().await.take() {
+            // A failure leaves the portal open; the pool closes it on the
+            // connection's next acquire
+            let _ = conn.close_portal().await;
+        }
+    }
+}
+
+impl Drop for RowStream {
+    fn drop(&mut self) {
+        // Dropped before it was exhausted: close the portal in the
+        // background so the connection's implicit transaction ends now
+        pyo3_async_runtimes::tokio::get_runtime()
+            .spawn(Self::close_source(Arc::clone(&self.inner)));
+    }
+}
+
+#[pymethods]
+impl RowStream {
+    /// Read the next batch, or None once all rows have been read
+    fn next_batch<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let inner = Arc::clone(&self.inner);
+        let column_case = self.column_case;
+        let bytes_format = self.bytes_format;
+        let intern_strings = self.intern_strings;
+        let uuid_as_object = self.uuid_as_object;
 
         pyo3_async_runtimes::tokio::future_into_py(py, async move {
-            let mut guard = conn.lock().await;
-            let c = guard.as_mut().ok_or_else(|| {
-                pyo3::exceptions::PyRuntimeError::new_err("Transaction not active")
-            })?;
+            Self::read_batch(
+                inner,
+                column_case,
+                bytes_format,
+                intern_strings,
+                uuid_as_object,
+            )
+            .await
+        })
+    }
 
-            let count = all_params.len();
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
 
-            // Send all queries without syncing
-            let mut results = Vec::with_capacity(count);
-            for params in all_params {
-                let pg_params: Vec<PgValue> = params.into_iter().map(sql_param_to_pg).collect();
-                let result = c
-                    .query_no_sync(&sql, &pg_params)
-                    .await
-                    .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
-                results.push(result);
+    fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let inner = Arc::clone(&self.inner);
+        let column_case = self.column_case;
+        let bytes_format = self.bytes_format;
+        let intern_strings = self.intern_strings;
+        let uuid_as_object = self.uuid_as_object;
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            Self::read_batch(
+                inner,
+                column_case,
+                bytes_format,
+                intern_strings,
+                uuid_as_object,
+            )
+            .await?
+            .ok_or_else(|| pyo3::exceptions::PyStopAsyncIteration::new_err(()))
+        })
+    }
+
+    /// Stop reading and release the connection
+    fn close<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let inner = Arc::clone(&self.inner);
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            Self::close_source(inner).await;
+            Ok(())
+        })
+    }
+}
+
+/// Chunks of CSV from a PostgreSQL `COPY ... TO STDOUT`
+///
+/// Each step of `async for` yields `bytes` holding whole rows. The pooled
+/// connection goes back to the pool once the data runs out; closing the
+/// stream before then cancels the COPY and discards the connection.
+#[pyclass]
+pub struct CopyOutStream {
+    inner: Arc<tokio::sync::Mutex<Option<Box<PgPooledConnection>>>>,
+}
+
+impl CopyOutStream {
+    /// Gather the next chunk, releasing the connection once the COPY is
+    /// finished or has failed.
+    async fn read_chunk(
+        inner: Arc<tokio::sync::Mutex<Option<Box<PgPooledConnection>>>>,
+    ) -> PyResult<Option<Py<PyBytes>>> {
+        let mut guard = inner.lock().await;
+        let Some(conn) = guard.as_mut() else {
+            return Ok(None);
+        };
+        let mut chunk = Vec::new();
+        while chunk.len() < COPY_OUT_CHUNK_BYTES {
+            match conn.copy_out_next().await {
+                Ok(Some(data)) => chunk.extend_from_slice(&data),
+                Ok(None) => {
+                    *guard = None;
+                    break;
+                }
+                Err(e) => {
+                    *guard = None;
+                    return Err(ForeignKeyError::from(e).into());
+                }
             }
+        }
+        if chunk.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Python::with_gil(|py| {
+            PyBytes::new(py, &chunk).unbind()
+        })))
+    }
+}
 
-            // Sync to ensure all commands are processed
-            c.sync()
-                .await
-                .map_err(|e| pyo3::exceptions::PyRuntimeError::new_err(e.to_string()))?;
+impl Drop for CopyOutStream {
+    fn drop(&mut self) {
+        // Release the connection on the runtime, where dropping it mid-COPY
+        // can send the cancel request
+        let inner = Arc::clone(&self.inner);
+        pyo3_async_runtimes::tokio::get_runtime().spawn(async move {
+            inner.lock().await.take();
+        });
+    }
+}
+
+#[pymethods]
+impl CopyOutStream {
+    /// Read the next chunk, or None once all rows have been read
+    fn next_chunk<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let inner = Arc::clone(&self.inner);
+
+        pyo3_async_runtimes::tokio::future_into_py(py, Self::read_chunk(inner))
+    }
+
+    fn __aiter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __anext__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let inner = Arc::clone(&self.inner);
+
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            Self::read_chunk(inner)
+                .await?
+                .ok_or_else(|| pyo3::exceptions::PyStopAsyncIteration::new_err(()))
+        })
+    }
+
+    /// Stop reading, cancelling the COPY if it hasn't finished
+    fn close<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let inner = Arc::clone(&self.inner);
 
-            Ok(count as u64)
+        pyo3_async_runtimes::tokio::future_into_py(py, async move {
+            inner.lock().await.take();
+            Ok(())
         })
     }
 }
 
-/// Convert Python objects to SQL parameters using type-dispatch.
+/// Bind Python parameters given either positionally (list/tuple) or by name (dict).
 ///
-/// This uses direct Python type object comparison instead of sequential extract() attempts,
-/// which is significantly faster (single type check vs up to 6 extract attempts).
-fn convert_py_params(py: Python<'_>, params: Vec<PyObject>) -> PyResult<Vec<SqlParam>> {
-    use pyo3::types::{PyBool, PyBytes, PyDict, PyFloat, PyInt, PyList, PyString};
+/// A dict binds `:name` placeholders: the SQL is rewritten to the driver's
+/// positional style and the values are ordered to match.
+fn bind_py_params(
+    py: Python<'_>,
+    sql: String,
+    params: Option<Bound<'_, PyAny>>,
+    style: PlaceholderStyle,
+) -> PyResult<(String, Vec<SqlParam>)> {
+    let Some(params) = params else {
+        return Ok((sql, Vec::new()));
+    };
 
-    let mut result = Vec::with_capacity(params.len());
+    if let Ok(named) = params.downcast::<pyo3::types::PyDict>() {
+        let (sql, names) = rewrite_named_params(&sql, style);
+        let values = named_param_values(named, &names)?;
+        return Ok((sql, convert_py_params(py, values)?));
+    }
 
-    for param in params {
-        let bound = param.bind(py);
+    Ok((sql, convert_py_params(py, params.extract()?)?))
+}
 
-        if bound.is_none() {
-            result.push(SqlParam::Null);
-            continue;
+/// Look up named parameter values in positional order.
+fn named_param_values(named: &Bound<'_, PyDict>, names: &[String]) -> PyResult<Vec<PyObject>> {
+    names
+        .iter()
+        .map(|name| {
+            named.get_item(name)?.map(Bound::unbind).ok_or_else(|| {
+                pyo3::exceptions::PyKeyError::new_err(format!("Missing named parameter: {}", name))
+            })
+        })
+        .collect()
+}
+
+/// Coerce bound parameters to the PostgreSQL types named in `param_types`,
+/// one entry per positional parameter (None leaves it as inferred).
+///
+/// Only `bool` is supported: an int 0 or 1 becomes a boolean, for code
+/// that stores flags as integers.
+fn apply_param_types(params: &mut [SqlParam], param_types: &[Option<String>]) -> PyResult<()> {
+    if param_types.len() != params.len() {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "param_types has {} entries but the query has {} parameters",
+            param_types.len(),
+            params.len()
+        )));
+    }
+    for (position, (param, hint)) in params.iter_mut().zip(param_types).enumerate() {
+        let Some(hint) = hint else { continue };
+        match hint.to_ascii_lowercase().as_str() {
+            "bool" | "boolean" => match *param {
+                SqlParam::Int(i @ (0 | 1)) => *param = SqlParam::Bool(i == 1),
+                SqlParam::Int(i) => {
+                    return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                        "parameter {}: cannot bind {} as bool, only 0 or 1",
+                        position + 1,
+                        i
+                    )))
+                }
+                _ => {}
+            },
+            _ => {
+                return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                    "parameter {}: unsupported param_types entry {:?}",
+                    position + 1,
+                    hint
+                )))
+            }
         }
+    }
+    Ok(())
+}
+
+/// Python type dispatch decision for a non-None parameter value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ParamKind {
+    Bool,
+    Int,
+    Float,
+    String,
+    Bytes,
+    Json,
+    Other,
+}
+
+impl ParamKind {
+    /// Classify a value by type-dispatch.
+    ///
+    /// This uses direct Python type object comparison instead of sequential extract() attempts,
+    /// which is significantly faster (single type check vs up to 6 extract attempts).
+    fn of(bound: &Bound<'_, PyAny>) -> Self {
+        use pyo3::types::{PyBool, PyBytes, PyDict, PyFloat, PyInt, PyList, PyString};
 
-        // Get the type once for fast dispatch
         // Note: PyBool must be checked before PyInt because bool is a subclass of int in Python
         if bound.is_instance_of::<PyBool>() {
-            // Use extract for bool since we need the actual value
-            result.push(SqlParam::Bool(bound.extract()?));
+            ParamKind::Bool
         } else if bound.is_instance_of::<PyInt>() {
-            result.push(SqlParam::Int(bound.extract()?));
+            ParamKind::Int
         } else if bound.is_instance_of::<PyFloat>() {
-            result.push(SqlParam::Float(bound.extract()?));
+            ParamKind::Float
         } else if bound.is_instance_of::<PyString>() {
-            result.push(SqlParam::String(bound.extract()?));
+            ParamKind::String
         } else if bound.is_instance_of::<PyBytes>() {
-            result.push(SqlParam::Bytes(bound.extract()?));
+            ParamKind::Bytes
         } else if bound.is_instance_of::<PyDict>() || bound.is_instance_of::<PyList>() {
-            // Convert Python dict/list to JSON string via serde_json::Value
-            // Two steps: pythonize (Python → Value) then to_vec (Value → bytes → String)
-            // Using to_vec is faster than to_string as it skips UTF-8 validation
-            let json_value: serde_json::Value = pythonize::depythonize(bound).map_err(|e| {
-                pyo3::exceptions::PyValueError::new_err(format!(
-                    "Failed to serialize to JSON: {}",
-                    e
-                ))
-            })?;
-            // Use to_vec for speed, then unsafe convert to String (JSON is always valid UTF-8)
-            let json_bytes = serde_json::to_vec(&json_value).map_err(|e| {
-                pyo3::exceptions::PyValueError::new_err(format!("Failed to serialize JSON: {}", e))
-            })?;
-            // SAFETY: serde_json always produces valid UTF-8
-            let json_string = unsafe { String::from_utf8_unchecked(json_bytes) };
-            result.push(SqlParam::Json(json_string));
+            ParamKind::Json
         } else {
+            ParamKind::Other
+        }
+    }
+}
+
+fn naive_date(date: &Bound<'_, PyDate>) -> NaiveDate {
+    NaiveDate::from_ymd_opt(
+        date.get_year(),
+        date.get_month() as u32,
+        date.get_day() as u32,
+    )
+    .expect("datetime.date is always a valid date")
+}
+
+/// `utcoffset()` of an aware datetime or time in seconds east, or None when
+/// it is naive.
+fn utc_offset_secs(value: &Bound<'_, PyAny>) -> PyResult<Option<i32>> {
+    let offset = value.call_method0(pyo3::intern!(value.py(), "utcoffset"))?;
+    if offset.is_none() {
+        return Ok(None);
+    }
+    let offset = offset.downcast::<PyDelta>()?;
+    Ok(Some(offset.get_days() * 86_400 + offset.get_seconds()))
+}
+
+/// Bind a datetime, normalizing an aware one to UTC.
+fn datetime_param(dt: &Bound<'_, PyDateTime>) -> PyResult<SqlParam> {
+    let naive = naive_date(dt.downcast::<PyDate>()?)
+        .and_hms_micro_opt(
+            dt.get_hour() as u32,
+            dt.get_minute() as u32,
+            dt.get_second() as u32,
+            dt.get_microsecond(),
+        )
+        .expect("datetime.datetime is always a valid datetime");
+    let micros = datetime_to_micros(naive);
+    Ok(match utc_offset_secs(dt.as_any())? {
+        Some(offset) => SqlParam::DateTime {
+            micros: micros - offset as i64 * 1_000_000,
+            utc: true,
+        },
+        None => SqlParam::DateTime { micros, utc: false },
+    })
+}
+
+/// Bind a time, keeping an aware one's UTC offset.
+fn time_param(time: &Bound<'_, PyTime>) -> PyResult<SqlParam> {
+    let seconds =
+        time.get_hour() as i64 * 3600 + time.get_minute() as i64 * 60 + time.get_second() as i64;
+    Ok(SqlParam::Time {
+        micros: seconds * 1_000_000 + time.get_microsecond() as i64,
+        tz_offset_secs: utc_offset_secs(time.as_any())?,
+    })
+}
+
+/// Cached `decimal.Decimal`, for binding decimals as NUMERIC.
+static DECIMAL_TYPE: pyo3::sync::GILOnceCell<Py<pyo3::types::PyType>> =
+    pyo3::sync::GILOnceCell::new();
+
+/// Cached `uuid.UUID`, for binding UUIDs as UUID.
+static UUID_TYPE: pyo3::sync::GILOnceCell<Py<pyo3::types::PyType>> = pyo3::sync::GILOnceCell::new();
+
+/// Cached `enum.Enum`, for binding enum members by value.
+static ENUM_TYPE: pyo3::sync::GILOnceCell<Py<pyo3::types::PyType>> = pyo3::sync::GILOnceCell::new();
+
+/// Convert one non-None Python value to a SQL parameter of the given kind.
+fn convert_py_param(bound: &Bound<'_, PyAny>, kind: ParamKind) -> PyResult<SqlParam> {
+    Ok(match kind {
+        // Use extract for bool since we need the actual value
+        ParamKind::Bool => SqlParam::Bool(bound.extract()?),
+        // Python ints are unbounded: anything outside i64 is bound as NUMERIC
+        ParamKind::Int => match bound.extract() {
+            Ok(i) => SqlParam::Int(i),
+            Err(e) if e.is_instance_of::<pyo3::exceptions::PyOverflowError>(bound.py()) => {
+                SqlParam::Numeric(bound.str()?.to_string())
+            }
+            Err(e) => return Err(e),
+        },
+        ParamKind::Float => SqlParam::Float(bound.extract()?),
+        ParamKind::String => SqlParam::String(bound.extract()?),
+        ParamKind::Bytes => SqlParam::Bytes(bound.extract()?),
+        ParamKind::Json => match list_array_param(bound)? {
+            Some(array) => array,
+            None => SqlParam::Json(py_to_json_string(bound)?),
+        },
+        ParamKind::Other => {
+            // ormkit.Int4(5) and friends carry their own binding
+            if let Some(param) = typed_param(bound) {
+                return Ok(param);
+            }
+            // timedelta normalizes to days plus 0 <= seconds < 86400, so only
+            // the day count can be negative
+            if let Ok(delta) = bound.downcast::<PyDelta>() {
+                return Ok(SqlParam::Interval {
+                    days: delta.get_days(),
+                    microseconds: delta.get_seconds() as i64 * 1_000_000
+                        + delta.get_microseconds() as i64,
+                });
+            }
+            // datetime is a date subclass, so it is checked first
+            if let Ok(dt) = bound.downcast::<PyDateTime>() {
+                return datetime_param(dt);
+            }
+            if let Ok(date) = bound.downcast::<PyDate>() {
+                return Ok(SqlParam::Date(date_to_days(naive_date(date))));
+            }
+            if let Ok(time) = bound.downcast::<PyTime>() {
+                return time_param(time);
+            }
+            // Decimal binds as NUMERIC so its precision and scale survive
+            let py = bound.py();
+            if bound.is_instance(DECIMAL_TYPE.import(py, "decimal", "Decimal")?)? {
+                // Fixed-point text: str() may use exponent notation ("1E+3")
+                let text = bound.call_method1(pyo3::intern!(py, "__format__"), ("f",))?;
+                return Ok(SqlParam::Numeric(text.to_string()));
+            }
+            if bound.is_instance(UUID_TYPE.import(py, "uuid", "UUID")?)? {
+                let bytes = bound.getattr(pyo3::intern!(py, "bytes"))?;
+                return Ok(SqlParam::Uuid(bytes.extract()?));
+            }
+            // Plain enum members bind as their value (int/str-mixin enums are
+            // already ints/strings); the value may itself be any bindable type
+            if bound.is_instance(ENUM_TYPE.import(py, "enum", "Enum")?)? {
+                let value = bound.getattr(pyo3::intern!(py, "value"))?;
+                if value.is_none() {
+                    return Ok(SqlParam::Null);
+                }
+                return convert_py_param(&value, ParamKind::of(&value));
+            }
             // Fallback: convert to string representation
-            let s = bound.str()?.to_string();
-            result.push(SqlParam::String(s));
+            SqlParam::String(bound.str()?.to_string())
+        }
+    })
+}
+
+/// Bind a list as an array when every non-None element is an int (in the
+/// i64 range), a str or a `uuid.UUID`, all of one kind. Anything else -
+/// empty lists, nesting, mixed or other element types - returns None and
+/// is bound as JSON.
+fn list_array_param(bound: &Bound<'_, PyAny>) -> PyResult<Option<SqlParam>> {
+    let Ok(list) = bound.downcast::<pyo3::types::PyList>() else {
+        return Ok(None);
+    };
+    let py = bound.py();
+    let uuid_type = UUID_TYPE.import(py, "uuid", "UUID")?;
+    let mut items = Vec::with_capacity(list.len());
+    let mut element: Option<std::mem::Discriminant<SqlParam>> = None;
+    for item in list.iter() {
+        let param = if item.is_none() {
+            items.push(SqlParam::Null);
+            continue;
+        } else if item.is_instance_of::<pyo3::types::PyBool>() {
+            return Ok(None);
+        } else if item.is_instance_of::<pyo3::types::PyInt>() {
+            match item.extract() {
+                Ok(i) => SqlParam::Int(i),
+                Err(_) => return Ok(None),
+            }
+        } else if item.is_instance_of::<pyo3::types::PyString>() {
+            SqlParam::String(item.extract()?)
+        } else if item.is_instance(uuid_type)? {
+            SqlParam::Uuid(item.getattr(pyo3::intern!(py, "bytes"))?.extract()?)
+        } else {
+            return Ok(None);
+        };
+        let kind = std::mem::discriminant(&param);
+        if *element.get_or_insert(kind) != kind {
+            return Ok(None);
+        }
+        items.push(param);
+    }
+    Ok(element.map(|_| SqlParam::Array(items)))
+}
+
+/// Serialize a Python value (dict, list, scalar) to a JSON string.
+pub(crate) fn py_to_json_string(bound: &Bound<'_, PyAny>) -> PyResult<String> {
+    // Convert Python dict/list to JSON string via serde_json::Value
+    // Two steps: Python → Value then to_vec (Value → bytes → String)
+    // Using to_vec is faster than to_string as it skips UTF-8 validation
+    let json_value = py_to_json(bound).map_err(JsonParamError::into_py_err)?;
+    // Use to_vec for speed, then unsafe convert to String (JSON is always valid UTF-8)
+    let json_bytes = serde_json::to_vec(&json_value).map_err(|e| {
+        pyo3::exceptions::PyValueError::new_err(format!("Failed to serialize JSON: {}", e))
+    })?;
+    // SAFETY: serde_json always produces valid UTF-8
+    Ok(unsafe { String::from_utf8_unchecked(json_bytes) })
+}
+
+/// Why a Python value could not be converted to JSON, and where.
+struct JsonParamError {
+    /// Path segments (`.key` or `[index]`) from the failing value outwards
+    path: Vec<String>,
+    message: String,
+}
+
+impl JsonParamError {
+    fn new(message: String) -> Self {
+        Self {
+            path: Vec::new(),
+            message,
+        }
+    }
+
+    /// Record the key or index the error occurred under.
+    fn at(mut self, segment: String) -> Self {
+        self.path.push(segment);
+        self
+    }
+
+    fn into_py_err(self) -> PyErr {
+        let path: String = self.path.iter().rev().map(String::as_str).collect();
+        pyo3::exceptions::PyValueError::new_err(format!(
+            "Failed to serialize to JSON at ${}: {}",
+            path, self.message
+        ))
+    }
+}
+
+/// Convert a Python value to JSON.
+///
+/// None becomes `null` at any depth. NaN and infinite floats have no JSON
+/// representation and are rejected rather than silently turned into `null`.
+fn py_to_json(bound: &Bound<'_, PyAny>) -> std::result::Result<serde_json::Value, JsonParamError> {
+    use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple};
+    use serde_json::Value;
+
+    let py_err = |e: PyErr| JsonParamError::new(e.to_string());
+
+    if bound.is_none() {
+        Ok(Value::Null)
+    } else if let Ok(b) = bound.downcast::<PyBool>() {
+        Ok(Value::Bool(b.is_true()))
+    } else if bound.is_instance_of::<PyInt>() {
+        if let Ok(i) = bound.extract::<i64>() {
+            Ok(Value::from(i))
+        } else if let Ok(u) = bound.extract::<u64>() {
+            Ok(Value::from(u))
+        } else {
+            Err(JsonParamError::new(format!(
+                "integer {} is out of range",
+                bound.str().map_err(py_err)?
+            )))
+        }
+    } else if let Ok(f) = bound.downcast::<PyFloat>() {
+        serde_json::Number::from_f64(f.value())
+            .map(Value::Number)
+            .ok_or_else(|| {
+                JsonParamError::new(format!(
+                    "{} is not valid JSON (NaN and Infinity are not allowed)",
+                    f.value()
+                ))
+            })
+    } else if let Ok(s) = bound.downcast::<PyString>() {
+        Ok(Value::String(s.to_str().map_err(py_err)?.to_string()))
+    } else if let Ok(dict) = bound.downcast::<PyDict>() {
+        let mut map = serde_json::Map::with_capacity(dict.len());
+        for (key, value) in dict.iter() {
+            let key = match key.downcast::<PyString>() {
+                Ok(key) => key.to_str().map_err(py_err)?.to_string(),
+                Err(_) => {
+                    return Err(JsonParamError::new(format!(
+                        "dict keys must be str, got {}",
+                        key.get_type().name().map_err(py_err)?
+                    )))
+                }
+            };
+            let value = py_to_json(&value).map_err(|e| e.at(format!(".{}", key)))?;
+            map.insert(key, value);
         }
+        Ok(Value::Object(map))
+    } else if bound.is_instance_of::<PyList>() || bound.is_instance_of::<PyTuple>() {
+        bound
+            .try_iter()
+            .map_err(py_err)?
+            .enumerate()
+            .map(|(i, item)| {
+                let item = item.map_err(py_err)?;
+                py_to_json(&item).map_err(|e| e.at(format!("[{}]", i)))
+            })
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map(Value::Array)
+    } else {
+        Err(JsonParamError::new(format!(
+            "object of type {} is not JSON serializable",
+            bound.get_type().name().map_err(py_err)?
+        )))
     }
+}
+
+/// Convert Python objects to SQL parameters using type-dispatch.
+fn convert_py_params(py: Python<'_>, params: Vec<PyObject>) -> PyResult<Vec<SqlParam>> {
+    params
+        .iter()
+        .map(|param| {
+            let bound = param.bind(py);
+            if bound.is_none() {
+                Ok(SqlParam::Null)
+            } else {
+                convert_py_param(bound, ParamKind::of(bound))
+            }
+        })
+        .collect()
+}
 
-    Ok(result)
+/// Convert a batch of parameter rows, reusing the first row's type dispatch.
+///
+/// Bulk inserts almost always bind the same Python types in every row, so
+/// each position remembers the first row's exact type and its `ParamKind`.
+/// Later values of that exact type skip the `is_instance_of` chain with a
+/// single pointer comparison; None and any other type are dispatched per
+/// element as usual.
+fn convert_py_param_batch(
+    py: Python<'_>,
+    rows: Vec<Vec<PyObject>>,
+) -> PyResult<Vec<Vec<SqlParam>>> {
+    let Some(first) = rows.first() else {
+        return Ok(Vec::new());
+    };
+
+    // Type pointers stay valid: the first row's values keep their types alive
+    let dispatch: Vec<Option<(*mut pyo3::ffi::PyTypeObject, ParamKind)>> = first
+        .iter()
+        .map(|param| {
+            let bound = param.bind(py);
+            (!bound.is_none()).then(|| (bound.get_type_ptr(), ParamKind::of(bound)))
+        })
+        .collect();
+
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .enumerate()
+                .map(|(i, param)| {
+                    let bound = param.bind(py);
+                    if bound.is_none() {
+                        return Ok(SqlParam::Null);
+                    }
+                    let kind = match dispatch.get(i) {
+                        Some(Some((ty, kind))) if *ty == bound.get_type_ptr() => *kind,
+                        _ => ParamKind::of(bound),
+                    };
+                    convert_py_param(bound, kind)
+                })
+                .collect()
+        })
+        .collect()
 }
@@ -5,6 +5,8 @@
 //! - Column information
 //! - Index information
 //! - Constraint information
+//! - Enum type definitions
+//! - Table and index storage sizes
 
 use pyo3::prelude::*;
 use serde::{Deserialize, Serialize};
@@ -57,6 +59,62 @@ impl IndexInfo {
     }
 }
 
+/// One column of an index, from SQLite's `PRAGMA index_xinfo`.
+#[pyclass]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IndexColumn {
+    #[pyo3(get)]
+    pub name: Option<String>, // None for an expression
+    #[pyo3(get)]
+    pub descending: bool,
+    #[pyo3(get)]
+    pub collation: String,
+}
+
+#[pymethods]
+impl IndexColumn {
+    fn __repr__(&self) -> String {
+        format!(
+            "IndexColumn(name={:?}, descending={}, collation='{}')",
+            self.name, self.descending, self.collation
+        )
+    }
+}
+
+/// Detailed information about a SQLite index, for query tuning.
+#[pyclass]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct IndexStats {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub columns: Vec<IndexColumn>, // Key columns, in index order
+    #[pyo3(get)]
+    pub unique: bool,
+    #[pyo3(get)]
+    pub partial: bool,
+    #[pyo3(get)]
+    pub origin: String, // "c" (CREATE INDEX), "u" (UNIQUE) or "pk" (PRIMARY KEY)
+    #[pyo3(get)]
+    pub auto_created: bool, // Created by SQLite for a UNIQUE or PRIMARY KEY constraint
+    #[pyo3(get)]
+    pub predicate: Option<String>, // WHERE clause of a partial index
+}
+
+#[pymethods]
+impl IndexStats {
+    fn __repr__(&self) -> String {
+        format!(
+            "IndexStats(name='{}', columns={}, unique={}, partial={}, predicate={:?})",
+            self.name,
+            self.columns.len(),
+            self.unique,
+            self.partial,
+            self.predicate
+        )
+    }
+}
+
 /// Information about a database constraint.
 #[pyclass]
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -109,10 +167,141 @@ impl TableInfo {
     }
 }
 
+/// A user-defined enum type and its labels.
+#[pyclass]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EnumInfo {
+    #[pyo3(get)]
+    pub name: String,
+    #[pyo3(get)]
+    pub labels: Vec<String>, // In sort order, as `ALTER TYPE ... ADD VALUE` places them
+}
+
+#[pymethods]
+impl EnumInfo {
+    fn __repr__(&self) -> String {
+        format!("EnumInfo(name='{}', labels={:?})", self.name, self.labels)
+    }
+}
+
+/// Storage used by a table and its indexes, in bytes.
+#[pyclass]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct TableSize {
+    #[pyo3(get)]
+    pub table: String,
+    #[pyo3(get)]
+    pub total_bytes: i64, // Table, indexes and TOAST together
+    #[pyo3(get)]
+    pub table_bytes: i64, // The table's main data alone
+    #[pyo3(get)]
+    pub index_bytes: i64,
+    #[pyo3(get)]
+    pub toast_bytes: i64, // Out-of-line storage for large values; 0 on SQLite
+}
+
+#[pymethods]
+impl TableSize {
+    fn __repr__(&self) -> String {
+        format!(
+            "TableSize(table='{}', total_bytes={}, index_bytes={})",
+            self.table, self.total_bytes, self.index_bytes
+        )
+    }
+}
+
+/// A row whose foreign key has no matching parent row.
+#[pyclass]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ForeignKeyViolation {
+    #[pyo3(get)]
+    pub table: String,
+    #[pyo3(get)]
+    pub rowid: Option<i64>, // None for WITHOUT ROWID tables
+    #[pyo3(get)]
+    pub parent: String,
+    #[pyo3(get)]
+    pub fk_index: i64, // Matches the id from PRAGMA foreign_key_list
+}
+
+#[pymethods]
+impl ForeignKeyViolation {
+    fn __repr__(&self) -> String {
+        format!(
+            "ForeignKeyViolation(table='{}', rowid={:?}, parent='{}', fk_index={})",
+            self.table, self.rowid, self.parent, self.fk_index
+        )
+    }
+}
+
+/// A session waiting on a lock, and one session holding it up.
+#[pyclass]
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct BlockedQuery {
+    #[pyo3(get)]
+    pub blocked_pid: i32,
+    #[pyo3(get)]
+    pub blocked_query: String,
+    #[pyo3(get)]
+    pub blocking_pid: i32,
+    #[pyo3(get)]
+    pub blocking_query: String, // Last statement, if the session is idle in transaction
+    #[pyo3(get)]
+    pub blocking_state: String, // e.g. "active", "idle in transaction"
+    #[pyo3(get)]
+    pub lock_type: Option<String>, // pg_locks.locktype of the awaited lock
+    #[pyo3(get)]
+    pub lock_mode: Option<String>,
+    #[pyo3(get)]
+    pub relation: Option<String>, // None for non-relation locks
+    #[pyo3(get)]
+    pub wait_seconds: f64, // Time since the blocked query started
+}
+
+#[pymethods]
+impl BlockedQuery {
+    fn __repr__(&self) -> String {
+        format!(
+            "BlockedQuery(blocked_pid={}, blocking_pid={}, lock_mode={:?}, relation={:?})",
+            self.blocked_pid, self.blocking_pid, self.lock_mode, self.relation
+        )
+    }
+}
+
 // ============================================================================
 // PostgreSQL Schema Introspection
 // ============================================================================
 
+/// Query resolving a relation OID (such as a decoded `regclass`) to its name
+pub const PG_REGCLASS_NAME_QUERY: &str = "SELECT relname::text FROM pg_class WHERE oid = $1::oid";
+
+/// Query pairing each session waiting on a lock with the sessions blocking it
+///
+/// `pg_blocking_pids` resolves the lock queue, including waits behind
+/// another waiter; the awaited lock itself comes from `pg_locks`.
+pub const PG_BLOCKING_QUERIES_QUERY: &str = r#"
+SELECT
+    blocked.pid,
+    blocked.query,
+    blocking.pid,
+    blocking.query,
+    blocking.state,
+    awaited.locktype,
+    awaited.mode,
+    awaited.relation::regclass::text,
+    EXTRACT(EPOCH FROM now() - blocked.query_start)::float8
+FROM pg_stat_activity AS blocked
+CROSS JOIN LATERAL unnest(pg_blocking_pids(blocked.pid)) AS holder(pid)
+JOIN pg_stat_activity AS blocking ON blocking.pid = holder.pid
+LEFT JOIN LATERAL (
+    SELECT locktype, mode, relation
+    FROM pg_locks
+    WHERE pid = blocked.pid AND NOT granted
+    LIMIT 1
+) AS awaited ON true
+ORDER BY blocked.pid, blocking.pid
+"#;
+
 /// Query to get all table names in PostgreSQL
 pub const PG_TABLES_QUERY: &str = r#"
 SELECT table_name
@@ -182,6 +371,36 @@ GROUP BY tc.constraint_name, tc.constraint_type, ccu.table_name, ccu.column_name
 ORDER BY tc.constraint_name
 "#;
 
+/// Query to get the enum types in PostgreSQL with their ordered labels
+pub const PG_ENUMS_QUERY: &str = r#"
+SELECT
+    t.typname::text as name,
+    array_agg(e.enumlabel::text ORDER BY e.enumsortorder) as labels
+FROM pg_type t
+JOIN pg_enum e ON e.enumtypid = t.oid
+JOIN pg_namespace n ON n.oid = t.typnamespace
+WHERE n.nspname = 'public'
+GROUP BY t.typname
+ORDER BY t.typname
+"#;
+
+/// Query to get the storage size of each PostgreSQL table in schema `$1`,
+/// largest first
+pub const PG_TABLE_SIZES_QUERY: &str = r#"
+SELECT
+    c.relname::text as name,
+    pg_total_relation_size(c.oid) as total_bytes,
+    pg_relation_size(c.oid) as table_bytes,
+    pg_indexes_size(c.oid) as index_bytes,
+    CASE WHEN c.reltoastrelid = 0 THEN 0
+         ELSE pg_total_relation_size(c.reltoastrelid) END as toast_bytes
+FROM pg_class c
+JOIN pg_namespace n ON n.oid = c.relnamespace
+WHERE n.nspname = $1
+  AND c.relkind IN ('r', 'p')
+ORDER BY total_bytes DESC, name
+"#;
+
 // ============================================================================
 // SQLite Schema Introspection
 // ============================================================================
@@ -195,6 +414,24 @@ WHERE type = 'table'
 ORDER BY name
 "#;
 
+/// Query to get the storage size of each SQLite table, largest first
+///
+/// Sums the pages of each table's b-tree and of its indexes' from the
+/// `dbstat` virtual table, which the bundled SQLite is built with.
+pub const SQLITE_TABLE_SIZES_QUERY: &str = r#"
+SELECT
+    m.tbl_name,
+    SUM(s.pgsize) as total_bytes,
+    SUM(CASE WHEN m.type = 'index' THEN s.pgsize ELSE 0 END) as index_bytes
+FROM dbstat s
+JOIN sqlite_master m ON m.name = s.name
+WHERE s.schema = 'main'
+  AND m.type IN ('table', 'index')
+  AND m.tbl_name NOT LIKE 'sqlite\_%' ESCAPE '\'
+GROUP BY m.tbl_name
+ORDER BY total_bytes DESC, m.tbl_name
+"#;
+
 /// SQLite PRAGMA for table info - returns columns with type, notnull, pk, dflt_value
 pub fn sqlite_table_info_pragma(table: &str) -> String {
     format!("PRAGMA table_info('{}')", table)
@@ -210,7 +447,99 @@ pub fn sqlite_index_info_pragma(index: &str) -> String {
     format!("PRAGMA index_info('{}')", index)
 }
 
+/// SQLite PRAGMA for extended index info, including sort order and collation
+pub fn sqlite_index_xinfo_pragma(index: &str) -> String {
+    format!("PRAGMA index_xinfo({})", crate::sql::quote_literal(index))
+}
+
+/// Query for the `CREATE INDEX` statement of an index (NULL if auto-created)
+pub const SQLITE_INDEX_SQL_QUERY: &str =
+    "SELECT sql FROM sqlite_master WHERE type = 'index' AND name = ?1";
+
+/// Extract the `WHERE` clause of a partial index's `CREATE INDEX` statement.
+///
+/// The predicate is whatever follows the column list's closing parenthesis
+/// and the `WHERE` keyword, without a trailing semicolon.
+pub fn sqlite_partial_index_predicate(create_sql: &str) -> Option<String> {
+    let bytes = create_sql.as_bytes();
+    let mut depth = 0;
+    let mut i = 0;
+    let mut list_end = None;
+
+    while i < bytes.len() {
+        match bytes[i] {
+            // Quoted identifier or string: skip to the closing quote
+            quote @ (b'\'' | b'"' | b'`') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    i += 1;
+                }
+            }
+            b'[' => {
+                while i < bytes.len() && bytes[i] != b']' {
+                    i += 1;
+                }
+            }
+            b'(' => depth += 1,
+            b')' => {
+                depth -= 1;
+                if depth == 0 {
+                    list_end = Some(i + 1);
+                    break;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    let rest = create_sql[list_end?..].trim_start();
+    if !rest.get(..5)?.eq_ignore_ascii_case("where") {
+        return None;
+    }
+    let predicate = rest[5..].trim().trim_end_matches(';').trim_end();
+    (!predicate.is_empty()).then(|| predicate.to_string())
+}
+
 /// SQLite PRAGMA for foreign key list
 pub fn sqlite_foreign_key_list_pragma(table: &str) -> String {
     format!("PRAGMA foreign_key_list('{}')", table)
 }
+
+/// SQLite PRAGMA listing rows that violate foreign keys, in one table or all
+pub fn sqlite_foreign_key_check_pragma(table: Option<&str>) -> String {
+    match table {
+        Some(table) => format!(
+            "PRAGMA foreign_key_check({})",
+            crate::sql::quote_literal(table)
+        ),
+        None => "PRAGMA foreign_key_check".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_partial_index_predicate() {
+        assert_eq!(
+            sqlite_partial_index_predicate(
+                "CREATE INDEX idx ON t (a, lower(b)) WHERE deleted_at IS NULL"
+            )
+            .as_deref(),
+            Some("deleted_at IS NULL")
+        );
+        assert_eq!(
+            sqlite_partial_index_predicate(
+                "CREATE UNIQUE INDEX \"i(x\" ON \"t)\" (\"a)\")\n  where (status = ')') ;"
+            )
+            .as_deref(),
+            Some("(status = ')')")
+        );
+        assert_eq!(
+            sqlite_partial_index_predicate("CREATE INDEX idx ON t (a)"),
+            None
+        );
+    }
+}
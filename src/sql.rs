@@ -0,0 +1,158 @@
+//! SQL quoting helpers for building dynamic SQL.
+//!
+//! Use these for identifiers and literals that can't be bound as
+//! parameters, such as table names in DDL. Values should still be passed as
+//! query parameters wherever possible.
+
+use pyo3::prelude::*;
+
+/// SQL dialect to quote for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dialect {
+    Postgres,
+    Sqlite,
+}
+
+impl Dialect {
+    /// Parse a `dialect` argument: `"postgres"` or `"sqlite"`.
+    pub fn from_name(name: &str) -> PyResult<Self> {
+        match name {
+            "postgres" | "postgresql" => Ok(Dialect::Postgres),
+            "sqlite" => Ok(Dialect::Sqlite),
+            _ => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "dialect must be \"postgres\" or \"sqlite\", got {:?}",
+                name
+            ))),
+        }
+    }
+}
+
+/// Quote a name as an SQL identifier.
+///
+/// Both PostgreSQL and SQLite use standard double-quoted identifiers, with
+/// an embedded double quote written twice.
+pub fn quote_ident(name: &str, dialect: Dialect) -> String {
+    match dialect {
+        Dialect::Postgres | Dialect::Sqlite => format!("\"{}\"", name.replace('"', "\"\"")),
+    }
+}
+
+/// Quote a possibly schema-qualified name such as `public.items`, quoting
+/// each dot-separated part as an identifier.
+pub fn quote_qualified(name: &str, dialect: Dialect) -> String {
+    name.split('.')
+        .map(|part| quote_ident(part, dialect))
+        .collect::<Vec<_>>()
+        .join(".")
+}
+
+/// Quote a value as an SQL string literal, writing embedded single quotes
+/// twice.
+///
+/// Backslashes are not escapes in standard SQL strings, which PostgreSQL
+/// uses unless `standard_conforming_strings` is turned off.
+pub fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Compose a PostgreSQL `VACUUM [FULL] [ANALYZE] [table]` command.
+pub fn pg_vacuum_sql(table: Option<&str>, full: bool, analyze: bool) -> String {
+    let mut sql = String::from("VACUUM");
+    if full {
+        sql.push_str(" FULL");
+    }
+    if analyze {
+        sql.push_str(" ANALYZE");
+    }
+    if let Some(table) = table {
+        sql.push(' ');
+        sql.push_str(&quote_ident(table, Dialect::Postgres));
+    }
+    sql
+}
+
+/// Neither database accepts NUL characters in identifiers or text.
+fn reject_nul(value: &str, what: &str) -> PyResult<()> {
+    if value.contains('\0') {
+        return Err(pyo3::exceptions::PyValueError::new_err(format!(
+            "{} cannot contain NUL characters",
+            what
+        )));
+    }
+    Ok(())
+}
+
+/// Quote a name as an SQL identifier for the given dialect
+#[pyfunction(name = "quote_ident")]
+#[pyo3(signature = (name, dialect="postgres"))]
+pub fn py_quote_ident(name: &str, dialect: &str) -> PyResult<String> {
+    let dialect = Dialect::from_name(dialect)?;
+    if name.is_empty() {
+        return Err(pyo3::exceptions::PyValueError::new_err(
+            "identifier cannot be empty",
+        ));
+    }
+    reject_nul(name, "identifier")?;
+    Ok(quote_ident(name, dialect))
+}
+
+/// Quote a value as an SQL string literal
+#[pyfunction(name = "quote_literal")]
+pub fn py_quote_literal(value: &str) -> PyResult<String> {
+    reject_nul(value, "string literal")?;
+    Ok(quote_literal(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quote_ident() {
+        for dialect in [Dialect::Postgres, Dialect::Sqlite] {
+            assert_eq!(quote_ident("users", dialect), "\"users\"");
+            assert_eq!(quote_ident("foo\"bar", dialect), "\"foo\"\"bar\"");
+            assert_eq!(
+                quote_ident("x\"; DROP TABLE users; --", dialect),
+                "\"x\"\"; DROP TABLE users; --\""
+            );
+        }
+    }
+
+    #[test]
+    fn test_quote_qualified() {
+        assert_eq!(
+            quote_qualified("public.items", Dialect::Postgres),
+            "\"public\".\"items\""
+        );
+        assert_eq!(quote_qualified("Items", Dialect::Sqlite), "\"Items\"");
+        assert_eq!(
+            quote_qualified("s.a\"b", Dialect::Postgres),
+            "\"s\".\"a\"\"b\""
+        );
+    }
+
+    #[test]
+    fn test_pg_vacuum_sql() {
+        assert_eq!(pg_vacuum_sql(None, false, false), "VACUUM");
+        assert_eq!(
+            pg_vacuum_sql(Some("users"), true, true),
+            "VACUUM FULL ANALYZE \"users\""
+        );
+        assert_eq!(
+            pg_vacuum_sql(Some("a\"b"), false, true),
+            "VACUUM ANALYZE \"a\"\"b\""
+        );
+    }
+
+    #[test]
+    fn test_quote_literal() {
+        assert_eq!(quote_literal("plain"), "'plain'");
+        assert_eq!(quote_literal("it's"), "'it''s'");
+        assert_eq!(quote_literal("a\\b"), "'a\\b'");
+        assert_eq!(
+            quote_literal("'; DROP TABLE users; --"),
+            "'''; DROP TABLE users; --'"
+        );
+    }
+}
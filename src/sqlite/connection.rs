@@ -1,9 +1,14 @@
 //! SQLite connection implementation.
 
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
 use tokio_rusqlite::Connection;
 
 use super::error::{SqliteError, SqliteResult};
 use super::types::SqliteValue;
+use crate::sql::{quote_ident, Dialect};
 
 /// Result of a query execution.
 #[derive(Debug)]
@@ -26,10 +31,82 @@ impl QueryResult {
     }
 }
 
+/// What an `INSERT ... ON CONFLICT` statement did.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpsertAction {
+    /// A new row was inserted
+    Insert,
+    /// An existing row was updated
+    Update,
+    /// Nothing changed (`DO NOTHING` hit a conflict)
+    Nothing,
+}
+
+impl UpsertAction {
+    /// Name used when surfacing the action to Python.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            UpsertAction::Insert => "insert",
+            UpsertAction::Update => "update",
+            UpsertAction::Nothing => "none",
+        }
+    }
+}
+
+/// Result of an upsert statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpsertResult {
+    /// Whether the row was inserted or updated
+    pub action: UpsertAction,
+    /// Rowid of the inserted row (`None` unless `action` is `Insert`)
+    pub rowid: Option<i64>,
+}
+
+/// Batches kept in flight between the connection thread and a `RowStream`
+const STREAM_BUFFER_BATCHES: usize = 2;
+
+/// Rows of a query read incrementally, in batches.
+///
+/// The statement runs on the connection's thread, which blocks once
+/// `STREAM_BUFFER_BATCHES` batches are waiting to be read, so at most that
+/// many batches are held in memory. Dropping the stream stops the statement
+/// at the next batch.
+pub struct RowStream {
+    /// Column names
+    pub columns: Vec<String>,
+    batches: tokio::sync::mpsc::Receiver<SqliteResult<Vec<Vec<SqliteValue>>>>,
+}
+
+impl RowStream {
+    /// Wait for the next batch of rows, or `None` once all rows are read.
+    pub async fn next_batch(&mut self) -> Option<SqliteResult<Vec<Vec<SqliteValue>>>> {
+        self.batches.recv().await
+    }
+
+    /// Number of batches read from the database but not yet consumed.
+    pub fn buffered(&self) -> usize {
+        self.batches.len()
+    }
+}
+
+/// Source of process-unique connection ids.
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(0);
+
 /// A SQLite connection.
+///
+/// Clones are handles to the same underlying connection.
+#[derive(Clone)]
 pub struct SqliteConnection {
+    /// Process-unique id
+    id: u64,
     conn: Connection,
     closed: bool,
+    /// Interrupts the statement running on the connection's thread
+    interrupt: Arc<rusqlite::InterruptHandle>,
+    /// Longest a statement may run before it is interrupted
+    statement_timeout: Option<Duration>,
+    /// Bind and read empty strings as NULL
+    empty_string_as_null: bool,
 }
 
 impl SqliteConnection {
@@ -63,101 +140,423 @@ impl SqliteConnection {
             .await?;
         }
 
+        let interrupt = conn.call(|c| Ok(c.get_interrupt_handle())).await?;
+
         Ok(Self {
+            id: NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed),
             conn,
             closed: false,
+            interrupt: Arc::new(interrupt),
+            statement_timeout: None,
+            empty_string_as_null: false,
         })
     }
 
+    /// Interrupt statements that run longer than `timeout`.
+    ///
+    /// An interrupted statement fails with `SqliteError::Timeout` and its
+    /// changes are rolled back.
+    pub fn set_statement_timeout(&mut self, timeout: Option<Duration>) {
+        self.statement_timeout = timeout;
+    }
+
+    /// Treat empty strings as NULL, as Oracle does.
+    ///
+    /// Empty-string parameters are bound as NULL, and empty text read back
+    /// by `query` and `query_stream` comes back as NULL.
+    pub fn set_empty_string_as_null(&mut self, enabled: bool) {
+        self.empty_string_as_null = enabled;
+    }
+
+    /// Take ownership of statement parameters, applying
+    /// `empty_string_as_null`.
+    fn bind_params(&self, params: impl Into<Vec<SqliteValue>>) -> Vec<SqliteValue> {
+        let params: Vec<SqliteValue> = params.into();
+        if !self.empty_string_as_null {
+            return params;
+        }
+        params
+            .into_iter()
+            .map(SqliteValue::empty_text_as_null)
+            .collect()
+    }
+
+    /// Run `function` on the connection's thread, interrupting it once the
+    /// statement timeout has elapsed.
+    async fn call<F, R>(&self, function: F) -> SqliteResult<R>
+    where
+        F: FnOnce(&mut rusqlite::Connection) -> tokio_rusqlite::Result<R> + Send + 'static,
+        R: Send + 'static,
+    {
+        let call = self.conn.call(function);
+        let Some(timeout) = self.statement_timeout else {
+            return call.await.map_err(SqliteError::from);
+        };
+
+        tokio::pin!(call);
+        tokio::select! {
+            result = &mut call => result.map_err(SqliteError::from),
+            _ = tokio::time::sleep(timeout) => {
+                self.interrupt.interrupt();
+                match call.await {
+                    Err(tokio_rusqlite::Error::Rusqlite(rusqlite::Error::SqliteFailure(e, _)))
+                        if e.code == rusqlite::ErrorCode::OperationInterrupted =>
+                    {
+                        Err(SqliteError::Timeout)
+                    }
+                    result => result.map_err(SqliteError::from),
+                }
+            }
+        }
+    }
+
     /// Execute a query and return results.
     /// Uses prepared statement caching for repeated queries.
-    pub async fn query(&self, sql: &str, params: &[SqliteValue]) -> SqliteResult<QueryResult> {
+    ///
+    /// Pass `params` as an owned `Vec` to move it to the connection thread;
+    /// a slice is copied first. Parameters are bound straight from it, with
+    /// no per-call vector of `&dyn ToSql`.
+    pub async fn query(
+        &self,
+        sql: &str,
+        params: impl Into<Vec<SqliteValue>>,
+    ) -> SqliteResult<QueryResult> {
         if self.closed {
             return Err(SqliteError::ConnectionClosed);
         }
 
         let sql = sql.to_string();
-        let params: Vec<SqliteValue> = params.to_vec();
+        let params = self.bind_params(params);
+        let empty_as_null = self.empty_string_as_null;
 
-        self.conn
-            .call(move |conn| {
-                // Use prepare_cached for O(1) lookup of repeated statements
-                let mut stmt = conn.prepare_cached(&sql)?;
-
-                // Get column names
-                let columns: Vec<String> =
-                    stmt.column_names().iter().map(|s| s.to_string()).collect();
-
-                // Bind parameters
-                let params_refs: Vec<&dyn rusqlite::ToSql> =
-                    params.iter().map(|p| p as &dyn rusqlite::ToSql).collect();
-
-                // Execute and collect rows
-                let mut rows_data = Vec::new();
-                let mut rows = stmt.query(params_refs.as_slice())?;
-
-                while let Some(row) = rows.next()? {
-                    let mut row_values = Vec::with_capacity(columns.len());
-                    for i in 0..columns.len() {
-                        let value = row.get_ref(i)?;
-                        row_values.push(SqliteValue::from_value_ref(value));
-                    }
-                    rows_data.push(row_values);
+        self.call(move |conn| {
+            // Use prepare_cached for O(1) lookup of repeated statements
+            let mut stmt = conn.prepare_cached(&sql)?;
+
+            // Get column names
+            let columns: Vec<String> = stmt.column_names().iter().map(|s| s.to_string()).collect();
+
+            // Execute and collect rows
+            let mut rows_data = Vec::new();
+            let mut rows = stmt.query(rusqlite::params_from_iter(&params))?;
+
+            while let Some(row) = rows.next()? {
+                let mut row_values = Vec::with_capacity(columns.len());
+                for i in 0..columns.len() {
+                    let value = SqliteValue::from_value_ref(row.get_ref(i)?);
+                    row_values.push(if empty_as_null {
+                        value.empty_text_as_null()
+                    } else {
+                        value
+                    });
                 }
+                rows_data.push(row_values);
+            }
+            drop(rows);
 
-                Ok(QueryResult {
-                    columns,
-                    rows: rows_data,
-                    rows_affected: 0,
-                })
+            // changes() still holds the previous write's count after a SELECT
+            let rows_affected = if stmt.readonly() { 0 } else { conn.changes() };
+
+            Ok(QueryResult {
+                columns,
+                rows: rows_data,
+                rows_affected,
             })
-            .await
-            .map_err(SqliteError::from)
+        })
+        .await
+    }
+
+    /// Run a query and read its rows in batches of `batch_size`.
+    ///
+    /// Unlike `query`, rows are handed over as they are read rather than
+    /// collected first, so memory stays bounded however many rows match. The
+    /// connection's thread is busy until the stream is finished or dropped.
+    /// The statement timeout does not apply, since a stream may be read
+    /// slowly on purpose.
+    pub async fn query_stream(
+        &self,
+        sql: &str,
+        params: impl Into<Vec<SqliteValue>>,
+        batch_size: usize,
+    ) -> SqliteResult<RowStream> {
+        if self.closed {
+            return Err(SqliteError::ConnectionClosed);
+        }
+
+        let sql = sql.to_string();
+        let params = self.bind_params(params);
+        let empty_as_null = self.empty_string_as_null;
+        let batch_size = batch_size.max(1);
+        let (columns_tx, columns_rx) = tokio::sync::oneshot::channel();
+        let (batch_tx, batches) = tokio::sync::mpsc::channel(STREAM_BUFFER_BATCHES);
+        let error_tx = batch_tx.clone();
+        let conn = self.conn.clone();
+
+        tokio::spawn(async move {
+            let result = conn
+                .call(move |conn| {
+                    let mut stmt = conn.prepare_cached(&sql)?;
+                    let columns: Vec<String> =
+                        stmt.column_names().iter().map(|s| s.to_string()).collect();
+                    let width = columns.len();
+                    if columns_tx.send(columns).is_err() {
+                        return Ok(());
+                    }
+
+                    let mut rows = stmt.query(rusqlite::params_from_iter(&params))?;
+                    let mut batch = Vec::with_capacity(batch_size);
+                    while let Some(row) = rows.next()? {
+                        let mut row_values = Vec::with_capacity(width);
+                        for i in 0..width {
+                            let value = SqliteValue::from_value_ref(row.get_ref(i)?);
+                            row_values.push(if empty_as_null {
+                                value.empty_text_as_null()
+                            } else {
+                                value
+                            });
+                        }
+                        batch.push(row_values);
+
+                        if batch.len() == batch_size {
+                            let full =
+                                std::mem::replace(&mut batch, Vec::with_capacity(batch_size));
+                            // Blocks while the reader is behind; fails once it's dropped
+                            if batch_tx.blocking_send(Ok(full)).is_err() {
+                                return Ok(());
+                            }
+                        }
+                    }
+
+                    if !batch.is_empty() {
+                        let _ = batch_tx.blocking_send(Ok(batch));
+                    }
+                    Ok(())
+                })
+                .await;
+
+            if let Err(e) = result {
+                let _ = error_tx.send(Err(e.into())).await;
+            }
+        });
+
+        match columns_rx.await {
+            Ok(columns) => Ok(RowStream { columns, batches }),
+            // The statement failed before it produced columns
+            Err(_) => {
+                let mut batches = batches;
+                Err(match batches.recv().await {
+                    Some(Err(e)) => e,
+                    _ => SqliteError::ConnectionClosed,
+                })
+            }
+        }
     }
 
     /// Execute a statement that doesn't return rows.
-    pub async fn execute(&self, sql: &str, params: &[SqliteValue]) -> SqliteResult<u64> {
+    pub async fn execute(
+        &self,
+        sql: &str,
+        params: impl Into<Vec<SqliteValue>>,
+    ) -> SqliteResult<u64> {
         if self.closed {
             return Err(SqliteError::ConnectionClosed);
         }
 
         let sql = sql.to_string();
-        let params: Vec<SqliteValue> = params.to_vec();
+        let params = self.bind_params(params);
 
-        self.conn
-            .call(move |conn| {
-                let params_refs: Vec<&dyn rusqlite::ToSql> =
-                    params.iter().map(|p| p as &dyn rusqlite::ToSql).collect();
+        self.call(move |conn| {
+            let rows_affected = conn.execute(&sql, rusqlite::params_from_iter(&params))?;
+            Ok(rows_affected as u64)
+        })
+        .await
+    }
+
+    /// Execute an `INSERT ... ON CONFLICT` statement and report what it did.
+    ///
+    /// SQLite only sets `last_insert_rowid()` when a row is actually
+    /// inserted, so it is reset to a sentinel first; if the statement
+    /// changed a row and the sentinel survived, the row was updated.
+    /// Inserts into a `WITHOUT ROWID` table never set it, so for those the
+    /// table's row count is compared instead, under a savepoint so no other
+    /// connection writes in between.
+    pub async fn execute_upsert(
+        &self,
+        sql: &str,
+        params: impl Into<Vec<SqliteValue>>,
+    ) -> SqliteResult<UpsertResult> {
+        if self.closed {
+            return Err(SqliteError::ConnectionClosed);
+        }
+
+        let sql = sql.to_string();
+        let params = self.bind_params(params);
+
+        self.call(move |conn| {
+            let count_sql = match upsert_target(&sql) {
+                Some((schema, table)) if is_without_rowid(conn, schema.as_deref(), &table)? => {
+                    Some(count_rows_sql(schema.as_deref(), &table))
+                }
+                _ => None,
+            };
+
+            let Some(count_sql) = count_sql else {
+                // SAFETY: the handle is valid for the life of `conn`, and
+                // this only sets the value last_insert_rowid() reports
+                unsafe {
+                    rusqlite::ffi::sqlite3_set_last_insert_rowid(conn.handle(), UNSET_ROWID);
+                }
+                let changes = conn.execute(&sql, rusqlite::params_from_iter(&params))?;
+                let rowid = conn.last_insert_rowid();
+                return Ok(match (changes, rowid) {
+                    (0, _) => UpsertResult {
+                        action: UpsertAction::Nothing,
+                        rowid: None,
+                    },
+                    (_, UNSET_ROWID) => UpsertResult {
+                        action: UpsertAction::Update,
+                        rowid: None,
+                    },
+                    (_, rowid) => UpsertResult {
+                        action: UpsertAction::Insert,
+                        rowid: Some(rowid),
+                    },
+                });
+            };
+
+            let count = |conn: &rusqlite::Connection| {
+                conn.query_row(&count_sql, [], |row| row.get::<_, i64>(0))
+            };
+            conn.execute_batch("SAVEPOINT ormkit_upsert")?;
+            let outcome = (|| {
+                let before = count(conn)?;
+                let changes = conn.execute(&sql, rusqlite::params_from_iter(&params))?;
+                Ok((changes, count(conn)? > before))
+            })();
+            let (changes, inserted) = match outcome {
+                Ok(outcome) => outcome,
+                Err(e) => {
+                    let _ = conn.execute_batch(
+                        "ROLLBACK TO SAVEPOINT ormkit_upsert; RELEASE SAVEPOINT ormkit_upsert",
+                    );
+                    return Err(tokio_rusqlite::Error::Rusqlite(e));
+                }
+            };
+            conn.execute_batch("RELEASE SAVEPOINT ormkit_upsert")?;
 
-                let rows_affected = conn.execute(&sql, params_refs.as_slice())?;
-                Ok(rows_affected as u64)
+            let action = match (changes, inserted) {
+                (0, _) => UpsertAction::Nothing,
+                (_, true) => UpsertAction::Insert,
+                (_, false) => UpsertAction::Update,
+            };
+            Ok(UpsertResult {
+                action,
+                rowid: None,
             })
-            .await
-            .map_err(SqliteError::from)
+        })
+        .await
+    }
+
+    /// Insert many rows with one prepared statement inside a single transaction.
+    ///
+    /// Either every row is inserted or none are.
+    pub async fn insert_rows(&self, sql: &str, rows: Vec<Vec<SqliteValue>>) -> SqliteResult<u64> {
+        self.execute_many(sql, rows).await
+    }
+
+    /// Execute a statement once per parameter set inside an immediate
+    /// transaction, reusing one cached prepared statement.
+    ///
+    /// Inside an explicit transaction a savepoint is used instead, so the
+    /// batch nests in it. Either every execution takes effect or none do.
+    /// Returns the total rows changed.
+    pub async fn execute_many(
+        &self,
+        sql: &str,
+        params_list: Vec<Vec<SqliteValue>>,
+    ) -> SqliteResult<u64> {
+        if self.closed {
+            return Err(SqliteError::ConnectionClosed);
+        }
+
+        let sql = sql.to_string();
+        let params_list: Vec<Vec<SqliteValue>> = params_list
+            .into_iter()
+            .map(|params| self.bind_params(params))
+            .collect();
+
+        self.call(move |conn| {
+            if conn.is_autocommit() {
+                let tx =
+                    conn.transaction_with_behavior(rusqlite::TransactionBehavior::Immediate)?;
+                let changed = execute_each(&tx, &sql, &params_list)?;
+                tx.commit()?;
+                Ok(changed)
+            } else {
+                let sp = conn.savepoint()?;
+                let changed = execute_each(&sp, &sql, &params_list)?;
+                sp.commit()?;
+                Ok(changed)
+            }
+        })
+        .await
     }
 
     /// Execute multiple statements (for DDL, etc.).
-    pub async fn execute_batch(&self, sql: &str) -> SqliteResult<()> {
+    ///
+    /// Returns the rows changed by the whole batch.
+    pub async fn execute_batch(&self, sql: &str) -> SqliteResult<u64> {
         if self.closed {
             return Err(SqliteError::ConnectionClosed);
         }
 
         let sql = sql.to_string();
 
+        self.call(move |conn| {
+            let before = conn.total_changes();
+            conn.execute_batch(&sql)?;
+            Ok(conn.total_changes() - before)
+        })
+        .await
+    }
+
+    /// Rows changed by the most recent INSERT, UPDATE or DELETE.
+    pub async fn changes(&self) -> SqliteResult<u64> {
+        if self.closed {
+            return Err(SqliteError::ConnectionClosed);
+        }
+
         self.conn
-            .call(move |conn| {
-                conn.execute_batch(&sql)?;
-                Ok(())
-            })
+            .call(|conn| Ok(conn.changes()))
+            .await
+            .map_err(SqliteError::from)
+    }
+
+    /// Rows changed by all INSERT, UPDATE and DELETE statements since the
+    /// connection was opened.
+    pub async fn total_changes(&self) -> SqliteResult<u64> {
+        if self.closed {
+            return Err(SqliteError::ConnectionClosed);
+        }
+
+        self.conn
+            .call(|conn| Ok(conn.total_changes()))
             .await
             .map_err(SqliteError::from)
     }
 
     /// Close the connection.
+    ///
+    /// Clones share the underlying connection, so their calls fail from now on.
     pub async fn close(mut self) -> SqliteResult<()> {
         self.closed = true;
-        // tokio_rusqlite handles cleanup on drop
-        Ok(())
+        self.conn.close().await.map_err(SqliteError::from)
+    }
+
+    /// Process-unique id of this connection, for telling connections apart
+    /// in logs.
+    pub fn id(&self) -> u64 {
+        self.id
     }
 
     /// Check if the connection is closed.
@@ -165,3 +564,121 @@ impl SqliteConnection {
         self.closed
     }
 }
+
+/// `last_insert_rowid()` value meaning "nothing inserted", for
+/// `execute_upsert`; no real insert picks the smallest possible rowid.
+const UNSET_ROWID: i64 = i64::MIN;
+
+/// Schema and name of the table an `INSERT` or `REPLACE` writes to.
+///
+/// Reads `INSERT [OR action] INTO [schema.]table` with bare, `"`, `` ` ``
+/// or `[]` quoted names. None for anything else, such as a leading `WITH`.
+fn upsert_target(sql: &str) -> Option<(Option<String>, String)> {
+    fn word<'a>(rest: &mut &'a str) -> Option<&'a str> {
+        let s = rest.trim_start();
+        let end = s
+            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+            .unwrap_or(s.len());
+        *rest = &s[end..];
+        (end > 0).then(|| &s[..end])
+    }
+    fn name(rest: &mut &str) -> Option<String> {
+        let s = rest.trim_start();
+        let close = match s.chars().next()? {
+            '"' => '"',
+            '`' => '`',
+            '[' => ']',
+            _ => {
+                *rest = s;
+                return word(rest).map(str::to_string);
+            }
+        };
+        // A doubled closing quote stands for itself
+        let mut out = String::new();
+        let mut chars = s[1..].char_indices().peekable();
+        while let Some((i, c)) = chars.next() {
+            if c == close {
+                if close != ']' && chars.peek().map(|&(_, c)| c) == Some(close) {
+                    chars.next();
+                } else {
+                    *rest = &s[1 + i + 1..];
+                    return Some(out);
+                }
+            }
+            out.push(c);
+        }
+        None
+    }
+
+    let mut rest = sql;
+    let mut keyword = word(&mut rest)?;
+    if keyword.eq_ignore_ascii_case("INSERT") {
+        keyword = word(&mut rest)?;
+        if keyword.eq_ignore_ascii_case("OR") {
+            word(&mut rest)?;
+            keyword = word(&mut rest)?;
+        }
+    } else if keyword.eq_ignore_ascii_case("REPLACE") {
+        keyword = word(&mut rest)?;
+    } else {
+        return None;
+    }
+    if !keyword.eq_ignore_ascii_case("INTO") {
+        return None;
+    }
+
+    let first = name(&mut rest)?;
+    match rest.strip_prefix('.') {
+        Some(mut after) => Some((Some(first), name(&mut after)?)),
+        None => Some((None, first)),
+    }
+}
+
+/// Is this a `WITHOUT ROWID` table? An unqualified name prefers `temp`,
+/// as SQLite's own lookup does.
+fn is_without_rowid(
+    conn: &rusqlite::Connection,
+    schema: Option<&str>,
+    table: &str,
+) -> rusqlite::Result<bool> {
+    let wr: Option<bool> = conn
+        .query_row(
+            "SELECT wr FROM pragma_table_list WHERE name = ?1 AND (?2 IS NULL OR schema = ?2) \
+             ORDER BY schema = 'temp' DESC LIMIT 1",
+            rusqlite::params![table, schema],
+            |row| row.get(0),
+        )
+        .map(Some)
+        .or_else(|e| match e {
+            rusqlite::Error::QueryReturnedNoRows => Ok(None),
+            e => Err(e),
+        })?;
+    Ok(wr.unwrap_or(false))
+}
+
+fn count_rows_sql(schema: Option<&str>, table: &str) -> String {
+    let table = quote_ident(table, Dialect::Sqlite);
+    match schema {
+        Some(schema) => format!(
+            "SELECT count(*) FROM {}.{}",
+            quote_ident(schema, Dialect::Sqlite),
+            table
+        ),
+        None => format!("SELECT count(*) FROM {}", table),
+    }
+}
+
+/// Run a cached prepared statement once per parameter set, returning the
+/// total rows changed.
+fn execute_each(
+    conn: &rusqlite::Connection,
+    sql: &str,
+    params_list: &[Vec<SqliteValue>],
+) -> rusqlite::Result<u64> {
+    let mut stmt = conn.prepare_cached(sql)?;
+    let mut changed = 0u64;
+    for params in params_list {
+        changed += stmt.execute(rusqlite::params_from_iter(params))? as u64;
+    }
+    Ok(changed)
+}
@@ -17,6 +17,12 @@ pub enum SqliteError {
     Type(String),
     /// Connection closed
     ConnectionClosed,
+    /// The connection pool has been closed
+    PoolClosed,
+    /// A statement ran past the statement timeout and was interrupted
+    Timeout,
+    /// No pooled connection became free within the acquire timeout
+    AcquireTimeout,
 }
 
 impl std::fmt::Display for SqliteError {
@@ -27,6 +33,9 @@ impl std::fmt::Display for SqliteError {
             SqliteError::Pool(e) => write!(f, "Pool error: {}", e),
             SqliteError::Type(e) => write!(f, "Type error: {}", e),
             SqliteError::ConnectionClosed => write!(f, "Connection closed"),
+            SqliteError::PoolClosed => write!(f, "Pool is closed"),
+            SqliteError::Timeout => write!(f, "Statement timed out"),
+            SqliteError::AcquireTimeout => write!(f, "Timed out waiting for a pooled connection"),
         }
     }
 }
@@ -55,6 +64,10 @@ impl From<rusqlite::Error> for SqliteError {
 
 impl From<tokio_rusqlite::Error> for SqliteError {
     fn from(e: tokio_rusqlite::Error) -> Self {
-        SqliteError::Pool(e.to_string())
+        match e {
+            // Keep the SQLite error code, so constraint failures can be told apart
+            tokio_rusqlite::Error::Rusqlite(e) => SqliteError::Sqlite(e),
+            e => SqliteError::Pool(e.to_string()),
+        }
     }
 }
@@ -13,8 +13,8 @@ mod tests;
 
 // Public API re-exports for library consumers
 #[allow(unused_imports)]
-pub use connection::SqliteConnection;
+pub use connection::{RowStream, SqliteConnection, UpsertAction, UpsertResult};
 #[allow(unused_imports)]
 pub use error::{SqliteError, SqliteResult};
-pub use pool::{SqlitePool, SqlitePoolConfig};
+pub use pool::{PooledConnection, SqlitePool, SqlitePoolConfig};
 pub use types::SqliteValue;
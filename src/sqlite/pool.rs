@@ -4,10 +4,12 @@
 //! and multiple read connections for optimal performance.
 
 use parking_lot::Mutex;
+use std::future::Future;
 use std::sync::Arc;
-use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use std::time::Duration;
+use tokio::sync::{OwnedMutexGuard, OwnedSemaphorePermit, Semaphore};
 
-use super::connection::{QueryResult, SqliteConnection};
+use super::connection::{QueryResult, RowStream, SqliteConnection, UpsertResult};
 use super::error::{SqliteError, SqliteResult};
 use super::types::SqliteValue;
 
@@ -18,6 +20,14 @@ pub struct SqlitePoolConfig {
     pub path: String,
     /// Maximum number of read connections
     pub max_read_connections: u32,
+    /// Run `SELECT 1` on idle connections before handing them out
+    pub test_before_acquire: bool,
+    /// Interrupt statements that run longer than this (default: no limit)
+    pub statement_timeout: Option<Duration>,
+    /// Longest `acquire` waits for a free connection (default: forever)
+    pub acquire_timeout: Option<Duration>,
+    /// Bind and read empty strings as NULL (Oracle semantics)
+    pub empty_string_as_null: bool,
 }
 
 impl SqlitePoolConfig {
@@ -25,6 +35,10 @@ impl SqlitePoolConfig {
         Self {
             path: path.to_string(),
             max_read_connections: 4,
+            test_before_acquire: false,
+            statement_timeout: None,
+            acquire_timeout: None,
+            empty_string_as_null: false,
         }
     }
 
@@ -32,6 +46,42 @@ impl SqlitePoolConfig {
         self.max_read_connections = max;
         self
     }
+
+    /// Validate idle connections with `SELECT 1` before handing them out.
+    ///
+    /// A connection whose database file became inaccessible (network
+    /// filesystem, deleted or restored file) is discarded and replaced. An
+    /// in-memory database lives only in its connection, so there acquire
+    /// fails instead of handing out a new, empty database.
+    pub fn test_before_acquire(mut self, enabled: bool) -> Self {
+        self.test_before_acquire = enabled;
+        self
+    }
+
+    /// Interrupt any statement on the pool's connections that runs longer
+    /// than `timeout`, failing it with `SqliteError::Timeout`.
+    pub fn statement_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.statement_timeout = timeout;
+        self
+    }
+
+    /// Fail `acquire` with `SqliteError::AcquireTimeout` after waiting this
+    /// long for a free connection. For `acquire_writer` the wait for the
+    /// write lock is bounded too.
+    pub fn acquire_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.acquire_timeout = timeout;
+        self
+    }
+
+    /// Treat empty strings as NULL, as Oracle does.
+    ///
+    /// Off by default, since it changes what is stored: empty-string
+    /// parameters are bound as NULL and empty text in query results is read
+    /// back as NULL. See `SqliteConnection::set_empty_string_as_null`.
+    pub fn empty_string_as_null(mut self, enabled: bool) -> Self {
+        self.empty_string_as_null = enabled;
+        self
+    }
 }
 
 /// A pooled connection.
@@ -39,10 +89,22 @@ pub struct PooledConnection {
     conn: Option<SqliteConnection>,
     pool: Arc<SqlitePoolInner>,
     _permit: OwnedSemaphorePermit,
+    /// The pool's write lock, held by connections from `acquire_writer`
+    /// until they are returned
+    _write_guard: Option<OwnedMutexGuard<()>>,
 }
 
 impl PooledConnection {
-    pub async fn query(&self, sql: &str, params: &[SqliteValue]) -> SqliteResult<QueryResult> {
+    /// Id of the underlying connection (None once returned to the pool).
+    pub fn id(&self) -> Option<u64> {
+        self.conn.as_ref().map(|c| c.id())
+    }
+
+    pub async fn query(
+        &self,
+        sql: &str,
+        params: impl Into<Vec<SqliteValue>>,
+    ) -> SqliteResult<QueryResult> {
         self.conn
             .as_ref()
             .ok_or(SqliteError::ConnectionClosed)?
@@ -50,19 +112,94 @@ impl PooledConnection {
             .await
     }
 
-    pub async fn execute(&self, sql: &str, params: &[SqliteValue]) -> SqliteResult<u64> {
+    /// Stream a query's rows in batches. Keep this connection alive until
+    /// the stream is finished so it isn't handed out while still busy.
+    pub async fn query_stream(
+        &self,
+        sql: &str,
+        params: impl Into<Vec<SqliteValue>>,
+        batch_size: usize,
+    ) -> SqliteResult<RowStream> {
+        self.conn
+            .as_ref()
+            .ok_or(SqliteError::ConnectionClosed)?
+            .query_stream(sql, params, batch_size)
+            .await
+    }
+
+    pub async fn execute(
+        &self,
+        sql: &str,
+        params: impl Into<Vec<SqliteValue>>,
+    ) -> SqliteResult<u64> {
         self.conn
             .as_ref()
             .ok_or(SqliteError::ConnectionClosed)?
             .execute(sql, params)
             .await
     }
+
+    pub async fn insert_rows(&self, sql: &str, rows: Vec<Vec<SqliteValue>>) -> SqliteResult<u64> {
+        self.conn
+            .as_ref()
+            .ok_or(SqliteError::ConnectionClosed)?
+            .insert_rows(sql, rows)
+            .await
+    }
+
+    pub async fn execute_many(
+        &self,
+        sql: &str,
+        params_list: Vec<Vec<SqliteValue>>,
+    ) -> SqliteResult<u64> {
+        self.conn
+            .as_ref()
+            .ok_or(SqliteError::ConnectionClosed)?
+            .execute_many(sql, params_list)
+            .await
+    }
+
+    pub async fn execute_upsert(
+        &self,
+        sql: &str,
+        params: impl Into<Vec<SqliteValue>>,
+    ) -> SqliteResult<UpsertResult> {
+        self.conn
+            .as_ref()
+            .ok_or(SqliteError::ConnectionClosed)?
+            .execute_upsert(sql, params)
+            .await
+    }
+
+    pub async fn execute_batch(&self, sql: &str) -> SqliteResult<u64> {
+        self.conn
+            .as_ref()
+            .ok_or(SqliteError::ConnectionClosed)?
+            .execute_batch(sql)
+            .await
+    }
+
+    pub async fn changes(&self) -> SqliteResult<u64> {
+        self.conn
+            .as_ref()
+            .ok_or(SqliteError::ConnectionClosed)?
+            .changes()
+            .await
+    }
+
+    pub async fn total_changes(&self) -> SqliteResult<u64> {
+        self.conn
+            .as_ref()
+            .ok_or(SqliteError::ConnectionClosed)?
+            .total_changes()
+            .await
+    }
 }
 
 impl Drop for PooledConnection {
     fn drop(&mut self) {
         if let Some(conn) = self.conn.take() {
-            if !conn.is_closed() {
+            if !conn.is_closed() && !self.pool.semaphore.is_closed() {
                 self.pool.idle_connections.lock().push(conn);
             }
         }
@@ -73,6 +210,21 @@ struct SqlitePoolInner {
     config: SqlitePoolConfig,
     idle_connections: Mutex<Vec<SqliteConnection>>,
     semaphore: Arc<Semaphore>,
+    /// Serializes write transactions, since SQLite allows one writer
+    write_lock: Arc<tokio::sync::Mutex<()>>,
+}
+
+impl SqlitePoolInner {
+    /// Await `wait`, failing with `SqliteError::AcquireTimeout` once the
+    /// configured acquire timeout passes.
+    async fn with_acquire_timeout<T>(&self, wait: impl Future<Output = T>) -> SqliteResult<T> {
+        match self.config.acquire_timeout {
+            Some(timeout) => tokio::time::timeout(timeout, wait)
+                .await
+                .map_err(|_| SqliteError::AcquireTimeout),
+            None => Ok(wait.await),
+        }
+    }
 }
 
 /// SQLite connection pool.
@@ -88,6 +240,7 @@ impl SqlitePool {
             semaphore: Arc::new(Semaphore::new(config.max_read_connections as usize)),
             config,
             idle_connections: Mutex::new(Vec::new()),
+            write_lock: Arc::new(tokio::sync::Mutex::new(())),
         });
 
         let pool = Self { inner };
@@ -103,11 +256,9 @@ impl SqlitePool {
     pub async fn acquire(&self) -> SqliteResult<PooledConnection> {
         let permit = self
             .inner
-            .semaphore
-            .clone()
-            .acquire_owned()
-            .await
-            .map_err(|_| SqliteError::Pool("Pool closed".to_string()))?;
+            .with_acquire_timeout(self.inner.semaphore.clone().acquire_owned())
+            .await?
+            .map_err(|_| SqliteError::PoolClosed)?;
 
         let conn = {
             let mut idle = self.inner.idle_connections.lock();
@@ -115,7 +266,30 @@ impl SqlitePool {
         };
 
         let conn = match conn {
-            Some(c) if !c.is_closed() => c,
+            Some(c) if !c.is_closed() => {
+                let check = if self.inner.config.test_before_acquire {
+                    c.query("SELECT 1", &[]).await.err()
+                } else {
+                    None
+                };
+                match check {
+                    None => c,
+                    Some(e) if self.inner.config.path == ":memory:" => {
+                        // Keep it, so later acquires fail the same way
+                        self.inner.idle_connections.lock().push(c);
+                        return Err(SqliteError::Pool(format!(
+                            "in-memory database connection failed its check ({}); \
+                             its data cannot be recovered",
+                            e
+                        )));
+                    }
+                    Some(_) => {
+                        // Stale handle - discard it and open a fresh one
+                        let _ = c.close().await;
+                        self.create_connection().await?
+                    }
+                }
+            }
             _ => self.create_connection().await?,
         };
 
@@ -123,23 +297,86 @@ impl SqlitePool {
             conn: Some(conn),
             pool: Arc::clone(&self.inner),
             _permit: permit,
+            _write_guard: None,
         })
     }
 
+    /// Acquire a connection holding the pool's write lock.
+    ///
+    /// Only one such connection is out at a time; others wait here until it
+    /// is returned, rather than on SQLite's busy timeout (or failing with
+    /// `SQLITE_BUSY` when a deferred transaction upgrades to a write).
+    /// Connections from `acquire` don't take the lock.
+    pub async fn acquire_writer(&self) -> SqliteResult<PooledConnection> {
+        let guard = self
+            .inner
+            .with_acquire_timeout(Arc::clone(&self.inner.write_lock).lock_owned())
+            .await?;
+        let mut conn = self.acquire().await?;
+        conn._write_guard = Some(guard);
+        Ok(conn)
+    }
+
     /// Execute a query on a pooled connection.
-    pub async fn query(&self, sql: &str, params: &[SqliteValue]) -> SqliteResult<QueryResult> {
+    pub async fn query(
+        &self,
+        sql: &str,
+        params: impl Into<Vec<SqliteValue>>,
+    ) -> SqliteResult<QueryResult> {
         let conn = self.acquire().await?;
         conn.query(sql, params).await
     }
 
     /// Execute a statement on a pooled connection.
-    pub async fn execute(&self, sql: &str, params: &[SqliteValue]) -> SqliteResult<u64> {
+    pub async fn execute(
+        &self,
+        sql: &str,
+        params: impl Into<Vec<SqliteValue>>,
+    ) -> SqliteResult<u64> {
         let conn = self.acquire().await?;
         conn.execute(sql, params).await
     }
 
+    /// Execute a statement once per parameter set, atomically, on a pooled
+    /// connection.
+    pub async fn execute_many(
+        &self,
+        sql: &str,
+        params_list: Vec<Vec<SqliteValue>>,
+    ) -> SqliteResult<u64> {
+        let conn = self.acquire().await?;
+        conn.execute_many(sql, params_list).await
+    }
+
+    /// Execute an upsert on a pooled connection, reporting insert vs update.
+    pub async fn execute_upsert(
+        &self,
+        sql: &str,
+        params: impl Into<Vec<SqliteValue>>,
+    ) -> SqliteResult<UpsertResult> {
+        let conn = self.acquire().await?;
+        conn.execute_upsert(sql, params).await
+    }
+
+    /// Rows changed since they were opened, summed over idle connections.
+    ///
+    /// Connections currently checked out are not included.
+    pub async fn total_changes(&self) -> SqliteResult<u64> {
+        let connections: Vec<SqliteConnection> = self.inner.idle_connections.lock().clone();
+
+        let mut total = 0;
+        for conn in &connections {
+            total += conn.total_changes().await?;
+        }
+        Ok(total)
+    }
+
     /// Close all connections.
+    ///
+    /// Subsequent `acquire` calls fail with `SqliteError::PoolClosed`.
     pub async fn close(&self) {
+        self.inner.semaphore.close();
+
         let connections = {
             let mut idle = self.inner.idle_connections.lock();
             std::mem::take(&mut *idle)
@@ -151,7 +388,10 @@ impl SqlitePool {
     }
 
     async fn create_connection(&self) -> SqliteResult<SqliteConnection> {
-        SqliteConnection::open(&self.inner.config.path).await
+        let mut conn = SqliteConnection::open(&self.inner.config.path).await?;
+        conn.set_statement_timeout(self.inner.config.statement_timeout);
+        conn.set_empty_string_as_null(self.inner.config.empty_string_as_null);
+        Ok(conn)
     }
 }
 
@@ -184,4 +424,171 @@ mod tests {
 
         pool.close().await;
     }
+
+    #[tokio::test]
+    async fn test_pooled_connections_have_distinct_ids() {
+        let config = SqlitePoolConfig::new(":memory:").max_read_connections(2);
+        let pool = SqlitePool::connect(config).await.unwrap();
+
+        let first = pool.acquire().await.unwrap();
+        let second = pool.acquire().await.unwrap();
+        assert_ne!(first.id().unwrap(), second.id().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_acquire_writer_is_exclusive() {
+        let config = SqlitePoolConfig::new(":memory:").max_read_connections(3);
+        let pool = SqlitePool::connect(config).await.unwrap();
+
+        let writer = pool.acquire_writer().await.unwrap();
+        // Plain connections are still handed out
+        let reader = pool.acquire().await.unwrap();
+        let waiting = tokio::time::timeout(Duration::from_millis(50), pool.acquire_writer());
+        assert!(waiting.await.is_err());
+
+        drop(reader);
+        drop(writer);
+        let next = tokio::time::timeout(Duration::from_secs(1), pool.acquire_writer());
+        assert!(next.await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_acquire_timeout() {
+        let config = SqlitePoolConfig::new(":memory:")
+            .max_read_connections(1)
+            .acquire_timeout(Some(Duration::from_millis(50)));
+        let pool = SqlitePool::connect(config).await.unwrap();
+
+        let held = pool.acquire().await.unwrap();
+        assert!(matches!(
+            pool.acquire().await,
+            Err(SqliteError::AcquireTimeout)
+        ));
+        drop(held);
+        assert!(pool.acquire().await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_empty_string_as_null() {
+        for enabled in [false, true] {
+            let config = SqlitePoolConfig::new(":memory:").empty_string_as_null(enabled);
+            let pool = SqlitePool::connect(config).await.unwrap();
+            pool.execute("CREATE TABLE test (name TEXT)", &[])
+                .await
+                .unwrap();
+            pool.execute(
+                "INSERT INTO test VALUES (?1)",
+                &[SqliteValue::Text(String::new())],
+            )
+            .await
+            .unwrap();
+
+            let stored = pool
+                .query("SELECT count(*) FROM test WHERE name IS NULL", &[])
+                .await
+                .unwrap();
+            assert_eq!(stored.rows[0][0], SqliteValue::Integer(enabled as i64));
+
+            // Empty text produced by the query itself is read back as NULL too
+            let read = pool.query("SELECT ''", &[]).await.unwrap();
+            let expected = if enabled {
+                SqliteValue::Null
+            } else {
+                SqliteValue::Text(String::new())
+            };
+            assert_eq!(read.rows[0][0], expected);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_acquire_replaces_failing_connection() {
+        let path =
+            std::env::temp_dir().join(format!("ormkit-test-replace-{}.db", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let config = SqlitePoolConfig::new(path.to_str().unwrap()).test_before_acquire(true);
+        let pool = SqlitePool::connect(config).await.unwrap();
+        pool.execute("CREATE TABLE test (id INTEGER)", &[])
+            .await
+            .unwrap();
+
+        // Break the idle connection behind the pool's back
+        let idle = pool.inner.idle_connections.lock()[0].clone();
+        idle.close().await.unwrap();
+
+        let conn = pool.acquire().await.unwrap();
+        let result = conn.query("SELECT 1", &[]).await.unwrap();
+        assert_eq!(result.rows[0][0], SqliteValue::Integer(1));
+        // The replacement opens the same file, so the table is still there
+        assert!(conn.query("SELECT * FROM test", &[]).await.is_ok());
+
+        drop(conn);
+        pool.close().await;
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_acquire_fails_for_broken_memory_connection() {
+        let config = SqlitePoolConfig::new(":memory:").test_before_acquire(true);
+        let pool = SqlitePool::connect(config).await.unwrap();
+        pool.execute("CREATE TABLE test (id INTEGER)", &[])
+            .await
+            .unwrap();
+
+        let idle = pool.inner.idle_connections.lock()[0].clone();
+        idle.close().await.unwrap();
+
+        // A replacement would be a new, empty database
+        for _ in 0..2 {
+            assert!(matches!(pool.acquire().await, Err(SqliteError::Pool(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_statement_timeout_interrupts_long_query() {
+        let config =
+            SqlitePoolConfig::new(":memory:").statement_timeout(Some(Duration::from_millis(100)));
+        let pool = SqlitePool::connect(config).await.unwrap();
+
+        let err = pool
+            .query(
+                "WITH RECURSIVE c(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM c LIMIT 1000000000) \
+                 SELECT count(*) FROM c",
+                &[],
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SqliteError::Timeout), "{:?}", err);
+
+        // The connection stays usable for quick statements
+        let result = pool.query("SELECT 1", &[]).await.unwrap();
+        assert_eq!(result.rows[0][0], SqliteValue::Integer(1));
+    }
+
+    #[tokio::test]
+    async fn test_acquire_without_validation_keeps_failing_connection() {
+        let pool = SqlitePool::connect(SqlitePoolConfig::new(":memory:"))
+            .await
+            .unwrap();
+
+        let idle = pool.inner.idle_connections.lock()[0].clone();
+        idle.close().await.unwrap();
+
+        assert!(pool.query("SELECT 1", &[]).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_acquire_after_close() {
+        let pool = SqlitePool::connect(SqlitePoolConfig::new(":memory:"))
+            .await
+            .unwrap();
+
+        pool.close().await;
+        assert!(matches!(pool.acquire().await, Err(SqliteError::PoolClosed)));
+        assert!(matches!(
+            pool.query("SELECT 1", &[]).await,
+            Err(SqliteError::PoolClosed)
+        ));
+    }
 }
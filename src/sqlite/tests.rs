@@ -97,3 +97,404 @@ async fn test_multiple_rows() {
     let result = conn.query("SELECT * FROM test", &[]).await.unwrap();
     assert_eq!(result.rows.len(), 100);
 }
+
+#[tokio::test]
+async fn test_execute_upsert_reports_action() {
+    let conn = SqliteConnection::open(":memory:").await.unwrap();
+
+    conn.execute(
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT UNIQUE, name TEXT)",
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let sql = "INSERT INTO users (email, name) VALUES (?, ?) \
+               ON CONFLICT (email) DO UPDATE SET name = excluded.name";
+
+    let first = conn
+        .execute_upsert(
+            sql,
+            &[
+                SqliteValue::Text("a@b.com".to_string()),
+                SqliteValue::Text("A".to_string()),
+            ],
+        )
+        .await
+        .unwrap();
+    assert_eq!(first.action, UpsertAction::Insert);
+    assert_eq!(first.rowid, Some(1));
+
+    let second = conn
+        .execute_upsert(
+            sql,
+            &[
+                SqliteValue::Text("a@b.com".to_string()),
+                SqliteValue::Text("B".to_string()),
+            ],
+        )
+        .await
+        .unwrap();
+    assert_eq!(second.action, UpsertAction::Update);
+    assert_eq!(second.rowid, None);
+
+    let ignored = conn
+        .execute_upsert(
+            "INSERT INTO users (email, name) VALUES (?, ?) ON CONFLICT (email) DO NOTHING",
+            &[
+                SqliteValue::Text("a@b.com".to_string()),
+                SqliteValue::Text("C".to_string()),
+            ],
+        )
+        .await
+        .unwrap();
+    assert_eq!(ignored.action, UpsertAction::Nothing);
+
+    let result = conn.query("SELECT name FROM users", &[]).await.unwrap();
+    assert_eq!(result.rows, vec![vec![SqliteValue::Text("B".to_string())]]);
+}
+
+#[tokio::test]
+async fn test_execute_upsert_insert_reusing_last_rowid() {
+    let conn = SqliteConnection::open(":memory:").await.unwrap();
+
+    conn.execute(
+        "CREATE TABLE users (id INTEGER PRIMARY KEY, email TEXT UNIQUE)",
+        &[],
+    )
+    .await
+    .unwrap();
+    conn.execute("INSERT INTO users (email) VALUES ('old@b.com')", &[])
+        .await
+        .unwrap();
+    conn.execute("DELETE FROM users", &[]).await.unwrap();
+
+    // The new row gets rowid 1 again, the same as the last insert
+    let result = conn
+        .execute_upsert(
+            "INSERT INTO users (email) VALUES (?) ON CONFLICT (email) DO NOTHING",
+            &[SqliteValue::Text("new@b.com".to_string())],
+        )
+        .await
+        .unwrap();
+    assert_eq!(result.action, UpsertAction::Insert);
+    assert_eq!(result.rowid, Some(1));
+}
+
+#[tokio::test]
+async fn test_execute_upsert_without_rowid_table() {
+    let conn = SqliteConnection::open(":memory:").await.unwrap();
+
+    conn.execute(
+        "CREATE TABLE \"Key Value\" (k TEXT PRIMARY KEY, v TEXT) WITHOUT ROWID",
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let sql = "INSERT INTO main.\"Key Value\" (k, v) VALUES (?, ?) \
+               ON CONFLICT (k) DO UPDATE SET v = excluded.v";
+    let params = |v: &str| {
+        vec![
+            SqliteValue::Text("a".to_string()),
+            SqliteValue::Text(v.to_string()),
+        ]
+    };
+
+    let first = conn.execute_upsert(sql, params("1")).await.unwrap();
+    assert_eq!(first.action, UpsertAction::Insert);
+    assert_eq!(first.rowid, None);
+
+    let second = conn.execute_upsert(sql, params("2")).await.unwrap();
+    assert_eq!(second.action, UpsertAction::Update);
+    assert_eq!(second.rowid, None);
+
+    let ignored = conn
+        .execute_upsert(
+            "INSERT INTO [Key Value] (k, v) VALUES (?, ?) ON CONFLICT DO NOTHING",
+            params("3"),
+        )
+        .await
+        .unwrap();
+    assert_eq!(ignored.action, UpsertAction::Nothing);
+
+    let result = conn
+        .query("SELECT v FROM \"Key Value\"", &[])
+        .await
+        .unwrap();
+    assert_eq!(result.rows, vec![vec![SqliteValue::Text("2".to_string())]]);
+}
+
+#[tokio::test]
+async fn test_constraint_failure_keeps_error_code() {
+    let conn = SqliteConnection::open(":memory:").await.unwrap();
+
+    conn.execute("CREATE TABLE users (email TEXT UNIQUE)", &[])
+        .await
+        .unwrap();
+    conn.execute("INSERT INTO users VALUES ('a@b.com')", &[])
+        .await
+        .unwrap();
+
+    let err = conn
+        .execute("INSERT INTO users VALUES ('a@b.com')", &[])
+        .await
+        .unwrap_err();
+    match err {
+        SqliteError::Sqlite(rusqlite::Error::SqliteFailure(e, _)) => {
+            assert_eq!(e.code, rusqlite::ErrorCode::ConstraintViolation);
+            assert_eq!(e.extended_code, rusqlite::ffi::SQLITE_CONSTRAINT_UNIQUE);
+        }
+        other => panic!("expected a SQLite failure, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn test_insert_rows() {
+    let conn = SqliteConnection::open(":memory:").await.unwrap();
+
+    conn.execute("CREATE TABLE test (id INTEGER, name TEXT)", &[])
+        .await
+        .unwrap();
+
+    let rows = (0..100)
+        .map(|i| {
+            vec![
+                SqliteValue::Integer(i),
+                SqliteValue::Text(format!("row{}", i)),
+            ]
+        })
+        .collect();
+    let inserted = conn
+        .insert_rows("INSERT INTO test (id, name) VALUES (?, ?)", rows)
+        .await
+        .unwrap();
+    assert_eq!(inserted, 100);
+
+    // A failing row rolls back the whole chunk
+    conn.execute("CREATE UNIQUE INDEX test_id ON test (id)", &[])
+        .await
+        .unwrap();
+    let rows = vec![
+        vec![SqliteValue::Integer(1000), SqliteValue::Null],
+        vec![SqliteValue::Integer(0), SqliteValue::Null],
+    ];
+    assert!(conn
+        .insert_rows("INSERT INTO test (id, name) VALUES (?, ?)", rows)
+        .await
+        .is_err());
+
+    let result = conn.query("SELECT COUNT(*) FROM test", &[]).await.unwrap();
+    assert_eq!(result.rows[0][0], SqliteValue::Integer(100));
+}
+
+#[tokio::test]
+async fn test_execute_many() {
+    let conn = SqliteConnection::open(":memory:").await.unwrap();
+    conn.execute_batch(
+        "CREATE TABLE test (id INTEGER PRIMARY KEY, n INTEGER CHECK (n >= 0));
+         INSERT INTO test VALUES (1, 10), (2, 20), (3, 30);",
+    )
+    .await
+    .unwrap();
+
+    let params = vec![
+        vec![SqliteValue::Integer(5), SqliteValue::Integer(1)],
+        vec![SqliteValue::Integer(5), SqliteValue::Integer(3)],
+    ];
+    let changed = conn
+        .execute_many("UPDATE test SET n = n - ? WHERE id = ?", params)
+        .await
+        .unwrap();
+    assert_eq!(changed, 2);
+
+    // The second update violates the CHECK, so the first is undone too
+    let params = vec![
+        vec![SqliteValue::Integer(10), SqliteValue::Integer(2)],
+        vec![SqliteValue::Integer(10), SqliteValue::Integer(1)],
+    ];
+    assert!(conn
+        .execute_many("UPDATE test SET n = n - ? WHERE id = ?", params)
+        .await
+        .is_err());
+
+    let result = conn
+        .query("SELECT n FROM test ORDER BY id", &[])
+        .await
+        .unwrap();
+    let values: Vec<&SqliteValue> = result.rows.iter().map(|row| &row[0]).collect();
+    assert_eq!(
+        values,
+        vec![
+            &SqliteValue::Integer(5),
+            &SqliteValue::Integer(20),
+            &SqliteValue::Integer(25)
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_execute_many_nests_in_transaction() {
+    let conn = SqliteConnection::open(":memory:").await.unwrap();
+    conn.execute_batch("CREATE TABLE test (id INTEGER PRIMARY KEY)")
+        .await
+        .unwrap();
+
+    conn.execute_batch("BEGIN").await.unwrap();
+    let params = vec![vec![SqliteValue::Integer(1)], vec![SqliteValue::Integer(2)]];
+    let changed = conn
+        .execute_many("INSERT INTO test VALUES (?)", params)
+        .await
+        .unwrap();
+    assert_eq!(changed, 2);
+
+    // A failing batch only undoes itself, not the enclosing transaction
+    let params = vec![vec![SqliteValue::Integer(3)], vec![SqliteValue::Integer(1)]];
+    assert!(conn
+        .execute_many("INSERT INTO test VALUES (?)", params)
+        .await
+        .is_err());
+    let result = conn.query("SELECT COUNT(*) FROM test", &[]).await.unwrap();
+    assert_eq!(result.rows[0][0], SqliteValue::Integer(2));
+
+    // Rolling back the transaction undoes the batch too
+    conn.execute_batch("ROLLBACK").await.unwrap();
+    let result = conn.query("SELECT COUNT(*) FROM test", &[]).await.unwrap();
+    assert_eq!(result.rows[0][0], SqliteValue::Integer(0));
+}
+
+#[tokio::test]
+async fn test_changes_and_total_changes() {
+    let conn = SqliteConnection::open(":memory:").await.unwrap();
+    conn.execute("CREATE TABLE test (id INTEGER PRIMARY KEY, name TEXT)", &[])
+        .await
+        .unwrap();
+    assert_eq!(conn.total_changes().await.unwrap(), 0);
+
+    for i in 0..3 {
+        conn.execute(
+            "INSERT INTO test (id, name) VALUES (?, ?)",
+            &[SqliteValue::Integer(i), SqliteValue::Null],
+        )
+        .await
+        .unwrap();
+    }
+    assert_eq!(conn.changes().await.unwrap(), 1);
+    assert_eq!(conn.total_changes().await.unwrap(), 3);
+
+    // Batches report the rows they changed and count towards the total
+    let changed = conn
+        .execute_batch(
+            "INSERT INTO test (id) VALUES (10); INSERT INTO test (id) VALUES (11);
+             UPDATE test SET name = 'x'",
+        )
+        .await
+        .unwrap();
+    assert_eq!(changed, 7);
+    assert_eq!(conn.changes().await.unwrap(), 5);
+    assert_eq!(conn.total_changes().await.unwrap(), 10);
+}
+
+#[tokio::test]
+async fn test_owned_params_are_moved_not_copied() {
+    let conn = SqliteConnection::open(":memory:").await.unwrap();
+    conn.execute("CREATE TABLE hot (id INTEGER, name TEXT)", &[])
+        .await
+        .unwrap();
+
+    // A hot loop hands each parameter vector over instead of cloning it
+    for i in 0..100 {
+        let params = vec![
+            SqliteValue::Integer(i),
+            SqliteValue::Text(format!("row{}", i)),
+        ];
+        conn.execute("INSERT INTO hot VALUES (?, ?)", params)
+            .await
+            .unwrap();
+    }
+
+    // Borrowed and owned parameters bind identically
+    let borrowed = [SqliteValue::Integer(42)];
+    let by_slice = conn
+        .query("SELECT name FROM hot WHERE id = ?", &borrowed)
+        .await
+        .unwrap();
+    let by_vec = conn
+        .query(
+            "SELECT name FROM hot WHERE id = ?",
+            vec![SqliteValue::Integer(42)],
+        )
+        .await
+        .unwrap();
+    assert_eq!(by_slice.rows, by_vec.rows);
+    assert_eq!(by_vec.rows[0][0], SqliteValue::Text("row42".to_string()));
+}
+
+#[tokio::test]
+async fn test_query_stream_in_batches() {
+    let conn = SqliteConnection::open(":memory:").await.unwrap();
+    conn.execute("CREATE TABLE big (id INTEGER)", &[])
+        .await
+        .unwrap();
+    conn.execute(
+        "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 100000)
+         INSERT INTO big SELECT i FROM n",
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let mut stream = conn
+        .query_stream("SELECT id FROM big ORDER BY id", vec![], 1000)
+        .await
+        .unwrap();
+    assert_eq!(stream.columns, vec!["id"]);
+
+    let first = stream.next_batch().await.unwrap().unwrap();
+    assert_eq!(first.len(), 1000);
+
+    // A slow reader leaves the connection thread blocked rather than
+    // letting it buffer the rest of the result
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    assert!(stream.buffered() <= 2);
+
+    let mut total = first.len();
+    let mut last = 1;
+    while let Some(batch) = stream.next_batch().await {
+        let batch = batch.unwrap();
+        assert!(batch.len() <= 1000);
+        total += batch.len();
+        if let Some(SqliteValue::Integer(id)) = batch.last().map(|row| &row[0]) {
+            last = *id;
+        }
+    }
+    assert_eq!(total, 100_000);
+    assert_eq!(last, 100_000);
+
+    // The connection is free again once the stream is done
+    let count = conn.query("SELECT COUNT(*) FROM big", &[]).await.unwrap();
+    assert_eq!(count.rows[0][0], SqliteValue::Integer(100_000));
+}
+
+#[tokio::test]
+async fn test_query_stream_reports_errors() {
+    let conn = SqliteConnection::open(":memory:").await.unwrap();
+    assert!(conn
+        .query_stream("SELECT * FROM missing", vec![], 10)
+        .await
+        .is_err());
+
+    // Dropping a stream part way frees the connection
+    let stream = conn
+        .query_stream(
+            "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 10000)
+             SELECT i FROM n",
+            vec![],
+            10,
+        )
+        .await
+        .unwrap();
+    drop(stream);
+    let result = conn.query("SELECT 1", &[]).await.unwrap();
+    assert_eq!(result.rows[0][0], SqliteValue::Integer(1));
+}
@@ -30,6 +30,15 @@ impl SqliteValue {
         }
     }
 
+    /// Replace an empty string with NULL, for `empty_string_as_null`.
+    #[inline]
+    pub fn empty_text_as_null(self) -> Self {
+        match self {
+            SqliteValue::Text(s) if s.is_empty() => SqliteValue::Null,
+            value => value,
+        }
+    }
+
     /// Try to get as i64.
     pub fn as_i64(&self) -> Option<i64> {
         match self {
@@ -0,0 +1,98 @@
+//! Typed parameter wrappers: `ormkit.Int4(5)`, `ormkit.Jsonb({...})`, ...
+//!
+//! A plain Python value is bound with an inferred type (every `int` is
+//! INT8, every `str` TEXT). Wrapping it picks the PostgreSQL type instead,
+//! for overloaded functions, partial indexes on a narrower column, or
+//! anywhere the server should see exactly that type. SQLite binds the
+//! wrapped value with the matching storage class.
+
+use pyo3::prelude::*;
+
+use crate::pool::{py_to_json_string, SqlParam};
+
+/// Define a frozen wrapper class holding a value already converted to the
+/// `SqlParam` it binds as.
+macro_rules! typed_param {
+    ($(#[$doc:meta])* $name:ident, |$value:ident: $ty:ty| $convert:expr) => {
+        $(#[$doc])*
+        #[pyclass(frozen, module = "ormkit._ormkit")]
+        pub struct $name {
+            param: SqlParam,
+            /// The wrapped value, as given
+            #[pyo3(get)]
+            value: PyObject,
+        }
+
+        #[pymethods]
+        impl $name {
+            #[new]
+            fn new(value: Bound<'_, PyAny>) -> PyResult<Self> {
+                let param = {
+                    let $value: $ty = value.extract()?;
+                    $convert
+                };
+                Ok(Self {
+                    param,
+                    value: value.unbind(),
+                })
+            }
+
+            fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+                Ok(format!(
+                    "{}({})",
+                    stringify!($name),
+                    self.value.bind(py).repr()?
+                ))
+            }
+        }
+    };
+}
+
+typed_param!(
+    /// Bind as `smallint`
+    Int2, |v: i16| SqlParam::Int2(v)
+);
+typed_param!(
+    /// Bind as `integer`
+    Int4, |v: i32| SqlParam::Int4(v)
+);
+typed_param!(
+    /// Bind as `bigint` (the default for Python ints in range)
+    Int8, |v: i64| SqlParam::Int(v)
+);
+typed_param!(
+    /// Bind as `real`
+    Float4, |v: f32| SqlParam::Float4(v)
+);
+typed_param!(
+    /// Bind as `double precision` (the default for Python floats)
+    Float8, |v: f64| SqlParam::Float(v)
+);
+typed_param!(
+    /// Bind as `numeric`, from an int, str or `decimal.Decimal`
+    Numeric, |v: Bound<'_, PyAny>| SqlParam::Numeric(v.str()?.to_string())
+);
+typed_param!(
+    /// Bind as `text`
+    Text, |v: String| SqlParam::String(v)
+);
+typed_param!(
+    /// Bind as `jsonb`, from any JSON-serializable value (including a str,
+    /// which becomes a JSON string rather than being parsed)
+    Jsonb, |v: Bound<'_, PyAny>| SqlParam::Json(py_to_json_string(&v)?)
+);
+
+/// The parameter a wrapped value binds as, or None for any other object.
+pub fn typed_param(bound: &Bound<'_, PyAny>) -> Option<SqlParam> {
+    macro_rules! unwrap_typed {
+        ($($name:ident),*) => {
+            $(
+                if let Ok(typed) = bound.downcast::<$name>() {
+                    return Some(typed.get().param.clone());
+                }
+            )*
+        };
+    }
+    unwrap_typed!(Int2, Int4, Int8, Float4, Float8, Numeric, Text, Jsonb);
+    None
+}
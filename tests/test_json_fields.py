@@ -258,3 +258,18 @@ class TestJSONTypeCoercion:
         products = await session.query(Product).filter(metadata__status="active").all()
         assert len(products) == 1
         assert products[0].name == "WithString"
+
+
+class TestJSONKeyOrder:
+    """JSON object key order survives the trip into Python."""
+
+    async def test_json_key_order_preserved_postgresql(self, postgres_pool) -> None:
+        """json keeps text order; jsonb's own storage order is not re-sorted."""
+        result = await postgres_pool.execute(
+            """SELECT '{"zeta": 1, "alpha": 2, "mid": 3}'::json AS j,"""
+            """ '{"b": 1, "aa": 2}'::jsonb AS jb"""
+        )
+        row = result.first()
+        assert list(row["j"].keys()) == ["zeta", "alpha", "mid"]
+        # jsonb stores shorter keys first; alphabetical order would give aa, b
+        assert list(row["jb"].keys()) == ["b", "aa"]
@@ -367,6 +367,24 @@ class TestMigrationCLI:
         assert status["current_revision"] == "abc123def456"
         assert len(status["pending"]) == 0
 
+    async def test_migrate_with_default_row_factory(self, alembic_dir: Path, sample_migration: Path, tmp_path: Path) -> None:
+        """Migrations read the version table regardless of the pool's default_row_factory."""
+        from ormkit import create_engine
+        from ormkit.cli import migrate_down, migrate_status, migrate_up
+
+        pool = await create_engine("sqlite::memory:", default_row_factory="tuple")
+        try:
+            await migrate_up(pool, tmp_path)
+            status = await migrate_status(pool, tmp_path)
+            assert status["current_revision"] == "abc123def456"
+            assert len(status["pending"]) == 0
+
+            await migrate_down(pool, tmp_path)
+            status = await migrate_status(pool, tmp_path)
+            assert status["current_revision"] is None
+        finally:
+            await pool.close()
+
 
 class TestSchemaIntrospection:
     """Test database schema reading (Rust backend)."""
@@ -1,5 +1,7 @@
 """Tests for new ORM features: Q objects, aggregates, streaming, etc."""
 
+import os
+
 import pytest
 from ormkit import (
     Base,
@@ -9,7 +11,25 @@ from ormkit import (
     relationship,
     create_engine,
     create_session,
+    CheckViolationError,
+    DatabaseError,
+    DataError,
+    ForeignKeyViolationError,
+    IntegrityError,
+    NotNullViolationError,
+    UniqueViolationError,
+    OperationalError,
+    ProgrammingError,
+    Int4,
+    Jsonb,
+    Numeric,
+    MultipleResultsFound,
+    NoResultFound,
+    PoolClosedError,
+    PoolTimeoutError,
     Q,
+    quote_ident,
+    quote_literal,
     joinedload,
     selectinload,
 )
@@ -361,3 +381,3171 @@ class TestGroupBy:
         # Count posts per author
         result = await session.query(NewFeaturePost).group_by("author_id").values("author_id")
         assert len(result) == 2  # Alice and Bob have posts
+
+
+# ========== Pool Closure Tests ==========
+
+
+class TestPoolClosed:
+    async def test_execute_after_close_raises_pool_closed(self):
+        """Test that a closed pool raises PoolClosedError, not a generic error."""
+        pool = await create_engine("sqlite::memory:")
+        await pool.close()
+
+        with pytest.raises(PoolClosedError):
+            await pool.execute("SELECT 1")
+
+    async def test_pool_closed_is_runtime_error(self):
+        """Test that existing RuntimeError handlers still catch it."""
+        assert issubclass(PoolClosedError, RuntimeError)
+
+
+# ========== Row Factory Tests ==========
+
+
+class TestRowFactory:
+    async def test_execute_tuples(self, sqlite_pool):
+        """Test that execute_tuples returns a list of tuples."""
+        rows = await sqlite_pool.execute_tuples("SELECT 1 AS a, 'x' AS b")
+        assert rows == [(1, "x")]
+
+    async def test_row_factory_tuple(self, sqlite_pool):
+        """Test that row_factory="tuple" yields tuples."""
+        rows = await sqlite_pool.execute(
+            "SELECT 1 AS a, 'x' AS b UNION ALL SELECT 2, 'y'", row_factory="tuple"
+        )
+        assert rows == [(1, "x"), (2, "y")]
+
+    async def test_row_factory_dict(self, sqlite_pool):
+        """Test that row_factory="dict" yields dicts."""
+        rows = await sqlite_pool.execute("SELECT 1 AS a", row_factory="dict")
+        assert rows == [{"a": 1}]
+
+    async def test_row_factory_callable(self, sqlite_pool):
+        """Test that a callable row_factory is invoked once per row."""
+        calls = []
+
+        def factory(row):
+            calls.append(row)
+            return row["a"] * 10
+
+        rows = await sqlite_pool.execute(
+            "SELECT 1 AS a UNION ALL SELECT 2", row_factory=factory
+        )
+        assert rows == [10, 20]
+        assert calls == [{"a": 1}, {"a": 2}]
+
+    async def test_row_factory_invalid(self, sqlite_pool):
+        """Test that an unknown row_factory name is rejected."""
+        with pytest.raises(ValueError):
+            await sqlite_pool.execute("SELECT 1", row_factory="namedtuple")
+
+
+# ========== Bulk Load Tests ==========
+
+
+def _bulk_rows(count):
+    """Generate rows lazily, as if streamed from a file."""
+    for i in range(count):
+        yield (i, f"name-{i}", i * 0.5)
+
+
+class TestBulkLoad:
+    async def test_bulk_load_sqlite(self, sqlite_pool):
+        """Test loading 10000 rows from a generator into SQLite."""
+        await sqlite_pool.execute(
+            "CREATE TABLE bulk_items (id INTEGER PRIMARY KEY, name TEXT, score REAL)"
+        )
+
+        loaded = await sqlite_pool.bulk_load(
+            "bulk_items", ["id", "name", "score"], _bulk_rows(10000)
+        )
+        assert loaded == 10000
+
+        result = await sqlite_pool.execute("SELECT COUNT(*) AS n FROM bulk_items")
+        assert result.scalar() == 10000
+
+        row = (await sqlite_pool.execute("SELECT * FROM bulk_items WHERE id = ?", [4321])).one()
+        assert row == {"id": 4321, "name": "name-4321", "score": 2160.5}
+
+    async def test_bulk_load_postgres(self, postgres_pool):
+        """Test loading 10000 rows from a generator into PostgreSQL via COPY."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS bulk_items")
+        await postgres_pool.execute(
+            "CREATE TABLE bulk_items (id int8 PRIMARY KEY, name text, score float8)"
+        )
+
+        try:
+            loaded = await postgres_pool.bulk_load(
+                "bulk_items", ["id", "name", "score"], _bulk_rows(10000)
+            )
+            assert loaded == 10000
+
+            result = await postgres_pool.execute("SELECT COUNT(*) AS n FROM bulk_items")
+            assert result.scalar() == 10000
+
+            row = (
+                await postgres_pool.execute("SELECT * FROM bulk_items WHERE id = $1", [4321])
+            ).one()
+            assert row == {"id": 4321, "name": "name-4321", "score": 2160.5}
+        finally:
+            await postgres_pool.execute("DROP TABLE IF EXISTS bulk_items")
+
+    async def test_bulk_load_postgres_temporal_and_array_columns(self, postgres_pool):
+        """Test COPY loading dates, times, timestamps and arrays into PostgreSQL."""
+        import datetime
+
+        await postgres_pool.execute("DROP TABLE IF EXISTS bulk_events")
+        await postgres_pool.execute(
+            "CREATE TABLE bulk_events (id int8, at timestamp, at_tz timestamptz, "
+            "day date, t time, t_tz timetz, tags text[], grid int4[])"
+        )
+
+        utc = datetime.timezone.utc
+        row = (
+            1,
+            datetime.datetime(2024, 3, 15, 4, 5, 6, 500000),
+            datetime.datetime(2024, 3, 15, 4, 5, 6, tzinfo=utc),
+            datetime.date(2024, 3, 15),
+            datetime.time(4, 5, 6, 1),
+            datetime.time(4, 5, 6, tzinfo=datetime.timezone(datetime.timedelta(hours=2))),
+            ['a "quoted" tag', "back\\slash", None],
+            [[1, 2], [3, 4]],
+        )
+        try:
+            loaded = await postgres_pool.bulk_load(
+                "bulk_events",
+                ["id", "at", "at_tz", "day", "t", "t_tz", "tags", "grid"],
+                iter([row]),
+            )
+            assert loaded == 1
+
+            result = await postgres_pool.execute(
+                "SELECT at, at_tz, day, t, t_tz::text AS t_tz, tags, grid FROM bulk_events"
+            )
+            loaded_row = result.one()
+            assert loaded_row["at"] == row[1]
+            assert loaded_row["at_tz"] == row[2]
+            assert loaded_row["day"] == row[3]
+            assert loaded_row["t"] == row[4]
+            assert loaded_row["t_tz"] == "04:05:06+02"
+            assert loaded_row["tags"] == row[6]
+            assert loaded_row["grid"] == row[7]
+        finally:
+            await postgres_pool.execute("DROP TABLE IF EXISTS bulk_events")
+
+    async def test_bulk_load_quotes_identifiers(self, sqlite_pool):
+        """Test that table and column names are quoted, so keywords and spaces work."""
+        await sqlite_pool.execute('CREATE TABLE "Bulk Items" ("order" INTEGER, "Name" TEXT)')
+
+        loaded = await sqlite_pool.bulk_load(
+            "Bulk Items", ["order", "Name"], iter([(1, "a"), (2, "b")])
+        )
+        assert loaded == 2
+
+        result = await sqlite_pool.execute('SELECT "Name" FROM "Bulk Items" ORDER BY "order"')
+        assert [row["Name"] for row in result.all()] == ["a", "b"]
+
+    async def test_bulk_load_schema_qualified_table(self, postgres_pool):
+        """Test that a schema-qualified table name is quoted part by part."""
+        await postgres_pool.execute("DROP SCHEMA IF EXISTS bulk_schema CASCADE")
+        await postgres_pool.execute("CREATE SCHEMA bulk_schema")
+        await postgres_pool.execute("CREATE TABLE bulk_schema.items (id int8, name text)")
+        try:
+            loaded = await postgres_pool.bulk_load(
+                "bulk_schema.items", ["id", "name"], iter([(1, "a"), (2, "b")])
+            )
+            assert loaded == 2
+
+            result = await postgres_pool.execute("SELECT COUNT(*) FROM bulk_schema.items")
+            assert result.scalar() == 2
+        finally:
+            await postgres_pool.execute("DROP SCHEMA IF EXISTS bulk_schema CASCADE")
+
+    async def test_bulk_load_rejects_wrong_width(self, sqlite_pool):
+        """Test that rows with the wrong number of values are rejected."""
+        await sqlite_pool.execute("CREATE TABLE bulk_items (id INTEGER, name TEXT)")
+
+        with pytest.raises(ValueError):
+            await sqlite_pool.bulk_load("bulk_items", ["id", "name"], iter([(1,)]))
+
+
+# ========== Named Parameter Tests ==========
+
+
+class TestNamedParams:
+    async def test_named_params_sqlite(self, sqlite_pool):
+        """Test binding a dict of named parameters on SQLite."""
+        result = await sqlite_pool.execute("SELECT :a + :b AS total", {"a": 1, "b": 2})
+        assert result.scalar() == 3
+
+    async def test_named_params_postgres(self, postgres_pool):
+        """Test binding a dict of named parameters on PostgreSQL."""
+        result = await postgres_pool.execute(
+            "SELECT :a::int8 + :b::int8 AS total", {"a": 1, "b": 2}
+        )
+        assert result.scalar() == 3
+
+    async def test_named_param_reused(self, sqlite_pool):
+        """Test that a name used twice binds the same value."""
+        result = await sqlite_pool.execute("SELECT :x * :x AS sq", {"x": 4})
+        assert result.scalar() == 16
+
+    async def test_missing_named_param(self, sqlite_pool):
+        """Test that a missing name raises KeyError."""
+        with pytest.raises(KeyError):
+            await sqlite_pool.execute("SELECT :a + :b", {"a": 1})
+
+
+# ========== Batch Parameter Tests ==========
+
+
+class TestExecuteManyParams:
+    async def test_homogeneous_batch(self, postgres_pool):
+        """Test a batch where every row binds the same Python types."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS batch_items")
+        await postgres_pool.execute(
+            "CREATE TABLE batch_items (id INT8, name TEXT, score FLOAT8, active BOOL)"
+        )
+        try:
+            rows = [[i, f"name-{i}", i / 2, i % 2 == 0] for i in range(500)]
+            async with await postgres_pool.transaction() as tx:
+                count = await tx.execute_many(
+                    "INSERT INTO batch_items VALUES ($1, $2, $3, $4)", rows
+                )
+            assert count == 500
+
+            result = await postgres_pool.execute(
+                "SELECT id, name, score, active FROM batch_items ORDER BY id"
+            )
+            assert [list(row.values()) for row in result.all()] == rows
+        finally:
+            await postgres_pool.execute("DROP TABLE IF EXISTS batch_items")
+
+    async def test_heterogeneous_batch(self, postgres_pool):
+        """Test that rows whose types differ from the first row still bind correctly."""
+
+        class Score(int):
+            pass
+
+        await postgres_pool.execute("DROP TABLE IF EXISTS batch_items")
+        await postgres_pool.execute("CREATE TABLE batch_items (id INT8, name TEXT)")
+        try:
+            rows = [[1, "a"], [None, "b"], [Score(3), None], [4, "d"]]
+            async with await postgres_pool.transaction() as tx:
+                await tx.execute_many("INSERT INTO batch_items VALUES ($1, $2)", rows)
+
+            result = await postgres_pool.execute(
+                "SELECT id, name FROM batch_items ORDER BY name NULLS LAST"
+            )
+            assert [list(row.values()) for row in result.all()] == [
+                [1, "a"],
+                [None, "b"],
+                [4, "d"],
+                [3, None],
+            ]
+        finally:
+            await postgres_pool.execute("DROP TABLE IF EXISTS batch_items")
+
+
+# ========== Pool Metrics Tests ==========
+
+
+class TestPoolMetrics:
+    async def test_metrics_count_queries_and_errors(self, postgres_pool):
+        """Test that metrics reflect executed queries, rows and the last error."""
+        before = postgres_pool.metrics()
+
+        await postgres_pool.execute("SELECT * FROM generate_series(1, 3)")
+        await postgres_pool.execute("SELECT 1")
+        with pytest.raises(Exception):
+            await postgres_pool.execute("SELECT * FROM metrics_missing_table")
+
+        after = postgres_pool.metrics()
+        assert after["queries_executed"] - before["queries_executed"] == 3
+        assert after["rows_returned"] - before["rows_returned"] == 4
+        assert after["errors"] - before["errors"] == 1
+        assert "metrics_missing_table" in after["last_error"]
+
+    async def test_metrics_sqlite_unsupported(self, sqlite_pool):
+        """Test that metrics are PostgreSQL only."""
+        with pytest.raises(Exception):
+            sqlite_pool.metrics()
+
+
+# ========== Polars Export Tests ==========
+
+
+class TestToPolars:
+    async def test_to_polars_matches_all(self, sqlite_pool):
+        """Test that the DataFrame shape and values match all()."""
+        pl = pytest.importorskip("polars")
+
+        await sqlite_pool.execute(
+            "CREATE TABLE polars_items (id INTEGER, name TEXT, score REAL, data BLOB)"
+        )
+        await sqlite_pool.execute(
+            "INSERT INTO polars_items VALUES (1, 'a', 1.5, x'00'), (2, NULL, 2, NULL), (3, 'c', NULL, x'ff')"
+        )
+
+        result = await sqlite_pool.execute("SELECT * FROM polars_items ORDER BY id")
+        df = result.to_polars()
+        rows = result.all()
+
+        assert df.shape == (3, 4)
+        assert df.columns == ["id", "name", "score", "data"]
+        assert df.row(2, named=True) == rows[2]
+        assert df["name"].null_count() == 1
+        assert df["id"].dtype == pl.Int64
+        assert df["score"].dtype == pl.Float64
+        assert df["score"].to_list() == [1.5, 2.0, None]
+
+    async def test_to_polars_empty(self, sqlite_pool):
+        """Test exporting an empty result keeps the columns."""
+        pytest.importorskip("polars")
+
+        result = await sqlite_pool.execute("SELECT 1 AS a, 'x' AS b WHERE 0")
+        df = result.to_polars()
+        assert df.shape == (0, 2)
+        assert df.columns == ["a", "b"]
+
+
+# ========== Prepared Statement Listing Tests ==========
+
+
+class TestPreparedStatements:
+    async def test_lists_cached_statements(self, postgres_pool):
+        """Test that both prepared queries appear with their SQL and names."""
+        async with await postgres_pool.transaction() as tx:
+            await tx.execute("SELECT $1::int8 AS a", [1])
+            await tx.execute("SELECT 'two' AS b")
+
+            statements = await tx.prepared_statements()
+            by_sql = {sql: (name, oids) for sql, name, oids in statements}
+
+            assert "SELECT $1::int8 AS a" in by_sql
+            assert "SELECT 'two' AS b" in by_sql
+            assert by_sql["SELECT $1::int8 AS a"][1] == [20]
+            assert by_sql["SELECT $1::int8 AS a"][0] != by_sql["SELECT 'two' AS b"][0]
+
+
+# ========== SQLite Total Changes Tests ==========
+
+
+class TestTotalChanges:
+    async def test_total_changes_accumulates(self, sqlite_pool):
+        """Test that total_changes counts every inserted, updated and deleted row."""
+        await sqlite_pool.execute("CREATE TABLE changes_items (id INTEGER PRIMARY KEY)")
+        before = await sqlite_pool.total_changes()
+
+        for i in range(3):
+            await sqlite_pool.execute("INSERT INTO changes_items (id) VALUES (?)", [i])
+        assert await sqlite_pool.total_changes() == before + 3
+
+        await sqlite_pool.execute("DELETE FROM changes_items")
+        assert await sqlite_pool.total_changes() == before + 6
+
+    async def test_total_changes_postgres_unsupported(self, postgres_pool):
+        """Test that total_changes is SQLite only."""
+        with pytest.raises(Exception):
+            await postgres_pool.total_changes()
+
+
+# ========== LISTEN/NOTIFY Tests ==========
+
+
+class TestListener:
+    async def test_notifications_survive_reconnect(self, postgres_pool):
+        """Test that a dropped listener reconnects, reports it and keeps its channels."""
+        import asyncio
+
+        listener = await postgres_pool.listen("ormkit_py_events")
+        try:
+            await postgres_pool.execute("NOTIFY ormkit_py_events, 'first'")
+            event = await asyncio.wait_for(listener.recv(), 5)
+            assert event == {
+                "type": "notification",
+                "channel": "ormkit_py_events",
+                "payload": "first",
+                "pid": event["pid"],
+            }
+
+            pid = listener.backend_pid
+            await postgres_pool.execute("SELECT pg_terminate_backend($1)", [pid])
+            event = await asyncio.wait_for(listener.recv(), 5)
+            assert event == {"type": "reconnected"}
+            assert listener.channels == ["ormkit_py_events"]
+
+            await postgres_pool.execute("NOTIFY ormkit_py_events, 'second'")
+            event = await asyncio.wait_for(listener.recv(), 5)
+            assert event["payload"] == "second"
+        finally:
+            await listener.close()
+
+    async def test_listen_and_close_during_recv(self, postgres_pool):
+        """Test that listen and close don't wait for a pending recv."""
+        import asyncio
+
+        listener = await postgres_pool.listen("ormkit_py_first")
+        try:
+            pending = asyncio.ensure_future(listener.recv())
+            await asyncio.sleep(0.1)
+
+            await asyncio.wait_for(listener.listen("ormkit_py_second"), 5)
+
+            await postgres_pool.execute("NOTIFY ormkit_py_second, 'hello'")
+            event = await asyncio.wait_for(pending, 5)
+            assert (event["channel"], event["payload"]) == ("ormkit_py_second", "hello")
+
+            pending = asyncio.ensure_future(listener.recv())
+            await asyncio.sleep(0.1)
+            await asyncio.wait_for(listener.close(), 5)
+            with pytest.raises(RuntimeError):
+                await asyncio.wait_for(pending, 5)
+        finally:
+            await listener.close()
+
+    async def test_listen_sqlite_unsupported(self, sqlite_pool):
+        """Test that LISTEN is PostgreSQL only."""
+        with pytest.raises(Exception):
+            await sqlite_pool.listen("events")
+
+
+# ========== QueryResult Group By Tests ==========
+
+
+class TestQueryResultGroupBy:
+    async def test_group_by_category(self, sqlite_pool):
+        """Test that rows are grouped by value, in result order, with NULLs under None."""
+        await sqlite_pool.execute("CREATE TABLE grouped_items (id INTEGER, category TEXT)")
+        await sqlite_pool.execute(
+            "INSERT INTO grouped_items VALUES (1, 'a'), (2, 'b'), (3, 'a'), (4, NULL), (5, NULL)"
+        )
+
+        result = await sqlite_pool.execute("SELECT * FROM grouped_items ORDER BY id")
+        groups = result.group_by("category")
+
+        assert list(groups) == ["a", "b", None]
+        assert [row["id"] for row in groups["a"]] == [1, 3]
+        assert groups["b"] == [{"id": 2, "category": "b"}]
+        assert [row["id"] for row in groups[None]] == [4, 5]
+
+    async def test_group_by_empty_and_unknown_column(self, sqlite_pool):
+        """Test grouping an empty result and a missing column."""
+        result = await sqlite_pool.execute("SELECT 1 AS a WHERE 0")
+        assert result.group_by("a") == {}
+        with pytest.raises(KeyError):
+            result.group_by("missing")
+
+
+# ========== Big Integer Parameter Tests ==========
+
+
+class TestBigIntParams:
+    async def test_big_int_sqlite(self, sqlite_pool):
+        """Test that an int beyond i64 keeps every digit in SQLite."""
+        result = await sqlite_pool.execute("SELECT ? AS v", [2**70])
+        assert result.scalar() == str(2**70)
+
+    async def test_i64_bounds_stay_integers(self, sqlite_pool):
+        """Test that ints at the i64 limits still bind as integers."""
+        result = await sqlite_pool.execute("SELECT ?, ?", [2**63 - 1, -(2**63)])
+        assert result.tuples() == [(2**63 - 1, -(2**63))]
+
+    async def test_big_int_postgres_numeric(self, postgres_pool):
+        """Test that an int beyond i64 is stored exactly in a numeric column."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS big_ints")
+        await postgres_pool.execute("CREATE TABLE big_ints (v numeric)")
+        try:
+            await postgres_pool.execute("INSERT INTO big_ints VALUES ($1)", [2**70])
+            await postgres_pool.execute("INSERT INTO big_ints VALUES ($1)", [-(2**70)])
+            result = await postgres_pool.execute("SELECT v::text FROM big_ints ORDER BY v")
+            assert result.scalars() == [str(-(2**70)), str(2**70)]
+        finally:
+            await postgres_pool.execute("DROP TABLE big_ints")
+
+    async def test_big_int_postgres_bigint_column(self, postgres_pool):
+        """Test that an int beyond i64 into a bigint column fails with a range error."""
+        with pytest.raises(Exception, match="out of range"):
+            await postgres_pool.execute("SELECT $1::bigint", [2**70])
+
+
+# ========== Pool execute_many Tests ==========
+
+
+class TestPoolExecuteMany:
+    async def test_execute_many_sqlite(self, sqlite_pool):
+        """Test inserting 100 rows returns the rows affected."""
+        await sqlite_pool.execute("CREATE TABLE many_items (id INTEGER PRIMARY KEY, name TEXT)")
+        count = await sqlite_pool.execute_many(
+            "INSERT INTO many_items VALUES (?, ?)", [[i, f"item{i}"] for i in range(100)]
+        )
+        assert count == 100
+        result = await sqlite_pool.execute("SELECT COUNT(*) FROM many_items")
+        assert result.scalar() == 100
+
+    async def test_execute_many_sqlite_rolls_back(self, sqlite_pool):
+        """Test that one constraint violation rolls back the whole batch."""
+        await sqlite_pool.execute("CREATE TABLE many_items (id INTEGER PRIMARY KEY, name TEXT)")
+        params = [[i, f"item{i}"] for i in range(100)]
+        params[50][0] = 0
+        with pytest.raises(Exception):
+            await sqlite_pool.execute_many("INSERT INTO many_items VALUES (?, ?)", params)
+        result = await sqlite_pool.execute("SELECT COUNT(*) FROM many_items")
+        assert result.scalar() == 0
+
+    async def test_execute_many_postgres_rolls_back(self, postgres_pool):
+        """Test that one constraint violation rolls back the whole batch on PostgreSQL."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS many_items")
+        await postgres_pool.execute("CREATE TABLE many_items (id INT PRIMARY KEY, name TEXT)")
+        try:
+            params = [[i, f"item{i}"] for i in range(100)]
+            params[50][0] = 0
+            with pytest.raises(Exception, match="duplicate key"):
+                await postgres_pool.execute_many("INSERT INTO many_items VALUES ($1, $2)", params)
+            result = await postgres_pool.execute("SELECT COUNT(*) FROM many_items")
+            assert result.scalar() == 0
+
+            params[50][0] = 50
+            count = await postgres_pool.execute_many(
+                "INSERT INTO many_items VALUES ($1, $2)", params
+            )
+            assert count == 100
+        finally:
+            await postgres_pool.execute("DROP TABLE many_items")
+
+
+# ========== Immediate BEGIN Tests ==========
+
+
+class TestImmediateBegin:
+    async def test_immediate_begin_opens_transaction(self, postgres_pool):
+        """Test that deferred=False has BEGIN executed before the first query."""
+        async with await postgres_pool.transaction(deferred=False) as tx:
+            assert tx.transaction_status == "in_transaction"
+            result = await tx.execute("SELECT 1 AS one")
+            assert result.scalar() == 1
+            assert tx.transaction_status == "in_transaction"
+
+    async def test_deferred_begin_waits_for_first_query(self, postgres_pool):
+        """Test that the default deferred BEGIN is not sent until the first query."""
+        async with await postgres_pool.transaction() as tx:
+            assert tx.transaction_status == "idle"
+            result = await tx.execute("SELECT 1 AS one")
+            assert result.scalar() == 1
+
+    async def test_immediate_begin_commits(self, postgres_pool):
+        """Test that writes in an immediate transaction are committed on exit."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS immediate_items")
+        await postgres_pool.execute("CREATE TABLE immediate_items (id INT)")
+        try:
+            async with await postgres_pool.transaction(deferred=False) as tx:
+                await tx.execute("INSERT INTO immediate_items VALUES (1)")
+            result = await postgres_pool.execute("SELECT COUNT(*) FROM immediate_items")
+            assert result.scalar() == 1
+        finally:
+            await postgres_pool.execute("DROP TABLE immediate_items")
+
+
+# ========== Enum Parameter Tests ==========
+
+
+class TestEnumParams:
+    async def test_int_enum_binds_as_int(self, sqlite_pool):
+        """Test that an IntEnum member binds as its integer value."""
+        import enum
+
+        class Priority(enum.IntEnum):
+            LOW = 1
+            HIGH = 2
+
+        result = await sqlite_pool.execute(
+            "SELECT ? AS v, typeof(?) AS t", [Priority.HIGH, Priority.HIGH]
+        )
+        assert result.first() == {"v": 2, "t": "integer"}
+
+    async def test_str_valued_enum_binds_as_value(self, sqlite_pool):
+        """Test that a plain Enum member binds its value, not str(member)."""
+        import enum
+
+        class Color(enum.Enum):
+            RED = "red"
+            GREEN = "green"
+
+        await sqlite_pool.execute("CREATE TABLE enum_items (color TEXT)")
+        await sqlite_pool.execute("INSERT INTO enum_items VALUES (?)", [Color.RED])
+        result = await sqlite_pool.execute(
+            "SELECT color FROM enum_items WHERE color = ?", [Color.RED]
+        )
+        assert result.scalars() == ["red"]
+
+    async def test_enum_values_of_other_types(self, sqlite_pool):
+        """Test that enum values are bound by their own type, including in batches."""
+        import enum
+
+        class Level(enum.Enum):
+            LOW = 1
+            RATIO = 0.5
+            UNSET = None
+
+        result = await sqlite_pool.execute("SELECT ?, ?, ?", [Level.LOW, Level.RATIO, Level.UNSET])
+        assert result.tuples() == [(1, 0.5, None)]
+
+        await sqlite_pool.execute("CREATE TABLE enum_levels (level)")
+        count = await sqlite_pool.execute_many(
+            "INSERT INTO enum_levels VALUES (?)", [[Level.LOW], [Level.RATIO]]
+        )
+        assert count == 2
+        result = await sqlite_pool.execute("SELECT level FROM enum_levels ORDER BY rowid")
+        assert result.scalars() == [1, 0.5]
+
+
+# ========== Result Size Estimate Tests ==========
+
+
+class TestEstimatedBytes:
+    async def test_estimate_grows_with_rows_and_strings(self, sqlite_pool):
+        """Test that the estimate scales with row count and string length."""
+        await sqlite_pool.execute("CREATE TABLE sized_items (id INTEGER, name TEXT, note TEXT)")
+        await sqlite_pool.execute_many(
+            "INSERT INTO sized_items VALUES (?, ?, ?)",
+            [[i, "x" * 10, None] for i in range(100)],
+        )
+
+        ten = await sqlite_pool.execute("SELECT * FROM sized_items LIMIT 10")
+        hundred = await sqlite_pool.execute("SELECT * FROM sized_items")
+        # 8 bytes per int + 10 per string, NULLs are free
+        assert ten.estimated_bytes() == 10 * (8 + 10)
+        assert hundred.estimated_bytes() == 10 * ten.estimated_bytes()
+
+        longer = await sqlite_pool.execute("SELECT id, name || name AS name FROM sized_items")
+        assert longer.estimated_bytes() == 100 * (8 + 20)
+
+    async def test_estimate_empty(self, sqlite_pool):
+        """Test that an empty result is estimated at zero bytes."""
+        result = await sqlite_pool.execute("SELECT 1 WHERE 0")
+        assert result.estimated_bytes() == 0
+
+
+# ========== JSON Parameter Tests ==========
+
+
+class TestJsonParams:
+    async def test_nested_none_becomes_null(self, sqlite_pool):
+        """Test that None at any depth serializes as JSON null."""
+        import json
+
+        value = {"a": None, "b": [1, None, {"c": None}], "d": (True, 1.5)}
+        result = await sqlite_pool.execute("SELECT ? AS v", [value])
+        text = result.scalar()
+        assert '"a":null' in text
+        assert json.loads(text) == {"a": None, "b": [1, None, {"c": None}], "d": [True, 1.5]}
+
+    async def test_nan_is_rejected_clearly(self, sqlite_pool):
+        """Test that NaN and Infinity fail with a message naming the value and its path."""
+        with pytest.raises(ValueError, match=r"\$\.metrics\[1\]: NaN is not valid JSON"):
+            await sqlite_pool.execute("SELECT ?", [{"metrics": [1.0, float("nan")]}])
+        with pytest.raises(ValueError, match="inf is not valid JSON"):
+            await sqlite_pool.execute("SELECT ?", [[float("inf")]])
+
+    async def test_unserializable_value_is_named(self, sqlite_pool):
+        """Test that a non-JSON value reports its type and location."""
+        with pytest.raises(ValueError, match=r"\$\.when: object of type object"):
+            await sqlite_pool.execute("SELECT ?", [{"when": object()}])
+
+
+# ========== Schema Change Cache Invalidation Tests ==========
+
+
+class TestSchemaChangeInvalidation:
+    async def test_query_after_alter_table(self, postgres_pool):
+        """Test that a cached query keeps working after the table is altered."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS ddl_items")
+        await postgres_pool.execute("CREATE TABLE ddl_items (id INT)")
+        try:
+            await postgres_pool.execute("INSERT INTO ddl_items VALUES (1)")
+            result = await postgres_pool.execute("SELECT * FROM ddl_items")
+            assert result.columns == ("id",)
+
+            await postgres_pool.execute("ALTER TABLE ddl_items ADD COLUMN name TEXT DEFAULT 'a'")
+            result = await postgres_pool.execute("SELECT * FROM ddl_items")
+            assert result.all() == [{"id": 1, "name": "a"}]
+
+            await postgres_pool.execute("ALTER TABLE ddl_items ALTER COLUMN id TYPE BIGINT")
+            result = await postgres_pool.execute("SELECT * FROM ddl_items")
+            assert result.all() == [{"id": 1, "name": "a"}]
+        finally:
+            await postgres_pool.execute("DROP TABLE ddl_items")
+
+
+# ========== Cross-Backend Transaction Tests ==========
+
+
+@pytest.fixture(params=["sqlite", "postgres"])
+def tx_pool(request):
+    """Run a test against both backends, with that backend's placeholder."""
+    if request.param == "sqlite":
+        return request.getfixturevalue("sqlite_pool"), "?"
+    return request.getfixturevalue("postgres_pool"), "$"
+
+
+class TestTransactionCrossBackend:
+    @pytest.fixture(autouse=True)
+    async def tx_items(self, tx_pool):
+        pool, _ = tx_pool
+        await pool.execute("DROP TABLE IF EXISTS tx_items")
+        await pool.execute("CREATE TABLE tx_items (id INTEGER PRIMARY KEY, name TEXT)")
+        yield
+        await pool.execute("DROP TABLE tx_items")
+
+    async def test_commit_on_exit(self, tx_pool):
+        """Test that writes are committed when the block exits normally."""
+        pool, _ = tx_pool
+        async with await pool.transaction() as tx:
+            await tx.execute("INSERT INTO tx_items VALUES (:id, :name)", {"id": 1, "name": "a"})
+            result = await tx.execute("SELECT name FROM tx_items WHERE id = :id", {"id": 1})
+            assert result.scalar() == "a"
+
+        result = await pool.execute("SELECT COUNT(*) FROM tx_items")
+        assert result.scalar() == 1
+
+    async def test_rollback_on_exception(self, tx_pool):
+        """Test that writes are rolled back when the block raises."""
+        pool, _ = tx_pool
+        with pytest.raises(ValueError):
+            async with await pool.transaction(deferred=False) as tx:
+                await tx.execute("INSERT INTO tx_items VALUES (:id, :name)", {"id": 1, "name": "a"})
+                raise ValueError("abort")
+
+        result = await pool.execute("SELECT COUNT(*) FROM tx_items")
+        assert result.scalar() == 0
+
+    async def test_execute_statement_and_many_count_rows(self, tx_pool):
+        """Test that execute_statement and execute_many return rows affected."""
+        pool, ph = tx_pool
+        async with await pool.transaction() as tx:
+            inserted = await tx.execute_many(
+                f"INSERT INTO tx_items VALUES ({ph}1, {ph}2)",
+                [[1, "a"], [2, "b"], [3, "c"]],
+            )
+            assert inserted == 3
+            updated = await tx.execute_statement(
+                "UPDATE tx_items SET name = :name WHERE id > :id", {"name": "z", "id": 1}
+            )
+            assert updated == 2
+
+        result = await pool.execute("SELECT name FROM tx_items ORDER BY id")
+        assert [row["name"] for row in result.all()] == ["a", "z", "z"]
+
+    async def test_connection_released_after_exit(self, tx_pool):
+        """Test that the transaction can't be used after the block exits."""
+        pool, _ = tx_pool
+        async with await pool.transaction() as tx:
+            await tx.execute("SELECT 1")
+
+        with pytest.raises(RuntimeError, match="Transaction not active"):
+            await tx.execute("SELECT 1")
+
+    async def test_nested_rolls_back_only_inner_writes(self, tx_pool):
+        """Test that a failing nested block discards only its own writes."""
+        pool, _ = tx_pool
+        async with await pool.transaction() as tx:
+            await tx.execute("INSERT INTO tx_items VALUES (:id, :name)", {"id": 1, "name": "a"})
+            with pytest.raises(ValueError):
+                async with tx.nested():
+                    await tx.execute(
+                        "INSERT INTO tx_items VALUES (:id, :name)", {"id": 2, "name": "b"}
+                    )
+                    raise ValueError("abort nested")
+            async with tx.nested():
+                await tx.execute("INSERT INTO tx_items VALUES (:id, :name)", {"id": 3, "name": "c"})
+
+        result = await pool.execute("SELECT id FROM tx_items ORDER BY id")
+        assert [row["id"] for row in result.all()] == [1, 3]
+
+    async def test_nested_recovers_from_failed_statement(self, tx_pool):
+        """Test that the outer transaction stays usable after a nested SQL error."""
+        pool, _ = tx_pool
+        async with await pool.transaction() as tx:
+            await tx.execute("INSERT INTO tx_items VALUES (:id, :name)", {"id": 1, "name": "a"})
+            with pytest.raises(RuntimeError):
+                async with tx.nested():
+                    await tx.execute(
+                        "INSERT INTO tx_items VALUES (:id, :name)", {"id": 1, "name": "dup"}
+                    )
+            await tx.execute("INSERT INTO tx_items VALUES (:id, :name)", {"id": 2, "name": "b"})
+
+        result = await pool.execute("SELECT COUNT(*) FROM tx_items")
+        assert result.scalar() == 2
+
+    async def test_savepoint_rollback_to_and_release(self, tx_pool):
+        """Test partial rollback with explicit savepoints."""
+        pool, _ = tx_pool
+        async with await pool.transaction() as tx:
+            await tx.savepoint("sp1")
+            await tx.execute("INSERT INTO tx_items VALUES (:id, :name)", {"id": 1, "name": "a"})
+            await tx.savepoint("sp2")
+            await tx.execute("INSERT INTO tx_items VALUES (:id, :name)", {"id": 2, "name": "b"})
+            await tx.rollback_to("sp2")
+            # The savepoint survives a rollback to it
+            await tx.execute("INSERT INTO tx_items VALUES (:id, :name)", {"id": 3, "name": "c"})
+            await tx.rollback_to("sp2")
+            await tx.release("sp1")
+
+        result = await pool.execute("SELECT id FROM tx_items ORDER BY id")
+        assert [row["id"] for row in result.all()] == [1]
+
+    async def test_rollback_to_recovers_from_failed_statement(self, tx_pool):
+        """Test that rollback_to makes the transaction usable after an SQL error."""
+        pool, _ = tx_pool
+        async with await pool.transaction() as tx:
+            await tx.execute("INSERT INTO tx_items VALUES (:id, :name)", {"id": 1, "name": "a"})
+            await tx.savepoint("before_dup")
+            with pytest.raises(RuntimeError):
+                await tx.execute(
+                    "INSERT INTO tx_items VALUES (:id, :name)", {"id": 1, "name": "dup"}
+                )
+            await tx.rollback_to("before_dup")
+            await tx.execute("INSERT INTO tx_items VALUES (:id, :name)", {"id": 2, "name": "b"})
+
+        result = await pool.execute("SELECT COUNT(*) FROM tx_items")
+        assert result.scalar() == 2
+
+    async def test_savepoint_name_is_quoted(self, tx_pool):
+        """Test that a savepoint name can't inject SQL."""
+        pool, _ = tx_pool
+        name = 'sp"; DROP TABLE tx_items; --'
+        async with await pool.transaction() as tx:
+            await tx.savepoint(name)
+            await tx.execute("INSERT INTO tx_items VALUES (:id, :name)", {"id": 1, "name": "a"})
+            await tx.rollback_to(name)
+            await tx.release(name)
+
+        result = await pool.execute("SELECT COUNT(*) FROM tx_items")
+        assert result.scalar() == 0
+
+    async def test_nested_savepoint_names_are_unique(self, tx_pool):
+        """Test that each nested() call gets its own savepoint name."""
+        pool, _ = tx_pool
+        async with await pool.transaction() as tx:
+            names = {tx.nested().name for _ in range(3)}
+        assert len(names) == 3
+
+
+# ========== Prepared Query Tests ==========
+
+
+class TestPreparedQuery:
+    async def test_execute_prepared_query(self, postgres_pool):
+        """Test that a prepared query executes repeatedly without re-parsing."""
+        query = postgres_pool.prepare("SELECT $1::int8 * 2 AS doubled")
+        result = await postgres_pool.execute(query, [1])
+        assert result.scalar() == 2
+
+        before = postgres_pool.metrics()["statements_prepared"]
+        for i in range(100):
+            result = await postgres_pool.execute(query, [i])
+            assert result.scalar() == i * 2
+        assert postgres_pool.metrics()["statements_prepared"] == before
+
+    async def test_named_params_and_row_factory(self, postgres_pool):
+        """Test that a prepared query accepts named params and a row factory."""
+        query = postgres_pool.prepare("SELECT :a::int8 + :b::int8 AS total")
+        rows = await postgres_pool.execute(query, {"a": 1, "b": 2}, row_factory="tuple")
+        assert rows == [(3,)]
+
+    async def test_prepare_is_postgres_only(self, sqlite_pool):
+        """Test that prepare is rejected on SQLite."""
+        with pytest.raises(RuntimeError, match="only supported for PostgreSQL"):
+            sqlite_pool.prepare("SELECT 1")
+
+
+# ========== Column Case Tests ==========
+
+
+class TestColumnCase:
+    async def test_upper_column_case(self):
+        """Test that column_case="upper" uppercases result column names."""
+        pool = await create_engine("sqlite::memory:", column_case="upper")
+        try:
+            result = await pool.execute("SELECT 1 AS id, 'a' AS Name")
+            assert result.columns == ("ID", "NAME")
+            assert result.first() == {"ID": 1, "NAME": "a"}
+        finally:
+            await pool.close()
+
+    async def test_lower_column_case_in_transaction(self):
+        """Test that transactions apply the pool's column casing."""
+        pool = await create_engine("sqlite::memory:", column_case="lower")
+        try:
+            async with await pool.transaction() as tx:
+                result = await tx.execute("SELECT 1 AS UserId")
+                assert result.columns == ("userid",)
+        finally:
+            await pool.close()
+
+    async def test_invalid_column_case(self):
+        """Test that an unknown column_case is rejected."""
+        with pytest.raises(ValueError, match="column_case"):
+            await create_engine("sqlite::memory:", column_case="title")
+
+
+# ========== Duplicate Column Name Tests ==========
+
+
+class TestDuplicateColumns:
+    async def test_self_join_suffixes_duplicate_columns(self, sqlite_pool):
+        """Test that a self-join keeps both columns of the same name."""
+        await sqlite_pool.execute("CREATE TABLE dup_nodes (id INTEGER, parent_id INTEGER)")
+        await sqlite_pool.execute("INSERT INTO dup_nodes VALUES (1, NULL), (2, 1)")
+
+        result = await sqlite_pool.execute(
+            "SELECT c.id, p.id FROM dup_nodes c JOIN dup_nodes p ON c.parent_id = p.id"
+        )
+        assert result.columns == ("id", "id_1")
+        assert result.all() == [{"id": 2, "id_1": 1}]
+        assert result.tuples() == [(2, 1)]
+
+    async def test_suffix_skips_existing_names(self, sqlite_pool):
+        """Test that a suffix never collides with a real column name."""
+        result = await sqlite_pool.execute("SELECT 1 AS id, 2 AS id_1, 3 AS id")
+        assert result.columns == ("id", "id_1", "id_2")
+        assert result.first() == {"id": 1, "id_1": 2, "id_2": 3}
+
+
+# ========== Timedelta Parameter Tests ==========
+
+
+class TestTimedeltaParams:
+    async def test_timedelta_in_interval_arithmetic(self, postgres_pool):
+        """Test that a timedelta binds as an interval usable in arithmetic."""
+        from datetime import timedelta
+
+        result = await postgres_pool.execute(
+            "SELECT EXTRACT(EPOCH FROM TIMESTAMP '2024-01-02 12:00' - $1)::int8 AS epoch",
+            [timedelta(days=1, hours=2)],
+        )
+        # 2024-01-01 10:00 UTC
+        assert result.scalar() == 1704103200
+
+    async def test_timedelta_with_microseconds(self, postgres_pool):
+        """Test that day and sub-second components both survive binding."""
+        from datetime import timedelta
+
+        result = await postgres_pool.execute(
+            "SELECT $1 = INTERVAL '2 days 00:00:01.5' AS same",
+            [timedelta(days=2, seconds=1, microseconds=500000)],
+        )
+        assert result.scalar() is True
+
+    async def test_interval_round_trips_as_timedelta(self, postgres_pool):
+        """Test that day/time intervals decode to timedelta and months stay text."""
+        from datetime import timedelta
+
+        delta = timedelta(days=2, seconds=1, microseconds=500000)
+        result = await postgres_pool.execute(
+            "SELECT $1::interval AS d, INTERVAL '-1 day 01:00' AS neg, "
+            "INTERVAL '1 mon 2 days' AS mon",
+            [delta],
+        )
+        row = result.first()
+        assert row["d"] == delta
+        assert row["neg"] == timedelta(days=-1, hours=1)
+        assert row["mon"] == "P1M2D"
+
+    async def test_timedelta_binds_as_text_on_sqlite(self, sqlite_pool):
+        """Test that SQLite receives a timedelta as an ISO 8601 duration."""
+        from datetime import timedelta
+
+        result = await sqlite_pool.execute("SELECT ?", [timedelta(days=1, hours=2)])
+        assert result.scalar() == "P1DT2H"
+
+
+# ========== Default Row Factory Tests ==========
+
+
+class TestDefaultRowFactory:
+    async def test_default_tuple_factory(self):
+        """Test that execute returns tuples when the pool default is "tuple"."""
+        pool = await create_engine("sqlite::memory:", default_row_factory="tuple")
+        try:
+            rows = await pool.execute("SELECT 1 AS a, 'x' AS b")
+            assert rows == [(1, "x")]
+        finally:
+            await pool.close()
+
+    async def test_per_query_factory_wins(self):
+        """Test that a per-query row_factory overrides the pool default."""
+        pool = await create_engine("sqlite::memory:", default_row_factory="tuple")
+        try:
+            rows = await pool.execute("SELECT 1 AS a", row_factory="dict")
+            assert rows == [{"a": 1}]
+        finally:
+            await pool.close()
+
+    async def test_default_callable_factory(self):
+        """Test that a callable default builds an object per row."""
+        pool = await create_engine(
+            "sqlite::memory:", default_row_factory=lambda row: row["a"] * 10
+        )
+        try:
+            assert await pool.execute("SELECT 2 AS a") == [20]
+        finally:
+            await pool.close()
+
+    async def test_result_factory_and_sessions_ignore_default(self):
+        """Test that "result" opts out of the default, as ORM sessions do."""
+        pool = await create_engine("sqlite::memory:", default_row_factory="tuple")
+        try:
+            result = await pool.execute("SELECT 1 AS a", row_factory="result")
+            assert result.first() == {"a": 1}
+
+            session = create_session(pool)
+            result = await session.execute_raw("SELECT 1 AS a")
+            assert result.first() == {"a": 1}
+        finally:
+            await pool.close()
+
+
+# ========== Nested Array and Composite Tests ==========
+
+
+class TestNestedArrays:
+    """Tests for multi-dimensional arrays and arrays of composites."""
+
+    async def test_2d_int_array(self, postgres_pool):
+        """Test that int[][] comes back as a list of lists."""
+        result = await postgres_pool.execute("SELECT ARRAY[[1, 2], [3, 4]] AS grid")
+        assert result.first()["grid"] == [[1, 2], [3, 4]]
+
+    async def test_array_of_composites(self, postgres_pool):
+        """Test that an array of ROW values comes back as a list of tuples."""
+        result = await postgres_pool.execute(
+            "SELECT ARRAY[ROW(1, 'a'), ROW(2, 'b')] AS pairs"
+        )
+        assert result.first()["pairs"] == [(1, "a"), (2, "b")]
+
+
+# ========== Database Error Detail Tests ==========
+
+
+class TestDatabaseErrorDetail:
+    """Tests for structured server error fields on DatabaseError."""
+
+    async def test_not_null_violation_fields(self, postgres_pool):
+        """Test that a not-null violation reports its sqlstate, table and column."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS err_items")
+        await postgres_pool.execute("CREATE TABLE err_items (id INTEGER, name TEXT NOT NULL)")
+        try:
+            with pytest.raises(DatabaseError) as exc_info:
+                await postgres_pool.execute("INSERT INTO err_items (id) VALUES (1)")
+            err = exc_info.value
+            assert err.sqlstate == "23502"
+            assert err.column == "name"
+            assert err.table == "err_items"
+            assert err.severity == "ERROR"
+            assert err.constraint is None
+        finally:
+            await postgres_pool.execute("DROP TABLE err_items")
+
+    async def test_raw_fields(self, postgres_pool):
+        """Test that every field the server sent is available by its code."""
+        with pytest.raises(DatabaseError) as exc_info:
+            await postgres_pool.execute("SELECT 1 FROM")
+        fields = exc_info.value.fields
+        assert fields["C"] == "42601"
+        assert int(fields["P"]) > 0
+
+        # Errors raised inside PL/pgSQL report where they happened
+        with pytest.raises(DatabaseError) as exc_info:
+            await postgres_pool.execute("DO $$ BEGIN PERFORM 1 / 0; END $$")
+        fields = exc_info.value.fields
+        assert fields["C"] == "22012"
+        assert "PL/pgSQL" in fields["W"]
+
+    async def test_database_error_is_runtime_error(self):
+        """Test that existing RuntimeError handlers still catch server errors."""
+        assert issubclass(DatabaseError, RuntimeError)
+
+    async def test_unique_violation_is_integrity_error(self, postgres_pool):
+        """Test that a unique violation raises IntegrityError with its sqlstate."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS err_items")
+        await postgres_pool.execute("CREATE TABLE err_items (id INTEGER PRIMARY KEY)")
+        try:
+            await postgres_pool.execute("INSERT INTO err_items VALUES (1)")
+            with pytest.raises(IntegrityError) as exc_info:
+                await postgres_pool.execute("INSERT INTO err_items VALUES (1)")
+            assert exc_info.value.sqlstate == "23505"
+            assert exc_info.value.constraint == "err_items_pkey"
+        finally:
+            await postgres_pool.execute("DROP TABLE err_items")
+
+    async def test_error_class_by_sqlstate(self, postgres_pool):
+        """Test that the SQLSTATE class picks the exception subclass."""
+        with pytest.raises(ProgrammingError) as exc_info:
+            await postgres_pool.execute("SELECT * FROM no_such_table")
+        assert exc_info.value.sqlstate == "42P01"
+
+        with pytest.raises(DataError) as exc_info:
+            await postgres_pool.execute("SELECT 1 / 0")
+        assert exc_info.value.sqlstate == "22012"
+
+        with pytest.raises(OperationalError) as exc_info:
+            await postgres_pool.execute(
+                "DO $$ BEGIN RAISE EXCEPTION 'deadlock' USING ERRCODE = '40P01'; END $$"
+            )
+        assert exc_info.value.sqlstate == "40P01"
+
+        # Codes outside the mapped classes raise DatabaseError itself
+        with pytest.raises(DatabaseError) as exc_info:
+            await postgres_pool.execute("DO $$ BEGIN RAISE EXCEPTION 'custom'; END $$")
+        assert type(exc_info.value) is DatabaseError
+        assert exc_info.value.sqlstate == "P0001"
+
+    async def test_integrity_violation_subclasses(self, postgres_pool):
+        """Test that common integrity violations raise their own classes."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS err_children")
+        await postgres_pool.execute("DROP TABLE IF EXISTS err_parents")
+        await postgres_pool.execute(
+            "CREATE TABLE err_parents (id INTEGER PRIMARY KEY, "
+            "qty INTEGER NOT NULL CONSTRAINT qty_positive CHECK (qty > 0))"
+        )
+        await postgres_pool.execute(
+            "CREATE TABLE err_children (id INTEGER, "
+            "parent_id INTEGER CONSTRAINT child_parent REFERENCES err_parents (id))"
+        )
+        try:
+            await postgres_pool.execute("INSERT INTO err_parents VALUES (1, 1)")
+            cases = [
+                (
+                    "INSERT INTO err_parents VALUES (1, 1)",
+                    UniqueViolationError,
+                    ("err_parents", None, "err_parents_pkey"),
+                ),
+                (
+                    "INSERT INTO err_children VALUES (1, 99)",
+                    ForeignKeyViolationError,
+                    ("err_children", None, "child_parent"),
+                ),
+                (
+                    "INSERT INTO err_parents (id) VALUES (2)",
+                    NotNullViolationError,
+                    ("err_parents", "qty", None),
+                ),
+                (
+                    "INSERT INTO err_parents VALUES (3, 0)",
+                    CheckViolationError,
+                    ("err_parents", None, "qty_positive"),
+                ),
+            ]
+            for sql, cls, fields in cases:
+                with pytest.raises(cls) as exc_info:
+                    await postgres_pool.execute(sql)
+                err = exc_info.value
+                assert isinstance(err, IntegrityError)
+                assert (err.table_name, err.column_name, err.constraint_name) == fields
+        finally:
+            await postgres_pool.execute("DROP TABLE IF EXISTS err_children")
+            await postgres_pool.execute("DROP TABLE IF EXISTS err_parents")
+
+    async def test_sqlite_integrity_violation_subclasses(self, sqlite_pool):
+        """Test that SQLite constraint failures raise the same classes as PostgreSQL."""
+        await sqlite_pool.execute("PRAGMA foreign_keys = ON")
+        await sqlite_pool.execute(
+            "CREATE TABLE err_parents (id INTEGER PRIMARY KEY, "
+            "code TEXT UNIQUE, qty INTEGER NOT NULL CHECK (qty > 0))"
+        )
+        await sqlite_pool.execute(
+            "CREATE TABLE err_children (id INTEGER, "
+            "parent_id INTEGER REFERENCES err_parents (id))"
+        )
+        await sqlite_pool.execute("INSERT INTO err_parents VALUES (1, 'a', 1)")
+        cases = [
+            ("INSERT INTO err_parents VALUES (1, 'b', 1)", UniqueViolationError),
+            ("INSERT INTO err_parents VALUES (2, 'a', 1)", UniqueViolationError),
+            ("INSERT INTO err_children VALUES (1, 99)", ForeignKeyViolationError),
+            ("INSERT INTO err_parents (id) VALUES (3)", NotNullViolationError),
+            ("INSERT INTO err_parents VALUES (4, 'd', 0)", CheckViolationError),
+        ]
+        for sql, cls in cases:
+            with pytest.raises(cls) as exc_info:
+                await sqlite_pool.execute(sql)
+            assert exc_info.value.sqlstate is None
+
+        async with await sqlite_pool.transaction() as tx:
+            with pytest.raises(UniqueViolationError):
+                await tx.execute("INSERT INTO err_parents VALUES (1, 'e', 1)")
+
+        # Other errors are unchanged
+        with pytest.raises(RuntimeError) as exc_info:
+            await sqlite_pool.execute("SELECT * FROM no_such_table")
+        assert not isinstance(exc_info.value, DatabaseError)
+
+    async def test_subclasses_are_database_errors(self):
+        """Test that every subclass is caught by DatabaseError handlers."""
+        for cls in (IntegrityError, OperationalError, ProgrammingError, DataError):
+            assert issubclass(cls, DatabaseError)
+        for cls in (
+            UniqueViolationError,
+            ForeignKeyViolationError,
+            NotNullViolationError,
+            CheckViolationError,
+        ):
+            assert issubclass(cls, IntegrityError)
+
+
+# ========== Range Type Tests ==========
+
+
+class TestRangeTypes:
+    """Tests for decoding PostgreSQL range types."""
+
+    async def test_int4range(self, postgres_pool):
+        """Test that int4range comes back as a Range with its bounds."""
+        result = await postgres_pool.execute("SELECT int4range(1, 10) AS r")
+        r = result.first()["r"]
+        assert (r.lower, r.upper, r.bounds, r.is_empty) == (1, 10, "[)", False)
+        assert repr(r) == "Range([1, 10))"
+
+    async def test_empty_and_unbounded_ranges(self, postgres_pool):
+        """Test empty ranges and infinite bounds."""
+        result = await postgres_pool.execute(
+            "SELECT 'empty'::int4range AS e, '[3,)'::int8range AS u"
+        )
+        row = result.first()
+        assert row["e"].is_empty
+        assert row["e"].lower is None and row["e"].upper is None
+        assert (row["u"].lower, row["u"].upper, row["u"].bounds) == (3, None, "[)")
+
+    async def test_tstzrange_bounds_are_aware_datetimes(self, postgres_pool):
+        """Test that tstzrange bounds decode to tz-aware datetimes."""
+        from datetime import datetime, timezone
+
+        result = await postgres_pool.execute(
+            "SELECT tstzrange('2023-01-01 00:00+00', '2023-02-01 00:00+00') AS r"
+        )
+        r = result.first()["r"]
+        assert r.lower == datetime(2023, 1, 1, tzinfo=timezone.utc)
+        assert r.upper == datetime(2023, 2, 1, tzinfo=timezone.utc)
+        assert r.lower.tzinfo is not None
+
+    async def test_tsrange_and_daterange_bounds(self, postgres_pool):
+        """Test that tsrange bounds are naive datetimes and daterange bounds dates."""
+        from datetime import date, datetime
+
+        result = await postgres_pool.execute(
+            "SELECT tsrange('2023-01-01 08:00', '2023-01-01 09:30') AS ts, "
+            "daterange('2023-01-01', NULL) AS d"
+        )
+        row = result.first()
+        assert (row["ts"].lower, row["ts"].upper) == (
+            datetime(2023, 1, 1, 8, 0),
+            datetime(2023, 1, 1, 9, 30),
+        )
+        assert row["ts"].lower.tzinfo is None
+        assert (row["d"].lower, row["d"].upper) == (date(2023, 1, 1), None)
+
+    async def test_multirange(self, postgres_pool):
+        """Test that a multirange comes back as a list of Ranges (PostgreSQL 14+)."""
+        version = (await postgres_pool.execute("SHOW server_version_num")).scalar()
+        if int(version) < 140000:
+            pytest.skip("multiranges need PostgreSQL 14")
+        result = await postgres_pool.execute(
+            "SELECT '{[1,3), [5,)}'::int4multirange AS m, '{}'::datemultirange AS e"
+        )
+        row = result.first()
+        assert [(r.lower, r.upper, r.bounds) for r in row["m"]] == [(1, 3, "[)"), (5, None, "[)")]
+        assert row["e"] == []
+
+
+# ========== SQL Quoting Tests ==========
+
+
+class TestSqlQuoting:
+    """Tests for quote_ident and quote_literal."""
+
+    @pytest.mark.parametrize("dialect", ["postgres", "sqlite"])
+    def test_quote_ident(self, dialect):
+        """Test that embedded quotes and semicolons stay inside the identifier."""
+        assert quote_ident('foo"bar', dialect) == '"foo""bar"'
+        assert quote_ident("x; DROP TABLE users", dialect) == '"x; DROP TABLE users"'
+
+    def test_quote_ident_rejects_bad_input(self):
+        """Test that unknown dialects, empty names and NUL characters are rejected."""
+        with pytest.raises(ValueError):
+            quote_ident("users", "oracle")
+        with pytest.raises(ValueError):
+            quote_ident("")
+        with pytest.raises(ValueError):
+            quote_ident("a\0b")
+
+    def test_quote_literal(self):
+        """Test that embedded single quotes are doubled."""
+        assert quote_literal("it's") == "'it''s'"
+        assert quote_literal("'; DROP TABLE users; --") == "'''; DROP TABLE users; --'"
+
+    async def test_quoted_names_round_trip(self, sqlite_pool):
+        """Test that a hostile table name is usable once quoted."""
+        table = quote_ident('odd"; name', "sqlite")
+        value = quote_literal("it's")
+        await sqlite_pool.execute(f"CREATE TABLE {table} (v TEXT)")
+        await sqlite_pool.execute(f"INSERT INTO {table} VALUES ({value})")
+        result = await sqlite_pool.execute(f"SELECT v FROM {table}")
+        assert result.scalar() == "it's"
+
+
+# ========== Statement Timeout Tests ==========
+
+
+class TestStatementTimeout:
+    """Tests for the pool-wide statement_timeout default."""
+
+    async def test_postgres_pool_timeout(self):
+        """Test that a query running past the pool timeout is cancelled."""
+        url = os.environ.get("DATABASE_URL")
+        if not url:
+            pytest.skip("DATABASE_URL not set")
+        pool = await create_engine(url, statement_timeout=0.1)
+        try:
+            with pytest.raises(DatabaseError, match="statement timeout") as exc_info:
+                await pool.execute("SELECT pg_sleep(1)")
+            assert exc_info.value.sqlstate == "57014"
+            result = await pool.execute("SELECT 1 AS one")
+            assert result.scalar() == 1
+        finally:
+            await pool.close()
+
+    async def test_sqlite_pool_timeout(self):
+        """Test that a long SQLite statement is interrupted."""
+        pool = await create_engine("sqlite::memory:", statement_timeout=0.1)
+        try:
+            with pytest.raises(RuntimeError, match="timed out"):
+                await pool.execute(
+                    "WITH RECURSIVE c(x) AS "
+                    "(SELECT 1 UNION ALL SELECT x + 1 FROM c LIMIT 1000000000) "
+                    "SELECT count(*) FROM c"
+                )
+        finally:
+            await pool.close()
+
+    async def test_rejects_non_positive_timeout(self):
+        """Test that a zero timeout is rejected rather than disabling the limit."""
+        with pytest.raises(ValueError, match="statement_timeout"):
+            await create_engine("sqlite::memory:", statement_timeout=0)
+
+
+# ========== Pinned Session Tests ==========
+
+
+class TestPinnedSession:
+    """Tests for ConnectionPool.session()."""
+
+    async def test_temp_table_visible_in_session(self, tx_pool):
+        """Test that a temp table created in a session is visible to later queries."""
+        pool, _ = tx_pool
+        async with await pool.session() as session:
+            await session.execute("CREATE TEMP TABLE session_scratch (id INTEGER)")
+            inserted = await session.execute_statement(
+                "INSERT INTO session_scratch VALUES (:a), (:b)", {"a": 1, "b": 2}
+            )
+            assert inserted == 2
+            result = await session.execute("SELECT COUNT(*) FROM session_scratch")
+            assert result.scalar() == 2
+
+    async def test_transaction_keeps_session_connection(self, tx_pool):
+        """Test that a session transaction leaves the connection with the session."""
+        pool, _ = tx_pool
+        async with await pool.session() as session:
+            await session.execute("CREATE TEMP TABLE session_tx (id INTEGER)")
+            with pytest.raises(ValueError):
+                async with await session.transaction() as tx:
+                    await tx.execute("INSERT INTO session_tx VALUES (:id)", {"id": 1})
+                    raise ValueError("abort")
+            async with await session.transaction() as tx:
+                await tx.execute("INSERT INTO session_tx VALUES (:id)", {"id": 2})
+
+            result = await session.execute("SELECT id FROM session_tx")
+            assert [row["id"] for row in result.all()] == [2]
+
+    async def test_closed_session_rejects_queries(self, sqlite_pool):
+        """Test that the session can't be used once its connection is returned."""
+        session = await sqlite_pool.session()
+        await session.close()
+        with pytest.raises(RuntimeError, match="Session is closed"):
+            await session.execute("SELECT 1")
+
+
+# ========== Row Streaming Tests ==========
+
+
+class TestRowStreaming:
+    """Tests for ConnectionPool.stream()."""
+
+    COUNT_SQL = (
+        "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < :limit) "
+        "SELECT i FROM n"
+    )
+
+    async def test_stream_yields_batches(self, sqlite_pool):
+        """Test that streamed rows arrive in batches of at most batch_size."""
+        stream = await sqlite_pool.stream(self.COUNT_SQL, {"limit": 100000}, batch_size=5000)
+        sizes = []
+        last = None
+        async for batch in stream:
+            sizes.append(len(batch))
+            last = batch.scalars()[-1]
+        assert sum(sizes) == 100000
+        assert max(sizes) == 5000
+        assert last == 100000
+
+    async def test_stream_close_releases_connection(self, sqlite_pool):
+        """Test that closing a stream early leaves the pool usable."""
+        stream = await sqlite_pool.stream(self.COUNT_SQL, {"limit": 10000}, batch_size=10)
+        first = await stream.next_batch()
+        assert first.scalars() == list(range(1, 11))
+        await stream.close()
+        assert await stream.next_batch() is None
+
+        result = await sqlite_pool.execute("SELECT 1")
+        assert result.scalar() == 1
+
+    async def test_stream_postgres_batches(self, postgres_pool):
+        """Test that PostgreSQL streams rows through a portal in batches."""
+        stream = await postgres_pool.stream(
+            "SELECT i FROM generate_series(1, $1::int) AS i", [25000], batch_size=1000
+        )
+        sizes = []
+        values = []
+        async for batch in stream:
+            sizes.append(len(batch))
+            values.extend(batch.scalars())
+        assert sizes == [1000] * 25
+        assert values == list(range(1, 25001))
+
+    async def test_stream_postgres_close_early(self, postgres_pool):
+        """Test that closing a PostgreSQL stream early ends its transaction."""
+        stream = await postgres_pool.stream(
+            "SELECT i FROM generate_series(1, 100000) AS i", batch_size=10
+        )
+        first = await stream.next_batch()
+        assert first.scalars() == list(range(1, 11))
+        await stream.close()
+        assert await stream.next_batch() is None
+
+        result = await postgres_pool.execute("SELECT now() = statement_timestamp() AS fresh")
+        assert result.first()["fresh"] is True
+
+    async def test_stream_postgres_error(self, postgres_pool):
+        """Test that a failing streamed query raises and leaves the pool usable."""
+        with pytest.raises(Exception):
+            stream = await postgres_pool.stream(
+                "SELECT 1 / (i - 5) FROM generate_series(1, 10) AS i", batch_size=2
+            )
+            async for _ in stream:
+                pass
+
+        result = await postgres_pool.execute("SELECT 1")
+        assert result.scalar() == 1
+
+
+# ========== Plain Object Mapping Tests ==========
+
+
+class Point:
+    def __init__(self, x, y, label="origin"):
+        self.x = x
+        self.y = y
+        self.label = label
+
+
+class TestToObjects:
+    """Tests for QueryResult.to_objects()."""
+
+    async def test_rows_become_objects(self, sqlite_pool):
+        """Test that each row is passed to __init__ as keyword arguments."""
+        result = await sqlite_pool.execute(
+            "SELECT 1 AS x, 2 AS y, 'a' AS label UNION ALL SELECT 3, 4, 'b'"
+        )
+        points = result.to_objects(Point)
+        assert [(p.x, p.y, p.label) for p in points] == [(1, 2, "a"), (3, 4, "b")]
+
+    async def test_missing_column_uses_default(self, sqlite_pool):
+        """Test that a parameter with a default may have no column."""
+        result = await sqlite_pool.execute("SELECT 1 AS x, 2 AS y")
+        (point,) = result.to_objects(Point)
+        assert point.label == "origin"
+
+    async def test_extra_columns_strict(self, sqlite_pool):
+        """Test that strict mode rejects columns __init__ doesn't take."""
+        result = await sqlite_pool.execute("SELECT 1 AS x, 2 AS y, 3 AS z")
+        with pytest.raises(TypeError):
+            result.to_objects(Point)
+
+        (point,) = result.to_objects(Point, strict=False)
+        assert (point.x, point.y) == (1, 2)
+        assert not hasattr(point, "z")
+
+    async def test_missing_required_column(self, sqlite_pool):
+        """Test that a required parameter without a column fails either way."""
+        result = await sqlite_pool.execute("SELECT 1 AS x")
+        with pytest.raises(TypeError):
+            result.to_objects(Point, strict=False)
+
+
+# ========== Bytes Format Tests ==========
+
+
+class TestBytesFormat:
+    """Tests for the bytes_format option."""
+
+    @staticmethod
+    def blob_sql(placeholder):
+        if placeholder == "?":
+            return "SELECT X'DEADBEEF' AS data"
+        return "SELECT '\\xdeadbeef'::bytea AS data"
+
+    async def test_formats_per_query(self, tx_pool):
+        """Test that each format converts a known byte string on both backends."""
+        pool, placeholder = tx_pool
+        sql = self.blob_sql(placeholder)
+
+        raw = await pool.execute(sql)
+        assert raw.scalar() == b"\xde\xad\xbe\xef"
+        hexed = await pool.execute(sql, bytes_format="hex")
+        assert hexed.scalar() == "deadbeef"
+        b64 = await pool.execute(sql, bytes_format="base64")
+        assert b64.first() == {"data": "3q2+7w=="}
+
+    async def test_pool_default(self):
+        """Test that the pool-wide format applies unless a query overrides it."""
+        pool = await create_engine("sqlite::memory:", bytes_format="hex")
+        try:
+            result = await pool.execute("SELECT X'00FF' AS data")
+            assert result.tuples() == [("00ff",)]
+            result = await pool.execute("SELECT X'00FF' AS data", bytes_format="raw")
+            assert result.scalar() == b"\x00\xff"
+            async with await pool.transaction() as tx:
+                result = await tx.execute("SELECT X'00FF' AS data")
+                assert result.scalar() == "00ff"
+        finally:
+            await pool.close()
+
+    async def test_invalid_format(self, sqlite_pool):
+        """Test that an unknown format is rejected."""
+        with pytest.raises(ValueError, match="bytes_format"):
+            await sqlite_pool.execute("SELECT 1", bytes_format="octal")
+
+
+# ========== Connection Id Tests ==========
+
+
+class TestConnectionId:
+    """Tests for connection ids on sessions, transactions and errors."""
+
+    async def test_concurrent_sessions_have_distinct_ids(self, tx_pool):
+        """Test that two sessions held at once report different connections."""
+        pool, _ = tx_pool
+        async with await pool.session() as first, await pool.session() as second:
+            assert isinstance(first.connection_id, int)
+            assert first.connection_id != second.connection_id
+            async with await first.transaction() as tx:
+                assert tx.connection_id == first.connection_id
+
+    async def test_server_error_carries_connection_id(self, postgres_pool):
+        """Test that a server error names the connection it came from."""
+        async with await postgres_pool.session() as session:
+            with pytest.raises(DatabaseError) as exc_info:
+                await session.execute("SELECT * FROM connection_id_missing")
+            assert exc_info.value.connection_id == session.connection_id
+
+
+# ========== Insert If Absent Tests ==========
+
+
+class TestInsertIfAbsent:
+    """Tests for ConnectionPool.insert_if_absent()."""
+
+    @pytest.fixture(autouse=True)
+    async def absent_items(self, tx_pool):
+        pool, _ = tx_pool
+        await pool.execute("DROP TABLE IF EXISTS absent_items")
+        await pool.execute(
+            "CREATE TABLE absent_items (id INTEGER PRIMARY KEY, sku TEXT UNIQUE, name TEXT NOT NULL)"
+        )
+        yield
+        await pool.execute("DROP TABLE absent_items")
+
+    async def test_duplicate_is_skipped(self, tx_pool):
+        """Test that a second insert with the same key reports nothing inserted."""
+        pool, _ = tx_pool
+        row = {"id": 1, "sku": "A-1", "name": "first"}
+        assert await pool.insert_if_absent("absent_items", row, ["sku"]) is True
+        duplicate = {"id": 2, "sku": "A-1", "name": "second"}
+        assert await pool.insert_if_absent("absent_items", duplicate, ["sku"]) is False
+
+        result = await pool.execute("SELECT id, name FROM absent_items")
+        assert result.tuples() == [(1, "first")]
+
+    async def test_other_constraint_errors_still_raise(self, tx_pool):
+        """Test that only conflicts on the given columns are ignored."""
+        pool, _ = tx_pool
+        with pytest.raises(RuntimeError):
+            await pool.insert_if_absent("absent_items", {"id": 1, "sku": "B-1", "name": None}, ["sku"])
+
+    async def test_requires_values_and_conflict_columns(self, sqlite_pool):
+        """Test that empty values or conflict columns are rejected."""
+        with pytest.raises(ValueError, match="column value"):
+            await sqlite_pool.insert_if_absent("absent_items", {}, ["sku"])
+        with pytest.raises(ValueError, match="conflict column"):
+            await sqlite_pool.insert_if_absent("absent_items", {"sku": "C-1"}, [])
+
+
+# ========== Transaction Batch Tests ==========
+
+
+class TestTransactionExecuteMany:
+    """Tests for Transaction.execute_many() reusing one statement."""
+
+    @pytest.fixture(autouse=True)
+    async def batch_items(self, tx_pool):
+        pool, _ = tx_pool
+        await pool.execute("DROP TABLE IF EXISTS batch_items")
+        await pool.execute("CREATE TABLE batch_items (id INTEGER PRIMARY KEY, name TEXT)")
+        yield
+        await pool.execute("DROP TABLE batch_items")
+
+    async def test_large_batch_as_first_statement(self, tx_pool):
+        """Test that a batch works as the first statement of a deferred transaction."""
+        pool, placeholder = tx_pool
+        sql = (
+            "INSERT INTO batch_items VALUES (?, ?)"
+            if placeholder == "?"
+            else "INSERT INTO batch_items VALUES ($1, $2)"
+        )
+        rows = [[i, f"item {i}"] for i in range(1000)]
+        async with await pool.transaction() as tx:
+            assert await tx.execute_many(sql, rows) == 1000
+            assert await tx.execute_many(sql, []) == 0
+
+        result = await pool.execute("SELECT COUNT(*) FROM batch_items")
+        assert result.scalar() == 1000
+
+    async def test_batch_prepares_once(self, postgres_pool):
+        """Test that a 1000-row batch parses its statement a single time."""
+        before = postgres_pool.metrics()["statements_prepared"]
+        async with await postgres_pool.transaction() as tx:
+            await tx.execute_many(
+                "INSERT INTO batch_items VALUES ($1, $2)",
+                [[i, f"item {i}"] for i in range(1000)],
+            )
+        assert postgres_pool.metrics()["statements_prepared"] - before == 1
+
+
+# ========== Parameter Count Tests ==========
+
+
+class TestParameterCount:
+    """Tests for rejecting missing parameters before they reach the server."""
+
+    async def test_missing_params_raise_client_error(self, postgres_pool):
+        """Test that omitting params for a parameterized query names the problem."""
+        with pytest.raises(RuntimeError, match="expects 1 parameter\\(s\\) but none were supplied"):
+            await postgres_pool.execute("SELECT $1::int4 AS n")
+
+        result = await postgres_pool.execute("SELECT $1::int4 AS n", [5])
+        assert result.scalar() == 5
+
+
+# ========== Foreign Key Check Tests ==========
+
+
+class TestForeignKeyCheck:
+    """Tests for ConnectionPool.foreign_key_check()."""
+
+    async def test_orphan_row_reported(self, sqlite_pool):
+        """Test that a child row loaded without enforcement is reported."""
+        await sqlite_pool.execute("CREATE TABLE fk_parents (id INTEGER PRIMARY KEY)")
+        await sqlite_pool.execute(
+            "CREATE TABLE fk_children (id INTEGER PRIMARY KEY, "
+            "parent_id INTEGER REFERENCES fk_parents(id))"
+        )
+        await sqlite_pool.execute("INSERT INTO fk_parents VALUES (1)")
+        await sqlite_pool.execute("INSERT INTO fk_children VALUES (10, 1), (11, 99)")
+
+        violations = await sqlite_pool.foreign_key_check()
+        assert len(violations) == 1
+        violation = violations[0]
+        assert violation.table == "fk_children"
+        assert violation.rowid == 11
+        assert violation.parent == "fk_parents"
+        assert violation.fk_index == 0
+
+        assert len(await sqlite_pool.foreign_key_check("fk_children")) == 1
+        assert await sqlite_pool.foreign_key_check("fk_parents") == []
+
+    async def test_postgres_unsupported(self, postgres_pool):
+        """Test that PostgreSQL rejects the SQLite-only check."""
+        with pytest.raises(RuntimeError, match="only supported for SQLite"):
+            await postgres_pool.foreign_key_check()
+
+
+# ========== Time With Time Zone Tests ==========
+
+
+class TestTimeTz:
+    """Tests for decoding timetz values with their offset."""
+
+    async def test_offset_preserved(self, postgres_pool):
+        """Test that timetz comes back as a tz-aware time keeping its offset."""
+        import datetime
+
+        result = await postgres_pool.execute(
+            "SELECT '12:00:00.25+02'::timetz AS t, '08:30:00-05:30'::timetz AS w"
+        )
+        row = result.first()
+        assert row["t"] == datetime.time(
+            12, 0, 0, 250000, tzinfo=datetime.timezone(datetime.timedelta(hours=2))
+        )
+        assert row["t"].utcoffset() == datetime.timedelta(hours=2)
+        assert row["w"].utcoffset() == -datetime.timedelta(hours=5, minutes=30)
+
+    async def test_end_of_day(self, postgres_pool):
+        """Test that 24:00, which datetime.time can't hold, comes back as text."""
+        result = await postgres_pool.execute("SELECT '24:00:00+01'::timetz AS t")
+        assert result.scalar() == "24:00:00+01:00"
+
+    async def test_plain_time_is_naive_time(self, postgres_pool):
+        """Test that time without time zone comes back as a naive datetime.time."""
+        import datetime
+
+        result = await postgres_pool.execute(
+            "SELECT '04:05:06.000001'::time AS t, '24:00:00'::time AS end_of_day"
+        )
+        row = result.first()
+        assert row["t"] == datetime.time(4, 5, 6, 1)
+        assert row["t"].tzinfo is None
+        assert row["end_of_day"] == "24:00:00"
+
+
+# ========== Statement Cache Memory Tests ==========
+
+
+class TestStatementCacheMemoryLimit:
+    """Tests for the statement_cache_memory_limit pool option."""
+
+    async def test_distinct_queries_under_tiny_limit(self):
+        """Test that many distinct queries still run when caches must shrink."""
+        url = os.environ.get("DATABASE_URL")
+        if not url:
+            pytest.skip("DATABASE_URL not set")
+        pool = await create_engine(url, max_connections=1, statement_cache_memory_limit=512)
+        try:
+            for i in range(30):
+                result = await pool.execute(f"SELECT {i}::int4 AS value_{i}")
+                assert result.scalar() == i
+            prepared = await pool.execute("SELECT count(*) AS n FROM pg_prepared_statements")
+            assert prepared.scalar() < 30
+        finally:
+            await pool.close()
+
+
+# ========== CSV Import Tests ==========
+
+
+class TestImportCsv:
+    """Tests for ConnectionPool.import_csv()."""
+
+    @pytest.fixture(autouse=True)
+    async def csv_items(self, tx_pool):
+        pool, _ = tx_pool
+        await pool.execute("DROP TABLE IF EXISTS csv_items")
+        await pool.execute(
+            "CREATE TABLE csv_items (id INTEGER, name TEXT, price NUMERIC(10, 2), "
+            "weight DOUBLE PRECISION, active BOOLEAN)"
+        )
+        yield
+        await pool.execute("DROP TABLE csv_items")
+
+    async def test_typed_import(self, tx_pool, tmp_path):
+        """Test that fields arrive with their column types."""
+        pool, _ = tx_pool
+        path = tmp_path / "items.csv"
+        path.write_text(
+            "id,name,active,price,weight\n"
+            '1,"Widget, large",true,12.50,1.5\n'
+            '2,"",false,,\n'
+            "3,,1,0.99,2\n"
+        )
+
+        assert await pool.import_csv("csv_items", path) == 3
+
+        result = await pool.execute(
+            "SELECT id, name, price, weight, active FROM csv_items ORDER BY id"
+        )
+        rows = result.tuples()
+        assert [row[0] for row in rows] == [1, 2, 3]
+        assert [row[1] for row in rows] == ["Widget, large", "", None]
+        assert rows[0][3] == 1.5 and rows[1][3] is None
+        assert [bool(row[4]) for row in rows] == [True, False, True]
+        assert float(rows[0][2]) == 12.5 and rows[1][2] is None
+
+    async def test_without_header(self, tx_pool, tmp_path):
+        """Test that headerless files map fields to the table's columns in order."""
+        pool, _ = tx_pool
+        path = tmp_path / "items.csv"
+        path.write_text("7,bolt,0.10,0.01,false\n")
+
+        assert await pool.import_csv("csv_items", str(path), header=False) == 1
+        result = await pool.execute("SELECT id, name FROM csv_items")
+        assert result.tuples() == [(7, "bolt")]
+
+    async def test_bad_value_reports_line_and_column(self, tx_pool, tmp_path):
+        """Test that a conversion error names the line and column."""
+        pool, _ = tx_pool
+        path = tmp_path / "items.csv"
+        path.write_text("id,name\n1,ok\nx2,bad\n")
+
+        with pytest.raises(ValueError, match='line 3, column "id": cannot convert "x2" to integer'):
+            await pool.import_csv("csv_items", path)
+
+    async def test_unknown_header_column(self, tx_pool, tmp_path):
+        """Test that a header naming a missing column is rejected."""
+        pool, _ = tx_pool
+        path = tmp_path / "items.csv"
+        path.write_text("id,colour\n1,red\n")
+
+        with pytest.raises(ValueError, match='column "colour" is not in table'):
+            await pool.import_csv("csv_items", path)
+
+    async def test_large_file_in_chunks(self, tx_pool, tmp_path):
+        """Test that a file spanning many chunks is read ahead and loaded in full."""
+        pool, _ = tx_pool
+        path = tmp_path / "items.csv"
+        path.write_text("id,name\n" + "".join(f"{i},item {i}\n" for i in range(5500)))
+
+        assert await pool.import_csv("csv_items", path) == 5500
+        result = await pool.execute("SELECT COUNT(*), MAX(id) FROM csv_items")
+        assert result.tuples() == [(5500, 5499)]
+
+    async def test_empty_and_missing_files(self, tx_pool, tmp_path):
+        """Test that an empty file loads nothing and a missing one raises OSError."""
+        pool, _ = tx_pool
+        path = tmp_path / "empty.csv"
+        path.write_text("")
+        assert await pool.import_csv("csv_items", path) == 0
+
+        with pytest.raises(OSError, match="cannot open"):
+            await pool.import_csv("csv_items", tmp_path / "missing.csv")
+
+
+# ========== SQL-Level PREPARE Tests ==========
+
+
+class TestSqlPrepare:
+    """Tests for SQL-level PREPARE/EXECUTE/DEALLOCATE through execute()."""
+
+    async def test_prepare_execute_sequence(self, postgres_pool):
+        """Test that a named statement can be prepared, run and re-prepared."""
+        async with await postgres_pool.transaction() as tx:
+            await tx.execute("PREPARE ormkit_sq (int) AS SELECT $1 + 1 AS n")
+            result = await tx.execute("EXECUTE ormkit_sq(41)")
+            assert result.scalar() == 42
+
+            # A different result type under the same EXECUTE text
+            await tx.execute("DEALLOCATE ormkit_sq")
+            await tx.execute("PREPARE ormkit_sq (int) AS SELECT 'n' || $1 AS s")
+            result = await tx.execute("EXECUTE ormkit_sq(41)")
+            assert result.scalar() == "n41"
+            await tx.execute("DEALLOCATE ormkit_sq")
+
+    async def test_bind_parameters_rejected(self, postgres_pool):
+        """Test that arguments must be written into the EXECUTE text."""
+        async with await postgres_pool.transaction() as tx:
+            await tx.execute("PREPARE ormkit_sq2 (int) AS SELECT $1")
+            with pytest.raises(RuntimeError, match="expects 0 parameter"):
+                await tx.execute("EXECUTE ormkit_sq2($1)", [1])
+
+
+# ========== Uncached Transaction Tests ==========
+
+
+class TestUncachedTransaction:
+    """Tests for transaction(cache_statements=False)."""
+
+    async def test_statement_cache_unchanged(self, postgres_pool):
+        """Test that distinct statements in the transaction aren't cached."""
+        async with await postgres_pool.transaction(cache_statements=False) as tx:
+            before = await tx.prepared_statements()
+            for i in range(50):
+                result = await tx.execute(f"SELECT $1::int + {i} AS n", [1])
+                assert result.scalar() == 1 + i
+            assert await tx.prepared_statements() == before
+
+    async def test_statements_and_batches(self, postgres_pool):
+        """Test that execute_statement and execute_many run uncached too."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS uncached_tx_test")
+        await postgres_pool.execute("CREATE TABLE uncached_tx_test (id int)")
+        try:
+            async with await postgres_pool.transaction(cache_statements=False) as tx:
+                before = await tx.prepared_statements()
+                assert await tx.execute_statement(
+                    "INSERT INTO uncached_tx_test VALUES ($1)", [1]
+                ) == 1
+                assert await tx.execute_many(
+                    "INSERT INTO uncached_tx_test VALUES ($1)", [[2], [3]]
+                ) == 2
+                assert await tx.prepared_statements() == before
+            result = await postgres_pool.execute("SELECT count(*) FROM uncached_tx_test")
+            assert result.scalar() == 3
+        finally:
+            await postgres_pool.execute("DROP TABLE IF EXISTS uncached_tx_test")
+
+
+# ========== String Interning Tests ==========
+
+
+LOW_CARDINALITY_SQL = """
+    WITH RECURSIVE n(i) AS (SELECT 0 UNION ALL SELECT i + 1 FROM n WHERE i < 9999)
+    SELECT i, CASE i % 3
+        WHEN 0 THEN 'awaiting payment confirmation'
+        WHEN 1 THEN 'shipped to the customer address'
+        ELSE 'returned and refunded in full'
+    END AS status
+    FROM n
+"""
+
+
+class TestInternStrings:
+    async def test_interning_shrinks_low_cardinality_results(self):
+        """Test that 10000 rows of 3 distinct strings store each string once."""
+        plain = await create_engine("sqlite::memory:")
+        interned = await create_engine("sqlite::memory:", intern_strings=True)
+        try:
+            plain_result = await plain.execute(LOW_CARDINALITY_SQL)
+            interned_result = await interned.execute(LOW_CARDINALITY_SQL)
+
+            assert interned_result.tuples() == plain_result.tuples()
+            # Ids are 8 bytes each either way; the strings collapse to 3
+            assert plain_result.estimated_bytes() > 300_000
+            assert interned_result.estimated_bytes() < 10000 * 8 + 100
+        finally:
+            await plain.close()
+            await interned.close()
+
+    async def test_interning_in_transaction(self):
+        """Test that transactions return interned strings as plain str."""
+        pool = await create_engine("sqlite::memory:", intern_strings=True)
+        try:
+            async with await pool.transaction() as tx:
+                result = await tx.execute("SELECT 'a' AS x UNION ALL SELECT 'a'")
+                assert result.scalars() == ["a", "a"]
+                assert result.all() == [{"x": "a"}, {"x": "a"}]
+        finally:
+            await pool.close()
+
+
+# ========== Typed Parameter Tests ==========
+
+
+class TestTypedParams:
+    async def test_int4_binds_as_int4(self, postgres_pool):
+        """Test that ormkit.Int4 sends an int4 parameter where an int sends int8."""
+        async with await postgres_pool.transaction() as tx:
+            result = await tx.execute("SELECT pg_typeof($1)::text AS t", [Int4(5)])
+            assert result.scalar() == "integer"
+
+            statements = await tx.prepared_statements()
+            oids = {sql: oids for sql, _, oids in statements}
+            assert oids["SELECT pg_typeof($1)::text AS t"] == [23]
+
+        result = await postgres_pool.execute("SELECT pg_typeof($1)::text AS t", [5])
+        assert result.scalar() == "bigint"
+
+    async def test_wrappers_rebind_reused_statements(self, postgres_pool):
+        """Test that batches and prepared queries re-prepare for wrapped types."""
+        sql = "SELECT pg_typeof($1)::text AS t"
+        async with await postgres_pool.transaction() as tx:
+            await tx.execute_many(sql, [[1]])
+            assert await tx.execute_many(sql, [[Int4(1)], [Int4(2)]]) == 2
+            statements = await tx.prepared_statements()
+            oids = {sql: oids for sql, _, oids in statements}
+            assert oids[sql] == [23]
+
+        query = postgres_pool.prepare(sql)
+        assert (await postgres_pool.execute(query, [5])).scalar() == "bigint"
+        assert (await postgres_pool.execute(query, [Int4(5)])).scalar() == "integer"
+        assert (await postgres_pool.execute(query, [5])).scalar() == "bigint"
+
+    async def test_jsonb_and_numeric(self, postgres_pool):
+        """Test that Jsonb and Numeric bind with their named types."""
+        result = await postgres_pool.execute(
+            "SELECT jsonb_typeof($1) AS j, pg_typeof($2)::text AS n",
+            [Jsonb("plain text"), Numeric("12.50")],
+        )
+        assert result.first() == {"j": "string", "n": "numeric"}
+
+    async def test_wrappers_on_sqlite(self, sqlite_pool):
+        """Test that wrapped values bind by their storage class on SQLite."""
+        result = await sqlite_pool.execute(
+            "SELECT ? AS n, typeof(?) AS t, ? AS j", [Int4(5), Int4(6), Jsonb({"a": 1})]
+        )
+        assert result.first() == {"n": 5, "t": "integer", "j": '{"a":1}'}
+
+    def test_wrapper_repr_and_range(self):
+        """Test that wrappers keep their value and reject out-of-range ints."""
+        assert Int4(5).value == 5
+        assert repr(Int4(5)) == "Int4(5)"
+        with pytest.raises(OverflowError):
+            Int4(2**31)
+
+
+# ========== Blocking Query Tests ==========
+
+
+class TestBlockingQueries:
+    async def test_reports_lock_holder(self, postgres_pool):
+        """Test that a query waiting on a row lock is paired with the holder."""
+        import asyncio
+
+        await postgres_pool.execute("DROP TABLE IF EXISTS lock_items")
+        await postgres_pool.execute("CREATE TABLE lock_items (id INT PRIMARY KEY, n INT)")
+        await postgres_pool.execute("INSERT INTO lock_items VALUES (1, 0)")
+        try:
+            async with await postgres_pool.transaction() as holder:
+                holder_pid = (await holder.execute("SELECT pg_backend_pid() AS pid")).scalar()
+                await holder.execute("UPDATE lock_items SET n = 1 WHERE id = 1")
+
+                waiter = asyncio.create_task(
+                    postgres_pool.execute("UPDATE lock_items SET n = 2 WHERE id = 1")
+                )
+                for _ in range(50):
+                    blocked = [
+                        b for b in await postgres_pool.blocking_queries()
+                        if b.blocking_pid == holder_pid
+                    ]
+                    if blocked:
+                        break
+                    await asyncio.sleep(0.1)
+
+                assert len(blocked) == 1
+                assert "SET n = 2" in blocked[0].blocked_query
+                assert "SET n = 1" in blocked[0].blocking_query
+                assert blocked[0].lock_type == "transactionid"
+
+            await asyncio.wait_for(waiter, 5)
+            assert await postgres_pool.blocking_queries() == []
+        finally:
+            await postgres_pool.execute("DROP TABLE lock_items")
+
+    async def test_sqlite_unsupported(self, sqlite_pool):
+        """Test that SQLite pools reject blocking_queries."""
+        with pytest.raises(RuntimeError, match="only supported for PostgreSQL"):
+            await sqlite_pool.blocking_queries()
+
+
+# ========== Typed Scalars Tests ==========
+
+
+class TestScalarsTyped:
+    async def test_int_column_yields_int64_array(self, sqlite_pool):
+        """Test that an integer column comes back as array('q')."""
+        import array
+
+        result = await sqlite_pool.execute(
+            "WITH RECURSIVE n(i) AS (SELECT 1 UNION ALL SELECT i + 1 FROM n WHERE i < 5) "
+            "SELECT i * 1000000000000 AS id FROM n"
+        )
+        ids = result.scalars_typed()
+        assert isinstance(ids, array.array)
+        assert ids.typecode == "q"
+        assert ids.tolist() == [i * 1000000000000 for i in range(1, 6)]
+
+    async def test_float_column_yields_double_array(self, sqlite_pool):
+        """Test that a float column comes back as array('d')."""
+        import array
+
+        result = await sqlite_pool.execute("SELECT 1.5 AS x UNION ALL SELECT -2.25")
+        values = result.scalars_typed()
+        assert isinstance(values, array.array)
+        assert values.typecode == "d"
+        assert values.tolist() == [1.5, -2.25]
+
+    async def test_falls_back_to_list(self, sqlite_pool):
+        """Test that NULLs, mixed types and text fall back to a list."""
+        for sql, expected in [
+            ("SELECT 1 AS x UNION ALL SELECT NULL", [1, None]),
+            ("SELECT 1 AS x UNION ALL SELECT 2.5", [1, 2.5]),
+            ("SELECT 'a' AS x", ["a"]),
+            ("SELECT 1 AS x WHERE 0", []),
+        ]:
+            result = await sqlite_pool.execute(sql)
+            assert result.scalars_typed() == expected
+
+
+# ========== Decimal Tests ==========
+
+
+class TestDecimal:
+    """NUMERIC columns come back as decimal.Decimal."""
+
+    async def test_numeric_column_is_decimal(self, postgres_pool):
+        from decimal import Decimal
+
+        await postgres_pool.execute("DROP TABLE IF EXISTS decimal_test")
+        await postgres_pool.execute(
+            "CREATE TABLE decimal_test (id int, amount numeric(12, 2))"
+        )
+        try:
+            await postgres_pool.execute(
+                "INSERT INTO decimal_test VALUES ($1, $2)", [1, Decimal("12345.67")]
+            )
+            result = await postgres_pool.execute(
+                "SELECT amount FROM decimal_test WHERE id = $1", [1]
+            )
+            amount = result.scalar()
+            assert isinstance(amount, Decimal)
+            assert amount == Decimal("12345.67")
+            assert str(amount) == "12345.67"
+        finally:
+            await postgres_pool.execute("DROP TABLE IF EXISTS decimal_test")
+
+    async def test_decimal_param_keeps_scale(self, postgres_pool):
+        from decimal import Decimal
+
+        result = await postgres_pool.execute(
+            "SELECT $1::numeric AS a, $2::numeric AS b",
+            [Decimal("1.500"), Decimal("1E+3")],
+        )
+        row = result.first()
+        assert str(row["a"]) == "1.500"
+        assert row["b"] == Decimal("1000")
+
+    async def test_special_values(self, postgres_pool):
+        from decimal import Decimal
+
+        result = await postgres_pool.execute(
+            "SELECT 'NaN'::numeric AS a, $1::numeric AS b", [Decimal("NaN")]
+        )
+        row = result.first()
+        assert row["a"].is_nan()
+        assert row["b"].is_nan()
+
+
+# ========== Unknown Type Tests ==========
+
+
+class TestUnknownType:
+    """Uncast string literals (type `unknown`) decode as str."""
+
+    async def test_uncast_literal_is_str(self, postgres_pool):
+        result = await postgres_pool.execute("SELECT 'hello' AS greeting")
+        assert result.scalar() == "hello"
+
+    async def test_uncast_literal_with_params(self, postgres_pool):
+        result = await postgres_pool.execute("SELECT 'hello' AS greeting, $1::int AS n", [1])
+        assert result.first() == {"greeting": "hello", "n": 1}
+
+
+# ========== Timestamp Tests ==========
+
+
+class TestTimestamps:
+    """TIMESTAMP and TIMESTAMPTZ columns come back as datetime.datetime."""
+
+    async def test_timestamp_is_naive(self, postgres_pool):
+        from datetime import datetime
+
+        result = await postgres_pool.execute(
+            "SELECT '2024-03-05 06:07:08.123456'::timestamp AS ts"
+        )
+        assert result.scalar() == datetime(2024, 3, 5, 6, 7, 8, 123456)
+
+    async def test_timestamptz_is_utc(self, postgres_pool):
+        from datetime import datetime, timezone
+
+        result = await postgres_pool.execute(
+            "SELECT '2024-03-05 08:07:08+02'::timestamptz AS ts"
+        )
+        value = result.scalar()
+        assert value.tzinfo is timezone.utc
+        assert value == datetime(2024, 3, 5, 6, 7, 8, tzinfo=timezone.utc)
+
+    async def test_before_epoch(self, postgres_pool):
+        from datetime import datetime
+
+        result = await postgres_pool.execute(
+            "SELECT '1999-12-31 23:59:59.5'::timestamp AS ts"
+        )
+        assert result.scalar() == datetime(1999, 12, 31, 23, 59, 59, 500000)
+
+    async def test_infinity(self, postgres_pool):
+        from datetime import datetime, timezone
+
+        result = await postgres_pool.execute(
+            "SELECT 'infinity'::timestamp AS a, '-infinity'::timestamptz AS b"
+        )
+        row = result.first()
+        assert row["a"] == datetime.max
+        assert row["b"] == datetime.min.replace(tzinfo=timezone.utc)
+
+
+# ========== Date Tests ==========
+
+
+class TestDates:
+    """DATE columns come back as datetime.date; date params bind as DATE."""
+
+    async def test_date_column(self, postgres_pool):
+        from datetime import date
+
+        result = await postgres_pool.execute("SELECT '2024-03-15'::date AS d")
+        assert result.scalar() == date(2024, 3, 15)
+
+    async def test_pre_2000_round_trip(self, postgres_pool):
+        from datetime import date
+
+        for value in [date(1999, 12, 31), date(1970, 1, 1), date(1, 1, 1)]:
+            result = await postgres_pool.execute("SELECT $1::date AS d", [value])
+            assert result.scalar() == value
+
+    async def test_date_param_compares_with_date_column(self, postgres_pool):
+        from datetime import date
+
+        result = await postgres_pool.execute(
+            "SELECT '1999-12-31'::date = $1 AS same", [date(1999, 12, 31)]
+        )
+        assert result.scalar() is True
+
+    async def test_infinity(self, postgres_pool):
+        from datetime import date
+
+        result = await postgres_pool.execute(
+            "SELECT 'infinity'::date AS a, '-infinity'::date AS b"
+        )
+        assert result.first() == {"a": date.max, "b": date.min}
+
+    async def test_sqlite_date_param(self, sqlite_pool):
+        from datetime import date
+
+        result = await sqlite_pool.execute("SELECT ? AS d", [date(1999, 12, 31)])
+        assert result.scalar() == "1999-12-31"
+
+
+# ========== Catalog Array Tests ==========
+
+
+class TestCatalogArrays:
+    """name[] and other catalog array types decode to Python lists."""
+
+    async def test_current_schemas(self, postgres_pool):
+        result = await postgres_pool.execute("SELECT current_schemas(true) AS schemas")
+        schemas = result.scalar()
+        assert isinstance(schemas, list)
+        assert all(isinstance(s, str) for s in schemas)
+        assert "pg_catalog" in schemas
+
+    async def test_oid_and_char_arrays(self, postgres_pool):
+        result = await postgres_pool.execute(
+            "SELECT ARRAY['r', 'v']::\"char\"[] AS kinds, ARRAY[16, 23]::oid[] AS oids"
+        )
+        assert result.first() == {"kinds": ["r", "v"], "oids": [16, 23]}
+
+
+# ========== Datetime Parameter Tests ==========
+
+
+class TestDatetimeParams:
+    """datetime, date and time parameters bind as their SQL types."""
+
+    async def test_timestamptz_column(self, postgres_pool):
+        from datetime import datetime, timedelta, timezone
+
+        await postgres_pool.execute("DROP TABLE IF EXISTS dt_param_test")
+        await postgres_pool.execute("CREATE TABLE dt_param_test (at timestamptz)")
+        try:
+            plus_two = timezone(timedelta(hours=2))
+            await postgres_pool.execute(
+                "INSERT INTO dt_param_test VALUES ($1)",
+                [datetime(2024, 3, 5, 8, 7, 8, 123456, tzinfo=plus_two)],
+            )
+            result = await postgres_pool.execute("SELECT at FROM dt_param_test")
+            assert result.scalar() == datetime(
+                2024, 3, 5, 6, 7, 8, 123456, tzinfo=timezone.utc
+            )
+        finally:
+            await postgres_pool.execute("DROP TABLE IF EXISTS dt_param_test")
+
+    async def test_naive_datetime_and_time(self, postgres_pool):
+        from datetime import datetime, time
+
+        result = await postgres_pool.execute(
+            "SELECT $1::timestamp = '1999-12-31 23:59:59.5' AS ts,"
+            " $2::time = '13:14:15.000016' AS t",
+            [datetime(1999, 12, 31, 23, 59, 59, 500000), time(13, 14, 15, 16)],
+        )
+        assert result.first() == {"ts": True, "t": True}
+
+    async def test_aware_time(self, postgres_pool):
+        from datetime import time, timedelta, timezone
+
+        result = await postgres_pool.execute(
+            "SELECT $1::timetz = '12:00:00+02' AS same",
+            [time(12, 0, tzinfo=timezone(timedelta(hours=2)))],
+        )
+        assert result.scalar() is True
+
+    async def test_sqlite_iso_text(self, sqlite_pool):
+        from datetime import datetime, time, timedelta, timezone
+
+        result = await sqlite_pool.execute(
+            "SELECT ? AS naive, ? AS aware, ? AS t",
+            [
+                datetime(2024, 3, 5, 6, 7, 8),
+                datetime(2024, 3, 5, 8, 7, 8, 500000, tzinfo=timezone(timedelta(hours=2))),
+                time(13, 14, 15, 16),
+            ],
+        )
+        assert result.first() == {
+            "naive": "2024-03-05 06:07:08",
+            "aware": "2024-03-05 06:07:08.500000",
+            "t": "13:14:15.000016",
+        }
+
+    async def test_sqlite_datetime_comparison(self, sqlite_pool):
+        from datetime import datetime
+
+        result = await sqlite_pool.execute(
+            "SELECT datetime('2024-03-05 06:07:08') = ? AS same",
+            [datetime(2024, 3, 5, 6, 7, 8)],
+        )
+        assert result.scalar() == 1
+
+
+# ========== Vacuum Tests ==========
+
+
+class TestVacuum:
+    """Tests for ConnectionPool.vacuum()."""
+
+    async def test_postgres_vacuum(self, postgres_pool):
+        """Test that VACUUM runs outside a transaction block."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS vacuum_test")
+        await postgres_pool.execute("CREATE TABLE vacuum_test (id int)")
+        try:
+            await postgres_pool.vacuum()
+            await postgres_pool.vacuum("vacuum_test", full=True, analyze=True)
+            await postgres_pool.vacuum("vacuum_test", analyze=True)
+        finally:
+            await postgres_pool.execute("DROP TABLE IF EXISTS vacuum_test")
+
+    async def test_postgres_vacuum_while_transaction_open(self, postgres_pool):
+        """Test that an open transaction elsewhere doesn't affect VACUUM."""
+        async with await postgres_pool.transaction(deferred=False) as tx:
+            await tx.execute("SELECT 1")
+            await postgres_pool.vacuum()
+
+    async def test_sqlite_vacuum(self, sqlite_pool):
+        await sqlite_pool.execute("CREATE TABLE vacuum_test (id INTEGER)")
+        await sqlite_pool.vacuum()
+        await sqlite_pool.vacuum(analyze=True)
+
+    async def test_sqlite_table_rejected(self, sqlite_pool):
+        with pytest.raises(RuntimeError, match="whole database"):
+            await sqlite_pool.vacuum("vacuum_test")
+
+
+# ========== UUID Object Tests ==========
+
+
+class TestUuidObjects:
+    """Tests for uuid.UUID params and the uuid_as_object pool option."""
+
+    async def test_postgres_uuid_as_object(self):
+        """Test that UUID columns come back as uuid.UUID when enabled."""
+        import uuid
+
+        url = os.environ.get("DATABASE_URL")
+        if not url:
+            pytest.skip("DATABASE_URL not set")
+        pool = await create_engine(url, uuid_as_object=True)
+        try:
+            value = uuid.uuid4()
+            result = await pool.execute(
+                "SELECT $1::uuid AS id, ARRAY[$1::uuid] AS ids", [value]
+            )
+            row = result.first()
+            assert row["id"] == value
+            assert isinstance(row["id"], uuid.UUID)
+            assert row["ids"] == [value]
+        finally:
+            await pool.close()
+
+    async def test_postgres_uuid_string_by_default(self, postgres_pool):
+        """Test that UUID columns stay strings without the option."""
+        import uuid
+
+        value = uuid.uuid4()
+        result = await postgres_pool.execute("SELECT $1 AS id", [value])
+        assert result.first()["id"] == str(value)
+
+    async def test_sqlite_uuid_param(self, sqlite_pool):
+        """Test that a uuid.UUID binds as its hyphenated text in SQLite."""
+        import uuid
+
+        value = uuid.uuid4()
+        result = await sqlite_pool.execute("SELECT ? AS id", [value])
+        assert result.first()["id"] == str(value)
+
+
+# ========== Param Type Hint Tests ==========
+
+
+class TestParamTypes:
+    """Tests for execute(param_types=...) coercion."""
+
+    async def test_postgres_int_as_bool(self, postgres_pool):
+        """Test that 1 with a bool hint matches a boolean column."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS param_types_test")
+        await postgres_pool.execute(
+            "CREATE TABLE param_types_test (id int, active boolean)"
+        )
+        try:
+            await postgres_pool.execute(
+                "INSERT INTO param_types_test VALUES ($1, $2), ($3, $4)",
+                [1, 1, 2, 0],
+                param_types=[None, "bool", None, "bool"],
+            )
+            result = await postgres_pool.execute(
+                "SELECT id FROM param_types_test WHERE active = $1",
+                [1],
+                param_types=["bool"],
+            )
+            assert result.all() == [{"id": 1}]
+        finally:
+            await postgres_pool.execute("DROP TABLE IF EXISTS param_types_test")
+
+    async def test_sqlite_int_as_bool(self, sqlite_pool):
+        result = await sqlite_pool.execute(
+            "SELECT ? AS flag", [0], param_types=["bool"]
+        )
+        assert result.first()["flag"] == 0
+
+    async def test_out_of_range_int_rejected(self, sqlite_pool):
+        with pytest.raises(ValueError, match="only 0 or 1"):
+            await sqlite_pool.execute("SELECT ?", [2], param_types=["bool"])
+
+    async def test_length_mismatch_rejected(self, sqlite_pool):
+        with pytest.raises(ValueError, match="param_types has 2 entries"):
+            await sqlite_pool.execute("SELECT ?", [1], param_types=["bool", None])
+
+
+# ========== Array Parameter Tests ==========
+
+
+class TestArrayParams:
+    """Tests for binding Python lists as PostgreSQL arrays."""
+
+    async def test_postgres_int_and_text_arrays(self, postgres_pool):
+        """Test that homogeneous int and str lists bind as arrays."""
+        result = await postgres_pool.execute(
+            "SELECT 2 = ANY($1) AS has_two, array_length($2::text[], 1) AS n",
+            [[1, 2, None], ["a", "b"]],
+        )
+        assert result.first() == {"has_two": True, "n": 2}
+
+    async def test_postgres_int4_array_column(self, postgres_pool):
+        """Test that a list round-trips through an int4[] column."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS array_params_test")
+        await postgres_pool.execute("CREATE TABLE array_params_test (ids int4[])")
+        try:
+            await postgres_pool.execute(
+                "INSERT INTO array_params_test VALUES ($1)", [[3, 1, 2]]
+            )
+            result = await postgres_pool.execute("SELECT ids FROM array_params_test")
+            assert result.first()["ids"] == [3, 1, 2]
+        finally:
+            await postgres_pool.execute("DROP TABLE IF EXISTS array_params_test")
+
+    async def test_postgres_uuid_array(self, postgres_pool):
+        import uuid
+
+        values = [uuid.uuid4(), uuid.uuid4()]
+        result = await postgres_pool.execute("SELECT $1 AS ids", [values])
+        assert result.first()["ids"] == [str(v) for v in values]
+
+    async def test_postgres_mixed_list_stays_json(self, postgres_pool):
+        result = await postgres_pool.execute("SELECT $1::jsonb AS v", [[1, "a"]])
+        assert result.first()["v"] == [1, "a"]
+
+    async def test_sqlite_list_binds_as_json(self, sqlite_pool):
+        result = await sqlite_pool.execute("SELECT ? AS v", [[1, 2, None]])
+        assert result.first()["v"] == "[1,2,null]"
+
+
+# ========== CSV Export Tests ==========
+
+
+class TestToCsv:
+    """Tests for QueryResult.to_csv()."""
+
+    async def test_header_quoting_and_null(self, sqlite_pool):
+        """Test the header row, quoting of a comma and NULL as an empty field."""
+        import io
+
+        result = await sqlite_pool.execute(
+            "SELECT 1 AS id, 'a,b' AS name, NULL AS note, '' AS empty, 1.5 AS score"
+        )
+        out = io.StringIO()
+        result.to_csv(out)
+        assert out.getvalue() == 'id,name,note,empty,score\r\n1,"a,b",,"",1.5\r\n'
+
+    async def test_no_header_and_delimiter(self, sqlite_pool):
+        import io
+
+        result = await sqlite_pool.execute("SELECT 'a,b' AS x, 'say \"hi\"' AS y")
+        out = io.StringIO()
+        result.to_csv(out, header=False, delimiter="\t")
+        assert out.getvalue() == 'a,b\t"say ""hi"""\r\n'
+
+    async def test_bytes_as_hex(self, sqlite_pool):
+        import io
+
+        result = await sqlite_pool.execute("SELECT X'01ff' AS b")
+        out = io.StringIO()
+        result.to_csv(out, header=False)
+        assert out.getvalue() == "01ff\r\n"
+
+    async def test_invalid_delimiter(self, sqlite_pool):
+        import io
+
+        result = await sqlite_pool.execute("SELECT 1")
+        with pytest.raises(ValueError, match="single character"):
+            result.to_csv(io.StringIO(), delimiter=";;")
+
+    async def test_postgres_types(self, postgres_pool):
+        """Test ISO-8601 timestamps, dates and arrays."""
+        import io
+
+        result = await postgres_pool.execute(
+            "SELECT '2024-01-02 03:04:05'::timestamp AS ts, "
+            "'2024-01-02'::date AS d, ARRAY[1, NULL] AS arr, true AS flag"
+        )
+        out = io.StringIO()
+        result.to_csv(out, header=False)
+        assert out.getvalue() == '2024-01-02T03:04:05,2024-01-02,"[1,null]",true\r\n'
+
+
+# ========== SQLite Transaction Serialization Tests ==========
+
+
+class TestSqliteTransactionWriter:
+    """Tests for SQLite transactions holding the pool's write lock."""
+
+    async def test_concurrent_transactions_serialize(self, tmp_path):
+        """Test that concurrent read-then-write transactions don't hit SQLITE_BUSY."""
+        import asyncio
+
+        pool = await create_engine(f"sqlite://{tmp_path / 'tx.db'}", max_connections=4)
+        try:
+            await pool.execute("CREATE TABLE counter (n INTEGER)")
+            await pool.execute("INSERT INTO counter VALUES (0)")
+
+            async def increment():
+                async with await pool.transaction() as tx:
+                    n = (await tx.execute("SELECT n FROM counter")).scalar()
+                    await asyncio.sleep(0.01)
+                    await tx.execute("UPDATE counter SET n = ?", [n + 1])
+
+            await asyncio.gather(*(increment() for _ in range(5)))
+            result = await pool.execute("SELECT n FROM counter")
+            assert result.scalar() == 5
+        finally:
+            await pool.close()
+
+
+# ========== Reset On Return Tests ==========
+
+
+class TestResetOnReturn:
+    """Tests for the reset_on_return pool option."""
+
+    async def test_postgres_temp_table_discarded(self):
+        """Test that session state doesn't survive a return to the pool."""
+        url = os.environ.get("DATABASE_URL")
+        if not url:
+            pytest.skip("DATABASE_URL not set")
+        pool = await create_engine(
+            url, min_connections=1, max_connections=1, reset_on_return=True
+        )
+        try:
+            await pool.execute("CREATE TEMP TABLE reset_py_test (id int)")
+            await pool.execute("SET application_name = 'leaky'")
+            with pytest.raises(DatabaseError, match="reset_py_test"):
+                await pool.execute("SELECT * FROM reset_py_test")
+            result = await pool.execute("SHOW application_name")
+            assert result.scalar() != "leaky"
+        finally:
+            await pool.close()
+
+
+# ========== Rows Affected Tests ==========
+
+
+class TestRowcountAffected:
+    """Tests for QueryResult.rowcount_affected."""
+
+    async def test_postgres_dml_counts(self, postgres_pool):
+        """Test counts for UPDATE, INSERT ... RETURNING and DDL."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS affected_test")
+        result = await postgres_pool.execute("CREATE TABLE affected_test (id int)")
+        assert result.rowcount_affected is None
+        try:
+            result = await postgres_pool.execute(
+                "INSERT INTO affected_test VALUES (1), (2), (3) RETURNING id"
+            )
+            assert result.rowcount == 3
+            assert result.rowcount_affected == 3
+
+            result = await postgres_pool.execute(
+                "UPDATE affected_test SET id = id + 10 WHERE id > 1"
+            )
+            assert result.rowcount == 0
+            assert result.rowcount_affected == 2
+
+            result = await postgres_pool.execute(
+                "DELETE FROM affected_test WHERE id = 99"
+            )
+            assert result.rowcount_affected == 0
+        finally:
+            await postgres_pool.execute("DROP TABLE IF EXISTS affected_test")
+
+    async def test_sqlite_is_none(self, sqlite_pool):
+        result = await sqlite_pool.execute("SELECT 1")
+        assert result.rowcount_affected is None
+
+
+# ========== Execute Returning Tests ==========
+
+
+class TestExecuteReturning:
+    """Tests for ConnectionPool.execute_returning()."""
+
+    async def test_postgres_update_returning(self, postgres_pool):
+        """Test that UPDATE ... RETURNING gives both the ids and the count."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS returning_test")
+        await postgres_pool.execute("CREATE TABLE returning_test (id int, x int, y int)")
+        try:
+            await postgres_pool.execute(
+                "INSERT INTO returning_test VALUES (1, 0, 2), (2, 0, 3), (3, 0, 2)"
+            )
+            result, affected = await postgres_pool.execute_returning(
+                "UPDATE returning_test SET x = 1 WHERE y = 2 RETURNING id"
+            )
+            assert sorted(result.scalars()) == [1, 3]
+            assert affected == 2
+
+            result, affected = await postgres_pool.execute_returning(
+                "UPDATE returning_test SET x = 1 WHERE y = $1", [99]
+            )
+            assert result.rowcount == 0
+            assert affected == 0
+        finally:
+            await postgres_pool.execute("DROP TABLE IF EXISTS returning_test")
+
+    async def test_sqlite_update_returning(self, sqlite_pool):
+        """Test that SQLite counts the rows changed by a RETURNING statement."""
+        await sqlite_pool.execute("CREATE TABLE returning_test (id INTEGER, x INTEGER, y INTEGER)")
+        await sqlite_pool.execute(
+            "INSERT INTO returning_test VALUES (1, 0, 2), (2, 0, 3), (3, 0, 2)"
+        )
+        result, affected = await sqlite_pool.execute_returning(
+            "UPDATE returning_test SET x = 1 WHERE y = ? RETURNING id", [2]
+        )
+        assert sorted(result.scalars()) == [1, 3]
+        assert affected == 2
+
+        result, affected = await sqlite_pool.execute_returning("SELECT id FROM returning_test")
+        assert result.rowcount == 3
+        assert affected == 0
+
+
+# ========== Index Stats Tests ==========
+
+
+class TestIndexStats:
+    """Tests for ConnectionPool.index_stats()."""
+
+    async def test_partial_index_details(self, sqlite_pool):
+        """Test that a partial index reports its predicate and column details."""
+        await sqlite_pool.execute(
+            "CREATE TABLE index_stats_test (id INTEGER PRIMARY KEY, email TEXT UNIQUE, "
+            "status TEXT, created_at TEXT)"
+        )
+        await sqlite_pool.execute(
+            "CREATE INDEX idx_active_recent ON index_stats_test "
+            "(status COLLATE NOCASE, created_at DESC) WHERE status != 'archived'"
+        )
+
+        stats = {s.name: s for s in await sqlite_pool.index_stats("index_stats_test")}
+        partial = stats["idx_active_recent"]
+        assert partial.partial
+        assert not partial.unique
+        assert not partial.auto_created
+        assert partial.origin == "c"
+        assert partial.predicate == "status != 'archived'"
+        assert [c.name for c in partial.columns] == ["status", "created_at"]
+        assert [c.descending for c in partial.columns] == [False, True]
+        assert partial.columns[0].collation == "NOCASE"
+        assert partial.columns[1].collation == "BINARY"
+
+        (auto,) = [s for s in stats.values() if s.origin == "u"]
+        assert auto.unique
+        assert auto.auto_created
+        assert not auto.partial
+        assert auto.predicate is None
+        assert [c.name for c in auto.columns] == ["email"]
+
+    async def test_postgres_unsupported(self, postgres_pool):
+        """Test that index_stats reports it is SQLite only."""
+        with pytest.raises(Exception, match="only supported for SQLite"):
+            await postgres_pool.index_stats("anything")
+
+
+# ========== Acquire Timeout Tests ==========
+
+
+class TestAcquireTimeout:
+    """Tests for the acquire_timeout pool option."""
+
+    async def test_sqlite_times_out_when_exhausted(self, tmp_path):
+        """Test that a busy pool raises PoolTimeoutError instead of hanging."""
+        pool = await create_engine(
+            f"sqlite://{tmp_path / 'acquire.db'}", max_connections=1, acquire_timeout=0.05
+        )
+        try:
+            async with await pool.session() as session:
+                await session.execute("SELECT 1")
+                with pytest.raises(PoolTimeoutError):
+                    await pool.execute("SELECT 1")
+            result = await pool.execute("SELECT 1")
+            assert result.scalar() == 1
+        finally:
+            await pool.close()
+
+    async def test_postgres_url_pool_timeout(self):
+        """Test that a PostgreSQL URL's pool_timeout bounds the wait."""
+        url = os.environ.get("DATABASE_URL")
+        if not url:
+            pytest.skip("DATABASE_URL not set")
+        separator = "&" if "?" in url else "?"
+        pool = await create_engine(
+            f"{url}{separator}pool_timeout=0.05", min_connections=1, max_connections=1
+        )
+        try:
+            async with await pool.session() as session:
+                await session.execute("SELECT 1")
+                with pytest.raises(PoolTimeoutError):
+                    await pool.execute("SELECT 1")
+        finally:
+            await pool.close()
+
+    async def test_rejects_non_positive(self):
+        """Test that acquire_timeout must be positive."""
+        with pytest.raises(ValueError, match="acquire_timeout"):
+            await create_engine("sqlite::memory:", acquire_timeout=0)
+
+
+# ========== Empty String As NULL Tests ==========
+
+
+class TestEmptyStringAsNull:
+    """Tests for the empty_string_as_null SQLite pool option."""
+
+    async def test_enabled_stores_null(self, tmp_path):
+        """Test that "" is stored and read back as NULL with the mode on."""
+        pool = await create_engine(
+            f"sqlite://{tmp_path / 'oracle.db'}", empty_string_as_null=True
+        )
+        try:
+            await pool.execute("CREATE TABLE names (name TEXT)")
+            await pool.execute("INSERT INTO names VALUES (?)", [""])
+            result = await pool.execute("SELECT name, name IS NULL AS is_null FROM names")
+            assert result.first() == {"name": None, "is_null": 1}
+        finally:
+            await pool.close()
+
+    async def test_disabled_keeps_empty_string(self, sqlite_pool):
+        """Test that "" stays an empty string by default."""
+        await sqlite_pool.execute("CREATE TABLE names (name TEXT)")
+        await sqlite_pool.execute("INSERT INTO names VALUES (?)", [""])
+        result = await sqlite_pool.execute("SELECT name, name IS NULL AS is_null FROM names")
+        assert result.first() == {"name": "", "is_null": 0}
+
+
+# ========== Query Cancellation Tests ==========
+
+
+class TestQueryCancellation:
+    """Tests for cancelling running PostgreSQL queries."""
+
+    async def test_cancelled_execute_stops_server_query(self, postgres_pool):
+        """Test that cancelling execute() cancels the query on the server."""
+        import asyncio
+
+        with pytest.raises(asyncio.TimeoutError):
+            await asyncio.wait_for(postgres_pool.execute("SELECT pg_sleep(10)"), 0.2)
+
+        result = await postgres_pool.execute("SELECT 1")
+        assert result.scalar() == 1
+
+        await asyncio.sleep(0.2)
+        result = await postgres_pool.execute(
+            "SELECT count(*) FROM pg_stat_activity "
+            "WHERE query = 'SELECT pg_sleep(10)' AND state = 'active'"
+        )
+        assert result.scalar() == 0
+
+
+# ========== Regclass Tests ==========
+
+
+class TestRegclass:
+    """Tests for regclass/regtype decoding and ConnectionPool.regclass_name()."""
+
+    async def test_regclass_decodes_to_oid(self, postgres_pool):
+        """Test that a regclass value arrives as its OID and resolves to a name."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS regclass_items")
+        await postgres_pool.execute("CREATE TABLE regclass_items (id int8 PRIMARY KEY)")
+
+        try:
+            oid = (await postgres_pool.execute("SELECT 'regclass_items'::regclass")).scalar()
+            assert isinstance(oid, int)
+            assert await postgres_pool.regclass_name(oid) == "regclass_items"
+            assert await postgres_pool.regclass_name(0) is None
+        finally:
+            await postgres_pool.execute("DROP TABLE IF EXISTS regclass_items")
+
+    async def test_regclass_name_sqlite_unsupported(self, sqlite_pool):
+        """Test that regclass_name is rejected on SQLite."""
+        with pytest.raises(RuntimeError, match="only supported for PostgreSQL"):
+            await sqlite_pool.regclass_name(1)
+
+
+# ========== Pipeline Depth Tests ==========
+
+
+class TestPipelineDepth:
+    """Tests for the pipeline_depth pool option."""
+
+    async def test_large_batch_small_window(self):
+        """Test that a batch far larger than the window completes in a transaction."""
+        url = os.environ.get("DATABASE_URL")
+        if not url:
+            pytest.skip("DATABASE_URL not set")
+        pool = await create_engine(url, max_connections=1, pipeline_depth=8)
+        try:
+            await pool.execute("DROP TABLE IF EXISTS pipeline_items")
+            await pool.execute("CREATE TABLE pipeline_items (id INT8, payload TEXT)")
+            rows = [[i, "x" * 512] for i in range(20000)]
+            async with await pool.transaction() as tx:
+                count = await tx.execute_many(
+                    "INSERT INTO pipeline_items VALUES ($1, $2)", rows
+                )
+            assert count == 20000
+
+            result = await pool.execute("SELECT COUNT(*) FROM pipeline_items")
+            assert result.scalar() == 20000
+        finally:
+            await pool.execute("DROP TABLE IF EXISTS pipeline_items")
+            await pool.close()
+
+    async def test_failure_mid_batch_aborts_transaction(self):
+        """Test that a failing row surfaces its error and nothing is committed."""
+        url = os.environ.get("DATABASE_URL")
+        if not url:
+            pytest.skip("DATABASE_URL not set")
+        pool = await create_engine(url, max_connections=1, pipeline_depth=4)
+        try:
+            await pool.execute("DROP TABLE IF EXISTS pipeline_items")
+            await pool.execute("CREATE TABLE pipeline_items (id INT8 PRIMARY KEY)")
+            rows = [[i if i != 60 else 0] for i in range(100)]
+            with pytest.raises(Exception, match="duplicate key"):
+                async with await pool.transaction() as tx:
+                    await tx.execute_many("INSERT INTO pipeline_items VALUES ($1)", rows)
+
+            result = await pool.execute("SELECT COUNT(*) FROM pipeline_items")
+            assert result.scalar() == 0
+        finally:
+            await pool.execute("DROP TABLE IF EXISTS pipeline_items")
+            await pool.close()
+
+
+# ========== Binary COPY Tests ==========
+
+
+class TestCopyIn:
+    """Tests for ConnectionPool.copy_in()."""
+
+    async def test_copy_in_postgres(self, postgres_pool):
+        """Test copying a generator of tuples with binary COPY."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS copy_items")
+        await postgres_pool.execute(
+            "CREATE TABLE copy_items (id int4 PRIMARY KEY, name text, score numeric, doc jsonb)"
+        )
+        try:
+            rows = (
+                (i, f"name-{i}" if i % 2 else None, i / 4, '{"n": %d}' % i) for i in range(5000)
+            )
+            copied = await postgres_pool.copy_in(
+                "copy_items", ["id", "name", "score", "doc"], rows
+            )
+            assert copied == 5000
+
+            row = (
+                await postgres_pool.execute(
+                    "SELECT id, name, score::text AS score, doc->>'n' AS n "
+                    "FROM copy_items WHERE id = $1",
+                    [101],
+                )
+            ).one()
+            assert row == {"id": 101, "name": "name-101", "score": "25.25", "n": "101"}
+        finally:
+            await postgres_pool.execute("DROP TABLE IF EXISTS copy_items")
+
+    async def test_copy_in_bad_value_keeps_nothing(self, postgres_pool):
+        """Test that a value that doesn't fit its column aborts the whole COPY."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS copy_items")
+        await postgres_pool.execute("CREATE TABLE copy_items (id int4, name text)")
+        try:
+            rows = [(1, "a"), (2**40, "b")]
+            with pytest.raises(Exception, match="out of range"):
+                await postgres_pool.copy_in("copy_items", ["id", "name"], rows)
+
+            result = await postgres_pool.execute("SELECT COUNT(*) FROM copy_items")
+            assert result.scalar() == 0
+        finally:
+            await postgres_pool.execute("DROP TABLE IF EXISTS copy_items")
+
+    async def test_copy_in_quotes_table_name(self, postgres_pool):
+        """Test that the table name is quoted in both the type probe and the COPY."""
+        await postgres_pool.execute('DROP TABLE IF EXISTS "Copy Items"')
+        await postgres_pool.execute('CREATE TABLE "Copy Items" (id int4, "Name" text)')
+        try:
+            copied = await postgres_pool.copy_in("Copy Items", ["id", "Name"], [(1, "a")])
+            assert copied == 1
+
+            row = (await postgres_pool.execute('SELECT * FROM "Copy Items"')).one()
+            assert row == {"id": 1, "Name": "a"}
+        finally:
+            await postgres_pool.execute('DROP TABLE IF EXISTS "Copy Items"')
+
+    async def test_copy_in_schema_qualified_table(self, postgres_pool):
+        """Test that copy_in accepts a schema-qualified table name."""
+        await postgres_pool.execute("DROP SCHEMA IF EXISTS copy_schema CASCADE")
+        await postgres_pool.execute("CREATE SCHEMA copy_schema")
+        await postgres_pool.execute("CREATE TABLE copy_schema.items (id int4, name text)")
+        try:
+            copied = await postgres_pool.copy_in("copy_schema.items", ["id", "name"], [(1, "a")])
+            assert copied == 1
+
+            row = (await postgres_pool.execute("SELECT * FROM copy_schema.items")).one()
+            assert row == {"id": 1, "name": "a"}
+        finally:
+            await postgres_pool.execute("DROP SCHEMA IF EXISTS copy_schema CASCADE")
+
+    async def test_copy_in_sqlite_unsupported(self, sqlite_pool):
+        """Test that copy_in is rejected on SQLite."""
+        with pytest.raises(RuntimeError, match="only supported for PostgreSQL"):
+            await sqlite_pool.copy_in("t", ["id"], [(1,)])
+
+
+# ========== Copy Out Tests ==========
+
+
+class TestCopyOut:
+    """Tests for ConnectionPool.copy_out() and copy_out_stream()."""
+
+    async def test_copy_out_csv(self, postgres_pool):
+        """Test exporting a query as CSV bytes, with and without a header."""
+        query = "SELECT n, 'item ' || n AS name FROM generate_series(1, 3) n;"
+        data = await postgres_pool.copy_out(query)
+        assert data == b"1,item 1\n2,item 2\n3,item 3\n"
+
+        data = await postgres_pool.copy_out(query, header=True)
+        assert data.splitlines()[0] == b"n,name"
+
+    async def test_copy_out_null_and_quoting(self, postgres_pool):
+        """Test that NULLs are empty fields and commas are quoted."""
+        data = await postgres_pool.copy_out("SELECT NULL::text, 'a,b'")
+        assert data == b',"a,b"\n'
+
+    async def test_copy_out_stream(self, postgres_pool):
+        """Test that streamed chunks hold whole rows and add up to the export."""
+        query = "SELECT n, repeat('x', 100) FROM generate_series(1, 5000) n"
+        chunks = []
+        async for chunk in await postgres_pool.copy_out_stream(query):
+            assert chunk.endswith(b"\n")
+            chunks.append(chunk)
+        assert len(chunks) > 1
+        assert b"".join(chunks) == await postgres_pool.copy_out(query)
+
+    async def test_copy_out_stream_close_early(self, postgres_pool):
+        """Test that closing a stream early leaves the pool usable."""
+        stream = await postgres_pool.copy_out_stream(
+            "SELECT n FROM generate_series(1, 1000000) n"
+        )
+        assert await stream.next_chunk()
+        await stream.close()
+        assert await stream.next_chunk() is None
+
+        result = await postgres_pool.execute("SELECT 1 AS n")
+        assert result.scalar() == 1
+
+    async def test_copy_out_error(self, postgres_pool):
+        """Test that a failing query raises."""
+        with pytest.raises(Exception, match="division by zero"):
+            await postgres_pool.copy_out("SELECT 1 / 0")
+
+    async def test_copy_out_sqlite_unsupported(self, sqlite_pool):
+        """Test that copy_out is rejected on SQLite."""
+        with pytest.raises(RuntimeError, match="only supported for PostgreSQL"):
+            await sqlite_pool.copy_out("SELECT 1")
+
+
+# ========== One Result Exception Tests ==========
+
+
+class RecordMissing(Exception):
+    pass
+
+
+class RecordAmbiguous(Exception):
+    pass
+
+
+class TestOneExceptions:
+    """Tests for QueryResult.one()'s result-count exceptions."""
+
+    async def test_default_exceptions(self, sqlite_pool):
+        """Test that one() raises NoResultFound/MultipleResultsFound (ValueErrors)."""
+        with pytest.raises(NoResultFound, match="got 0"):
+            (await sqlite_pool.execute("SELECT 1 WHERE 0")).one()
+        with pytest.raises(MultipleResultsFound, match="got 2"):
+            (await sqlite_pool.execute("SELECT 1 UNION ALL SELECT 2")).one()
+        with pytest.raises(ValueError):
+            (await sqlite_pool.execute("SELECT 1 WHERE 0")).one()
+        with pytest.raises(MultipleResultsFound):
+            (await sqlite_pool.execute("SELECT 1 UNION ALL SELECT 2")).one_or_none()
+
+    async def test_custom_exception_for_zero_rows(self, sqlite_pool):
+        """Test that no_result_exc is raised for an empty result."""
+        result = await sqlite_pool.execute("SELECT 1 WHERE 0")
+        with pytest.raises(RecordMissing, match="Expected exactly 1 row, got 0"):
+            result.one(no_result_exc=RecordMissing, multi_result_exc=RecordAmbiguous)
+
+    async def test_custom_exception_for_multiple_rows(self, sqlite_pool):
+        """Test that multi_result_exc is raised for more than one row."""
+        result = await sqlite_pool.execute("SELECT 1 UNION ALL SELECT 2")
+        with pytest.raises(RecordAmbiguous, match="got 2"):
+            result.one(no_result_exc=RecordMissing, multi_result_exc=RecordAmbiguous)
+
+    async def test_exactly_one_row(self, sqlite_pool):
+        """Test that one() still returns the row when there is exactly one."""
+        result = await sqlite_pool.execute("SELECT 1 AS n")
+        assert result.one(no_result_exc=RecordMissing) == {"n": 1}
+
+
+# ========== Enum Introspection Tests ==========
+
+
+class TestGetEnums:
+    """Tests for ConnectionPool.get_enums()."""
+
+    async def test_get_enums_postgres(self, postgres_pool):
+        """Test that an enum's name and labels come back in sort order."""
+        await postgres_pool.execute("DROP TYPE IF EXISTS ticket_status")
+        await postgres_pool.execute(
+            "CREATE TYPE ticket_status AS ENUM ('open', 'in_progress', 'closed')"
+        )
+        try:
+            # A label added BEFORE another sorts ahead of it
+            await postgres_pool.execute(
+                "ALTER TYPE ticket_status ADD VALUE 'triaged' BEFORE 'in_progress'"
+            )
+            enums = {e.name: e.labels for e in await postgres_pool.get_enums()}
+            assert enums["ticket_status"] == ["open", "triaged", "in_progress", "closed"]
+        finally:
+            await postgres_pool.execute("DROP TYPE IF EXISTS ticket_status")
+
+    async def test_get_enums_sqlite(self, sqlite_pool):
+        """Test that SQLite, which has no enum types, returns an empty list."""
+        assert await sqlite_pool.get_enums() == []
+
+
+# ========== Table Size Tests ==========
+
+
+class TestTableSizes:
+    """Tests for ConnectionPool.table_sizes()."""
+
+    async def test_table_sizes_postgres(self, postgres_pool):
+        """Test that a filled, indexed table reports table and index sizes."""
+        await postgres_pool.execute("DROP TABLE IF EXISTS size_small")
+        await postgres_pool.execute("DROP TABLE IF EXISTS size_big")
+        await postgres_pool.execute("CREATE TABLE size_small (id INTEGER PRIMARY KEY, name TEXT)")
+        await postgres_pool.execute("CREATE TABLE size_big (id INTEGER PRIMARY KEY, name TEXT)")
+        try:
+            await postgres_pool.execute("INSERT INTO size_small VALUES (1, 'a')")
+            await postgres_pool.execute(
+                "INSERT INTO size_big SELECT n, repeat('x', 100) FROM generate_series(1, 20000) n"
+            )
+            await postgres_pool.execute("CREATE INDEX size_big_name ON size_big (name)")
+
+            sizes = {s.table: s for s in await postgres_pool.table_sizes()}
+            big, small = sizes["size_big"], sizes["size_small"]
+            assert big.table_bytes > 0 and big.index_bytes > 0
+            assert big.total_bytes >= big.table_bytes + big.index_bytes
+            assert big.total_bytes > 10 * small.total_bytes
+        finally:
+            await postgres_pool.execute("DROP TABLE IF EXISTS size_small")
+            await postgres_pool.execute("DROP TABLE IF EXISTS size_big")
+
+    async def test_table_sizes_sqlite(self, sqlite_pool):
+        """Test that SQLite reports table and index sizes from dbstat."""
+        await sqlite_pool.execute("CREATE TABLE size_small (id INTEGER PRIMARY KEY, name TEXT)")
+        await sqlite_pool.execute("CREATE TABLE size_big (id INTEGER PRIMARY KEY, name TEXT)")
+        await sqlite_pool.execute("INSERT INTO size_small VALUES (1, 'a')")
+        await sqlite_pool.execute_many(
+            "INSERT INTO size_big VALUES (?, ?)", [[n, "x" * 100] for n in range(5000)]
+        )
+        await sqlite_pool.execute("CREATE INDEX size_big_name ON size_big (name)")
+
+        sizes = {s.table: s for s in await sqlite_pool.table_sizes()}
+        big, small = sizes["size_big"], sizes["size_small"]
+        assert big.table_bytes > 0 and big.index_bytes > 0
+        assert big.total_bytes == big.table_bytes + big.index_bytes
+        assert big.total_bytes > 10 * small.total_bytes
+        assert big.toast_bytes == 0
+
+    async def test_table_sizes_sqlite_keeps_sqlite_prefixed_names(self, sqlite_pool):
+        """Test that only internal sqlite_ tables are skipped, not names like sqlites."""
+        await sqlite_pool.execute("CREATE TABLE sqlites (id INTEGER PRIMARY KEY)")
+        await sqlite_pool.execute("CREATE TABLE t (id INTEGER PRIMARY KEY AUTOINCREMENT)")
+        await sqlite_pool.execute("INSERT INTO t DEFAULT VALUES")
+
+        tables = {s.table for s in await sqlite_pool.table_sizes()}
+        assert "sqlites" in tables
+        assert "sqlite_sequence" not in tables
+
+    async def test_table_sizes_postgres_schema(self, postgres_pool):
+        """Test that table_sizes reports the tables of the given schema."""
+        await postgres_pool.execute("DROP SCHEMA IF EXISTS size_schema CASCADE")
+        await postgres_pool.execute("CREATE SCHEMA size_schema")
+        try:
+            await postgres_pool.execute("CREATE TABLE size_schema.size_other (id INTEGER)")
+
+            tables = {s.table for s in await postgres_pool.table_sizes(schema="size_schema")}
+            assert tables == {"size_other"}
+            assert "size_other" not in {s.table for s in await postgres_pool.table_sizes()}
+        finally:
+            await postgres_pool.execute("DROP SCHEMA IF EXISTS size_schema CASCADE")
@@ -268,6 +268,39 @@ class TestSessionUpsert:
         assert loaded is not None
         assert loaded.name == "Original"
 
+    async def test_upsert_return_action(self, users_table) -> None:
+        """Upsert reports "insert" on first call and "update" on conflict."""
+        session = AsyncSession(users_table)
+        user1, outcome = await session.upsert(
+            User(email="action@example.com", name="First"),
+            conflict_target="email",
+            return_action=True,
+        )
+        assert outcome["action"] == "insert"
+        assert outcome["rowid"] == user1.id
+
+        user2, outcome = await session.upsert(
+            User(email="action@example.com", name="Second"),
+            conflict_target="email",
+            return_action=True,
+        )
+        assert outcome["action"] == "update"
+        assert user2.id == user1.id
+        assert user2.name == "Second"
+
+    async def test_execute_upsert_on_pool(self, users_table) -> None:
+        """Pool-level execute_upsert reports the action taken."""
+        sql = (
+            "INSERT INTO users (email, name) VALUES (?, ?) "
+            "ON CONFLICT (email) DO UPDATE SET name = excluded.name"
+        )
+        first = await users_table.execute_upsert(sql, ["pool@example.com", "A"])
+        assert first["action"] == "insert"
+        assert first["rowid"] is not None
+
+        second = await users_table.execute_upsert(sql, ["pool@example.com", "B"])
+        assert second == {"action": "update", "rowid": None}
+
 
 class TestUpsertEdgeCases:
     """Test edge cases for upsert operations."""